@@ -0,0 +1,143 @@
+#ifndef TRAVELING_SALESMAN_H
+#define TRAVELING_SALESMAN_H
+
+// Generated by cbindgen from src/capi.rs, do not edit by hand.
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status codes returned by fallible functions.
+typedef enum TspStatus {
+  TSP_STATUS_OK = 0,
+  // A required pointer argument was null
+  TSP_STATUS_NULL_POINTER,
+  // The map has no points
+  TSP_STATUS_EMPTY_MAP,
+  // An argument was out of range
+  TSP_STATUS_INVALID_ARGUMENT,
+  // The solver panicked; the library remains usable but the result was discarded
+  TSP_STATUS_PANIC,
+} TspStatus;
+
+// The solvers available through `tsp_solve`. The GA-family solvers use their builder defaults.
+typedef enum TspAlgorithm {
+  TSP_ALGORITHM_NEAREST_NEIGHBOR = 0,
+  TSP_ALGORITHM_BRANCH_AND_BOUND,
+  TSP_ALGORITHM_BRUTE_FORCE,
+  TSP_ALGORITHM_GA,
+  TSP_ALGORITHM_BRKGA,
+  TSP_ALGORITHM_DIFFERENTIAL_EVOLUTION,
+  TSP_ALGORITHM_EDGE_HISTOGRAM,
+} TspAlgorithm;
+
+// Why a solve stopped. Mirrors `Termination`.
+typedef enum TspTermination {
+  TSP_TERMINATION_COMPLETED = 0,
+  TSP_TERMINATION_TIME_LIMIT,
+  TSP_TERMINATION_CANCELLED,
+  TSP_TERMINATION_TARGET_GAP,
+  TSP_TERMINATION_EVALUATION_LIMIT,
+} TspTermination;
+
+// The points to visit. Created by `tsp_map_new` and released with `tsp_map_free`.
+typedef struct TspMap TspMap;
+
+// The outcome of a solve. Created by `tsp_solve` and released with `tsp_result_free`.
+typedef struct TspResult TspResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a map from `count` x and y coordinates. Returns null if either array is null while `count` is
+// non-zero.
+//
+// # Safety
+//
+// `xs` and `ys` must each point to at least `count` readable floats.
+struct TspMap *tsp_map_new(const float *xs,
+                           const float *ys,
+                           size_t count);
+
+// Releases a map. Passing null does nothing.
+//
+// # Safety
+//
+// `map` must be null or a pointer returned by `tsp_map_new` which has not been freed yet.
+void tsp_map_free(struct TspMap *map);
+
+// The number of points in the map, or zero if it is null.
+//
+// # Safety
+//
+// `map` must be null or a live pointer returned by `tsp_map_new`.
+size_t tsp_map_size(const struct TspMap *map);
+
+// Solves the map with the given algorithm, one of the `TspAlgorithm` values, stopping after `time_limit` seconds
+// if it is positive. On success the result is written to `out` and must be released with `tsp_result_free`.
+// Unknown algorithms and time limits that are NaN, infinite or too long to represent are invalid arguments.
+//
+// # Safety
+//
+// `map` must be a live pointer returned by `tsp_map_new` and `out` must point to writable memory.
+enum TspStatus tsp_solve(const struct TspMap *map,
+                         uint32_t algorithm,
+                         double time_limit,
+                         struct TspResult **out);
+
+// Releases a result. Passing null does nothing.
+//
+// # Safety
+//
+// `result` must be null or a pointer written by `tsp_solve` which has not been freed yet.
+void tsp_result_free(struct TspResult *result);
+
+// The length of the tour, or NaN if the result is null.
+//
+// # Safety
+//
+// `result` must be null or a live pointer written by `tsp_solve`.
+float tsp_result_length(const struct TspResult *result);
+
+// The number of points in the tour, or zero if the result is null.
+//
+// # Safety
+//
+// `result` must be null or a live pointer written by `tsp_solve`.
+size_t tsp_result_tour_len(const struct TspResult *result);
+
+// Copies up to `capacity` indices of the tour into `out` and returns the number copied.
+//
+// # Safety
+//
+// `result` must be null or a live pointer written by `tsp_solve`, and `out` must be null or point to at least
+// `capacity` writable elements.
+size_t tsp_result_tour(const struct TspResult *result,
+                       size_t *out,
+                       size_t capacity);
+
+// The number of iterations the solver ran, or zero if the result is null.
+//
+// # Safety
+//
+// `result` must be null or a live pointer written by `tsp_solve`.
+uint64_t tsp_result_iterations(const struct TspResult *result);
+
+// Why the solver stopped. A null result reports `Completed`.
+//
+// # Safety
+//
+// `result` must be null or a live pointer written by `tsp_solve`.
+enum TspTermination tsp_result_termination(const struct TspResult *result);
+
+// A static, nul-terminated description of the status, one of the `TspStatus` values, or of an unknown status.
+const char *tsp_status_message(uint32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TRAVELING_SALESMAN_H */
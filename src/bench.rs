@@ -0,0 +1,549 @@
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::generate::PointDistribution;
+use crate::instrument::Recorder;
+use crate::map::Map;
+use crate::solve::{Parallelism, SolveOptions, Solver, run_tasks};
+use crate::tsplib;
+
+// Two-sided 95% critical values of Student's t distribution for 1 to 30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042
+];
+
+// Samples up to this size without ties are tested against the exact distribution of the statistic, and larger
+// ones against its normal approximation
+const EXACT_LIMIT: usize = 30;
+
+// The critical value for the degrees of freedom, falling back on the normal distribution beyond the table
+fn t_95(freedom: usize) -> f64 {
+    T_95.get(freedom.max(1) - 1).cloned().unwrap_or(1.96)
+}
+
+/// Summary statistics of repeated measurements of the same quantity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Summary {
+    pub mean: f64,
+    pub median: f64,
+    /// The sample standard deviation
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Half the width of the 95% confidence interval for the mean, from Student's t distribution. Zero for a
+    /// single measurement.
+    pub ci95: f64
+}
+
+impl Summary {
+    /// Summarizes the values, which must not be empty.
+    pub fn of(values: &[f64]) -> Self {
+        assert!(!values.is_empty(), "At least one value is required");
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = sorted.len();
+        // The middle value, or the mean of the two middle values for an even count
+        let median = (sorted[(count - 1) / 2] + sorted[count / 2]) / 2.0;
+
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let (std_dev, ci95) = if count > 1 {
+            let variance = sorted.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / (count - 1) as f64;
+            let t = t_95(count - 1);
+            (variance.sqrt(), t * variance.sqrt() / (count as f64).sqrt())
+        } else {
+            (0.0, 0.0)
+        };
+
+        Summary {
+            mean,
+            median,
+            std_dev,
+            min: sorted[0],
+            max: sorted[count - 1],
+            ci95
+        }
+    }
+}
+
+impl Summary {
+    /// The 95% confidence interval for the mean.
+    pub fn interval(&self) -> (f64, f64) {
+        (self.mean - self.ci95, self.mean + self.ci95)
+    }
+}
+
+/// The 95% confidence interval for the difference between the means of `a` and `b`, from Welch's t test, which
+/// does not assume that both have the same variance. If the interval does not contain zero, the means differ at
+/// the 5% level. Both need at least two values.
+pub fn difference_ci95(a: &[f64], b: &[f64]) -> (f64, f64) {
+    assert!(a.len() > 1 && b.len() > 1, "At least two values of each are required");
+
+    // The mean and the squared standard error of the mean
+    let moments = |values: &[f64]| {
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = values.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / (count - 1.0);
+        (mean, variance / count)
+    };
+    let (a_mean, a_error) = moments(a);
+    let (b_mean, b_error) = moments(b);
+
+    let error = a_error + b_error;
+    // The Welch-Satterthwaite degrees of freedom, rounded down to stay on the safe side
+    let freedom = if error > 0.0 {
+        error * error / (a_error * a_error / (a.len() - 1) as f64 + b_error * b_error / (b.len() - 1) as f64)
+    } else {
+        1.0
+    };
+    let half = t_95(freedom as usize) * error.sqrt();
+    let difference = a_mean - b_mean;
+    (difference - half, difference + half)
+}
+
+/// The outcome of a two-sided test of whether two sets of measurements differ.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Test {
+    /// The test's statistic, whose meaning depends on the test
+    pub statistic: f64,
+    /// The probability of a statistic at least this far from its expected value if the measurements do not differ
+    pub p_value: f64,
+    /// Whether the p-value comes from the exact distribution of the statistic rather than its normal approximation
+    pub exact: bool
+}
+
+impl Test {
+    /// Whether the difference is significant at the given level, such as 0.05.
+    pub fn significant(&self, level: f64) -> bool {
+        self.p_value < level
+    }
+}
+
+/// The Mann-Whitney U test of whether the values of `a` tend to be larger or smaller than those of `b`, for
+/// independent runs such as those of two solvers or two settings with different seeds. The statistic is the
+/// number of pairs in which the value of `a` is the larger, with ties counting a half, so it is below
+/// `a.len() * b.len() / 2` when `a` has the shorter tours. Neither may be empty.
+pub fn mann_whitney(a: &[f64], b: &[f64]) -> Test {
+    assert!(!a.is_empty() && !b.is_empty(), "At least one value of each is required");
+
+    let (n, m) = (a.len(), b.len());
+    let combined: Vec<f64> = a.iter().chain(b).copied().collect();
+    let (ranks, ties) = rank(&combined);
+    let rank_sum: f64 = ranks[..n].iter().sum();
+    let statistic = rank_sum - (n * (n + 1)) as f64 / 2.0;
+
+    let pairs = (n * m) as f64;
+    if ties == 0.0 && n <= EXACT_LIMIT && m <= EXACT_LIMIT {
+        return exact(statistic, &mann_whitney_counts(n, m));
+    }
+
+    let total = (n + m) as f64;
+    let variance = pairs / 12.0 * ((total + 1.0) - ties / (total * (total - 1.0)));
+    approximate(statistic, pairs / 2.0, variance)
+}
+
+/// The Wilcoxon signed-rank test of whether the values of `a` tend to be larger or smaller than their partners in
+/// `b`, for paired runs such as two solvers on the same instances or with the same seeds. Pairs with equal values
+/// are dropped. The statistic is the sum of the ranks of the differences in which `a` is the larger, so it is
+/// below a quarter of `n * (n + 1)` for the `n` pairs kept when `a` has the shorter tours. Both must have the same
+/// length.
+pub fn wilcoxon(a: &[f64], b: &[f64]) -> Test {
+    assert_eq!(a.len(), b.len(), "The values must be paired");
+
+    let differences: Vec<f64> = a.iter().zip(b).map(|(a, b)| a - b).filter(|&difference| difference != 0.0).collect();
+    let n = differences.len();
+    if n == 0 {
+        return Test {
+            statistic: 0.0,
+            p_value: 1.0,
+            exact: true
+        };
+    }
+
+    let magnitudes: Vec<f64> = differences.iter().map(|difference| difference.abs()).collect();
+    let (ranks, ties) = rank(&magnitudes);
+    let statistic: f64 = ranks.iter()
+        .zip(&differences)
+        .filter(|(_, &difference)| difference > 0.0)
+        .map(|(rank, _)| rank)
+        .sum();
+
+    if ties == 0.0 && n <= EXACT_LIMIT {
+        return exact(statistic, &wilcoxon_counts(n));
+    }
+
+    let pairs = n as f64;
+    let variance = pairs * (pairs + 1.0) * (2.0 * pairs + 1.0) / 24.0 - ties / 48.0;
+    approximate(statistic, pairs * (pairs + 1.0) / 4.0, variance)
+}
+
+// The ranks of the values from one, with tied values sharing the mean of their ranks, and the sum of t^3 - t over
+// the groups of t tied values
+fn rank(values: &[f64]) -> (Vec<f64>, f64) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut ties = 0.0;
+    let mut first = 0;
+    while first < order.len() {
+        let count = order[first..].iter().take_while(|&&index| values[index] == values[order[first]]).count();
+        let rank = (2 * first + count + 1) as f64 / 2.0;
+        order[first..first + count].iter().for_each(|&index| ranks[index] = rank);
+        ties += (count * count * count - count) as f64;
+        first += count;
+    }
+
+    (ranks, ties)
+}
+
+// The number of ways of interleaving n values with m larger and smaller ones that give each value of U
+fn mann_whitney_counts(n: usize, m: usize) -> Vec<f64> {
+    // counts[j] holds the distribution for i values of the first sample and j of the second, built up over i
+    let mut counts: Vec<Vec<f64>> = (0..=m).map(|_| vec![1.0]).collect();
+    for i in 1..=n {
+        let mut next: Vec<Vec<f64>> = Vec::with_capacity(m + 1);
+        next.push(vec![1.0]);
+        for j in 1..=m {
+            // The largest value is either from the first sample, beating all j of the second, or from the second
+            let mut distribution = vec![0.0; i * j + 1];
+            for (u, &count) in counts[j].iter().enumerate() {
+                distribution[u + j] += count;
+            }
+            for (u, &count) in next[j - 1].iter().enumerate() {
+                distribution[u] += count;
+            }
+            next.push(distribution);
+        }
+        counts = next;
+    }
+
+    counts.swap_remove(m)
+}
+
+// The number of subsets of the ranks 1 to n with each sum
+fn wilcoxon_counts(n: usize) -> Vec<f64> {
+    let mut counts = vec![0.0; n * (n + 1) / 2 + 1];
+    counts[0] = 1.0;
+    for rank in 1..=n {
+        for sum in (rank..counts.len()).rev() {
+            counts[sum] += counts[sum - rank];
+        }
+    }
+    counts
+}
+
+// The two-sided p-value of a statistic with the distribution given by the number of ways of reaching each value
+fn exact(statistic: f64, counts: &[f64]) -> Test {
+    let total: f64 = counts.iter().sum();
+    let value = statistic.round() as usize;
+    let below: f64 = counts[..=value].iter().sum();
+    let above: f64 = counts[value..].iter().sum();
+    Test {
+        statistic,
+        p_value: (2.0 * below.min(above) / total).min(1.0),
+        exact: true
+    }
+}
+
+// The two-sided p-value of a statistic by its normal approximation, with a continuity correction
+fn approximate(statistic: f64, mean: f64, variance: f64) -> Test {
+    let distance = ((statistic - mean).abs() - 0.5).max(0.0);
+    let p_value = if variance > 0.0 { erfc(distance / (2.0 * variance).sqrt()) } else { 1.0 };
+    Test {
+        statistic,
+        p_value: p_value.min(1.0),
+        exact: false
+    }
+}
+
+// The complementary error function, to a relative error below 1.2e-7 (Numerical Recipes' erfcc)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = [
+        -1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806,
+        0.27886807, -1.13520398, 1.48851587, -0.82215223, 0.17087277
+    ]
+        .iter()
+        .rev()
+        .fold(0.0, |sum, &coefficient| sum * t + coefficient);
+    let value = t * (-z * z + polynomial).exp();
+    if x >= 0.0 { value } else { 2.0 - value }
+}
+
+/// The results of every repetition of one solver on one instance.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Cell {
+    pub instance: String,
+    pub points: usize,
+    pub solver: String,
+    pub length: Summary,
+    pub seconds: Summary,
+    /// The solver's iterations, whose meaning depends on the solver
+    pub iterations: Summary,
+    /// The length of every repetition, for testing whether solvers differ
+    pub lengths: Vec<f64>,
+    /// The mean length relative to the shortest tour any solver found for the instance
+    pub gap: f64,
+    /// The mean length relative to the optimum, if the instance is named after a known TSPLIB instance, or after a
+    /// catalog instance whose optimum is known when built with the `catalog` feature
+    pub above_optimal: Option<f64>
+}
+
+impl Cell {
+    /// Tests whether the lengths of this cell's repetitions differ from those of another, which should be for the
+    /// same instance, with [`mann_whitney`]. Its statistic is below half the product of the repetitions of the two
+    /// when this cell's tours are the shorter.
+    pub fn versus(&self, other: &Cell) -> Test {
+        mann_whitney(&self.lengths, &other.lengths)
+    }
+}
+
+/// Runs every solver on every instance a number of times. Solvers and instances are named so that the results
+/// can be told apart. With a seed in the options, each repetition is seeded with the next integer after the last.
+///
+/// Up to [`parallelism`](SolveOptions::parallelism) solves run at once, each on a single thread. Solves running
+/// side by side compete for memory bandwidth, so their times are less comparable than those of solves run alone.
+pub struct Benchmark<'a> {
+    pub instances: &'a [(String, Map)],
+    pub solvers: &'a [(String, Box<dyn Solver + Sync>)],
+    pub repetitions: u32,
+    pub options: &'a SolveOptions
+}
+
+// The length, seconds and iterations of one solve
+type Measurement = (f64, f64, f64);
+
+impl<'a> Benchmark<'a> {
+    /// Returns one cell per instance and solver, grouped by instance in the order given.
+    pub fn run(&self) -> Result<Vec<Cell>> {
+        assert!(self.repetitions > 0, "At least one repetition is required");
+
+        let repetitions = self.repetitions as usize;
+        let tasks = self.instances.len() * self.solvers.len() * repetitions;
+        let measurements = run_tasks(tasks, self.options.parallelism, |task| self.measure(task))
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut cells = Vec::with_capacity(self.instances.len() * self.solvers.len());
+        let mut runs = measurements.chunks(repetitions);
+        for (instance, map) in self.instances {
+            let first = cells.len();
+
+            for (solver, _) in self.solvers {
+                // Infallible: there is a chunk of repetitions for every instance and solver
+                let runs = runs.next().unwrap();
+                let summarize = |field: fn(&Measurement) -> f64| {
+                    Summary::of(&runs.iter().map(field).collect::<Vec<_>>())
+                };
+                cells.push(Cell {
+                    instance: instance.clone(),
+                    points: map.size(),
+                    solver: solver.clone(),
+                    length: summarize(|run| run.0),
+                    seconds: summarize(|run| run.1),
+                    iterations: summarize(|run| run.2),
+                    lengths: runs.iter().map(|run| run.0).collect(),
+                    gap: 0.0,
+                    above_optimal: None
+                });
+            }
+
+            compare(&mut cells[first..]);
+        }
+
+        Ok(cells)
+    }
+
+    // Tasks are numbered by instance, then solver, then repetition
+    fn measure(&self, task: usize) -> Result<Measurement> {
+        let repetitions = self.repetitions as usize;
+        let repetition = task % repetitions;
+        let (_, solver) = &self.solvers[task / repetitions % self.solvers.len()];
+        let (_, map) = &self.instances[task / repetitions / self.solvers.len()];
+
+        // Repetitions of a seeded benchmark get distinct seeds, or they would all give the same tour
+        let options = SolveOptions {
+            seed: self.options.seed.map(|seed| seed.wrapping_add(repetition as u64)),
+            parallelism: Parallelism::Off,
+            ..self.options.clone()
+        };
+        let result = solver.solve(map, &options)?;
+        Ok((result.length as f64, result.elapsed.as_secs_f64(), result.iterations as f64))
+    }
+}
+
+/// A quantity an [`Experiment`] records for every solve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Length,
+    Seconds,
+    /// The solver's iterations, whose meaning depends on the solver
+    Iterations,
+    /// The length relative to the shortest tour any solver found for the same instance
+    Gap,
+    /// The total of one of the solver's [instrumentation](crate::instrument) counters, which is zero for solvers
+    /// that do not count it
+    Counter(&'static str)
+}
+
+/// One solve of an [`Experiment`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    /// The number of points of the instance
+    pub size: usize,
+    /// Which of the instances of this size was solved, counting from zero
+    pub instance: usize,
+    pub solver: String,
+    pub repetition: u32,
+    /// The value of each of the experiment's metrics, in the same order
+    pub values: Vec<f64>
+}
+
+/// The metrics of every solve of an [`Experiment`], grouped by size, then instance, then solver, then repetition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub metrics: Vec<Metric>,
+    pub rows: Vec<Row>
+}
+
+impl Table {
+    /// The value of the metric for the row, if the experiment recorded it.
+    pub fn value(&self, row: &Row, metric: Metric) -> Option<f64> {
+        self.metrics.iter().position(|&recorded| recorded == metric).map(|index| row.values[index])
+    }
+
+    /// Summarizes the metric over every instance and repetition, for each size and solver in the order of the
+    /// rows. Empty if the experiment did not record it.
+    pub fn summarize(&self, metric: Metric) -> Vec<(usize, String, Summary)> {
+        let mut groups: Vec<(usize, String, Vec<f64>)> = Vec::new();
+        for row in &self.rows {
+            let value = match self.value(row, metric) {
+                Some(value) => value,
+                None => return Vec::new()
+            };
+            match groups.iter_mut().find(|(size, solver, _)| *size == row.size && *solver == row.solver) {
+                Some((.., values)) => values.push(value),
+                None => groups.push((row.size, row.solver.clone(), vec![value]))
+            }
+        }
+
+        groups.into_iter().map(|(size, solver, values)| (size, solver, Summary::of(&values))).collect()
+    }
+}
+
+/// Runs every solver a number of times on instances generated with the given distribution, a number of them for
+/// each size, and records the chosen metrics of every solve. With a seed in the options, the instances are
+/// generated from it and the repetitions seeded as in a [`Benchmark`], which also describes how solves are run in
+/// parallel. Counters are read from a recorder that replaces the instrumentation of the options.
+pub struct Experiment<'a> {
+    pub distribution: &'a (dyn PointDistribution + Sync),
+    pub sizes: &'a [usize],
+    /// The instances generated of every size
+    pub instances: usize,
+    pub repetitions: u32,
+    pub solvers: &'a [(String, Box<dyn Solver + Sync>)],
+    pub metrics: &'a [Metric],
+    pub options: &'a SolveOptions
+}
+
+impl<'a> Experiment<'a> {
+    pub fn run(&self) -> Result<Table> {
+        assert!(self.repetitions > 0, "At least one repetition is required");
+
+        let mut rng = self.options.rng();
+        let mut maps = Vec::with_capacity(self.sizes.len() * self.instances);
+        for &size in self.sizes {
+            for instance in 0..self.instances {
+                maps.push((size, instance, Map::random(size, self.distribution, &mut rng)));
+            }
+        }
+
+        let repetitions = self.repetitions as usize;
+        let tasks = maps.len() * self.solvers.len() * repetitions;
+        let solves = run_tasks(tasks, self.options.parallelism, |task| {
+            let (_, _, map) = &maps[task / repetitions / self.solvers.len()];
+            self.solve(map, task)
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        let mut rows = Vec::with_capacity(tasks);
+        for (task, (length, values)) in solves.iter().enumerate() {
+            let (size, instance, _) = maps[task / repetitions / self.solvers.len()];
+            let (solver, _) = &self.solvers[task / repetitions % self.solvers.len()];
+            let first = task - task % (repetitions * self.solvers.len());
+            let shortest = solves[first..first + repetitions * self.solvers.len()]
+                .iter()
+                .map(|&(length, _)| length)
+                .fold(f64::MAX, f64::min);
+
+            let mut values = values.clone();
+            for (value, &metric) in values.iter_mut().zip(self.metrics) {
+                if metric == Metric::Gap {
+                    *value = if shortest > 0.0 { (length - shortest) / shortest } else { 0.0 };
+                }
+            }
+            rows.push(Row {
+                size,
+                instance,
+                solver: solver.clone(),
+                repetition: (task % repetitions) as u32,
+                values
+            });
+        }
+
+        Ok(Table {
+            metrics: self.metrics.to_vec(),
+            rows
+        })
+    }
+
+    // The length of one solve and its metrics, with the gaps left to be filled in once every solver has run.
+    // Tasks are numbered by instance, then solver, then repetition
+    fn solve(&self, map: &Map, task: usize) -> Result<(f64, Vec<f64>)> {
+        let repetitions = self.repetitions as usize;
+        let repetition = task % repetitions;
+        let (_, solver) = &self.solvers[task / repetitions % self.solvers.len()];
+
+        let recorder = Arc::new(Recorder::new());
+        let counted = self.metrics.iter().any(|metric| matches!(metric, Metric::Counter(_)));
+        let options = SolveOptions {
+            seed: self.options.seed.map(|seed| seed.wrapping_add(repetition as u64)),
+            parallelism: Parallelism::Off,
+            instrumentation: if counted { Some(Arc::clone(&recorder) as _) } else { None },
+            ..self.options.clone()
+        };
+        let result = solver.solve(map, &options)?;
+
+        let values = self.metrics.iter()
+            .map(|&metric| match metric {
+                Metric::Length => result.length as f64,
+                Metric::Seconds => result.elapsed.as_secs_f64(),
+                Metric::Iterations => result.iterations as f64,
+                Metric::Gap => 0.0,
+                Metric::Counter(name) => recorder.counter(name) as f64
+            })
+            .collect();
+        Ok((result.length as f64, values))
+    }
+}
+
+// Fills in the gaps and distances above the optimum of the cells of one instance
+pub(crate) fn compare(cells: &mut [Cell]) {
+    let reference = cells.iter().map(|cell| cell.length.min).fold(f64::MAX, f64::min);
+    for cell in cells {
+        cell.gap = if reference > 0.0 { (cell.length.mean - reference) / reference } else { 0.0 };
+        let optimum = tsplib::optimum(&cell.instance);
+        #[cfg(feature = "catalog")]
+        let optimum = optimum.or_else(|| crate::catalog::optimum(&cell.instance));
+        cell.above_optimal = optimum
+            .map(|optimum| (cell.length.mean - optimum as f64) / optimum as f64);
+    }
+}
@@ -0,0 +1,115 @@
+use clap::ValueEnum;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use traveling_salesman::bench::{Benchmark, Cell};
+use traveling_salesman::error::Result;
+use traveling_salesman::io as tsp_io;
+#[cfg(feature = "parquet")]
+use traveling_salesman::parquet;
+use traveling_salesman::report::Report;
+use traveling_salesman::solve::Solver;
+use traveling_salesman::time::parse_duration;
+
+use crate::{Algorithm, Config, instance_name};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Instances to solve, in TSPLIB, CSV or JSON format, or directories to take every instance from
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Comma separated list of solvers to compare
+    #[arg(short, long, value_enum, value_delimiter = ',', default_values_t = [Algorithm::Nn, Algorithm::Ga])]
+    algo: Vec<Algorithm>,
+    /// How many times to run each solver on each instance
+    #[arg(short, long, default_value_t = 1)]
+    repetitions: u32,
+    /// Time limit for each run
+    #[arg(short, long, value_name = "DURATION", value_parser = parse_duration)]
+    time_limit: Option<Duration>,
+    /// How to lay out the comparison table. Markdown also has a table of every solver's wins and losses
+    #[arg(short, long, value_enum, default_value_t = TableFormat::Markdown)]
+    format: TableFormat,
+    /// Write the table to this file instead of standard output
+    #[arg(short, long)]
+    out: Option<PathBuf>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TableFormat {
+    Markdown,
+    Csv,
+    /// Every statistic of every cell, for loading into pandas or polars
+    #[cfg(feature = "parquet")]
+    Parquet
+}
+
+pub fn run(args: Args, config: &Config) -> Result<()> {
+    let options = config.options(args.time_limit);
+    let instances = instances(&args.inputs)?
+        .iter()
+        .map(|input| Ok((instance_name(input), tsp_io::load_map(input)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let solvers: Vec<(String, Box<dyn Solver + Sync>)> = args.algo
+        .iter()
+        .map(|algo| (algo.name().to_owned(), algo.solver(config) as _))
+        .collect();
+
+    let benchmark = Benchmark {
+        instances: &instances,
+        solvers: &solvers,
+        repetitions: args.repetitions.max(1),
+        options: &options
+    };
+    let cells = benchmark.run()?;
+
+    match &args.out {
+        Some(out) => write_table(io::BufWriter::new(fs::File::create(out)?), cells, args.format),
+        None => write_table(io::stdout().lock(), cells, args.format)
+    }
+}
+
+// Expands directories into the instance files directly inside them, in name order
+fn instances(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if !input.is_dir() {
+            paths.push(input.clone());
+            continue;
+        }
+
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(input)? {
+            let path = entry?.path();
+            let known = path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ["tsp", "csv", "json"].iter().any(|known| ext.eq_ignore_ascii_case(known)))
+                .unwrap_or(false);
+            if path.is_file() && known {
+                entries.push(path);
+            }
+        }
+        entries.sort();
+        paths.extend(entries);
+    }
+
+    Ok(paths)
+}
+
+fn write_table<W: Write>(out: W, cells: Vec<Cell>, format: TableFormat) -> Result<()> {
+    match format {
+        TableFormat::Markdown => Report::from_cells(cells).write_markdown(out),
+        TableFormat::Csv => Report::from_cells(cells).write_csv(out),
+        #[cfg(feature = "parquet")]
+        TableFormat::Parquet => {
+            // The writer must be sendable, which standard output is not
+            let (mut out, mut buffer) = (out, Vec::new());
+            parquet::write_cells(&mut buffer, &cells)?;
+            out.write_all(&buffer)?;
+            out.flush()?;
+            Ok(())
+        }
+    }
+}
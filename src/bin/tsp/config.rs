@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use traveling_salesman::error::{Error, Result};
+use traveling_salesman::ga::{GaSolver, GaSolverBuilder, Settings};
+use traveling_salesman::map::{Decoder, RandomKeyPath};
+use traveling_salesman::solve::SolveOptions;
+
+use crate::Algorithm;
+
+/// Settings for a run read from the TOML file given with `--config`. Flags on the command line take precedence.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The solver used when `--algo` is not given
+    pub algo: Option<Algorithm>,
+    /// Seeds instance generation and every solver
+    pub seed: Option<u64>,
+    pub options: SolveOptions,
+    pub ga: GaConfig
+}
+
+/// The `[ga]` table, configuring the roulette wheel GA.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GaConfig {
+    pub settings: Settings,
+    pub population: Option<usize>,
+    pub generations: Option<u32>,
+    pub fix: Option<bool>,
+    /// How the random keys are turned into tours, `sorted` unless given
+    pub decoder: Option<Decoder>
+}
+
+impl Config {
+    /// Reads the file, failing if the GA settings in it do not [validate](Settings::validate) or the GA population
+    /// is smaller than two.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&text).map_err(|error| {
+            let line = error.span().map(|span| text[..span.start].matches('\n').count() + 1).unwrap_or(0);
+            Error::Parse {
+                line,
+                message: error.message().to_owned()
+            }
+        })?;
+        config.ga.settings.validate()?;
+        if let Some(population) = config.ga.population.filter(|&population| population < 2) {
+            return Err(Error::InvalidSettings(format!(
+                "the [ga] population is {}, but a population needs at least two individuals",
+                population
+            )));
+        }
+        Ok(config)
+    }
+
+    /// The configured options and seed, with the time limit replaced if one was given on the command line.
+    pub fn options(&self, time_limit: Option<Duration>) -> SolveOptions {
+        SolveOptions {
+            time_limit: time_limit.or(self.options.time_limit),
+            seed: self.seed,
+            ..self.options.clone()
+        }
+    }
+}
+
+impl GaConfig {
+    /// A GA builder with fixing enabled and any configured values applied.
+    pub fn builder(&self) -> GaSolverBuilder<RandomKeyPath> {
+        let mut builder = GaSolver::builder().settings(self.settings.clone()).fix(self.fix.unwrap_or(true));
+        if let Some(population) = self.population {
+            builder = builder.population(population);
+        }
+        if let Some(generations) = self.generations {
+            builder = builder.generations(generations);
+        }
+        if self.decoder == Some(Decoder::Greedy) {
+            builder = builder.chromosome(RandomKeyPath::greedy);
+        }
+        builder
+    }
+}
@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::PathBuf;
+
+use traveling_salesman::error::Result;
+use traveling_salesman::io::{self as tsp_io, Format};
+
+use crate::{FileFormat, instance_name};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The instance to convert, or the one the tour belongs to
+    #[arg(short, long)]
+    input: PathBuf,
+    /// Convert this tour of the instance instead of the instance itself
+    #[arg(short, long)]
+    tour: Option<PathBuf>,
+    /// Write to this file instead of standard output
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// The format of the file being converted. Defaults to the format of its extension
+    #[arg(long, value_enum)]
+    from: Option<FileFormat>,
+    /// Defaults to the format of the output file's extension, or TSPLIB when printing
+    #[arg(long, value_enum)]
+    to: Option<FileFormat>
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let name = instance_name(args.tour.as_ref().unwrap_or(&args.input));
+    let to = match (args.to, &args.out) {
+        (Some(format), _) => Format::from(format),
+        (None, Some(out)) => Format::from_path(out),
+        (None, None) => Format::Tsplib
+    };
+    let out: Box<dyn io::Write> = match &args.out {
+        Some(out) => Box::new(BufWriter::new(File::create(out)?)),
+        None => Box::new(io::stdout().lock())
+    };
+
+    // The format given only applies to the file being converted, so an instance read for a tour goes by its name
+    match &args.tour {
+        Some(path) => {
+            let map = tsp_io::load_map(&args.input)?;
+            let from = args.from.map(Format::from).unwrap_or_else(|| Format::from_path(path));
+            let tour = tsp_io::read_tour(BufReader::new(File::open(path)?), &map, from)?;
+            tsp_io::write_tour(out, &tour, &name, to)
+        },
+        None => {
+            let from = args.from.map(Format::from).unwrap_or_else(|| Format::from_path(&args.input));
+            let map = tsp_io::read_map(BufReader::new(File::open(&args.input)?), from)?;
+            tsp_io::write_map(out, &map, &name, to)
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use ratatui::crossterm::event::{self, Event, KeyCode};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Color;
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::canvas::{self, Canvas, Points};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use traveling_salesman::error::Result;
+use traveling_salesman::map::Map;
+use traveling_salesman::solve::{Broadcast, CancelToken, Progress, ProgressEvent, SolveOptions, SolveResult};
+
+// How often the screen is redrawn, which is also how long a key press can take to register
+const REFRESH: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct State {
+    best: Option<f32>,
+    diversity: Option<f32>,
+    generation: u32,
+    generations: u32,
+    tour: Vec<usize>
+}
+
+/// A full screen view of a running GA, fed by its progress events. Pressing `q` or escape cancels the solve.
+struct Dashboard {
+    state: Mutex<State>,
+    start: Instant
+}
+
+impl Progress for Dashboard {
+    fn report(&self, event: ProgressEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            ProgressEvent::Improvement { length, .. } => state.best = Some(length),
+            ProgressEvent::Generation { generation, generations } => {
+                state.generation = generation;
+                state.generations = generations;
+            },
+            ProgressEvent::Diversity(diversity) => state.diversity = Some(diversity),
+            _ => {}
+        }
+    }
+
+    fn tour(&self, tour: &[usize]) {
+        let mut state = self.state.lock().unwrap();
+        state.tour.clear();
+        state.tour.extend_from_slice(tour);
+    }
+}
+
+/// Runs `solve` with options reporting to a dashboard drawn on the terminal until it returns. Observers already
+/// in `options` keep receiving events.
+pub fn run<F>(map: &Map, options: SolveOptions, solve: F) -> Result<SolveResult>
+where
+    F: FnOnce(&SolveOptions) -> Result<SolveResult>
+{
+    let dashboard = Arc::new(Dashboard {
+        state: Mutex::new(State::default()),
+        start: Instant::now()
+    });
+    let mut observers: Vec<Arc<dyn Progress>> = vec![Arc::clone(&dashboard) as Arc<dyn Progress>];
+    observers.extend(options.progress.clone());
+    let cancel = options.cancel.clone().unwrap_or_default();
+    let options = SolveOptions {
+        cancel: Some(cancel.clone()),
+        progress: Some(Arc::new(Broadcast(observers))),
+        ..options
+    };
+
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let ui = scope.spawn(|| {
+            let mut terminal = ratatui::init();
+            let result = draw_until(&mut terminal, map, &dashboard, &cancel, &done);
+            ratatui::restore();
+            result
+        });
+
+        let result = solve(&options);
+        done.store(true, Ordering::Relaxed);
+        // Infallible: drawing does not panic, and a failed terminal only loses the display
+        ui.join().unwrap().ok();
+        result
+    })
+}
+
+fn draw_until(
+    terminal: &mut DefaultTerminal,
+    map: &Map,
+    dashboard: &Dashboard,
+    cancel: &CancelToken,
+    done: &AtomicBool
+) -> std::io::Result<()> {
+    while !done.load(Ordering::Relaxed) {
+        terminal.draw(|frame| draw(frame, map, dashboard))?;
+        if event::poll(REFRESH)? {
+            if let Event::Key(key) = event::read()? {
+                if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
+                    cancel.cancel();
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn draw(frame: &mut Frame<'_>, map: &Map, dashboard: &Dashboard) {
+    let state = dashboard.state.lock().unwrap();
+    let elapsed = dashboard.start.elapsed().as_secs_f64();
+    let rate = if elapsed > 0.0 { state.generation as f64 / elapsed } else { 0.0 };
+    let format = |value: Option<f32>, precision: usize| {
+        value.map(|value| format!("{:.*}", precision, value)).unwrap_or_else(|| "-".to_owned())
+    };
+
+    let [stats, plot] = Layout::vertical([Constraint::Length(6), Constraint::Min(0)]).areas(frame.area());
+    let lines = vec![
+        Line::from(format!("best length  {}", format(state.best, 4))),
+        Line::from(format!("diversity    {}", format(state.diversity.map(|diversity| diversity * 100.0), 1) + "%")),
+        Line::from(format!("generation   {} / {}", state.generation, state.generations)),
+        Line::from(format!("rate         {:.1} generations/s", rate))
+    ];
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" GA (q to stop) ")), stats);
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for point in map {
+        min_x = min_x.min(point.x as f64);
+        min_y = min_y.min(point.y as f64);
+        max_x = max_x.max(point.x as f64);
+        max_y = max_y.max(point.y as f64);
+    }
+
+    let coords: Vec<(f64, f64)> = map.iter().map(|point| (point.x as f64, point.y as f64)).collect();
+    let canvas = Canvas::default()
+        .block(Block::bordered().title(" best tour "))
+        .marker(Marker::Braille)
+        .x_bounds([min_x, max_x])
+        .y_bounds([min_y, max_y])
+        .paint(|context| {
+            for edge in state.tour.windows(2) {
+                let ((x1, y1), (x2, y2)) = (coords[edge[0]], coords[edge[1]]);
+                context.draw(&canvas::Line::new(x1, y1, x2, y2, Color::Cyan));
+            }
+            context.draw(&Points {
+                coords: &coords,
+                color: Color::White
+            });
+        });
+    frame.render_widget(canvas, plot);
+}
@@ -0,0 +1,57 @@
+use clap::ValueEnum;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use traveling_salesman::error::Result;
+use traveling_salesman::explain::{self, Explanation};
+use traveling_salesman::io as tsp_io;
+use traveling_salesman::solve::{SolveResult, nearest_neighbor};
+use traveling_salesman::time::parse_duration;
+
+use crate::Config;
+
+/// The solvers that can explain their decisions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Explained {
+    /// Nearest neighbor construction
+    Nn,
+    /// 2-opt local search from the nearest neighbor tour
+    #[value(name = "2opt")]
+    TwoOpt,
+    /// Branch-and-bound, on at most 10 points
+    Bb
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The instance to solve, best kept small
+    #[arg(short, long)]
+    input: PathBuf,
+    #[arg(short, long, value_enum, default_value_t = Explained::Nn)]
+    algo: Explained,
+    /// Stop after this long (e.g. `30s`, `500ms`, `2m`)
+    #[arg(short, long, value_name = "DURATION", value_parser = parse_duration)]
+    time_limit: Option<Duration>,
+    /// Print every step as a line of JSON instead of in words
+    #[arg(long)]
+    json: bool
+}
+
+pub fn run(args: Args, config: &Config) -> Result<()> {
+    let map = tsp_io::load_map(&args.input)?;
+    let options = config.options(args.time_limit);
+    let (result, explanation): (SolveResult, Explanation) = match args.algo {
+        Explained::Nn => explain::explain_nearest_neighbor(&map)?,
+        Explained::TwoOpt => explain::explain_two_opt(&map, &nearest_neighbor(&map)?.tour, &options)?,
+        Explained::Bb => explain::explain_branch_and_bound(&map, &options)?
+    };
+
+    if args.json {
+        explanation.write_json_lines(io::stdout().lock())?;
+    } else {
+        explanation.write_text(io::stdout().lock())?;
+        println!("length: {}", result.length);
+    }
+    Ok(())
+}
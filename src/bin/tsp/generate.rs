@@ -0,0 +1,66 @@
+use clap::ValueEnum;
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use std::io;
+use std::path::PathBuf;
+
+use traveling_salesman::error::Result;
+use traveling_salesman::generate::{self, Distribution};
+use traveling_salesman::io::{self as tsp_io, Format};
+
+use crate::{Config, FileFormat, instance_name};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The number of points
+    #[arg(short = 'n', long)]
+    points: usize,
+    /// How the points are spread out
+    #[arg(short, long, value_enum, default_value_t = Spread::Disc)]
+    distribution: Spread,
+    /// The number of clusters for the clustered distribution. Defaults to one per fifty points
+    #[arg(short, long)]
+    clusters: Option<usize>,
+    /// Write the instance to this file instead of standard output
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// Defaults to the format of the output file's extension, or TSPLIB when printing
+    #[arg(short, long, value_enum)]
+    format: Option<FileFormat>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Spread {
+    /// Uniform in polar coordinates on the unit disc
+    Disc,
+    /// Uniform over the unit square
+    Uniform,
+    /// Normally distributed around random centers
+    Clustered,
+    /// A square grid
+    Grid
+}
+
+pub fn run(args: Args, config: &Config) -> Result<()> {
+    let distribution = match args.distribution {
+        Spread::Disc => Distribution::Disc,
+        Spread::Uniform => Distribution::Uniform,
+        Spread::Clustered => Distribution::clustered(args.clusters.unwrap_or(args.points / 50)),
+        Spread::Grid => Distribution::Grid
+    };
+
+    let mut rng = ChaCha20Rng::seed_from_u64(config.seed.unwrap_or_else(random));
+    let map = generate::generate(args.points, distribution, &mut rng);
+
+    match &args.out {
+        Some(out) => {
+            let format = args.format.map(Format::from).unwrap_or_else(|| Format::from_path(out));
+            let file = io::BufWriter::new(std::fs::File::create(out)?);
+            tsp_io::write_map(file, &map, &instance_name(out), format)
+        },
+        None => {
+            let format = args.format.map(Format::from).unwrap_or(Format::Tsplib);
+            tsp_io::write_map(io::stdout().lock(), &map, "random", format)
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use clap::ValueEnum;
+use rand::{SeedableRng, random};
+use rand_chacha::ChaCha20Rng;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use traveling_salesman::checkpoint::GaCheckpoint;
+use traveling_salesman::error::Result;
+use traveling_salesman::history::History;
+use traveling_salesman::ga::warm_start;
+use traveling_salesman::io as tsp_io;
+use traveling_salesman::local::{lin_kernighan, or_opt, two_opt, uncross};
+use traveling_salesman::map::{Map, Path, RandomKeyPath};
+use traveling_salesman::solve::{Progress, SolveOptions, Termination};
+use traveling_salesman::telemetry::Telemetry;
+use traveling_salesman::time::{Instant, parse_duration};
+
+use crate::progress::Bar;
+use crate::{Config, instance_name};
+
+/// Local search passes that `--moves` runs instead of the GA.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Move {
+    /// Reverse segments of the tour
+    #[value(name = "2opt")]
+    TwoOpt,
+    /// Move runs of up to three points elsewhere
+    #[value(name = "oropt")]
+    OrOpt,
+    /// Lin-Kernighan chains of flips
+    Lk,
+    /// Reverse only the segments between crossing edges
+    Uncross
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The instance the tour belongs to
+    #[arg(short, long)]
+    input: PathBuf,
+    /// The tour to improve
+    #[arg(short, long)]
+    tour: PathBuf,
+    /// Where to write the improved tour. Defaults to overwriting the input tour
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// Polish the tour with these local search passes, in order, repeated until a round no longer shortens it,
+    /// instead of running the GA
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "MOVES",
+        conflicts_with_all = ["population", "generations", "history", "telemetry"]
+    )]
+    moves: Option<Vec<Move>>,
+    /// Defaults to the config file's GA population, or 100
+    #[arg(short, long)]
+    population: Option<usize>,
+    /// Defaults to the config file's GA generations, or 1000
+    #[arg(short, long)]
+    generations: Option<u32>,
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    time_limit: Option<Duration>,
+    /// Write the best and mean length of each generation to this CSV file
+    #[arg(long, value_name = "CSV")]
+    history: Option<PathBuf>,
+    /// Stream every generation and improvement to this file as JSON Lines while the GA runs
+    #[arg(long, value_name = "JSONL")]
+    telemetry: Option<PathBuf>
+}
+
+pub fn run(args: Args, config: &Config) -> Result<()> {
+    let map = tsp_io::load_map(&args.input)?;
+    let tour = tsp_io::load_tour(&args.tour, &map)?;
+    let before = tour.evaluate(&map);
+    let (length, improved) = match &args.moves {
+        Some(moves) => polish(&map, tour.clone(), moves, &config.options(args.time_limit))?,
+        None => evolve(&map, &tour, &args, config)?
+    };
+
+    let out = args.out.as_ref().unwrap_or(&args.tour);
+    if length < before {
+        tsp_io::save_tour(out, &improved, &instance_name(&args.input))?;
+        println!("improved: {} -> {}", before, length);
+    } else {
+        if args.out.is_some() {
+            tsp_io::save_tour(out, &tour, &instance_name(&args.input))?;
+        }
+        println!("no improvement: {}", before);
+    }
+
+    Ok(())
+}
+
+// Runs the passes over the tour in order until a round of them no longer shortens it or the time limit runs out,
+// returning the length and tour
+fn polish(map: &Map, mut tour: Vec<usize>, moves: &[Move], options: &SolveOptions) -> Result<(f32, Vec<usize>)> {
+    let start = Instant::now();
+    let mut length = tour.evaluate(map);
+    let bar = Bar::new();
+    'rounds: loop {
+        let before = length;
+        for &pass in moves {
+            // Every pass gets what is left of the time limit
+            let options = SolveOptions {
+                time_limit: options.time_limit.map(|limit| limit.saturating_sub(start.elapsed())),
+                ..bar.attach(options.clone(), &[])
+            };
+            let result = match pass {
+                Move::TwoOpt => two_opt(map, &tour, &options),
+                Move::OrOpt => or_opt(map, &tour, &options),
+                Move::Lk => lin_kernighan(map, &tour, &options),
+                Move::Uncross => uncross(map, &tour, &options)
+            };
+            let result = result.inspect_err(|_| bar.finish())?;
+            if result.length < length {
+                length = result.length;
+                tour = result.tour;
+            }
+            if result.termination != Termination::Completed {
+                break 'rounds;
+            }
+        }
+        if length >= before {
+            break;
+        }
+    }
+    bar.finish();
+    Ok((length, tour))
+}
+
+// Runs the GA from a population seeded with the tour, returning the length and tour of the best it finds
+fn evolve(map: &Map, tour: &[usize], args: &Args, config: &Config) -> Result<(f32, Vec<usize>)> {
+    // Seed a tenth of a random population with the tour and let the GA recombine it with the rest
+    let size = args.population.or(config.ga.population).unwrap_or(100).max(2);
+    // Draw the initial keys from the same stream the GA continues with
+    let seed = config.seed.unwrap_or_else(random);
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut population: Vec<RandomKeyPath> = (0..size).map(|_| RandomKeyPath::with_rng(map, &mut rng)).collect();
+    let copies = (population.len() / 10).max(1);
+    warm_start(map, &mut population, tour, copies)?;
+
+    let solver = config.ga.builder()
+        .population(population.len())
+        .generations(args.generations.or(config.ga.generations).unwrap_or(1000))
+        .build();
+    let mut checkpoint = GaCheckpoint::new(population, seed);
+    checkpoint.capture_rng(&rng)?;
+    let history = args.history.as_ref().map(|_| Arc::new(History::new()));
+    let telemetry = args.telemetry.as_ref().map(Telemetry::create).transpose()?.map(Arc::new);
+    let mut observers: Vec<Arc<dyn Progress>> = Vec::new();
+    observers.extend(history.clone().map(|history| history as Arc<dyn Progress>));
+    observers.extend(telemetry.clone().map(|telemetry| telemetry as Arc<dyn Progress>));
+    let bar = Bar::new();
+    let result = solver.resume(map, &bar.attach(config.options(args.time_limit), &observers), &mut checkpoint);
+    bar.finish();
+    let result = result?;
+    if let Some(telemetry) = &telemetry {
+        telemetry.finish()?;
+    }
+    if let (Some(path), Some(history)) = (&args.history, &history) {
+        history.save_csv(path)?;
+    }
+    Ok((result.length, result.tour))
+}
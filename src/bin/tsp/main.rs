@@ -0,0 +1,287 @@
+mod bench;
+mod config;
+mod convert;
+#[cfg(feature = "tui")]
+mod dashboard;
+mod explain;
+mod generate;
+mod improve;
+mod progress;
+#[cfg(feature = "server")]
+mod serve;
+mod solve;
+mod visualize;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use config::Config;
+use serde::Deserialize;
+use traveling_salesman::error::Error;
+use traveling_salesman::io::Format;
+use traveling_salesman::island::IslandGa;
+use traveling_salesman::local::SimulatedAnnealing;
+use traveling_salesman::portfolio::Portfolio;
+use traveling_salesman::registry::Registry;
+use traveling_salesman::solve::*;
+use std::path::PathBuf;
+use std::process;
+
+// Listed under `--help`, since scripts rely on them
+const EXIT_CODES: &str = "Exit codes:
+  0  success
+  1  internal error, such as a file that could not be read or written
+  2  invalid command line
+  3  invalid input: an instance or tour that could not be parsed or does not fit together
+  4  infeasible: no tour satisfies the constraints, or the problem contradicts itself
+  5  stopped by the time limit or cancelled, with the best tour found so far";
+
+/// Solve, generate, and inspect traveling salesman instances.
+#[derive(Parser)]
+#[command(name = "tsp", version, after_help = EXIT_CODES)]
+struct Cli {
+    /// Read solver settings, seeds and budgets from a TOML file
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Seed every random choice, so that a run can be repeated exactly. Overrides the config file's seed
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+    /// How many threads branch-and-bound and benchmarks may use: `off`, `all`, or a number
+    #[arg(long, global = true, value_name = "THREADS")]
+    threads: Option<Parallelism>,
+    /// How to print errors to standard error
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    errors: ErrorFormat,
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve an instance and print or save the tour
+    Solve(solve::Args),
+    /// Write a random instance in TSPLIB, CSV or JSON format
+    Generate(generate::Args),
+    /// Compare solvers over instances or directories of instances
+    Bench(bench::Args),
+    /// Try to shorten an existing tour
+    Improve(improve::Args),
+    /// Translate an instance or tour between the TSPLIB, CSV and JSON formats
+    Convert(convert::Args),
+    /// Draw an instance and optionally a tour as SVG
+    Visualize(visualize::Args),
+    /// Print every decision nearest neighbor, 2-opt or branch-and-bound makes on a small instance
+    Explain(explain::Args),
+    /// Accept instances over HTTP and solve them in the background
+    #[cfg(feature = "server")]
+    Serve(serve::Args)
+}
+
+/// The solvers selectable from the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    /// Branch-and-bound (exact)
+    Bb,
+    /// Brute force (exact)
+    Bf,
+    /// Held-Karp dynamic programming (exact, 25 points in 1 GiB, 32 at most)
+    Hk,
+    /// Nearest neighbor construction
+    Nn,
+    /// 2-opt local search from the nearest neighbor tour
+    #[value(name = "2opt")]
+    #[serde(rename = "2opt")]
+    TwoOpt,
+    /// Lin-Kernighan local search from the nearest neighbor tour
+    Lk,
+    /// Lin-Kernighan over alpha-nearness candidates from minimum 1-trees
+    #[value(name = "lk-alpha")]
+    #[serde(rename = "lk-alpha")]
+    LkAlpha,
+    /// Simulated annealing from the nearest neighbor tour
+    Sa,
+    /// Roulette wheel GA over random keys
+    Ga,
+    /// Island model GA, populations in a ring exchanging their best
+    Island,
+    /// Biased random-key GA
+    Brkga,
+    /// Differential evolution
+    De,
+    /// Edge histogram EDA
+    Eda,
+    /// The GA, simulated annealing and Lin-Kernighan at once, sharing the best tour
+    Portfolio
+}
+
+impl Algorithm {
+    /// The name the algorithm is selected by on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Bb => "bb",
+            Algorithm::Bf => "bf",
+            Algorithm::Hk => "hk",
+            Algorithm::Nn => "nn",
+            Algorithm::TwoOpt => "2opt",
+            Algorithm::Lk => "lk",
+            Algorithm::LkAlpha => "lk-alpha",
+            Algorithm::Sa => "sa",
+            Algorithm::Ga => "ga",
+            Algorithm::Island => "island",
+            Algorithm::Brkga => "brkga",
+            Algorithm::De => "de",
+            Algorithm::Eda => "eda",
+            Algorithm::Portfolio => "portfolio"
+        }
+    }
+
+    /// The solver, with the GA configured from the `[ga]` table of the config file.
+    pub fn solver(self, config: &Config) -> Box<dyn Solver + Send + Sync> {
+        // Infallible: every algorithm is registered under its name
+        registry(config).create(self.name()).unwrap()
+    }
+}
+
+/// The built-in solvers, with the GA and those built on it configured from the `[ga]` table of the config file.
+pub fn registry(config: &Config) -> Registry {
+    let mut registry = Registry::builtin();
+    let (ga, island, portfolio) = (config.ga.clone(), config.ga.clone(), config.ga.clone());
+    registry
+        .register("ga", "Roulette wheel GA over random keys", move || ga.builder().build())
+        .register("island", "Island model GA, populations in a ring exchanging their best", move || {
+            IslandGa::new(island.builder().build())
+        })
+        .register("portfolio", "The GA, simulated annealing and Lin-Kernighan at once, sharing the best tour", move || {
+            Portfolio::new(portfolio.builder().build(), SimulatedAnnealing::default())
+        });
+    registry
+}
+
+/// How errors are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ErrorFormat {
+    /// `error: ` followed by the message
+    Text,
+    /// An object with the error's `kind`, `message` and exit `code`, on one line
+    Json
+}
+
+/// The outcomes `tsp` exits with, so that scripts can tell them apart. Usage errors exit with 2 from clap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Exit {
+    Success = 0,
+    Internal = 1,
+    Input = 3,
+    Infeasible = 4,
+    Stopped = 5
+}
+
+impl Exit {
+    fn of(error: &Error) -> Self {
+        match error {
+            Error::EmptyMap
+            | Error::NonFinitePoint { .. }
+            | Error::NanDistance { .. }
+            | Error::DimensionMismatch { .. }
+            | Error::InvalidTour(_)
+            | Error::Parse { .. }
+            | Error::UnknownSolver(_)
+            | Error::InvalidSettings(_)
+            | Error::TooLarge(_) => Exit::Input,
+            Error::InvalidProblem(_) => Exit::Infeasible,
+            _ => Exit::Internal
+        }
+    }
+
+    // The name of the kind of error in `--errors json`
+    fn kind(self) -> &'static str {
+        match self {
+            Exit::Success => "success",
+            Exit::Internal => "internal",
+            Exit::Input => "input",
+            Exit::Infeasible => "infeasible",
+            Exit::Stopped => "stopped"
+        }
+    }
+}
+
+impl From<Termination> for Exit {
+    fn from(termination: Termination) -> Self {
+        match termination {
+            Termination::Completed | Termination::TargetGap => Exit::Success,
+            Termination::TimeLimit | Termination::Cancelled | Termination::EvaluationLimit => Exit::Stopped
+        }
+    }
+}
+
+/// File formats for instances and tours. Defaults to guessing from the file extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FileFormat {
+    Tsplib,
+    Csv,
+    Json
+}
+
+impl From<FileFormat> for Format {
+    fn from(format: FileFormat) -> Self {
+        match format {
+            FileFormat::Tsplib => Format::Tsplib,
+            FileFormat::Csv => Format::Csv,
+            FileFormat::Json => Format::Json
+        }
+    }
+}
+
+/// The file name without its extension, used to name written instances and tours.
+pub fn instance_name(path: &std::path::Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "instance".to_owned())
+}
+
+fn main() {
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt::init();
+
+    let cli = Cli::parse();
+    let errors = cli.errors;
+    let exit = run_command(cli).unwrap_or_else(|error| {
+        let exit = Exit::of(&error);
+        match errors {
+            ErrorFormat::Text => eprintln!("error: {}", error),
+            ErrorFormat::Json => {
+                let report = serde_json::json!({
+                    "kind": exit.kind(),
+                    "message": error.to_string(),
+                    "code": exit as i32
+                });
+                eprintln!("{}", report);
+            }
+        }
+        exit
+    });
+    process::exit(exit as i32);
+}
+
+fn run_command(cli: Cli) -> Result<Exit, Error> {
+    let mut config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default()
+    };
+    if cli.seed.is_some() {
+        config.seed = cli.seed;
+    }
+    if let Some(parallelism) = cli.threads {
+        config.options.parallelism = parallelism;
+    }
+
+    match cli.command {
+        Command::Solve(args) => return solve::run(args, &config).map(Exit::from),
+        Command::Generate(args) => generate::run(args, &config),
+        Command::Bench(args) => bench::run(args, &config),
+        Command::Improve(args) => improve::run(args, &config),
+        Command::Convert(args) => convert::run(args),
+        Command::Visualize(args) => visualize::run(args),
+        Command::Explain(args) => explain::run(args, &config),
+        #[cfg(feature = "server")]
+        Command::Serve(args) => serve::run(args, &config)
+    }?;
+    Ok(Exit::Success)
+}
@@ -0,0 +1,101 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use traveling_salesman::solve::{Broadcast, Progress, ProgressEvent, SolveOptions};
+
+// Completion fractions are shown in steps of a tenth of a percent
+const COMPLETION_STEPS: u64 = 1000;
+
+/// A terminal progress bar fed by a solver's progress events. It starts as a spinner showing the best length
+/// and becomes a bar once the solver reports generations or completion. Nothing is drawn unless standard error
+/// is a terminal.
+pub struct Bar {
+    bar: ProgressBar,
+    styled: AtomicBool,
+    best: Mutex<Option<f32>>,
+    nodes: AtomicU64
+}
+
+impl Bar {
+    pub fn new() -> Arc<Self> {
+        let bar = ProgressBar::new_spinner();
+        // Infallible: the template is a constant
+        bar.set_style(ProgressStyle::with_template("{spinner} [{elapsed_precise}] {msg}").unwrap());
+        bar.enable_steady_tick(Duration::from_millis(100));
+
+        Arc::new(Bar {
+            bar,
+            styled: AtomicBool::new(false),
+            best: Mutex::new(None),
+            nodes: AtomicU64::new(0)
+        })
+    }
+
+    /// Options which report to this bar and to the other observers, with the other fields taken from `options`.
+    pub fn attach(self: &Arc<Self>, options: SolveOptions, others: &[Arc<dyn Progress>]) -> SolveOptions {
+        let mut observers: Vec<Arc<dyn Progress>> = vec![Arc::clone(self) as Arc<dyn Progress>];
+        observers.extend(others.iter().cloned());
+        SolveOptions {
+            progress: Some(Arc::new(Broadcast(observers))),
+            ..options
+        }
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+
+    fn style(&self, template: &str, length: u64) {
+        if !self.styled.swap(true, Ordering::Relaxed) {
+            // Infallible: the templates are constants
+            self.bar.set_style(ProgressStyle::with_template(template).unwrap().progress_chars("=> "));
+            self.bar.set_length(length);
+        }
+    }
+
+    fn update_message(&self) {
+        let mut message = String::new();
+        if let Some(best) = *self.best.lock().unwrap() {
+            message.push_str(&format!("best {:.4}", best));
+        }
+
+        let nodes = self.nodes.load(Ordering::Relaxed);
+        if nodes > 0 {
+            message.push_str(&format!(", {} nodes", nodes));
+        }
+        self.bar.set_message(message);
+    }
+}
+
+impl Progress for Bar {
+    fn detailed(&self) -> bool {
+        false
+    }
+
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Improvement { length, .. } => {
+                *self.best.lock().unwrap() = Some(length);
+                self.update_message();
+            },
+            ProgressEvent::Generation { generation, generations } => {
+                let template = "[{elapsed_precise}] {wide_bar} {pos}/{len} generations, eta {eta} {msg}";
+                self.style(template, generations as u64);
+                self.bar.set_position(generation as u64);
+            },
+            // The covered fraction includes pruned subtrees, so the ETA follows the pruning rate
+            ProgressEvent::Completion(fraction) => {
+                self.style("[{elapsed_precise}] {wide_bar} {percent}%, eta {eta} {msg}", COMPLETION_STEPS);
+                self.bar.set_position((fraction.clamp(0.0, 1.0) as f64 * COMPLETION_STEPS as f64) as u64);
+            },
+            ProgressEvent::Nodes(nodes) => {
+                self.nodes.store(nodes, Ordering::Relaxed);
+                self.update_message();
+            },
+            ProgressEvent::Sample { .. } | ProgressEvent::Diversity(_) => {}
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use clap::ValueEnum;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tiny_http::{Header, Method, Request, Response, Server};
+use traveling_salesman::error::{Error, Result};
+use traveling_salesman::io::{self as tsp_io, Format};
+use traveling_salesman::map::Map;
+use traveling_salesman::solve::{CancelToken, ProgressEvent, SolveOptions, SolveResult};
+use traveling_salesman::time::{Instant, parse_duration};
+
+use crate::{Algorithm, Config};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    address: String,
+    /// Time limit for jobs which do not set one. Defaults to the config file's limit
+    #[arg(short, long, value_name = "DURATION", value_parser = parse_duration)]
+    time_limit: Option<Duration>
+}
+
+enum Status {
+    Running,
+    Done(SolveResult),
+    Failed(String)
+}
+
+struct Job {
+    name: String,
+    algo: Algorithm,
+    started: Instant,
+    best: Option<f32>,
+    status: Status,
+    cancel: CancelToken
+}
+
+type Jobs = Mutex<HashMap<u64, Job>>;
+
+/// Serves solves over HTTP:
+///
+/// - `POST /solve?algo=lk&time_limit=10s&name=...` with a TSPLIB, CSV or JSON instance as the body starts a job and
+///   responds with its id. The format is taken from the content type, or guessed from the body.
+/// - `GET /solve/{id}` reports whether the job is running, done or failed, and the best length so far.
+/// - `GET /solve/{id}/tour` responds with the full result once the job is done.
+/// - `DELETE /solve/{id}` stops a running job early, keeping the best tour found so far.
+pub fn run(args: Args, config: &Config) -> Result<()> {
+    let server = Server::http(&args.address).map_err(|error| Error::Io {
+        kind: std::io::ErrorKind::AddrNotAvailable,
+        message: error.to_string()
+    })?;
+    eprintln!("listening on http://{}", args.address);
+
+    let jobs: Arc<Jobs> = Arc::default();
+    let mut next_id = 0u64;
+    thread::scope(|scope| {
+        for mut request in server.incoming_requests() {
+            let path = request.url().split('?').next().unwrap_or("").to_owned();
+            let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+            let method = request.method().clone();
+            let (status, body) = match (&method, segments.as_slice()) {
+                (Method::Post, ["solve"]) => {
+                    let id = next_id;
+                    next_id += 1;
+                    submit(scope, &jobs, id, &mut request, &args, config)
+                },
+                (method, ["solve", id, rest @ ..]) => match id.parse() {
+                    Ok(id) => lookup(&jobs, id, method, rest),
+                    Err(_) => not_found()
+                },
+                _ => not_found()
+            };
+
+            // Infallible: the header is a constant
+            let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+            let response = Response::from_string(body.to_string()).with_status_code(status).with_header(header);
+            // The client may have hung up, which only affects that client
+            let _ = request.respond(response);
+        }
+    });
+
+    Ok(())
+}
+
+// Starts solving the instance in the request's body on a new thread
+fn submit<'scope>(
+    scope: &'scope thread::Scope<'scope, '_>,
+    jobs: &Arc<Jobs>,
+    id: u64,
+    request: &mut Request,
+    args: &Args,
+    config: &'scope Config
+) -> (u16, Value) {
+    let query = query(request.url());
+    let algo = match query.get("algo").map(|algo| Algorithm::from_str(algo, true)) {
+        None => config.algo.unwrap_or(Algorithm::Ga),
+        Some(Ok(algo)) => algo,
+        Some(Err(message)) => return (400, json!({ "error": message }))
+    };
+    let time_limit = match query.get("time_limit").map(|limit| parse_duration(limit)) {
+        None => args.time_limit,
+        Some(Ok(limit)) => Some(limit),
+        Some(Err(message)) => return (400, json!({ "error": message }))
+    };
+
+    let mut body = String::new();
+    if let Err(error) = request.as_reader().read_to_string(&mut body) {
+        return (400, json!({ "error": error.to_string() }));
+    }
+    let content_type = request.headers()
+        .iter()
+        .find(|header| header.field.equiv("Content-Type"))
+        .map(|header| header.value.as_str().to_ascii_lowercase())
+        .unwrap_or_default();
+    let format = if content_type.contains("json") || body.trim_start().starts_with('{') {
+        Format::Json
+    } else if content_type.contains("csv") {
+        Format::Csv
+    } else {
+        Format::Tsplib
+    };
+    let map: Map = match tsp_io::read_map(body.as_bytes(), format) {
+        Ok(map) => map,
+        Err(error) => return (400, json!({ "error": error.to_string() }))
+    };
+
+    let cancel = CancelToken::new();
+    let job = Job {
+        name: query.get("name").cloned().unwrap_or_else(|| format!("job-{}", id)),
+        algo,
+        started: Instant::now(),
+        best: None,
+        status: Status::Running,
+        cancel: cancel.clone()
+    };
+    jobs.lock().unwrap().insert(id, job);
+
+    let progress_jobs = Arc::clone(jobs);
+    let options = SolveOptions {
+        cancel: Some(cancel),
+        progress: Some(Arc::new(move |event| {
+            if let ProgressEvent::Improvement { length, .. } = event {
+                if let Some(job) = progress_jobs.lock().unwrap().get_mut(&id) {
+                    job.best = Some(length);
+                }
+            }
+        })),
+        ..config.options(time_limit)
+    };
+    let jobs = Arc::clone(jobs);
+    scope.spawn(move || {
+        let status = match algo.solver(config).solve(&map, &options) {
+            Ok(result) => Status::Done(result),
+            Err(error) => Status::Failed(error.to_string())
+        };
+        if let Some(job) = jobs.lock().unwrap().get_mut(&id) {
+            job.status = status;
+        }
+    });
+
+    (202, json!({ "id": id }))
+}
+
+fn lookup(jobs: &Jobs, id: u64, method: &Method, rest: &[&str]) -> (u16, Value) {
+    let jobs = jobs.lock().unwrap();
+    let job = match jobs.get(&id) {
+        Some(job) => job,
+        None => return not_found()
+    };
+
+    match (method, rest) {
+        (Method::Get, []) => {
+            let mut body = json!({
+                "id": id,
+                "name": job.name,
+                "algo": job.algo.name(),
+                "best": job.best,
+                "elapsed": job.started.elapsed().as_secs_f64()
+            });
+            match &job.status {
+                Status::Running => body["status"] = json!("running"),
+                Status::Done(result) => {
+                    body["status"] = json!("done");
+                    body["length"] = json!(result.length);
+                    body["elapsed"] = json!(result.elapsed.as_secs_f64());
+                    body["termination"] = json!(result.termination);
+                },
+                Status::Failed(message) => {
+                    body["status"] = json!("failed");
+                    body["error"] = json!(message);
+                }
+            }
+            (200, body)
+        },
+        (Method::Get, ["tour"]) => match &job.status {
+            Status::Done(result) => (200, json!(result)),
+            Status::Running => (409, json!({ "error": "the job is still running" })),
+            Status::Failed(message) => (422, json!({ "error": message }))
+        },
+        (Method::Delete, []) => {
+            job.cancel.cancel();
+            (202, json!({ "id": id }))
+        },
+        _ => not_found()
+    }
+}
+
+fn not_found() -> (u16, Value) {
+    (404, json!({ "error": "not found" }))
+}
+
+// The query string's parameters. Values are used as is, without percent decoding.
+fn query(url: &str) -> HashMap<String, String> {
+    url.split_once('?')
+        .map_or("", |(_, query)| query)
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (key.to_owned(), value.to_owned()),
+            None => (pair.to_owned(), String::new())
+        })
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
@@ -0,0 +1,410 @@
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use traveling_salesman::endpoints::{Endpoints, FixedEndpoints};
+use traveling_salesman::error::Result;
+use traveling_salesman::history::History;
+use traveling_salesman::io::{self as tsp_io, Format};
+#[cfg(feature = "live")]
+use traveling_salesman::live::LiveServer;
+#[cfg(feature = "viz")]
+use traveling_salesman::plot;
+use traveling_salesman::map::{Map, Point};
+use traveling_salesman::render::{Animation, SvgOptions};
+#[cfg(feature = "tui")]
+use traveling_salesman::solve::Broadcast;
+use traveling_salesman::solve::{Progress, SolveOptions, SolveResult, Solver, Termination, trace_branch_and_bound};
+use traveling_salesman::telemetry::Telemetry;
+use traveling_salesman::time::parse_duration;
+use traveling_salesman::tsplib;
+
+use crate::progress::Bar;
+use crate::config::GaConfig;
+#[cfg(feature = "tui")]
+use crate::dashboard;
+use crate::{Algorithm, Config, instance_name};
+
+// How long each tour of an animation is shown
+const ANIMATION_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The instance to solve, in TSPLIB or CSV format
+    #[arg(short, long, required_unless_present = "pipe")]
+    input: Option<PathBuf>,
+    /// Read instances from standard input instead, one JSON object with `name` and `points` per line, and write
+    /// the result of each to standard output as a line of JSON, as `--format json` prints it
+    #[arg(
+        value_name = "-",
+        value_parser = ["-"],
+        conflicts_with_all = ["input", "history", "telemetry", "animation", "tree", "out", "format"]
+    )]
+    pipe: Option<String>,
+    /// Defaults to the config file's choice, or the GA
+    #[arg(short, long, value_enum)]
+    algo: Option<Algorithm>,
+    /// Stop after this long (e.g. `30s`, `500ms`, `2m`) and report the best tour found so far
+    #[arg(short, long, value_name = "DURATION", value_parser = parse_duration)]
+    time_limit: Option<Duration>,
+    /// Stop the GA, simulated annealing, iterated local search or 2-opt after evaluating this many tours or moves
+    #[arg(long, value_name = "COUNT")]
+    max_evaluations: Option<u64>,
+    /// Run branch-and-bound as a beam search keeping this many paths at every depth, which is quicker but does not
+    /// prove its tour optimal
+    #[arg(long, value_name = "WIDTH")]
+    beam_width: Option<usize>,
+    /// Start the path at this point, by its index in the instance
+    #[arg(long, value_name = "INDEX")]
+    start: Option<usize>,
+    /// End the path at this point, by its index in the instance
+    #[arg(long, value_name = "INDEX")]
+    end: Option<usize>,
+    /// Stop the GA, simulated annealing or iterated local search once the tour is within this gap (e.g. `1%` or
+    /// `0.01`) of the optimum given with `--optimum`, or else of a lower bound on it
+    #[arg(long, value_name = "GAP", value_parser = parse_gap)]
+    target_gap: Option<f32>,
+    /// Write the best and mean length over the run to this CSV file, for the GA and simulated annealing
+    #[arg(long, value_name = "CSV")]
+    history: Option<PathBuf>,
+    /// Stream every generation and improvement to this file as JSON Lines while the solve runs
+    #[arg(long, value_name = "JSONL")]
+    telemetry: Option<PathBuf>,
+    /// Draw every new best tour of the GA as an animated SVG, or as one SVG per tour if this is a directory
+    #[arg(long, value_name = "SVG")]
+    animation: Option<PathBuf>,
+    /// Serve a page drawing the best tour of the GA as it improves at this address, such as `127.0.0.1:8080`
+    #[cfg(feature = "live")]
+    #[arg(long, value_name = "ADDRESS", conflicts_with = "pipe")]
+    live: Option<String>,
+    /// Also write the tree that branch-and-bound searches on this instance, of at most 10 points, as a Graphviz DOT
+    /// file
+    #[arg(long, value_name = "DOT")]
+    tree: Option<PathBuf>,
+    /// Draw the best and mean length over the run as a PNG chart
+    #[cfg(feature = "viz")]
+    #[arg(long, value_name = "PNG", conflicts_with = "pipe")]
+    chart: Option<PathBuf>,
+    /// Show a live dashboard of the GA instead of a progress bar. Press q to stop early
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "pipe")]
+    tui: bool,
+    /// The optimal length, to report how far the tour is above it and to measure the gap against. Known TSPLIB
+    /// instances are recognized by their file name for the report only
+    #[arg(long, value_name = "LENGTH")]
+    optimum: Option<f32>,
+    /// Also measure the tour the way TSPLIB does, as a closed tour with every distance rounded to the nearest
+    /// integer, and compare that length with the optimum so that optimal tours match published optima exactly
+    #[arg(long)]
+    integer: bool,
+    /// Save the tour to this file, in a format chosen by its extension
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// How to print the result
+    #[arg(short, long, visible_alias = "output", value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// A human readable summary
+    Text,
+    /// A TSPLIB tour file
+    Tsplib,
+    /// One point index per line
+    Csv,
+    /// The full result and the settings that produced it, as a JSON object
+    Json
+}
+
+// Everything needed to interpret or reproduce a run, for scripts consuming `--format json`
+#[derive(Serialize)]
+struct Report<'a> {
+    instance: &'a str,
+    points: usize,
+    algo: &'static str,
+    seed: u64,
+    time_limit: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ga: Option<&'a GaConfig>,
+    #[serde(flatten)]
+    result: &'a SolveResult,
+    gap: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integer_length: Option<u64>,
+    above_optimal: Option<f32>
+}
+
+// An instance read from standard input in pipe mode
+#[derive(Deserialize)]
+struct Instance {
+    #[serde(default)]
+    name: String,
+    points: Vec<Point>
+}
+
+// A line of standard input in pipe mode that could not be solved
+#[derive(Serialize)]
+struct Failure<'a> {
+    line: usize,
+    error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'a str>
+}
+
+/// Solves the instance, or every instance on standard input, returning how the solve ended, or the first solve
+/// of the instances on standard input that did not complete.
+pub fn run(args: Args, config: &Config) -> Result<Termination> {
+    let input = match &args.input {
+        Some(input) => input,
+        None => return pipe(&args, config)
+    };
+    let map = tsp_io::load_map(input)?;
+    let algo = args.algo.or(config.algo).unwrap_or(Algorithm::Ga);
+    #[cfg(not(feature = "viz"))]
+    let record = args.history.is_some();
+    #[cfg(feature = "viz")]
+    let record = args.history.is_some() || args.chart.is_some();
+    let history = if record { Some(Arc::new(History::new())) } else { None };
+    let telemetry = args.telemetry.as_ref().map(Telemetry::create).transpose()?.map(Arc::new);
+    let mut observers: Vec<Arc<dyn Progress>> = Vec::new();
+    observers.extend(history.clone().map(|history| history as Arc<dyn Progress>));
+    observers.extend(telemetry.clone().map(|telemetry| telemetry as Arc<dyn Progress>));
+    let animation = args.animation.as_ref().map(|_| Arc::new(Animation::new()));
+    observers.extend(animation.clone().map(|animation| animation as Arc<dyn Progress>));
+    #[cfg(feature = "live")]
+    if let Some(address) = &args.live {
+        let live = LiveServer::start(address, &map)?;
+        eprintln!("Following the solve at http://{}/", live.address());
+        observers.push(Arc::new(live));
+    }
+    let solver = algo.solver(config);
+    let solver = FixedEndpoints {
+        solver: &*solver,
+        endpoints: endpoints(&args)
+    };
+    // Draw a seed when none was given, so that the run can be repeated from its output
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let options = SolveOptions {
+        seed: Some(seed),
+        ..options(&args, config)
+    };
+    #[cfg(feature = "tui")]
+    let result = if args.tui {
+        let options = SolveOptions {
+            progress: Some(Arc::new(Broadcast(observers)) as Arc<dyn Progress>),
+            ..options
+        };
+        dashboard::run(&map, options, |options| solver.solve(&map, options))?
+    } else {
+        solve_with_bar(&map, &solver, options, &observers)?
+    };
+    #[cfg(not(feature = "tui"))]
+    let result = solve_with_bar(&map, &solver, options, &observers)?;
+    if let Some(telemetry) = &telemetry {
+        telemetry.finish()?;
+    }
+    if let (Some(path), Some(animation)) = (&args.animation, &animation) {
+        if path.is_dir() {
+            animation.save_frames(path, &map, &SvgOptions::default())?;
+        } else {
+            animation.save_svg(path, &map, &SvgOptions::default(), ANIMATION_DELAY)?;
+        }
+    }
+    if let Some(path) = &args.tree {
+        let (_, tree) = trace_branch_and_bound(&map, &config.options(args.time_limit))?;
+        tree.save_dot(path)?;
+    }
+    if let (Some(path), Some(history)) = (&args.history, &history) {
+        history.save_csv(path)?;
+    }
+    #[cfg(feature = "viz")]
+    if let (Some(path), Some(history)) = (&args.chart, &history) {
+        plot::save_convergence_png(path, &history.samples(), (800, 500))?;
+    }
+    let name = instance_name(input);
+    let optimum = args.optimum.or_else(|| tsplib::optimum(&name));
+    let integer_length = if args.integer { Some(tsplib::tour_length(&map, &result.tour)) } else { None };
+    let above_optimal = above_optimal(&result, optimum, integer_length);
+
+    if let Some(out) = &args.out {
+        tsp_io::save_tour(out, &result.tour, &name)?;
+    }
+
+    match args.format {
+        OutputFormat::Text => {
+            println!("length: {}", result.length);
+            if let Some(length) = integer_length {
+                println!("integer length: {}", length);
+            }
+            if let (Some(optimum), Some(above)) = (optimum, above_optimal) {
+                println!("optimum: {} ({:.2}% above)", optimum, above * 100.0);
+            }
+            println!("time: {:?}", result.elapsed);
+            if result.evaluations > 0 {
+                println!("evaluations: {}", result.evaluations);
+            }
+            println!("seed: {}", seed);
+            println!("termination: {:?}", result.termination);
+            if args.out.is_none() {
+                println!("tour: {}", result.tour.iter().join(" "));
+            }
+        },
+        OutputFormat::Tsplib => tsp_io::write_tour(io::stdout().lock(), &result.tour, &name, Format::Tsplib)?,
+        OutputFormat::Csv => tsp_io::write_tour(io::stdout().lock(), &result.tour, &name, Format::Csv)?,
+        OutputFormat::Json => {
+            let report = Report {
+                instance: &name,
+                points: map.size(),
+                algo: algo.name(),
+                seed,
+                time_limit: config.options(args.time_limit).time_limit.map(|limit| limit.as_secs_f64()),
+                ga: if algo == Algorithm::Ga { Some(&config.ga) } else { None },
+                result: &result,
+                gap: result.gap(),
+                integer_length,
+                above_optimal
+            };
+            // Infallible: the report contains no maps with non-string keys
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+    }
+
+    Ok(result.termination)
+}
+
+// The options of the config file with those given on the command line. A TSPLIB optimum is for closed tours with
+// rounded distances, so only an optimum given explicitly bounds the length of the tour.
+fn options(args: &Args, config: &Config) -> SolveOptions {
+    let options = config.options(args.time_limit);
+    SolveOptions {
+        lower_bound: args.optimum,
+        target_gap: args.target_gap.or(options.target_gap),
+        max_evaluations: args.max_evaluations.or(options.max_evaluations),
+        beam_width: args.beam_width.or(options.beam_width),
+        ..options
+    }
+}
+
+fn endpoints(args: &Args) -> Endpoints {
+    Endpoints {
+        start: args.start,
+        end: args.end
+    }
+}
+
+// Parses a gap as a percentage like `1%` or a fraction like `0.01`
+fn parse_gap(text: &str) -> result::Result<f32, String> {
+    let text = text.trim();
+    let (number, scale) = match text.strip_suffix('%') {
+        Some(number) => (number.trim(), 0.01),
+        None => (text, 1.0)
+    };
+    match number.parse::<f32>() {
+        Ok(gap) if gap.is_finite() && gap >= 0.0 => Ok(gap * scale),
+        _ => Err(format!("invalid gap `{}`, expected a percentage like `1%` or a fraction like `0.01`", text))
+    }
+}
+
+// How far the length, or the integer length if there is one, is above the optimum, as a fraction of it
+fn above_optimal(result: &SolveResult, optimum: Option<f32>, integer_length: Option<u64>) -> Option<f32> {
+    let compared = integer_length.map_or(result.length, |length| length as f32);
+    optimum.map(|optimum| (compared - optimum) / optimum)
+}
+
+// Solves every instance on standard input in turn, writing a report or failure for each as soon as it is done.
+// A line that cannot be read or solved does not stop the rest. Instances without a name are named by their line.
+fn pipe(args: &Args, config: &Config) -> Result<Termination> {
+    let algo = args.algo.or(config.algo).unwrap_or(Algorithm::Ga);
+    let solver = algo.solver(config);
+    let solver = FixedEndpoints {
+        solver: &*solver,
+        endpoints: endpoints(args)
+    };
+    let options = options(args, config);
+    let mut out = io::stdout().lock();
+    let mut termination = Termination::Completed;
+    for (index, line) in io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let number = index + 1;
+        let instance = match serde_json::from_str::<Instance>(&line) {
+            Ok(instance) => instance,
+            Err(error) => {
+                let failure = Failure {
+                    line: number,
+                    error: format!("parse error: {}", error),
+                    instance: None
+                };
+                // Infallible: the failure contains no maps with non-string keys
+                writeln!(out, "{}", serde_json::to_string(&failure).unwrap())?;
+                out.flush()?;
+                continue;
+            }
+        };
+        let name = if instance.name.is_empty() { number.to_string() } else { instance.name };
+        // Every instance draws its own seed, so that each can be repeated from its report alone
+        let seed = config.seed.map_or_else(rand::random, |seed| seed.wrapping_add(index as u64));
+        let options = SolveOptions {
+            seed: Some(seed),
+            ..options.clone()
+        };
+        let solved = Map::try_from_points(instance.points).and_then(|map| {
+            let result = solver.solve(&map, &options)?;
+            Ok((map, result))
+        });
+
+        let text = match solved {
+            Ok((map, result)) => {
+                if termination == Termination::Completed {
+                    termination = result.termination;
+                }
+                let optimum = args.optimum.or_else(|| tsplib::optimum(&name));
+                let integer_length = if args.integer { Some(tsplib::tour_length(&map, &result.tour)) } else { None };
+                let report = Report {
+                    instance: &name,
+                    points: map.size(),
+                    algo: algo.name(),
+                    seed,
+                    time_limit: options.time_limit.map(|limit| limit.as_secs_f64()),
+                    ga: if algo == Algorithm::Ga { Some(&config.ga) } else { None },
+                    result: &result,
+                    gap: result.gap(),
+                    integer_length,
+                    above_optimal: above_optimal(&result, optimum, integer_length)
+                };
+                serde_json::to_string(&report)
+            },
+            Err(error) => serde_json::to_string(&Failure {
+                line: number,
+                error: error.to_string(),
+                instance: Some(&name)
+            })
+        };
+        // Infallible: neither contains maps with non-string keys
+        writeln!(out, "{}", text.unwrap())?;
+        out.flush()?;
+    }
+
+    Ok(termination)
+}
+
+fn solve_with_bar(
+    map: &Map,
+    solver: &dyn Solver,
+    options: SolveOptions,
+    observers: &[Arc<dyn Progress>]
+) -> Result<SolveResult> {
+    let bar = Bar::new();
+    let result = solver.solve(map, &bar.attach(options, observers));
+    bar.finish();
+    result
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use traveling_salesman::error::Result;
+use traveling_salesman::io as tsp_io;
+#[cfg(feature = "viz")]
+use traveling_salesman::plot;
+use traveling_salesman::render::{self, SvgOptions};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The instance to draw
+    #[arg(short, long)]
+    input: PathBuf,
+    /// A tour to draw over the points
+    #[arg(short, long)]
+    tour: Option<PathBuf>,
+    /// The image to write: PNG if it ends in `.png` and the `viz` feature is enabled, otherwise SVG
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Width and height of the image in pixels
+    #[arg(short, long, default_value_t = 800)]
+    size: u32,
+    /// Highlight this many of the tour's longest edges
+    #[arg(short, long, default_value_t = 0)]
+    worst: usize
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let map = tsp_io::load_map(&args.input)?;
+    let tour = args.tour.as_ref().map(|tour| tsp_io::load_tour(tour, &map)).transpose()?;
+
+    let options = SvgOptions {
+        size: args.size,
+        worst_edges: args.worst
+    };
+    match args.out.extension().and_then(|ext| ext.to_str()) {
+        #[cfg(feature = "viz")]
+        Some(ext) if ext.eq_ignore_ascii_case("png") => plot::save_png(&args.out, &map, tour.as_deref(), &options),
+        _ => render::save_svg(&args.out, &map, tour.as_deref(), &options)
+    }
+}
@@ -0,0 +1,288 @@
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::ga::{BrkgaSolver, DifferentialEvolutionSolver, EdgeHistogramSolver, GaSolver};
+use crate::map::{Map, Point};
+use crate::solve::{BranchAndBound, BruteForce, NearestNeighbor, SolveOptions, SolveResult, Solver, Termination};
+
+/// The points to visit. Created by `tsp_map_new` and released with `tsp_map_free`.
+pub struct TspMap(Map);
+
+/// The outcome of a solve. Created by `tsp_solve` and released with `tsp_result_free`.
+pub struct TspResult(SolveResult);
+
+/// Status codes returned by fallible functions.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TspStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer,
+    /// The map has no points
+    EmptyMap,
+    /// An argument was out of range
+    InvalidArgument,
+    /// The solver panicked; the library remains usable but the result was discarded
+    Panic
+}
+
+/// The solvers available through `tsp_solve`. The GA-family solvers use their builder defaults.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TspAlgorithm {
+    NearestNeighbor = 0,
+    BranchAndBound,
+    BruteForce,
+    Ga,
+    Brkga,
+    DifferentialEvolution,
+    EdgeHistogram
+}
+
+/// Why a solve stopped. Mirrors `Termination`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TspTermination {
+    Completed = 0,
+    TimeLimit,
+    Cancelled,
+    TargetGap,
+    EvaluationLimit
+}
+
+impl TspStatus {
+    // The status with the given value, since C can pass any integer where an enum is expected
+    fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(TspStatus::Ok),
+            1 => Some(TspStatus::NullPointer),
+            2 => Some(TspStatus::EmptyMap),
+            3 => Some(TspStatus::InvalidArgument),
+            4 => Some(TspStatus::Panic),
+            _ => None
+        }
+    }
+}
+
+impl TspAlgorithm {
+    // The algorithm with the given value, since C can pass any integer where an enum is expected
+    fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(TspAlgorithm::NearestNeighbor),
+            1 => Some(TspAlgorithm::BranchAndBound),
+            2 => Some(TspAlgorithm::BruteForce),
+            3 => Some(TspAlgorithm::Ga),
+            4 => Some(TspAlgorithm::Brkga),
+            5 => Some(TspAlgorithm::DifferentialEvolution),
+            6 => Some(TspAlgorithm::EdgeHistogram),
+            _ => None
+        }
+    }
+}
+
+impl From<&Error> for TspStatus {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::EmptyMap => TspStatus::EmptyMap,
+            _ => TspStatus::InvalidArgument
+        }
+    }
+}
+
+/// Creates a map from `count` x and y coordinates. Returns null if either array is null while `count` is
+/// non-zero.
+///
+/// # Safety
+///
+/// `xs` and `ys` must each point to at least `count` readable floats.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_map_new(xs: *const f32, ys: *const f32, count: usize) -> *mut TspMap {
+    if count > 0 && (xs.is_null() || ys.is_null()) {
+        return ptr::null_mut();
+    }
+
+    let points = if count == 0 {
+        Vec::new()
+    } else {
+        let (xs, ys) = (slice::from_raw_parts(xs, count), slice::from_raw_parts(ys, count));
+        xs.iter().zip(ys.iter()).map(|(&x, &y)| Point { x, y }).collect()
+    };
+
+    Box::into_raw(Box::new(TspMap(Map::from_points(points))))
+}
+
+/// Releases a map. Passing null does nothing.
+///
+/// # Safety
+///
+/// `map` must be null or a pointer returned by `tsp_map_new` which has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_map_free(map: *mut TspMap) {
+    if !map.is_null() {
+        drop(Box::from_raw(map));
+    }
+}
+
+/// The number of points in the map, or zero if it is null.
+///
+/// # Safety
+///
+/// `map` must be null or a live pointer returned by `tsp_map_new`.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_map_size(map: *const TspMap) -> usize {
+    map.as_ref().map(|map| map.0.size()).unwrap_or(0)
+}
+
+/// Solves the map with the given algorithm, one of the `TspAlgorithm` values, stopping after `time_limit` seconds
+/// if it is positive. On success the result is written to `out` and must be released with `tsp_result_free`.
+/// Unknown algorithms and time limits that are NaN, infinite or too long to represent are invalid arguments.
+///
+/// # Safety
+///
+/// `map` must be a live pointer returned by `tsp_map_new` and `out` must point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_solve(
+    map: *const TspMap,
+    algorithm: u32,
+    time_limit: f64,
+    out: *mut *mut TspResult
+) -> TspStatus {
+    let map = match map.as_ref() {
+        Some(map) => &map.0,
+        None => return TspStatus::NullPointer
+    };
+    if out.is_null() {
+        return TspStatus::NullPointer;
+    }
+    let algorithm = match TspAlgorithm::from_raw(algorithm) {
+        Some(algorithm) => algorithm,
+        None => return TspStatus::InvalidArgument
+    };
+    let time_limit = match time_limit {
+        _ if time_limit.is_nan() => return TspStatus::InvalidArgument,
+        _ if time_limit <= 0.0 => None,
+        _ => match Duration::try_from_secs_f64(time_limit) {
+            Ok(limit) => Some(limit),
+            Err(_) => return TspStatus::InvalidArgument
+        }
+    };
+
+    let options = SolveOptions {
+        time_limit,
+        ..SolveOptions::default()
+    };
+
+    // Unwinding into C is undefined behavior
+    let solved = panic::catch_unwind(AssertUnwindSafe(|| match algorithm {
+        TspAlgorithm::NearestNeighbor => NearestNeighbor.solve(map, &options),
+        TspAlgorithm::BranchAndBound => BranchAndBound.solve(map, &options),
+        TspAlgorithm::BruteForce => BruteForce.solve(map, &options),
+        TspAlgorithm::Ga => GaSolver::builder().build().solve(map, &options),
+        TspAlgorithm::Brkga => BrkgaSolver::builder().build().solve(map, &options),
+        TspAlgorithm::DifferentialEvolution => DifferentialEvolutionSolver::builder().build().solve(map, &options),
+        TspAlgorithm::EdgeHistogram => EdgeHistogramSolver::builder().build().solve(map, &options)
+    }));
+
+    match solved {
+        Ok(Ok(result)) => {
+            *out = Box::into_raw(Box::new(TspResult(result)));
+            TspStatus::Ok
+        },
+        Ok(Err(error)) => TspStatus::from(&error),
+        Err(_) => TspStatus::Panic
+    }
+}
+
+/// Releases a result. Passing null does nothing.
+///
+/// # Safety
+///
+/// `result` must be null or a pointer written by `tsp_solve` which has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_result_free(result: *mut TspResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// The length of the tour, or NaN if the result is null.
+///
+/// # Safety
+///
+/// `result` must be null or a live pointer written by `tsp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_result_length(result: *const TspResult) -> f32 {
+    result.as_ref().map(|result| result.0.length).unwrap_or(f32::NAN)
+}
+
+/// The number of points in the tour, or zero if the result is null.
+///
+/// # Safety
+///
+/// `result` must be null or a live pointer written by `tsp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_result_tour_len(result: *const TspResult) -> usize {
+    result.as_ref().map(|result| result.0.tour.len()).unwrap_or(0)
+}
+
+/// Copies up to `capacity` indices of the tour into `out` and returns the number copied.
+///
+/// # Safety
+///
+/// `result` must be null or a live pointer written by `tsp_solve`, and `out` must be null or point to at least
+/// `capacity` writable elements.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_result_tour(result: *const TspResult, out: *mut usize, capacity: usize) -> usize {
+    let tour = match result.as_ref() {
+        Some(result) if !out.is_null() => &result.0.tour,
+        _ => return 0
+    };
+
+    let count = tour.len().min(capacity);
+    ptr::copy_nonoverlapping(tour.as_ptr(), out, count);
+    count
+}
+
+/// The number of iterations the solver ran, or zero if the result is null.
+///
+/// # Safety
+///
+/// `result` must be null or a live pointer written by `tsp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_result_iterations(result: *const TspResult) -> u64 {
+    result.as_ref().map(|result| result.0.iterations).unwrap_or(0)
+}
+
+/// Why the solver stopped. A null result reports `Completed`.
+///
+/// # Safety
+///
+/// `result` must be null or a live pointer written by `tsp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn tsp_result_termination(result: *const TspResult) -> TspTermination {
+    match result.as_ref().map(|result| result.0.termination) {
+        Some(Termination::TimeLimit) => TspTermination::TimeLimit,
+        Some(Termination::Cancelled) => TspTermination::Cancelled,
+        Some(Termination::TargetGap) => TspTermination::TargetGap,
+        Some(Termination::EvaluationLimit) => TspTermination::EvaluationLimit,
+        _ => TspTermination::Completed
+    }
+}
+
+/// A static, nul-terminated description of the status, one of the `TspStatus` values, or of an unknown status.
+#[no_mangle]
+pub extern "C" fn tsp_status_message(status: u32) -> *const c_char {
+    let message: &'static [u8] = match TspStatus::from_raw(status) {
+        Some(TspStatus::Ok) => b"ok\0",
+        Some(TspStatus::NullPointer) => b"a required pointer was null\0",
+        Some(TspStatus::EmptyMap) => b"the map has no points\0",
+        Some(TspStatus::InvalidArgument) => b"an argument was out of range\0",
+        Some(TspStatus::Panic) => b"the solver panicked\0",
+        None => b"unknown status\0"
+    };
+    message.as_ptr() as *const c_char
+}
@@ -0,0 +1,110 @@
+use crate::io::{self, Format};
+use crate::map::Map;
+
+/// How hard an instance of the [catalog](INSTANCES) is to solve to optimality.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Held-Karp proves the optimum in well under a second
+    Easy,
+    /// The optimum is known, but proving it takes Held-Karp seconds and gigabytes, or is out of its reach
+    Medium,
+    /// The optimum is unknown, and only the best tour known and a lower bound are given
+    Hard
+}
+
+/// A generated instance embedded in the crate, with what is known about its shortest tour. Lengths are those of
+/// open paths over the map's straight line distances, as [`Path::evaluate`](crate::map::Path::evaluate) measures
+/// them, not of TSPLIB's closed tours of rounded distances, although the points are stored in TSPLIB format.
+#[derive(Clone, Copy, Debug)]
+pub struct Instance {
+    /// The distribution the points were drawn from and how many there are
+    pub name: &'static str,
+    pub difficulty: Difficulty,
+    pub size: usize,
+    /// The length of an optimal tour, if one is known
+    pub optimum: Option<f32>,
+    /// The length of the shortest tour known, which is the optimum when it is known
+    pub best_known: f32,
+    /// A length no tour is shorter than: the optimum when it is known, and otherwise the Held-Karp bound of
+    /// penalized 1-trees
+    pub lower_bound: f32,
+    // The points, in TSPLIB format
+    data: &'static str
+}
+
+impl Instance {
+    pub fn map(&self) -> Map {
+        // Infallible: the embedded instances are valid TSPLIB files
+        io::read_map(self.data.as_bytes(), Format::Tsplib).unwrap()
+    }
+
+    /// How far the length is above the best tour known, relative to its length.
+    pub fn above_best(&self, length: f32) -> f32 {
+        (length - self.best_known) / self.best_known
+    }
+
+    /// How far the best tour known may be above the optimum, relative to the lower bound, which is zero when the
+    /// optimum is known.
+    pub fn gap(&self) -> f32 {
+        (self.best_known - self.lower_bound) / self.lower_bound
+    }
+}
+
+// An instance whose optimum is known, or one with only the best tour known and a lower bound
+macro_rules! instance {
+    ($name:literal, $difficulty:ident, $size:literal, optimum $optimum:literal) => {
+        Instance {
+            optimum: Some($optimum),
+            ..instance!($name, $difficulty, $size, best $optimum, bound $optimum)
+        }
+    };
+    ($name:literal, $difficulty:ident, $size:literal, best $best:literal, bound $bound:literal) => {
+        Instance {
+            name: $name,
+            difficulty: Difficulty::$difficulty,
+            size: $size,
+            optimum: None,
+            best_known: $best,
+            lower_bound: $bound,
+            data: include_str!(concat!("catalog/", $name, ".tsp"))
+        }
+    };
+}
+
+/// Instances of 12 to 200 points drawn from the [distributions](crate::generate) of this crate, the coordinates
+/// rounded to four decimals, for quick regression tests and demos without downloading TSPLIB. Ordered by
+/// difficulty, and by size within each.
+pub const INSTANCES: &[Instance] = &[
+    instance!("uniform-12", Easy, 12, optimum 2.1035724),
+    instance!("clustered-14", Easy, 14, optimum 2.7962823),
+    instance!("disc-16", Easy, 16, optimum 4.807296),
+    instance!("uniform-22", Medium, 22, optimum 2.5538352),
+    instance!("ring-24", Medium, 24, optimum 4.61009),
+    instance!("clustered-25", Medium, 25, optimum 3.0153222),
+    instance!("grid-64", Medium, 64, optimum 63.0),
+    instance!("uniform-50", Hard, 50, best 5.330235, bound 5.3194584),
+    instance!("clustered-60", Hard, 60, best 5.136637, bound 5.1352206),
+    instance!("uniform-100", Hard, 100, best 7.650441, bound 7.6039243),
+    instance!("disc-100", Hard, 100, best 12.434881, bound 12.308302),
+    instance!("clustered-150", Hard, 150, best 8.138204, bound 8.018314),
+    instance!("uniform-200", Hard, 200, best 10.447, bound 10.380808)
+];
+
+/// The instance of the catalog with the given name, ignoring case.
+pub fn instance(name: &str) -> Option<&'static Instance> {
+    INSTANCES.iter().find(|instance| instance.name.eq_ignore_ascii_case(name))
+}
+
+/// The optimal length of the catalog instance with the given name, ignoring case, if it is known.
+pub fn optimum(name: &str) -> Option<f32> {
+    instance(name).and_then(|instance| instance.optimum)
+}
+
+/// The maps of every instance of at most the given difficulty with their names, ready for a
+/// [`Benchmark`](crate::bench::Benchmark).
+pub fn maps(difficulty: Difficulty) -> Vec<(String, Map)> {
+    INSTANCES.iter()
+        .filter(|instance| instance.difficulty <= difficulty)
+        .map(|instance| (instance.name.to_string(), instance.map()))
+        .collect()
+}
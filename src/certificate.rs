@@ -0,0 +1,255 @@
+use itertools::Itertools;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path;
+
+use crate::checkpoint::{Reader, parse_values, save_atomic};
+use crate::error::{Error, Result};
+use crate::map::{Map, check_permutation};
+
+const HEADER: &str = "branch-and-bound-certificate 1";
+
+// Bounds and lengths are summed in single precision by the search and in double precision by the check, so they
+// are compared with this much slack relative to the length of the tour
+const TOLERANCE: f64 = 1e-4;
+
+/// A subtree of the search that branch-and-bound cut off, because no path completing `path` could be shorter
+/// than `bound`, and `bound` was no shorter than `incumbent`, the length of the best tour found when it was
+/// reached.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Prune {
+    pub path: Vec<usize>,
+    pub bound: f32,
+    pub incumbent: f32
+}
+
+/// The claim that `tour` is a shortest path through the map, with every pruning decision the search that found
+/// it made, in the order it made them, from
+/// [`certify_branch_and_bound`](crate::solve::certify_branch_and_bound).
+///
+/// [`verify`](Certificate::verify) checks the claim without trusting the search: it walks the same search tree,
+/// recomputes the bound of every pruned path from the map, and measures every complete path it is not cut off
+/// from. A certificate can be saved and checked later, or by someone who only has the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Certificate {
+    pub tour: Vec<usize>,
+    /// The length of the tour, the sum of the distances along it
+    pub length: f32,
+    pub prunes: Vec<Prune>
+}
+
+impl Certificate {
+    /// See [`GaCheckpoint::save`](crate::checkpoint::GaCheckpoint::save).
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        save_atomic(path.as_ref(), |out| self.write(out))
+    }
+
+    pub fn load<P: AsRef<path::Path>>(path: P) -> Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the certificate as text: the length and tour, then the number of prunes and one line for each,
+    /// with its bound, the incumbent and the path.
+    pub fn write<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "{}", HEADER)?;
+        writeln!(out, "length {}", self.length)?;
+        writeln!(out, "tour {}", self.tour.iter().join(" "))?;
+        writeln!(out, "prunes {}", self.prunes.len())?;
+        for prune in &self.prunes {
+            writeln!(out, "prune {} {} {}", prune.bound, prune.incumbent, prune.path.iter().join(" "))?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Reads a certificate written by [`write`](Certificate::write). Nothing is checked against a map until it
+    /// is [verified](Certificate::verify).
+    pub fn read<R: BufRead>(input: R) -> Result<Self> {
+        let mut reader = Reader::new(input);
+        reader.header(HEADER)?;
+        let length = reader.field("length")?;
+        let tour = reader.values("tour")?;
+        let count: usize = reader.field("prunes")?;
+
+        let mut prunes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let line = reader.named("prune")?;
+            let mut parts = line.trim().splitn(3, char::is_whitespace);
+            let mut number = |name: &str| {
+                parts.next().unwrap_or("").parse().map_err(|error| format!("invalid {}: {}", name, error))
+            };
+            let parsed = number("bound").and_then(|bound| Ok((bound, number("incumbent")?)));
+            let (bound, incumbent) = parsed.map_err(|message| reader.error(message))?;
+            let path = parse_values(parts.next().unwrap_or("")).map_err(|message| reader.error(message))?;
+            prunes.push(Prune {
+                path,
+                bound,
+                incumbent
+            });
+        }
+
+        Ok(Certificate {
+            tour,
+            length,
+            prunes
+        })
+    }
+
+    /// Checks that the tour is a shortest path through the map, failing with [`Error::InvalidCertificate`] if the
+    /// certificate does not show it.
+    ///
+    /// The tour must be a permutation satisfying the map's [precedences](Map::with_precedences) and
+    /// [edge constraints](Map::with_edge_constraints), of the certified length. The search tree of
+    /// [`branch_and_bound`](crate::solve::branch_and_bound) is then walked depth first, extending paths by
+    /// points in ascending order under the same constraints. Every prune must be reached in the order it was
+    /// logged, and the sum of its path's length and the nearest neighbor distances of the points off it, leaving
+    /// out the lowest, must be at least its incumbent, which must be at least the certified length. Every
+    /// complete path that no prune cut off must be at least as long as the tour. Lengths are compared with a
+    /// small tolerance for rounding.
+    ///
+    /// The walk takes about as long as the search did, and a certificate of a search stopped by its time limit
+    /// leaves the rest of the tree to be walked in full.
+    pub fn verify(&self, map: &Map) -> Result<()> {
+        check_permutation(map, &self.tour).map_err(|error| invalid(format!("the tour is not valid: {}", error)))?;
+        if let Some(precedences) = map.precedences() {
+            precedences.check(&self.tour).map_err(|error| invalid(format!("the tour is infeasible: {}", error)))?;
+        }
+        if let Some(edges) = map.edge_constraints() {
+            edges.check(&self.tour).map_err(|error| invalid(format!("the tour is infeasible: {}", error)))?;
+        }
+        let length = self.length as f64;
+        let tolerance = TOLERANCE * length.abs().max(1.0);
+        let measured = path_length(map, &self.tour);
+        if (measured - length).abs() > tolerance {
+            return Err(invalid(format!("the tour has length {}, not {}", measured, self.length)));
+        }
+
+        let nearest = (0..map.size())
+            .map(|i| {
+                (0..map.size()).filter(|&j| j != i).map(|j| map.dist(i, j) as f64).fold(f64::MAX, f64::min)
+            })
+            .collect();
+        let mut replay = Replay {
+            map,
+            certificate: self,
+            nearest,
+            visited: vec![false; map.size()],
+            path: Vec::with_capacity(map.size()),
+            next: 0,
+            tolerance
+        };
+        replay.walk(0.0)?;
+        match self.prunes.get(replay.next) {
+            Some(prune) => Err(invalid(format!("the pruned path {:?} is never reached", prune.path))),
+            None => Ok(())
+        }
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidCertificate(message)
+}
+
+// The sum of the distances along the path
+pub(crate) fn path_length(map: &Map, path: &[usize]) -> f64 {
+    path.windows(2).map(|edge| map.dist(edge[0], edge[1]) as f64).sum()
+}
+
+// The depth-first walk of the search tree behind `Certificate::verify`
+struct Replay<'a> {
+    map: &'a Map,
+    certificate: &'a Certificate,
+    // The distance from every point to its nearest neighbor
+    nearest: Vec<f64>,
+    visited: Vec<bool>,
+    path: Vec<usize>,
+    // The first prune not yet reached
+    next: usize,
+    tolerance: f64
+}
+
+impl Replay<'_> {
+    // Walks every extension of the current path, which has the given length
+    fn walk(&mut self, accumulated: f64) -> Result<()> {
+        if self.path.len() == self.map.size() {
+            if accumulated < self.certificate.length as f64 - self.tolerance {
+                return Err(invalid(format!(
+                    "the path {:?} of length {} is shorter than the tour and was not pruned",
+                    self.path, accumulated
+                )));
+            }
+            return Ok(());
+        }
+
+        for point in 0..self.map.size() {
+            if self.visited[point] || !self.ready(point) || !self.joins(point) {
+                continue;
+            }
+
+            let length = match self.path.last() {
+                Some(&last) => accumulated + self.map.dist(last, point) as f64,
+                None => 0.0
+            };
+            self.path.push(point);
+            self.visited[point] = true;
+            let pruned = self.certificate.prunes.get(self.next).is_some_and(|prune| prune.path == self.path);
+            let walked = if pruned { self.check_prune(length) } else { self.walk(length) };
+            self.visited[point] = false;
+            self.path.pop();
+            walked?;
+        }
+        Ok(())
+    }
+
+    // Checks the prune of the current path, which has the given length, and moves on to the next
+    fn check_prune(&mut self, accumulated: f64) -> Result<()> {
+        let prune = &self.certificate.prunes[self.next];
+        self.next += 1;
+
+        let first = (0..self.map.size()).find(|&point| !self.visited[point]);
+        let remaining: f64 = (0..self.map.size())
+            .filter(|&point| !self.visited[point] && Some(point) != first)
+            .map(|point| self.nearest[point])
+            .sum();
+        let bound = accumulated + remaining;
+        if bound < prune.incumbent as f64 - self.tolerance {
+            return Err(invalid(format!(
+                "the pruned path {:?} has bound {}, below the incumbent {}",
+                prune.path, bound, prune.incumbent
+            )));
+        }
+        if (bound - prune.bound as f64).abs() > self.tolerance {
+            return Err(invalid(format!(
+                "the pruned path {:?} has bound {}, not {}",
+                prune.path, bound, prune.bound
+            )));
+        }
+        if prune.incumbent < self.certificate.length - self.tolerance as f32 {
+            return Err(invalid(format!(
+                "the pruned path {:?} was cut off by an incumbent of {}, shorter than the tour",
+                prune.path, prune.incumbent
+            )));
+        }
+        Ok(())
+    }
+
+    // Whether every point that must come before the given one is on the path
+    fn ready(&self, point: usize) -> bool {
+        self.map.precedences().is_none_or(|precedences| {
+            precedences.predecessors(point).iter().all(|&before| self.visited[before])
+        })
+    }
+
+    // Whether the given point may follow the last one on the path: the edge must not be forbidden, the last point
+    // must have no fixed neighbor left to go to, and the new point no fixed neighbor already on the path other
+    // than the last point
+    fn joins(&self, point: usize) -> bool {
+        let (edges, last) = match (self.map.edge_constraints(), self.path.last()) {
+            (Some(edges), Some(&last)) => (edges, last),
+            _ => return true
+        };
+        !edges.is_forbidden(last, point)
+            && edges.fixed_neighbors(last).iter().all(|&other| self.visited[other])
+            && edges.fixed_neighbors(point).iter().all(|&other| other == last || !self.visited[other])
+    }
+}
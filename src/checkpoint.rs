@@ -0,0 +1,429 @@
+use itertools::Itertools;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path;
+use std::result;
+use std::str::FromStr;
+
+use crate::context::SolverContext;
+use crate::error::{Error, Result};
+use crate::ga::{
+    Chromosome, Exchange, GaParams, GaSolver, check_population, random_population, repair_neighbors, run_generations
+};
+use crate::map::{Map, check_permutation};
+use crate::solve::{Budget, Reporter, SolveOptions, SolveResult, check_map};
+use crate::time::Instant;
+
+const GA_HEADER: &str = "ga-checkpoint 1";
+const BRANCH_HEADER: &str = "branch-and-bound-checkpoint 1";
+
+/// Chromosomes which can be written to a checkpoint as a single line of text and read back.
+pub trait Persist: Sized {
+    /// The chromosome as one line of text, without a trailing newline.
+    fn persist(&self) -> String;
+
+    /// Reads back a line written by [`persist`](Persist::persist). The result is validated against the map
+    /// by the caller.
+    fn restore(map: &Map, line: &str) -> result::Result<Self, String>;
+}
+
+impl Persist for Vec<usize> {
+    fn persist(&self) -> String {
+        self.iter().join(" ")
+    }
+
+    fn restore(_map: &Map, line: &str) -> result::Result<Self, String> {
+        parse_values(line)
+    }
+}
+
+/// Parses a line of whitespace separated values.
+pub(crate) fn parse_values<T>(line: &str) -> result::Result<Vec<T>, String>
+where
+    T: FromStr,
+    T::Err: Display
+{
+    line.split_whitespace()
+        .map(|value| value.parse().map_err(|error| format!("invalid value {:?}: {}", value, error)))
+        .collect()
+}
+
+/// The state of a roulette wheel GA run: its population, the generation it reached, and the position of its
+/// random number generator. Resuming from a checkpoint continues the run exactly where it stopped.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaCheckpoint<C> {
+    pub generation: u32,
+    pub seed: u64,
+    /// The offset into the random stream for `seed`, in 32-bit words
+    pub word_pos: u128,
+    pub population: Vec<C>
+}
+
+impl<C> GaCheckpoint<C> {
+    /// The random number generator at the position the checkpoint stores, which resuming continues from.
+    pub fn rng(&self) -> ChaCha20Rng {
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+
+    /// Stores the position of a generator, so that a run can be replayed exactly from the state it had at that
+    /// point. Fails unless the generator was seeded with the checkpoint's seed, since only its position is kept.
+    pub fn capture_rng(&mut self, rng: &ChaCha20Rng) -> Result<()> {
+        // A generator that has not been used yet cannot report its position, so draw a few numbers from a copy and
+        // count back the two words each takes. Generators cannot be compared either, so the same numbers must
+        // come from the checkpoint's seed at that position.
+        let mut copy = rng.clone();
+        let numbers: Vec<u64> = (0..4).map(|_| copy.next_u64()).collect();
+        let word_pos = copy.get_word_pos() - 2 * numbers.len() as u128;
+        let mut seeded = ChaCha20Rng::seed_from_u64(self.seed);
+        seeded.set_word_pos(word_pos);
+        if numbers.iter().any(|&number| number != seeded.next_u64()) {
+            let message = format!("the generator was not seeded with the checkpoint's seed {}", self.seed);
+            return Err(Error::InvalidProblem(message));
+        }
+
+        self.word_pos = word_pos;
+        Ok(())
+    }
+}
+
+impl<C: Chromosome + Persist> GaCheckpoint<C> {
+    /// A checkpoint for a run which has not started yet.
+    pub fn new(population: Vec<C>, seed: u64) -> Self {
+        GaCheckpoint {
+            generation: 0,
+            seed,
+            word_pos: 0,
+            population
+        }
+    }
+
+    /// Writes the checkpoint to a file. The previous contents are only replaced once the new checkpoint has
+    /// been written in full, so a crash while saving leaves the last checkpoint intact.
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        save_atomic(path.as_ref(), |out| self.write(out))
+    }
+
+    pub fn load<P: AsRef<path::Path>>(map: &Map, path: P) -> Result<Self> {
+        Self::read(map, BufReader::new(File::open(path)?))
+    }
+
+    pub fn write<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "{}", GA_HEADER)?;
+        writeln!(out, "generation {}", self.generation)?;
+        writeln!(out, "seed {}", self.seed)?;
+        writeln!(out, "word_pos {}", self.word_pos)?;
+        writeln!(out, "population {}", self.population.len())?;
+        for indv in self.population.iter() {
+            writeln!(out, "{}", indv.persist())?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Reads a checkpoint written by [`write`](GaCheckpoint::write), checking every individual against the map.
+    pub fn read<R: BufRead>(map: &Map, input: R) -> Result<Self> {
+        let mut reader = Reader::new(input);
+        reader.header(GA_HEADER)?;
+        let generation = reader.field("generation")?;
+        let seed = reader.field("seed")?;
+        let word_pos = reader.field("word_pos")?;
+        let count: usize = reader.field("population")?;
+
+        // The count is only trusted as far as the individuals that follow it, so nothing is reserved up front
+        let mut population = Vec::new();
+        for _ in 0..count {
+            let line = reader.line()?;
+            let indv = C::restore(map, &line).map_err(|message| reader.error(message))?;
+            indv.validate(map)?;
+            population.push(indv);
+        }
+
+        Ok(GaCheckpoint {
+            generation,
+            seed,
+            word_pos,
+            population
+        })
+    }
+}
+
+impl<C: Chromosome> GaSolver<C> {
+    /// Continues the run stored in the checkpoint until all generations have run or the budget runs out,
+    /// then updates the checkpoint so that the run can be saved and resumed again later.
+    pub fn resume(&self, map: &Map, options: &SolveOptions, checkpoint: &mut GaCheckpoint<C>) -> Result<SolveResult> {
+        self.resume_with_snapshots(map, options, checkpoint, u32::MAX, |_| Ok(()))
+    }
+
+    /// Resumes like [`resume`](Self::resume), updating the checkpoint every `every` generations and handing it to
+    /// `snapshot`, which can save or clone it. Each snapshot holds the population and the position of the random
+    /// number generator at the start of its generation, so resuming from it replays the rest of the run exactly,
+    /// for debugging an interesting generation or a failure. The run is the same as without snapshots. An error
+    /// from `snapshot` stops the run.
+    pub fn resume_with_snapshots<F>(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        checkpoint: &mut GaCheckpoint<C>,
+        every: u32,
+        mut snapshot: F
+    ) -> Result<SolveResult>
+    where
+        F: FnMut(&GaCheckpoint<C>) -> Result<()>
+    {
+        self.resume_with(map, options, checkpoint, every, |checkpoint| snapshot(checkpoint))
+    }
+
+    /// Solves like [`solve`](crate::solve::Solver::solve), from the same initial population for the same seed, trading
+    /// individuals with other threads through the exchange after every generation.
+    pub fn solve_with_exchange(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        exchange: &Exchange<C>
+    ) -> Result<SolveResult> {
+        check_map(map)?;
+        let seed = options.seed.unwrap_or_else(rand::random);
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let population = random_population(map, self.population, self.new_chromosome, &mut rng);
+        let mut checkpoint = GaCheckpoint {
+            generation: 0,
+            seed,
+            word_pos: rng.get_word_pos(),
+            population
+        };
+        self.resume_with_exchange(map, options, &mut checkpoint, exchange)
+    }
+
+    /// Resumes like [`resume`](Self::resume), trading individuals with other threads through the exchange before
+    /// the first generation and after every one. The final population is published as well.
+    pub fn resume_with_exchange(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        checkpoint: &mut GaCheckpoint<C>,
+        exchange: &Exchange<C>
+    ) -> Result<SolveResult> {
+        let trade = |checkpoint: &mut GaCheckpoint<C>| {
+            exchange.admit(map, &mut checkpoint.population, options.objective);
+            exchange.publish(checkpoint.generation, &checkpoint.population);
+        };
+        check_map(map)?;
+        trade(checkpoint);
+        let result = self.resume_with(map, options, checkpoint, 1, |checkpoint| {
+            trade(checkpoint);
+            Ok(())
+        });
+        exchange.publish(checkpoint.generation, &checkpoint.population);
+        result
+    }
+
+    // Resumes, handing the checkpoint to `between` every `every` generations
+    fn resume_with<F>(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        checkpoint: &mut GaCheckpoint<C>,
+        every: u32,
+        mut between: F
+    ) -> Result<SolveResult>
+    where
+        F: FnMut(&mut GaCheckpoint<C>) -> Result<()>
+    {
+        let start = Instant::now();
+        check_map(map)?;
+        self.settings.validate()?;
+        check_population(checkpoint.population.len(), 2)?;
+        checkpoint.population.iter().try_for_each(|indv| indv.validate(map))?;
+
+        let mut rng = checkpoint.rng();
+        let neighbors = repair_neighbors(map, self.fix, SolverContext::of(options, map));
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start).objective(options.objective);
+        let first = checkpoint.generation;
+        let mut best: Option<(f32, Vec<usize>)> = None;
+        loop {
+            let params = GaParams {
+                settings: &self.settings,
+                recombinator: &self.recombinator,
+                repair: neighbors.as_ref().into(),
+                objective: options.objective,
+                first_generation: checkpoint.generation,
+                generations: checkpoint.generation.saturating_add(every.max(1)).min(self.generations)
+            };
+            let outcome =
+                run_generations(&params, map, &mut checkpoint.population, &mut budget, &mut reporter, &mut rng);
+            let ran = outcome.generations > checkpoint.generation;
+            checkpoint.generation = outcome.generations;
+            checkpoint.word_pos = rng.get_word_pos();
+            if best.as_ref().is_none_or(|(loss, _)| outcome.loss < *loss) {
+                best = Some((outcome.loss, outcome.tour));
+            }
+
+            if checkpoint.generation >= self.generations || budget.exhausted() {
+                break;
+            }
+            if ran {
+                between(checkpoint)?;
+            }
+        }
+
+        // Infallible: the loop runs at least once
+        let (_, tour) = best.unwrap();
+        let mut result = SolveResult::new(map, tour, (checkpoint.generation - first) as u64, start);
+        result.evaluations = budget.evaluations();
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+/// The state of an interrupted branch-and-bound search: the best tour found so far and the first subtree of
+/// the depth-first search which has not been fully explored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchCheckpoint {
+    pub tour: Vec<usize>,
+    /// The partial path at which the search stopped. Every path ordered before it has been explored or pruned.
+    pub frontier: Vec<usize>,
+    pub iterations: u64
+}
+
+impl BranchCheckpoint {
+    /// See [`GaCheckpoint::save`].
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        save_atomic(path.as_ref(), |out| self.write(out))
+    }
+
+    pub fn load<P: AsRef<path::Path>>(map: &Map, path: P) -> Result<Self> {
+        Self::read(map, BufReader::new(File::open(path)?))
+    }
+
+    pub fn write<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "{}", BRANCH_HEADER)?;
+        writeln!(out, "iterations {}", self.iterations)?;
+        writeln!(out, "tour {}", self.tour.iter().join(" "))?;
+        writeln!(out, "frontier {}", self.frontier.iter().join(" "))?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn read<R: BufRead>(map: &Map, input: R) -> Result<Self> {
+        let mut reader = Reader::new(input);
+        reader.header(BRANCH_HEADER)?;
+        let iterations = reader.field("iterations")?;
+        let tour = reader.values("tour")?;
+        let frontier = reader.values("frontier")?;
+
+        let checkpoint = BranchCheckpoint {
+            tour,
+            frontier,
+            iterations
+        };
+        checkpoint.validate(map)?;
+        Ok(checkpoint)
+    }
+
+    /// Checks that the tour is a permutation of the map's points and the frontier a partial one.
+    pub fn validate(&self, map: &Map) -> Result<()> {
+        check_permutation(map, &self.tour)?;
+
+        let mut seen = vec![false; map.size()];
+        for &city in self.frontier.iter() {
+            match seen.get_mut(city) {
+                Some(flag) if !*flag => *flag = true,
+                _ => return Err(Error::InvalidTour(format!("frontier point {} is repeated or out of range", city)))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Writes to a sibling file first and renames it over the target once complete
+pub(crate) fn save_atomic<F>(path: &path::Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>
+{
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+
+    let mut out = BufWriter::new(File::create(&temp)?);
+    write(&mut out)?;
+    out.get_ref().sync_all()?;
+    drop(out);
+
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+// Reads `name value` lines while keeping track of the line number for error messages
+pub(crate) struct Reader<R> {
+    lines: Lines<R>,
+    line: usize
+}
+
+impl<R: BufRead> Reader<R> {
+    pub(crate) fn new(input: R) -> Self {
+        Reader {
+            lines: input.lines(),
+            line: 0
+        }
+    }
+
+    pub(crate) fn error(&self, message: String) -> Error {
+        Error::Parse {
+            line: self.line,
+            message
+        }
+    }
+
+    fn line(&mut self) -> Result<String> {
+        self.line += 1;
+        match self.lines.next() {
+            Some(line) => Ok(line?),
+            None => Err(self.error("unexpected end of checkpoint".to_owned()))
+        }
+    }
+
+    pub(crate) fn header(&mut self, expected: &str) -> Result<()> {
+        let line = self.line()?;
+        if line.trim() == expected {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}, found {:?}", expected, line)))
+        }
+    }
+
+    // The rest of a line starting with `name`
+    pub(crate) fn named(&mut self, name: &str) -> Result<String> {
+        let line = self.line()?;
+        let mut parts = line.trim().splitn(2, char::is_whitespace);
+        if parts.next() != Some(name) {
+            return Err(self.error(format!("expected {:?}, found {:?}", name, line)));
+        }
+
+        Ok(parts.next().unwrap_or("").to_owned())
+    }
+
+    pub(crate) fn field<T>(&mut self, name: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display
+    {
+        let value = self.named(name)?;
+        value.trim().parse().map_err(|error| self.error(format!("invalid {}: {}", name, error)))
+    }
+
+    pub(crate) fn values<T>(&mut self, name: &str) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: Display
+    {
+        let value = self.named(name)?;
+        parse_values(&value).map_err(|message| self.error(message))
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::error::{Error, Result};
+use crate::local::two_opt_in_place;
+use crate::map::{Map, Path, Point, check_permutation};
+use crate::solve::{Budget, Objective, Reporter, SolveOptions, SolveResult, Solver, check_map, nearest_neighbor};
+use crate::time::Instant;
+
+/// Checks that the tour visits the points of every cluster one after another, where `clusters` holds the cluster
+/// of every point by index.
+pub fn check_clusters(map: &Map, clusters: &[usize], tour: &[usize]) -> Result<()> {
+    check_labels(map, clusters)?;
+    check_permutation(map, tour)?;
+
+    let mut finished = BTreeSet::new();
+    for edge in tour.windows(2) {
+        let (from, to) = (clusters[edge[0]], clusters[edge[1]]);
+        if from == to {
+            continue;
+        }
+        if finished.contains(&to) {
+            return Err(Error::InvalidTour(format!("cluster {} is visited in more than one stretch", to)));
+        }
+        finished.insert(from);
+    }
+
+    Ok(())
+}
+
+fn check_labels(map: &Map, clusters: &[usize]) -> Result<()> {
+    if clusters.len() == map.size() {
+        Ok(())
+    } else {
+        Err(Error::DimensionMismatch {
+            expected: map.size(),
+            found: clusters.len()
+        })
+    }
+}
+
+/// Finds a tour which visits the points of every cluster one after another, as when picking a warehouse zone by
+/// zone. `clusters` holds the cluster of every point by index.
+///
+/// The clusters are ordered by a 2-opt tour through their centroids, and the points within each by a 2-opt path
+/// from the point closest to the previous cluster. Each cluster's path is then run forwards or backwards,
+/// whichever makes the edges between clusters shortest, and finally the whole tour is improved with the 2-opt
+/// moves that keep every cluster together: reversals within a cluster, and reversals of whole runs of clusters.
+/// The iteration count is the number of reversals made.
+pub fn clustered(map: &Map, clusters: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_labels(map, clusters)?;
+
+    let mut budget = Budget::new(options, start, 1);
+    let mut silent = Reporter::silent();
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (point, &cluster) in clusters.iter().enumerate() {
+        groups.entry(cluster).or_default().push(point);
+    }
+    let mut groups: Vec<Vec<usize>> = groups.into_values().collect();
+
+    // Order the clusters by their centroids
+    let centroids: Map = groups.iter().map(|points| centroid(map, points)).collect();
+    let mut order = nearest_neighbor(&centroids)?.tour;
+    let any = |_: &[usize], _: usize, _: usize| true;
+    let mut moves = two_opt_in_place(&centroids, &mut order, Objective::Minimize, any, &mut budget, &mut silent);
+
+    // Route through each cluster, entering at the point closest to where the route comes from
+    let mut paths: Vec<Vec<usize>> = Vec::with_capacity(groups.len());
+    for (k, &cluster) in order.iter().enumerate() {
+        let points = &mut groups[cluster];
+        if k > 0 {
+            let previous = centroids[order[k - 1]];
+            let closest = (0..points.len())
+                .min_by(|&a, &b| map[points[a]].dist_sq(&previous).total_cmp(&map[points[b]].dist_sq(&previous)))
+                // Infallible: every cluster has a point
+                .unwrap();
+            points.swap(0, closest);
+        }
+
+        let local: Map = points.iter().map(|&point| map[point]).collect();
+        let mut path = nearest_neighbor(&local)?.tour;
+        moves += two_opt_in_place(&local, &mut path, Objective::Minimize, any, &mut budget, &mut silent);
+        paths.push(path.into_iter().map(|index| points[index]).collect());
+    }
+
+    orient(map, &mut paths);
+    let mut tour: Vec<usize> = paths.concat();
+
+    // A reversal keeps the clusters together if it stays within one, or if it starts and ends at cluster borders
+    let allowed = |tour: &[usize], i: usize, j: usize| {
+        let starts = i == 0 || clusters[tour[i - 1]] != clusters[tour[i]];
+        let ends = j + 1 == tour.len() || clusters[tour[j + 1]] != clusters[tour[j]];
+        clusters[tour[i]] == clusters[tour[j]] || (starts && ends)
+    };
+    let mut reporter = Reporter::new(options, start);
+    reporter.improved(tour.evaluate(map));
+    moves += two_opt_in_place(map, &mut tour, Objective::Minimize, allowed, &mut budget, &mut reporter);
+
+    let mut result = SolveResult::new(map, tour, moves, start);
+    result.termination = budget.termination();
+    Ok(result)
+}
+
+pub(crate) fn centroid(map: &Map, points: &[usize]) -> Point {
+    let sum = points.iter().fold(Point::new(), |sum, &point| sum + map[point]);
+    let count = points.len() as f32;
+    Point {
+        x: sum.x / count,
+        y: sum.y / count
+    }
+}
+
+// Reverses the paths that should run backwards to make the edges between consecutive paths as short as possible,
+// choosing each path's direction by dynamic programming over the directions of the one before it
+pub(crate) fn orient(map: &Map, paths: &mut [Vec<usize>]) {
+    let ends = |path: &[usize], reversed: bool| {
+        // Infallible: every cluster has a point
+        let (first, last) = (*path.first().unwrap(), *path.last().unwrap());
+        if reversed { (last, first) } else { (first, last) }
+    };
+
+    // `best[k][d]` is the shortest joining length up to path `k` running in direction `d`, reached from the
+    // direction `from[k][d]` of path `k - 1`
+    let mut best = vec![[0f32; 2]; paths.len()];
+    let mut from = vec![[false; 2]; paths.len()];
+    for k in 1..paths.len() {
+        for direction in 0..2 {
+            let (entry, _) = ends(&paths[k], direction == 1);
+            let joined = |previous: usize| {
+                let (_, exit) = ends(&paths[k - 1], previous == 1);
+                best[k - 1][previous] + map.dist(exit, entry)
+            };
+            let (forward, backward) = (joined(0), joined(1));
+            best[k][direction] = forward.min(backward);
+            from[k][direction] = backward < forward;
+        }
+    }
+
+    let mut reversed = match best.last() {
+        Some(last) => last[1] < last[0],
+        None => return
+    };
+    for k in (0..paths.len()).rev() {
+        if reversed {
+            paths[k].reverse();
+        }
+        reversed = from[k][reversed as usize];
+    }
+}
+
+/// Solves with [`clustered`] for a fixed assignment of points to clusters.
+#[derive(Clone, Debug)]
+pub struct ClusteredSolver {
+    pub clusters: Vec<usize>
+}
+
+impl Solver for ClusteredSolver {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        clustered(map, &self.clusters, options)
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::error::Result;
+use crate::local::NEIGHBORS;
+use crate::map::{Map, Point};
+use crate::matrix::DistanceMatrix;
+use crate::neighbors::Neighbors;
+use crate::solve::{SolveOptions, check_map};
+
+/// The structures solvers build from a map before they search it, built once so that every solver run on the map
+/// can share them: the [distance matrix](DistanceMatrix), the [candidate lists](Neighbors::candidates) of the
+/// nearest points, and the minimum spanning tree.
+///
+/// A context is handed to solvers through [`SolveOptions::context`], and those that would build one of its
+/// structures take it from there instead whenever the context was built for the map they are solving: the genetic
+/// algorithms for their repairs, Lin-Kernighan for its candidates and Held-Karp for its distances. A context for
+/// another map is ignored.
+pub struct SolverContext {
+    // What the context was built from, to tell whether it fits the map of a solve
+    points: Box<[Point]>,
+    attached: bool,
+    distances: DistanceMatrix,
+    neighbors: Neighbors,
+    tree: Vec<(usize, usize)>,
+    tree_length: f32
+}
+
+impl SolverContext {
+    /// Builds every structure for the map, which takes time quadratic in its size. Fails like
+    /// [`DistanceMatrix::try_new`] if the matrix would not fit in memory.
+    pub fn new(map: &Map) -> Result<Self> {
+        check_map(map)?;
+        let distances = DistanceMatrix::try_new(map)?;
+        let neighbors = Neighbors::candidates(map, NEIGHBORS);
+        let (tree, tree_length) = spanning_tree(distances.size(), |i, j| distances.get(i, j));
+        Ok(SolverContext {
+            points: map.as_slice().into(),
+            attached: map.distances().is_some() || map.has_costs(),
+            distances,
+            neighbors,
+            tree,
+            tree_length
+        })
+    }
+
+    pub fn distances(&self) -> &DistanceMatrix {
+        &self.distances
+    }
+
+    /// The candidates of every point, as [`Neighbors::candidates`] chooses them for local search.
+    pub fn neighbors(&self) -> &Neighbors {
+        &self.neighbors
+    }
+
+    /// The edges of a minimum spanning tree of the map, each with the point nearer the root first.
+    pub fn spanning_tree(&self) -> &[(usize, usize)] {
+        &self.tree
+    }
+
+    /// The length of the [minimum spanning tree](Self::spanning_tree), which no tour can be shorter than, since
+    /// every tour is a spanning tree itself.
+    pub fn lower_bound(&self) -> f32 {
+        self.tree_length
+    }
+
+    /// Whether the context was built for a map with the same points as this one, and distances from the same
+    /// source. Maps with [attached distances](Map::with_distances) or [costs](Map::with_costs) are taken to have
+    /// the same ones when their points are the same.
+    pub fn fits(&self, map: &Map) -> bool {
+        map.size() == self.points.len()
+            && (map.distances().is_some() || map.has_costs()) == self.attached
+            && map.as_slice() == &*self.points
+    }
+
+    // The context of the options, if it was built for the map
+    pub(crate) fn of<'a>(options: &'a SolveOptions, map: &Map) -> Option<&'a SolverContext> {
+        options.context.as_deref().filter(|context| context.fits(map))
+    }
+}
+
+impl Debug for SolverContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolverContext")
+            .field("size", &self.points.len())
+            .field("neighbors", &self.neighbors)
+            .field("lower_bound", &self.tree_length)
+            .finish()
+    }
+}
+
+// A minimum spanning tree of `size` points by Prim's algorithm from the first point, as its edges and their total
+// length
+pub(crate) fn spanning_tree<F: Fn(usize, usize) -> f32>(size: usize, cost: F) -> (Vec<(usize, usize)>, f32) {
+    let mut joined = vec![false; size];
+    // The nearest point of the tree to every point not yet in it, and how far it is
+    let mut nearest = vec![(0, f32::INFINITY); size];
+    let mut edges = Vec::with_capacity(size.saturating_sub(1));
+    let mut length = 0.0;
+    let mut next = 0;
+    loop {
+        joined[next] = true;
+        let (parent, distance) = nearest[next];
+        if next != 0 {
+            edges.push((parent, next));
+            length += distance;
+        }
+
+        let current = next;
+        let mut closest = None;
+        for point in (0..size).filter(|&point| !joined[point]) {
+            let distance = cost(current, point);
+            if distance < nearest[point].1 {
+                nearest[point] = (current, distance);
+            }
+            if closest.is_none_or(|closest: usize| nearest[point].1 < nearest[closest].1) {
+                closest = Some(point);
+            }
+        }
+        match closest {
+            Some(point) => next = point,
+            None => break
+        }
+    }
+    (edges, length)
+}
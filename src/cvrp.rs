@@ -0,0 +1,356 @@
+use rand::prelude::*;
+use std::mem;
+
+use crate::error::{Error, Result};
+use crate::ga::check_population;
+use crate::map::Map;
+use crate::matrix::check_quadratic;
+use crate::mtsp::{MultiSolution, route_length};
+use crate::solve::{Budget, Reporter, SolveOptions, Termination, check_map};
+use crate::time::Instant;
+
+/// The capacitated vehicle routing problem: closed routes leaving the depot and returning to it, which between
+/// them visit every other point of the map exactly once, where the demands of the points on a route add up to at
+/// most the capacity of a vehicle. There are as many vehicles as needed, and the total length is minimized.
+#[derive(Clone, Copy, Debug)]
+pub struct Problem<'a> {
+    pub map: &'a Map,
+    pub depot: usize,
+    /// The demand of every point, by index. The depot's is ignored
+    pub demands: &'a [u32],
+    pub capacity: u32
+}
+
+impl Problem<'_> {
+    /// Checks that there is a demand for every point and that no single point demands more than a vehicle holds.
+    pub fn validate(&self) -> Result<()> {
+        check_map(self.map)?;
+        if self.demands.len() != self.map.size() {
+            return Err(Error::DimensionMismatch {
+                expected: self.map.size(),
+                found: self.demands.len()
+            });
+        }
+        if self.depot >= self.map.size() {
+            return Err(Error::InvalidProblem(format!("the depot {} is not a point of the map", self.depot)));
+        }
+
+        let mut customers = (0..self.map.size()).filter(|&point| point != self.depot);
+        match customers.find(|&point| self.demands[point] > self.capacity) {
+            Some(point) => Err(Error::InvalidProblem(format!(
+                "point {} demands {}, more than the capacity of {}",
+                point,
+                self.demands[point],
+                self.capacity
+            ))),
+            None => Ok(())
+        }
+    }
+
+    /// The total demand of the points of a route.
+    pub fn load(&self, route: &[usize]) -> u64 {
+        route.iter().map(|&point| self.demands[point] as u64).sum()
+    }
+
+    /// The total length of the routes.
+    pub fn cost<R: AsRef<[usize]>>(&self, routes: &[R]) -> f32 {
+        routes.iter().map(|route| route_length(self.map, self.depot, route.as_ref())).sum()
+    }
+
+    /// Checks that every route is non-empty and within capacity, and that together they visit every point but the
+    /// depot exactly once.
+    pub fn check_routes<R: AsRef<[usize]>>(&self, routes: &[R]) -> Result<()> {
+        let mut seen = vec![false; self.map.size()];
+        seen[self.depot] = true;
+        for (vehicle, route) in routes.iter().enumerate() {
+            let route = route.as_ref();
+            if route.is_empty() {
+                return Err(Error::InvalidTour(format!("vehicle {} visits no points", vehicle)));
+            }
+
+            for &point in route {
+                match seen.get_mut(point) {
+                    Some(true) if point == self.depot => {
+                        return Err(Error::InvalidTour(format!("vehicle {} visits the depot", vehicle)));
+                    },
+                    Some(true) => return Err(Error::InvalidTour(format!("point {} is visited more than once", point))),
+                    Some(flag) => *flag = true,
+                    None => return Err(Error::InvalidTour(format!("point {} is out of range", point)))
+                }
+            }
+
+            let load = self.load(route);
+            if load > self.capacity as u64 {
+                return Err(Error::InvalidTour(format!(
+                    "vehicle {} carries {}, more than the capacity of {}",
+                    vehicle,
+                    load,
+                    self.capacity
+                )));
+            }
+        }
+
+        match seen.iter().position(|&flag| !flag) {
+            Some(point) => Err(Error::InvalidTour(format!("point {} is not visited", point))),
+            None => Ok(())
+        }
+    }
+
+    fn solution(&self, routes: Vec<Vec<usize>>, iterations: u64, start: Instant) -> MultiSolution {
+        let lengths: Vec<f32> = routes.iter().map(|route| route_length(self.map, self.depot, route)).collect();
+        MultiSolution {
+            cost: lengths.iter().sum(),
+            lengths,
+            depots: vec![self.depot; routes.len()],
+            routes,
+            iterations,
+            elapsed: start.elapsed(),
+            termination: Termination::Completed
+        }
+    }
+
+    #[inline]
+    fn dist(&self, a: usize, b: usize) -> f32 {
+        self.map.dist(a, b)
+    }
+}
+
+/// The Clarke-Wright savings construction. Every point starts on a route of its own, and then, from the largest
+/// saving `d(depot, i) + d(depot, j) - d(i, j)` down, the routes ending at `i` and `j` are joined whenever their
+/// loads fit in one vehicle. The iteration count is the number of joins.
+pub fn savings(problem: &Problem<'_>) -> Result<MultiSolution> {
+    let start = Instant::now();
+    problem.validate()?;
+
+    let depot = problem.depot;
+    let customers: Vec<usize> = (0..problem.map.size()).filter(|&point| point != depot).collect();
+    let count = customers.len() * customers.len().saturating_sub(1) / 2;
+    let memory = (count as u64).saturating_mul(mem::size_of::<(f32, usize, usize)>() as u64);
+    check_quadratic(memory, "the savings list", problem.map.size())?;
+    let mut pairs = Vec::with_capacity(count);
+    for (index, &i) in customers.iter().enumerate() {
+        for &j in &customers[index + 1..] {
+            let saving = problem.dist(depot, i) + problem.dist(depot, j) - problem.dist(i, j);
+            if saving > 0.0 {
+                pairs.push((saving, i, j));
+            }
+        }
+    }
+    pairs.sort_by(|(a, ..), (b, ..)| b.total_cmp(a));
+
+    // Routes that were joined into another are left empty, and `route_of` follows every point to its route
+    let mut routes: Vec<Vec<usize>> = (0..problem.map.size())
+        .map(|point| if point == depot { Vec::new() } else { vec![point] })
+        .collect();
+    let mut loads: Vec<u64> = (0..problem.map.size()).map(|point| problem.load(&routes[point])).collect();
+    let mut route_of: Vec<usize> = (0..problem.map.size()).collect();
+
+    let mut joins = 0;
+    for (_, i, j) in pairs {
+        let (first, second) = (route_of[i], route_of[j]);
+        if first == second || loads[first] + loads[second] > problem.capacity as u64 {
+            continue;
+        }
+
+        // Both points must be at an end of their routes, to be joined by the edge between them
+        let at_end = |route: &[usize], point: usize| route.first() == Some(&point) || route.last() == Some(&point);
+        if !at_end(&routes[first], i) || !at_end(&routes[second], j) {
+            continue;
+        }
+
+        let mut joined = std::mem::take(&mut routes[first]);
+        let mut appended = std::mem::take(&mut routes[second]);
+        if joined.last() != Some(&i) {
+            joined.reverse();
+        }
+        if appended.first() != Some(&j) {
+            appended.reverse();
+        }
+        for &point in &appended {
+            route_of[point] = first;
+        }
+        joined.extend(appended);
+        routes[first] = joined;
+        loads[first] += loads[second];
+        loads[second] = 0;
+        joins += 1;
+    }
+
+    routes.retain(|route| !route.is_empty());
+    Ok(problem.solution(routes, joins, start))
+}
+
+/// Cuts a giant tour through every point but the depot into routes, choosing the cuts that give the shortest total
+/// length among all ways of cutting it into consecutive routes within capacity. This is the decoder of Prins'
+/// route-first, cluster-second GA, and runs in `O(n b)` time for routes of at most `b` points.
+pub fn split(problem: &Problem<'_>, giant: &[usize]) -> Vec<Vec<usize>> {
+    let depot = problem.depot;
+    let n = giant.len();
+    // `best[j]` is the shortest length of routes covering the first `j` points, the last of which starts after
+    // point `from[j]`
+    let mut best = vec![f32::INFINITY; n + 1];
+    let mut from = vec![0usize; n + 1];
+    best[0] = 0.0;
+    for i in 0..n {
+        let mut load = 0u64;
+        let mut length = 0f32;
+        for j in i..n {
+            load += problem.demands[giant[j]] as u64;
+            if load > problem.capacity as u64 {
+                break;
+            }
+
+            length = if j == i {
+                problem.dist(depot, giant[i]) + problem.dist(giant[i], depot)
+            } else {
+                length - problem.dist(giant[j - 1], depot) + problem.dist(giant[j - 1], giant[j])
+                    + problem.dist(giant[j], depot)
+            };
+            if best[i] + length < best[j + 1] {
+                best[j + 1] = best[i] + length;
+                from[j + 1] = i;
+            }
+        }
+    }
+
+    let mut routes = Vec::new();
+    let mut end = n;
+    while end > 0 {
+        let start = from[end];
+        routes.push(giant[start..end].to_vec());
+        end = start;
+    }
+    routes.reverse();
+    routes
+}
+
+/// A GA over giant tours, decoded into routes by [`split`]. Each generation the `elites` best tours survive, and the
+/// rest of the population is filled with children of tournament winners, made by order crossover and then
+/// mutated by reversing or swapping. The population starts from the [`savings`] routes and random tours.
+#[derive(Clone, Debug)]
+pub struct CvrpGa {
+    pub population: usize,
+    pub generations: u32,
+    /// The number of individuals competing in each tournament
+    pub tournament: usize,
+    pub elites: usize,
+    pub crossover_prob: f32,
+    pub mutate_prob: f32
+}
+
+impl Default for CvrpGa {
+    fn default() -> Self {
+        CvrpGa {
+            population: 100,
+            generations: 1000,
+            tournament: 3,
+            elites: 2,
+            crossover_prob: 0.9,
+            mutate_prob: 0.3
+        }
+    }
+}
+
+impl CvrpGa {
+    /// Checks that the population has at least two individuals and that tournaments have at least one, failing
+    /// with [`Error::InvalidSettings`] otherwise.
+    pub fn validate(&self) -> Result<()> {
+        check_population(self.population, 2)?;
+        if self.tournament == 0 {
+            return Err(Error::InvalidSettings("the tournament is 0, but needs at least one individual".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Runs the GA for the configured number of generations or until the budget runs out, returning the routes of
+    /// the best giant tour found. The iteration count is the number of generations run.
+    pub fn solve(&self, problem: &Problem<'_>, options: &SolveOptions) -> Result<MultiSolution> {
+        let start = Instant::now();
+        problem.validate()?;
+        self.validate()?;
+
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start);
+        let mut rng = options.rng();
+        let evaluate = |giant: &[usize]| problem.cost(&split(problem, giant));
+
+        let customers: Vec<usize> = (0..problem.map.size()).filter(|&point| point != problem.depot).collect();
+        let mut population: Vec<Vec<usize>> = vec![savings(problem)?.routes.concat()];
+        population.extend((1..self.population).map(|_| {
+            let mut giant = customers.clone();
+            giant.shuffle(&mut rng);
+            giant
+        }));
+        let mut ranked: Vec<(f32, Vec<usize>)> = population.into_iter()
+            .map(|giant| (evaluate(&giant), giant))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        reporter.improved(ranked[0].0);
+
+        let elites = self.elites.clamp(1, self.population - 1);
+        let mut present = vec![false; problem.map.size()];
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            let mut next: Vec<(f32, Vec<usize>)> = ranked[..elites].to_vec();
+            while next.len() < self.population {
+                // The population is ranked, so the lowest index drawn wins
+                let mut tournament = || (0..self.tournament).map(|_| rng.gen_range(0, ranked.len())).min().unwrap();
+                let (first, second) = (tournament(), tournament());
+                let mut child = if rng.gen::<f32>() < self.crossover_prob {
+                    order_crossover(&ranked[first].1, &ranked[second].1, &mut present, &mut rng)
+                } else {
+                    ranked[first].1.clone()
+                };
+                if rng.gen::<f32>() < self.mutate_prob && child.len() > 1 {
+                    let (a, b) = (rng.gen_range(0, child.len()), rng.gen_range(0, child.len()));
+                    if rng.gen() {
+                        child[a.min(b)..=a.max(b)].reverse();
+                    } else {
+                        child.swap(a, b);
+                    }
+                }
+                next.push((evaluate(&child), child));
+            }
+
+            next.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            ranked = next;
+            reporter.improved(ranked[0].0);
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        let routes = split(problem, &ranked[0].1);
+        let mut result = problem.solution(routes, generations as u64, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+// Copies a random slice of the first parent into the child and fills the rest of it with the remaining points in
+// the order the second parent visits them, starting after the slice. `present` must be all false, and is left so
+pub(crate) fn order_crossover<R>(first: &[usize], second: &[usize], present: &mut [bool], rng: &mut R) -> Vec<usize>
+where
+    R: Rng + ?Sized
+{
+    let n = first.len();
+    if n < 2 {
+        return first.to_vec();
+    }
+
+    let (a, b) = (rng.gen_range(0, n), rng.gen_range(0, n));
+    let (from, to) = (a.min(b), a.max(b) + 1);
+    let mut child = vec![0; n];
+    child[from..to].copy_from_slice(&first[from..to]);
+    first[from..to].iter().for_each(|&point| present[point] = true);
+
+    let mut position = to % n;
+    for &point in second[to % n..].iter().chain(second[..to % n].iter()) {
+        if !present[point] {
+            child[position] = point;
+            position = (position + 1) % n;
+        }
+    }
+
+    first[from..to].iter().for_each(|&point| present[point] = false);
+    child
+}
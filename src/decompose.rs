@@ -0,0 +1,253 @@
+use rand::prelude::*;
+use std::panic;
+use std::thread;
+
+use crate::clustered::{centroid, orient};
+use crate::error::{Error, Result};
+use crate::local::{LinKernighan, two_opt_in_place};
+use crate::map::{Map, Path, Point};
+use crate::portfolio::member_options;
+use crate::solve::{
+    Budget, Objective, Parallelism, Reporter, SolveOptions, SolveResult, Solver, Termination, check_map, nearest_neighbor
+};
+use crate::time::Instant;
+
+/// How [`Decomposition`] splits the map into clusters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Partition {
+    /// Square cells of a grid over the bounding box of the points, as many as there are to be clusters. Quick, but
+    /// cells at the edge of a map that is not spread evenly can hold far more or fewer points than the rest.
+    Grid,
+    /// K-means over the positions of the points, starting from randomly chosen points and moving every center to
+    /// the centroid of its points this many times. Clusters follow the shape of the map, but every round takes
+    /// time proportional to the size of the map times the number of clusters.
+    KMeans {
+        rounds: usize
+    }
+}
+
+/// Divide and conquer for instances too large to solve whole: splits the map into clusters of about
+/// `cluster_size` points, solves each on its own with `solver`, and stitches the paths together.
+///
+/// The clusters are ordered by a 2-opt tour through their centroids, and each cluster's path is run forwards or
+/// backwards, whichever makes the edges between clusters shortest. The seams are then smoothed by 2-opt over the
+/// `seam_window` points on either side of each, which can move points across the border of their cluster. Every
+/// solve and every seam is small, so the whole takes time about linear in the size of the map, and the clusters
+/// are solved in parallel with [`parallelism`](SolveOptions::parallelism). The iteration count is the sum of
+/// those of every cluster's solve and the reversals made along the seams.
+///
+/// Clusters are formed from the positions of the points, so the map may not have
+/// [attached distances](Map::with_distances), [costs](Map::with_costs) or any constraints. The solver always
+/// minimizes.
+#[derive(Clone, Debug)]
+pub struct Decomposition<S> {
+    pub solver: S,
+    pub cluster_size: usize,
+    pub partition: Partition,
+    pub seam_window: usize
+}
+
+impl<S> Decomposition<S> {
+    /// Clusters of about 500 points in the cells of a grid, solved with the given solver, and seams smoothed 50
+    /// points to either side.
+    pub fn new(solver: S) -> Self {
+        Decomposition {
+            solver,
+            cluster_size: 500,
+            partition: Partition::Grid,
+            seam_window: 50
+        }
+    }
+}
+
+impl Default for Decomposition<LinKernighan> {
+    fn default() -> Self {
+        Self::new(LinKernighan::default())
+    }
+}
+
+impl<S: Solver + Sync> Solver for Decomposition<S> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        let constrained = map.time_windows().is_some()
+            || map.precedences().is_some()
+            || map.edge_constraints().is_some()
+            || map.turn_penalty().is_some();
+        if map.distances().is_some() || map.has_costs() || constrained {
+            return Err(Error::InvalidProblem(
+                "decomposition needs straight line distances and no constraints".to_string()
+            ));
+        }
+
+        let mut budget = Budget::new(options, start, 1);
+        let mut silent = Reporter::silent();
+        let mut rng = options.rng();
+        let count = map.size().div_ceil(self.cluster_size.max(1));
+        let groups = match self.partition {
+            Partition::Grid => grid(map, count),
+            Partition::KMeans { rounds } => k_means(map, count, rounds, &mut rng)
+        };
+
+        // Order the clusters by their centroids
+        let centroids: Map = groups.iter().map(|points| centroid(map, points)).collect();
+        let mut order = nearest_neighbor(&centroids)?.tour;
+        let any = |_: &[usize], _: usize, _: usize| true;
+        let mut iterations =
+            two_opt_in_place(&centroids, &mut order, Objective::Minimize, any, &mut budget, &mut silent);
+
+        let seed = rng.gen();
+        let solved = self.solve_clusters(map, &groups, &order, options, start, seed)?;
+        let mut termination = budget.termination();
+        let mut paths = Vec::with_capacity(solved.len());
+        for (result, &cluster) in solved.into_iter().zip(&order) {
+            iterations += result.iterations;
+            if result.termination != Termination::Completed {
+                termination = result.termination;
+            }
+            paths.push(result.tour.into_iter().map(|index| groups[cluster][index]).collect());
+        }
+        orient(map, &mut paths);
+
+        let seams: Vec<usize> = paths.iter().scan(0, |end, path: &Vec<usize>| {
+            *end += path.len();
+            Some(*end)
+        }).collect();
+        let mut tour: Vec<usize> = paths.concat();
+        let mut reporter = Reporter::new(options, start);
+        reporter.improved(tour.evaluate(map));
+        let n = tour.len();
+        for &seam in &seams[..seams.len().saturating_sub(1)] {
+            if budget.exhausted() {
+                break;
+            }
+
+            // The points at either end of the window stay where they are, unless they end the whole tour
+            let (low, high) = (seam.saturating_sub(self.seam_window), (seam + self.seam_window).min(n));
+            let window = &mut tour[low..high];
+            let len = window.len();
+            let inside = |_: &[usize], i: usize, j: usize| (low == 0 || i > 0) && (high == n || j + 1 < len);
+            iterations += two_opt_in_place(map, window, Objective::Minimize, inside, &mut budget, &mut silent);
+        }
+        reporter.improved(tour.evaluate(map));
+
+        if budget.termination() != Termination::Completed {
+            termination = budget.termination();
+        }
+        let mut result = SolveResult::new(map, tour, iterations, start);
+        result.termination = termination;
+        Ok(result)
+    }
+}
+
+impl<S: Solver + Sync> Decomposition<S> {
+    // Solves the clusters in the given order, splitting them across threads, and returns their results in the
+    // same order with tours over the indices within each cluster
+    fn solve_clusters(
+        &self,
+        map: &Map,
+        groups: &[Vec<usize>],
+        order: &[usize],
+        options: &SolveOptions,
+        start: Instant,
+        seed: u64
+    ) -> Result<Vec<SolveResult>> {
+        let threads = options.parallelism.threads().min(order.len()).max(1);
+        let solve = |k: usize| {
+            let points = &groups[order[k]];
+            let local: Map = points.iter().map(|&point| map[point]).collect();
+            // Every cluster draws its own seed, so that a seeded solve is the same however many threads run it
+            let member = member_options(options, start, seed.wrapping_add(k as u64));
+            // The clusters are already split across threads
+            let member = SolveOptions {
+                objective: Objective::Minimize,
+                parallelism: Parallelism::Off,
+                ..member
+            };
+            self.solver.solve(&local, &member)
+        };
+
+        let mut solved: Vec<(usize, Result<SolveResult>)> = thread::scope(|scope| {
+            let solve = &solve;
+            // Neighboring clusters in the order can be of very different sizes, so interleave them
+            let workers: Vec<_> = (0..threads)
+                .map(|first| scope.spawn(move || {
+                    (first..order.len()).step_by(threads).map(|k| (k, solve(k))).collect::<Vec<_>>()
+                }))
+                .collect();
+            // A worker whose solver panicked passes the panic on to the caller
+            workers.into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+                .collect()
+        });
+        solved.sort_by_key(|&(k, _)| k);
+        solved.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+// The smallest box holding every point of the map, as its lowest and highest corners
+fn bounds(map: &Map) -> (Point, Point) {
+    let (mut low, mut high) = (map[0], map[0]);
+    for point in map {
+        low = Point {
+            x: low.x.min(point.x),
+            y: low.y.min(point.y)
+        };
+        high = Point {
+            x: high.x.max(point.x),
+            y: high.y.max(point.y)
+        };
+    }
+    (low, high)
+}
+
+// The points in each cell of a grid of about `count` square cells, leaving out the empty ones
+fn grid(map: &Map, count: usize) -> Vec<Vec<usize>> {
+    let (low, high) = bounds(map);
+    let (width, height) = ((high.x - low.x).max(f32::MIN_POSITIVE), (high.y - low.y).max(f32::MIN_POSITIVE));
+    // Cells are squares, so a wide map gets more columns than rows
+    let side = ((width * height) / count.max(1) as f32).sqrt().max(f32::MIN_POSITIVE);
+    let columns = ((width / side).ceil() as usize).max(1);
+    let rows = ((height / side).ceil() as usize).max(1);
+
+    let mut cells = vec![Vec::new(); columns * rows];
+    for (index, point) in map.iter().enumerate() {
+        let column = (((point.x - low.x) / side) as usize).min(columns - 1);
+        let row = (((point.y - low.y) / side) as usize).min(rows - 1);
+        cells[row * columns + column].push(index);
+    }
+    cells.retain(|cell| !cell.is_empty());
+    cells
+}
+
+// The points nearest to each of `count` centers, which start at randomly chosen points and move to the centroid
+// of their points every round, leaving out centers that end up with none
+fn k_means<R: Rng + ?Sized>(map: &Map, count: usize, rounds: usize, rng: &mut R) -> Vec<Vec<usize>> {
+    let count = count.clamp(1, map.size());
+    let mut centers: Vec<Point> =
+        rand::seq::index::sample(rng, map.size(), count).into_iter().map(|index| map[index]).collect();
+    let assign = |centers: &[Point]| {
+        let mut clusters = vec![Vec::new(); centers.len()];
+        for index in 0..map.size() {
+            let point = map[index];
+            // Infallible: there is at least one center
+            let nearest = (0..centers.len())
+                .min_by(|&a, &b| point.dist_sq(&centers[a]).total_cmp(&point.dist_sq(&centers[b])))
+                .unwrap();
+            clusters[nearest].push(index);
+        }
+        clusters
+    };
+
+    let mut clusters = assign(&centers);
+    for _ in 0..rounds {
+        for (center, points) in centers.iter_mut().zip(&clusters) {
+            if !points.is_empty() {
+                *center = centroid(map, points);
+            }
+        }
+        clusters = assign(&centers);
+    }
+    clusters.retain(|cluster| !cluster.is_empty());
+    clusters
+}
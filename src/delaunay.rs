@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::map::Map;
+
+// Marks a triangle edge on the outside of the triangulation, which only the edges of the enclosing triangle are
+const OUTSIDE: usize = usize::MAX;
+
+// How much larger than the bounding box of the points the enclosing triangle is. The farther out its corners, the
+// fewer of the edges along the convex hull it hides.
+const ENCLOSING_SCALE: f64 = 64.0;
+
+// A triangulation being built, with every triangle's corners in counterclockwise order and, opposite each corner,
+// the triangle across the edge that does not touch it
+struct Triangulation {
+    points: Vec<(f64, f64)>,
+    corners: Vec<[usize; 3]>,
+    across: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    last: usize
+}
+
+// Twice the signed area of the triangle `a`, `b`, `c`, positive when the corners run counterclockwise
+fn orientation(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+// Whether `p` is strictly inside the circle through the counterclockwise triangle `a`, `b`, `c`
+fn in_circle(a: (f64, f64), b: (f64, f64), c: (f64, f64), p: (f64, f64)) -> bool {
+    let (ax, ay) = (a.0 - p.0, a.1 - p.1);
+    let (bx, by) = (b.0 - p.0, b.1 - p.1);
+    let (cx, cy) = (c.0 - p.0, c.1 - p.1);
+    let determinant = (ax * ax + ay * ay) * (bx * cy - cx * by) - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay);
+    determinant > 0.0
+}
+
+impl Triangulation {
+    // A single triangle around the points, which lie between `low` and `high`, with its corners added after them
+    fn enclosing(mut points: Vec<(f64, f64)>, low: (f64, f64), high: (f64, f64)) -> Self {
+        let extent = (high.0 - low.0).max(high.1 - low.1).max(1.0) * ENCLOSING_SCALE;
+        let center = ((low.0 + high.0) / 2.0, (low.1 + high.1) / 2.0);
+
+        let first = points.len();
+        points.push((center.0 - extent, center.1 - extent));
+        points.push((center.0 + extent, center.1 - extent));
+        points.push((center.0, center.1 + extent));
+        Triangulation {
+            points,
+            corners: vec![[first, first + 1, first + 2]],
+            across: vec![[OUTSIDE; 3]],
+            alive: vec![true],
+            free: Vec::new(),
+            last: 0
+        }
+    }
+
+    // Whether `p` is on the outer side of the edge opposite corner `k` of the triangle
+    fn beyond(&self, triangle: usize, k: usize, p: (f64, f64)) -> bool {
+        let corners = self.corners[triangle];
+        let (a, b) = (self.points[corners[(k + 1) % 3]], self.points[corners[(k + 2) % 3]]);
+        orientation(a, b, p) < 0.0
+    }
+
+    // The triangle containing `p`, found by walking towards it from the last one made, or by trying every
+    // triangle should rounding send the walk in circles
+    fn locate(&self, p: (f64, f64)) -> usize {
+        let mut triangle = self.last;
+        for _ in 0..self.corners.len() {
+            match (0..3).find(|&k| self.beyond(triangle, k, p)) {
+                Some(k) if self.across[triangle][k] != OUTSIDE => triangle = self.across[triangle][k],
+                _ => return triangle
+            }
+        }
+
+        (0..self.corners.len())
+            .find(|&triangle| self.alive[triangle] && (0..3).all(|k| !self.beyond(triangle, k, p)))
+            .unwrap_or(self.last)
+    }
+
+    fn make(&mut self, corners: [usize; 3], across: [usize; 3]) -> usize {
+        match self.free.pop() {
+            Some(triangle) => {
+                self.corners[triangle] = corners;
+                self.across[triangle] = across;
+                self.alive[triangle] = true;
+                triangle
+            },
+            None => {
+                self.corners.push(corners);
+                self.across.push(across);
+                self.alive.push(true);
+                self.corners.len() - 1
+            }
+        }
+    }
+
+    // Adds the point with Bowyer-Watson: the triangles whose circumcircles contain it are removed, and the hole
+    // they leave is filled with triangles fanning out from the point. Points on top of one already added are
+    // skipped.
+    fn insert(&mut self, point: usize) {
+        let p = self.points[point];
+        let start = self.locate(p);
+        if self.corners[start].iter().any(|&corner| self.points[corner] == p) {
+            return;
+        }
+
+        let mut cavity = vec![start];
+        let mut removed = HashSet::new();
+        removed.insert(start);
+        let mut next = 0;
+        while next < cavity.len() {
+            let triangle = cavity[next];
+            next += 1;
+            for &other in &self.across[triangle] {
+                if other == OUTSIDE || removed.contains(&other) {
+                    continue;
+                }
+                let [a, b, c] = self.corners[other];
+                if in_circle(self.points[a], self.points[b], self.points[c], p) {
+                    removed.insert(other);
+                    cavity.push(other);
+                }
+            }
+        }
+
+        // Every edge of the cavity's boundary, running counterclockwise, with the triangle outside it
+        let mut boundary = Vec::new();
+        for &triangle in &cavity {
+            let corners = self.corners[triangle];
+            for k in 0..3 {
+                let other = self.across[triangle][k];
+                if other == OUTSIDE || !removed.contains(&other) {
+                    boundary.push((corners[(k + 1) % 3], corners[(k + 2) % 3], other, triangle));
+                }
+            }
+        }
+        // The new triangles, by the corner their boundary edge starts from and the one it ends at
+        let mut starting = HashMap::with_capacity(boundary.len());
+        let mut ending = HashMap::with_capacity(boundary.len());
+        for (a, b, outside, old) in boundary {
+            let triangle = self.make([a, b, point], [OUTSIDE, OUTSIDE, outside]);
+            if outside != OUTSIDE {
+                // Infallible: the triangle outside the cavity was next to the removed one
+                let k = (0..3).find(|&k| self.across[outside][k] == old).unwrap();
+                self.across[outside][k] = triangle;
+            }
+            starting.insert(a, triangle);
+            ending.insert(b, triangle);
+            self.last = triangle;
+        }
+        for (&a, &triangle) in &starting {
+            let b = self.corners[triangle][1];
+            // Across the edge from `b` to the point is the triangle starting from `b`, and across the one from the
+            // point to `a` the triangle ending at `a`
+            self.across[triangle][0] = starting.get(&b).copied().unwrap_or(OUTSIDE);
+            self.across[triangle][1] = ending.get(&a).copied().unwrap_or(OUTSIDE);
+        }
+
+        // Only now, so that no new triangle takes the place of one the triangles outside still point to
+        for &triangle in &cavity {
+            self.alive[triangle] = false;
+            self.free.push(triangle);
+        }
+    }
+}
+
+// Triangulates the map's points, inserting them row by row through a grid of about one point per cell, with the
+// direction of the rows alternating so that consecutive points stay close
+fn build(map: &Map) -> Triangulation {
+    let size = map.size();
+    let points: Vec<(f64, f64)> = (0..size).map(|index| (map[index].x as f64, map[index].y as f64)).collect();
+    let (mut low, mut high) = ((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY));
+    for &(x, y) in &points {
+        low = (low.0.min(x), low.1.min(y));
+        high = (high.0.max(x), high.1.max(y));
+    }
+
+    let cells = (size as f64).sqrt().ceil();
+    let cell = |value: f64, low: f64, high: f64| ((value - low) / (high - low).max(f64::MIN_POSITIVE) * cells) as i64;
+    let mut order: Vec<usize> = (0..size).collect();
+    order.sort_by_key(|&index| {
+        let (x, y) = points[index];
+        let (column, row) = (cell(x, low.0, high.0), cell(y, low.1, high.1));
+        (row, if row % 2 == 0 { column } else { -column })
+    });
+
+    let mut triangulation = Triangulation::enclosing(points, low, high);
+    for point in order {
+        triangulation.insert(point);
+    }
+    triangulation
+}
+
+/// The Delaunay triangulation of the map's points, as the indices of the corners of every triangle in
+/// counterclockwise order. No point of the map is inside the circle through the corners of any triangle, which
+/// makes the edges those between points that are near each other in every direction; they include every edge of
+/// the minimum spanning tree, and in practice nearly every edge of an optimal tour.
+///
+/// Points are added one at a time in an order that keeps each near the last, so that finding where it goes is
+/// quick, and the triangulation takes time close to linear in the size of the map. Points on top of an earlier one
+/// are left out, and a few edges along the convex hull may be missing, since the triangulation starts from a large
+/// triangle around the points rather than an infinitely large one. Only the positions of the points are used, not
+/// any [attached distances](Map::with_distances).
+pub fn triangulate(map: &Map) -> Vec<[usize; 3]> {
+    let triangulation = build(map);
+    (0..triangulation.corners.len())
+        .filter(|&triangle| triangulation.alive[triangle])
+        .map(|triangle| triangulation.corners[triangle])
+        .filter(|corners| corners.iter().all(|&corner| corner < map.size()))
+        .collect()
+}
+
+/// The edges of the [Delaunay triangulation](triangulate) of the map, each once with the lower index first, in
+/// order. Points next to each other on a line that all the points lie on are joined, even though they make no
+/// triangle.
+pub fn edges(map: &Map) -> Vec<(usize, usize)> {
+    let size = map.size();
+    let triangulation = build(map);
+    let mut edges = Vec::new();
+    for (corners, &alive) in triangulation.corners.iter().zip(&triangulation.alive) {
+        if !alive {
+            continue;
+        }
+        for k in 0..3 {
+            let (a, b) = (corners[k], corners[(k + 1) % 3]);
+            if a < size && b < size {
+                edges.push((a.min(b), a.max(b)));
+            }
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
@@ -0,0 +1,161 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::map::{Map, Point};
+
+/// How to format a map or a tour as text, so that dumps of large instances stay readable and can be compared line
+/// by line. Wrap the map or tour with [`map`](Self::map) or [`tour`](Self::tour) and format the result.
+///
+/// By default everything is on a single line, with as many digits as it takes to read every coordinate back
+/// exactly, which is also how [`Map`] displays itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// The digits after the decimal point of every coordinate, or as many as it takes to read it back exactly if
+    /// none
+    pub precision: Option<usize>,
+    /// Every point on a line of its own after its index, and every index of a tour on a line of its own
+    pub line_per_point: bool,
+    /// The sections of a TSPLIB file as [`io::write_map`](crate::io::write_map) and
+    /// [`io::write_tour`](crate::io::write_tour) write them, without the name, which always puts every point on a
+    /// line of its own and counts indices from one
+    pub tsplib: bool
+}
+
+impl DisplayOptions {
+    pub fn precision(self, digits: usize) -> Self {
+        DisplayOptions {
+            precision: Some(digits),
+            ..self
+        }
+    }
+
+    pub fn line_per_point(self) -> Self {
+        DisplayOptions {
+            line_per_point: true,
+            ..self
+        }
+    }
+
+    pub fn tsplib(self) -> Self {
+        DisplayOptions {
+            tsplib: true,
+            ..self
+        }
+    }
+
+    pub fn map(self, map: &Map) -> MapDisplay<'_> {
+        MapDisplay {
+            map,
+            options: self
+        }
+    }
+
+    pub fn tour(self, tour: &[usize]) -> TourDisplay<'_> {
+        TourDisplay {
+            tour,
+            options: self
+        }
+    }
+
+    fn coordinate(&self, f: &mut Formatter<'_>, value: f32) -> fmt::Result {
+        match self.precision {
+            Some(digits) => write!(f, "{:.*}", digits, value),
+            None => write!(f, "{}", value)
+        }
+    }
+
+    // The point as `(x, y)`, or as `x y` in TSPLIB
+    fn point(&self, f: &mut Formatter<'_>, point: Point) -> fmt::Result {
+        if self.tsplib {
+            self.coordinate(f, point.x)?;
+            f.write_str(" ")?;
+            self.coordinate(f, point.y)
+        } else {
+            f.write_str("(")?;
+            self.coordinate(f, point.x)?;
+            f.write_str(", ")?;
+            self.coordinate(f, point.y)?;
+            f.write_str(")")
+        }
+    }
+}
+
+/// A map formatted with [`DisplayOptions`].
+pub struct MapDisplay<'a> {
+    map: &'a Map,
+    options: DisplayOptions
+}
+
+impl Display for MapDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let options = &self.options;
+        if options.tsplib {
+            writeln!(f, "TYPE : TSP")?;
+            writeln!(f, "DIMENSION : {}", self.map.size())?;
+            writeln!(f, "EDGE_WEIGHT_TYPE : EUC_2D")?;
+            writeln!(f, "NODE_COORD_SECTION")?;
+            for (index, &point) in self.map.iter().enumerate() {
+                write!(f, "{} ", index + 1)?;
+                options.point(f, point)?;
+                writeln!(f)?;
+            }
+            return write!(f, "EOF");
+        }
+
+        if options.line_per_point {
+            for (index, &point) in self.map.iter().enumerate() {
+                if index > 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "{} ", index)?;
+                options.point(f, point)?;
+            }
+            return Ok(());
+        }
+
+        f.write_str("[")?;
+        for (index, &point) in self.map.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            options.point(f, point)?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// A tour formatted with [`DisplayOptions`]: its indices separated by spaces, or one on every line. The precision
+/// does not apply.
+pub struct TourDisplay<'a> {
+    tour: &'a [usize],
+    options: DisplayOptions
+}
+
+impl Display for TourDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.options.tsplib {
+            writeln!(f, "TYPE : TOUR")?;
+            writeln!(f, "DIMENSION : {}", self.tour.len())?;
+            writeln!(f, "TOUR_SECTION")?;
+            for city in self.tour {
+                writeln!(f, "{}", city + 1)?;
+            }
+            writeln!(f, "-1")?;
+            return write!(f, "EOF");
+        }
+
+        let separator = if self.options.line_per_point { "\n" } else { " " };
+        for (position, city) in self.tour.iter().enumerate() {
+            if position > 0 {
+                f.write_str(separator)?;
+            }
+            write!(f, "{}", city)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&DisplayOptions::default().map(self), f)
+    }
+}
@@ -0,0 +1,182 @@
+use std::f64::consts::PI;
+
+use crate::error::{Error, Result};
+use crate::map::{Map, Point};
+use crate::matrix::DistanceMatrix;
+
+/// The turns and straight of a Dubins path, in order: `L` turns left, `R` right, and `S` goes straight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Word {
+    Lsl,
+    Rsr,
+    Lsr,
+    Rsl,
+    Rlr,
+    Lrl
+}
+
+/// The shortest path between two poses of a vehicle that can only move forward and turns no tighter than a given
+/// radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DubinsPath {
+    pub word: Word,
+    /// The lengths of the three parts, in the units of the points
+    pub segments: [f32; 3]
+}
+
+impl DubinsPath {
+    pub fn length(&self) -> f32 {
+        self.segments.iter().sum()
+    }
+}
+
+/// Travel by a vehicle with a minimum turning radius, such as a fixed-wing drone or a car-like robot, which passes
+/// every point along a given heading. [`distances`](Self::distances) measures the shortest
+/// [Dubins paths](https://en.wikipedia.org/wiki/Dubins_path) between the points for [`Map::with_distances`].
+///
+/// The solvers' distances are symmetric and tours may be walked either way, so a heading only fixes the axis the
+/// vehicle passes a point along, in either direction: the distance between two points is that of the shortest path
+/// over the four choices of direction at its ends, which is the same both ways.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dubins {
+    radius: f32,
+    headings: Vec<f32>
+}
+
+impl Dubins {
+    /// Checks that the radius is positive and finite and that every heading, in radians counterclockwise from the
+    /// x axis, is finite. The headings are those of the map's points, in order.
+    pub fn new(radius: f32, headings: Vec<f32>) -> Result<Self> {
+        if !(radius.is_finite() && radius > 0.0) {
+            return Err(Error::InvalidProblem(format!("the turning radius {} is not positive and finite", radius)));
+        }
+        if let Some(heading) = headings.iter().find(|heading| !heading.is_finite()) {
+            return Err(Error::InvalidProblem(format!("the heading {} is not finite", heading)));
+        }
+
+        Ok(Dubins {
+            radius,
+            headings
+        })
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn headings(&self) -> &[f32] {
+        &self.headings
+    }
+
+    /// The shortest path from `from` heading `from_heading` to `to` heading `to_heading`, which ends facing the
+    /// given way, unlike the distances.
+    pub fn path(&self, from: Point, from_heading: f32, to: Point, to_heading: f32) -> DubinsPath {
+        let radius = self.radius as f64;
+        let (dx, dy) = ((to.x - from.x) as f64, (to.y - from.y) as f64);
+        // Scaled so that the radius is one
+        let distance = dx.hypot(dy) / radius;
+        let direction = if distance > 0.0 { dy.atan2(dx) } else { 0.0 };
+        let alpha = modulo(from_heading as f64 - direction);
+        let beta = modulo(to_heading as f64 - direction);
+
+        let (word, segments) = [Word::Lsl, Word::Rsr, Word::Lsr, Word::Rsl, Word::Rlr, Word::Lrl]
+            .iter()
+            .filter_map(|&word| segments(word, alpha, beta, distance).map(|segments| (word, segments)))
+            .min_by(|(_, a), (_, b)| a.iter().sum::<f64>().total_cmp(&b.iter().sum::<f64>()))
+            // Infallible: the LSL and RSR paths always exist between points apart, and the RLR and LRL ones when
+            // they are close
+            .unwrap();
+        DubinsPath {
+            word,
+            segments: [
+                (segments[0] * radius) as f32,
+                (segments[1] * radius) as f32,
+                (segments[2] * radius) as f32
+            ]
+        }
+    }
+
+    /// The length of the shortest path between the points of the map at the given indices, passing each along its
+    /// heading in whichever direction is shorter.
+    pub fn length(&self, map: &Map, a: usize, b: usize) -> f32 {
+        let (from, to) = (self.headings[a], self.headings[b]);
+        let mut shortest = f32::INFINITY;
+        for from in [from, from + PI as f32] {
+            for to in [to, to + PI as f32] {
+                shortest = shortest.min(self.path(map[a], from, map[b], to).length());
+            }
+        }
+        shortest
+    }
+
+    /// The length of the shortest Dubins path between every pair of the map's points, for
+    /// [`Map::with_distances`]. Fails if the map does not have one heading per point.
+    pub fn distances(&self, map: &Map) -> Result<DistanceMatrix> {
+        if self.headings.len() != map.size() {
+            return Err(Error::DimensionMismatch {
+                expected: map.size(),
+                found: self.headings.len()
+            });
+        }
+
+        Ok(DistanceMatrix::from_fn(map.size(), |i, j| self.length(map, i, j)))
+    }
+}
+
+// The angle in `[0, 2 pi)`
+fn modulo(angle: f64) -> f64 {
+    angle.rem_euclid(2.0 * PI)
+}
+
+// The lengths of the parts of the path of the given word between poses at a distance apart along the x axis, with a
+// turning radius of one and headings of `alpha` and `beta`, or `None` if no path of the word joins them. These are the
+// closed forms of Shkel and Lumelsky, "Classification of the Dubins set", 2001.
+fn segments(word: Word, alpha: f64, beta: f64, distance: f64) -> Option<[f64; 3]> {
+    let (sin_a, cos_a, sin_b, cos_b) = (alpha.sin(), alpha.cos(), beta.sin(), beta.cos());
+    let cos_ab = (alpha - beta).cos();
+    let d = distance;
+    match word {
+        Word::Lsl => {
+            let squared = 2.0 + d * d - 2.0 * cos_ab + 2.0 * d * (sin_a - sin_b);
+            let turn = (cos_b - cos_a).atan2(d + sin_a - sin_b);
+            (squared >= 0.0).then(|| [modulo(turn - alpha), squared.sqrt(), modulo(beta - turn)])
+        },
+        Word::Rsr => {
+            let squared = 2.0 + d * d - 2.0 * cos_ab + 2.0 * d * (sin_b - sin_a);
+            let turn = (cos_a - cos_b).atan2(d - sin_a + sin_b);
+            (squared >= 0.0).then(|| [modulo(alpha - turn), squared.sqrt(), modulo(turn - beta)])
+        },
+        Word::Lsr => {
+            let squared = -2.0 + d * d + 2.0 * cos_ab + 2.0 * d * (sin_a + sin_b);
+            (squared >= 0.0).then(|| {
+                let straight = squared.sqrt();
+                let turn = (-cos_a - cos_b).atan2(d + sin_a + sin_b) - (-2.0f64).atan2(straight);
+                [modulo(turn - alpha), straight, modulo(turn - beta)]
+            })
+        },
+        Word::Rsl => {
+            let squared = -2.0 + d * d + 2.0 * cos_ab - 2.0 * d * (sin_a + sin_b);
+            (squared >= 0.0).then(|| {
+                let straight = squared.sqrt();
+                let turn = (cos_a + cos_b).atan2(d - sin_a - sin_b) - 2.0f64.atan2(straight);
+                [modulo(alpha - turn), straight, modulo(beta - turn)]
+            })
+        },
+        Word::Rlr => {
+            let cos_middle = (6.0 - d * d + 2.0 * cos_ab + 2.0 * d * (sin_a - sin_b)) / 8.0;
+            (cos_middle.abs() <= 1.0).then(|| {
+                let middle = modulo(2.0 * PI - cos_middle.acos());
+                let first = modulo(alpha - (cos_a - cos_b).atan2(d - sin_a + sin_b) + middle / 2.0);
+                [first, middle, modulo(alpha - beta - first + middle)]
+            })
+        },
+        Word::Lrl => {
+            let cos_middle = (6.0 - d * d + 2.0 * cos_ab + 2.0 * d * (sin_b - sin_a)) / 8.0;
+            (cos_middle.abs() <= 1.0).then(|| {
+                let middle = modulo(2.0 * PI - cos_middle.acos());
+                let first = modulo(-alpha - (cos_a - cos_b).atan2(d + sin_a - sin_b) + middle / 2.0);
+                [first, middle, modulo(beta - alpha - first + middle)]
+            })
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use rand_chacha::ChaCha20Rng;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::error::{Error, Result};
+use crate::ga::{
+    Chromosome, GaSolver, GenerationPool, RouletteWheelSelection, best_by, random_population, repair_neighbors
+};
+use crate::local::{Ils, two_opt_in_place};
+use crate::map::{Map, Path, Point};
+use crate::neighbors::Neighbors;
+use crate::solve::{Budget, Objective, Reporter, SolveOptions, SolveResult, check_map, nearest_neighbor};
+use crate::time::Instant;
+
+/// A point arriving, leaving or moving while a [`DynamicSolver`] runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    /// Adds a point after the map's last one
+    Insert(Point),
+    /// Removes the point with this index, moving every later point down by one
+    Remove(usize),
+    /// Moves the point with this index to a new position, keeping its index
+    Move(usize, Point)
+}
+
+impl Change {
+    /// The map after the change. Only maps with nothing attached but a turn penalty can change, since the
+    /// distances, costs, time windows, precedences and edge constraints attached to a map have no values for a new
+    /// point. Points inserted or moved must have finite coordinates.
+    pub fn apply(&self, map: &Map) -> Result<Map> {
+        let attached = map.distances().is_some()
+            || map.has_costs()
+            || map.time_windows().is_some()
+            || map.precedences().is_some()
+            || map.edge_constraints().is_some();
+        if attached {
+            let message = "only maps with nothing attached but a turn penalty can change";
+            return Err(Error::InvalidProblem(message.to_string()));
+        }
+
+        let mut points = map.clone_to_vec();
+        match *self {
+            Change::Insert(point) => points.push(point),
+            Change::Remove(index) if index >= points.len() => {
+                return Err(Error::InvalidProblem(format!("there is no point {} to remove", index)));
+            },
+            Change::Remove(_) if points.len() == 1 => return Err(Error::EmptyMap),
+            Change::Remove(index) => {
+                points.remove(index);
+            },
+            Change::Move(index, _) if index >= points.len() => {
+                return Err(Error::InvalidProblem(format!("there is no point {} to move", index)));
+            },
+            Change::Move(index, point) => points[index] = point
+        }
+        let changed = Map::try_from_points(points)?;
+        Ok(match map.turn_penalty() {
+            Some(&turns) => changed.with_turn_penalty(turns),
+            None => changed
+        })
+    }
+
+    /// Carries a tour over the map before the change to `map`, the map after it: a removed point is dropped and
+    /// the points after it renumbered, and a new point goes where it adds the least to the objective's loss. A
+    /// moved point is taken out of the tour and goes back in the same way, leaving the rest of it as it was.
+    pub fn repair(&self, map: &Map, tour: &[usize], objective: Objective) -> Vec<usize> {
+        match *self {
+            Change::Insert(_) => insert_cheapest(map, tour, map.size() - 1, objective),
+            Change::Remove(index) => tour.iter()
+                .filter(|&&point| point != index)
+                .map(|&point| if point > index { point - 1 } else { point })
+                .collect(),
+            Change::Move(index, _) => {
+                let rest: Vec<usize> = tour.iter().copied().filter(|&point| point != index).collect();
+                insert_cheapest(map, &rest, index, objective)
+            }
+        }
+    }
+}
+
+// The tour with the point put where it adds the least to the objective's loss
+fn insert_cheapest(map: &Map, tour: &[usize], point: usize, objective: Objective) -> Vec<usize> {
+    let added = |position: usize| {
+        let before = position.checked_sub(1).map(|k| tour[k]);
+        let after = tour.get(position).copied();
+        let length = match (before, after) {
+            (Some(a), Some(b)) => map.dist(a, point) + map.dist(point, b) - map.dist(a, b),
+            (Some(a), None) => map.dist(a, point),
+            (None, Some(b)) => map.dist(point, b),
+            (None, None) => 0.0
+        };
+        objective.loss(length)
+    };
+    let position = (0..=tour.len())
+        .min_by(|&a, &b| added(a).total_cmp(&added(b)))
+        // Infallible: there is always at least one position
+        .unwrap();
+
+    let mut repaired = tour.to_vec();
+    repaired.insert(position, point);
+    repaired
+}
+
+/// A solver whose map can change between its steps, carrying its current solutions over to the new map instead
+/// of starting again. Driven by [`run`].
+pub trait DynamicSolver {
+    /// The map as it stands after every change so far
+    fn map(&self) -> &Map;
+
+    /// Moves the solver onto the map after the change.
+    fn change(&mut self, change: Change) -> Result<()>;
+
+    /// Searches for a little while: one generation, or one kick.
+    fn step(&mut self);
+
+    /// The best tour of the current map found so far
+    fn best(&self) -> &[usize];
+
+    /// Re-solves warm after a few edits, as an interactive editor or a simulation makes them: applies the changes
+    /// in order, then takes up to `steps` steps from the repaired solutions, stopping early if the time limit runs
+    /// out or the token is cancelled. The result is for the map after the changes, and its iteration count is the
+    /// number of steps taken.
+    fn resolve(&mut self, changes: &[Change], steps: u64, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        for &change in changes {
+            self.change(change)?;
+        }
+
+        let mut budget = Budget::new(options, start, 1);
+        let mut taken = 0;
+        while taken < steps && !budget.exhausted() {
+            self.step();
+            taken += 1;
+        }
+
+        let mut result = SolveResult::new(self.map(), self.best().to_vec(), taken, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+/// Steps the solver, applying every change that has arrived on `changes` before each step. Stops when the time
+/// limit runs out or the token is cancelled, or, if the options have neither, once the sender is dropped and its
+/// last changes have been applied. The result is for the map as it stands at the end, which
+/// [`DynamicSolver::map`] returns, and its iteration count is the number of steps.
+pub fn run<S>(solver: &mut S, changes: &Receiver<Change>, options: &SolveOptions) -> Result<SolveResult>
+where
+    S: DynamicSolver
+{
+    let start = Instant::now();
+    let mut budget = Budget::new(options, start, 1);
+    let limited = options.time_limit.is_some() || options.cancel.is_some();
+    let mut disconnected = false;
+    let mut steps = 0;
+    loop {
+        while !disconnected {
+            match changes.try_recv() {
+                Ok(change) => solver.change(change)?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => disconnected = true
+            }
+        }
+        if budget.exhausted() || (disconnected && !limited) {
+            break;
+        }
+
+        solver.step();
+        steps += 1;
+    }
+
+    let mut result = SolveResult::new(solver.map(), solver.best().to_vec(), steps, start);
+    result.termination = budget.termination();
+    Ok(result)
+}
+
+/// The roulette wheel GA of a [`GaSolver`] run one generation per step. A change decodes every individual on the
+/// old map, carries its tour over with [`Change::repair`], and encodes it again on the new one. The solver's
+/// generation count and population schedule are not used: the population keeps its size until [`run`] stops.
+pub struct DynamicGa<C> {
+    solver: GaSolver<C>,
+    map: Map,
+    population: Vec<C>,
+    objective: Objective,
+    neighbors: Option<Neighbors>,
+    pool: GenerationPool<C>,
+    rng: ChaCha20Rng,
+    loss: f32,
+    best: Vec<usize>
+}
+
+impl<C: Chromosome> DynamicGa<C> {
+    /// Starts from a random population, seeded and aimed at the objective of the options.
+    pub fn new(solver: GaSolver<C>, map: Map, options: &SolveOptions) -> Result<Self> {
+        check_map(&map)?;
+        solver.settings.validate()?;
+        let mut rng = options.rng();
+        let population = random_population(&map, solver.population, solver.new_chromosome, &mut rng);
+        population.iter().try_for_each(|indv| indv.validate(&map))?;
+
+        let objective = options.objective;
+        let (loss, best) = best_by(&map, &population, objective);
+        Ok(DynamicGa {
+            neighbors: repair_neighbors(&map, solver.fix, None),
+            solver,
+            map,
+            population,
+            objective,
+            pool: GenerationPool::new(),
+            rng,
+            loss,
+            best
+        })
+    }
+
+    pub fn population(&self) -> &[C] {
+        &self.population
+    }
+}
+
+impl<C: Chromosome> DynamicSolver for DynamicGa<C> {
+    fn map(&self) -> &Map {
+        &self.map
+    }
+
+    fn change(&mut self, change: Change) -> Result<()> {
+        let map = change.apply(&self.map)?;
+        for indv in &mut self.population {
+            let tour = change.repair(&map, &indv.tour(&self.map), self.objective);
+            let mut carried = (self.solver.new_chromosome)(&map);
+            carried.encode(&map, &tour)?;
+            *indv = carried;
+        }
+
+        self.best = change.repair(&map, &self.best, self.objective);
+        self.loss = self.objective.loss(self.best.evaluate(&map));
+        self.neighbors = repair_neighbors(&map, self.solver.fix, None);
+        // The pool's spare individuals are sized for the old map
+        self.pool = GenerationPool::new();
+        self.map = map;
+        Ok(())
+    }
+
+    fn step(&mut self) {
+        let current = RouletteWheelSelection::evolve_with_pool(
+            &self.solver.settings,
+            &self.map,
+            &mut self.population,
+            &self.solver.recombinator,
+            self.neighbors.as_ref().into(),
+            self.objective,
+            &mut self.pool,
+            &mut self.rng
+        );
+
+        // The reported loss may belong to an individual that was not kept, so re-check the survivors
+        if current < self.loss {
+            let (loss, best) = best_by(&self.map, &self.population, self.objective);
+            if loss < self.loss {
+                self.loss = loss;
+                self.best = best;
+            }
+        }
+    }
+
+    fn best(&self) -> &[usize] {
+        &self.best
+    }
+}
+
+/// The [iterated local search](crate::local::IteratedLocalSearch) run one kick per step. A change carries the
+/// current tour over with [`Change::repair`] and brings it back to a 2-opt local optimum.
+pub struct DynamicIls {
+    map: Map,
+    search: Ils,
+    objective: Objective,
+    rng: ChaCha20Rng
+}
+
+impl DynamicIls {
+    /// Starts from the nearest neighbor tour, seeded and aimed at the objective of the options.
+    pub fn new(map: Map, options: &SolveOptions) -> Result<Self> {
+        let tour = nearest_neighbor(&map)?.tour;
+        let objective = options.objective;
+        let search = Ils::new(&map, tour, objective, &mut Budget::unlimited(), &mut Reporter::silent());
+        Ok(DynamicIls {
+            map,
+            search,
+            objective,
+            rng: options.rng()
+        })
+    }
+}
+
+impl DynamicSolver for DynamicIls {
+    fn map(&self) -> &Map {
+        &self.map
+    }
+
+    fn change(&mut self, change: Change) -> Result<()> {
+        let map = change.apply(&self.map)?;
+        let tour = change.repair(&map, &self.search.tour, self.objective);
+        self.search = Ils::new(&map, tour, self.objective, &mut Budget::unlimited(), &mut Reporter::silent());
+        self.map = map;
+        Ok(())
+    }
+
+    fn step(&mut self) {
+        self.search.kick(&self.map, self.objective, &mut self.rng, &mut Budget::unlimited(), &mut Reporter::silent());
+    }
+
+    fn best(&self) -> &[usize] {
+        &self.search.tour
+    }
+}
+
+/// A tour kept up to date as points arrive and leave, for streams of points with no solver running between them.
+/// A new point goes where it adds the least to the tour, a removed one is cut out, and after every `cleanup`
+/// changes the tour is brought back to a 2-opt local optimum, which undoes the crossings that greedy insertion
+/// leaves behind.
+#[derive(Clone, Debug)]
+pub struct OnlineTour {
+    map: Map,
+    tour: Vec<usize>,
+    objective: Objective,
+    cleanup: usize,
+    // The changes since the tour was last cleaned up
+    pending: usize
+}
+
+impl OnlineTour {
+    /// Starts from the nearest neighbor tour of the map, brought to a 2-opt local optimum. A `cleanup` of zero
+    /// never cleans up on its own, leaving it to [`clean_up`](Self::clean_up).
+    pub fn new(map: Map, objective: Objective, cleanup: usize) -> Result<Self> {
+        let tour = nearest_neighbor(&map)?.tour;
+        let mut online = OnlineTour {
+            map,
+            tour,
+            objective,
+            cleanup,
+            pending: 0
+        };
+        online.clean_up();
+        Ok(online)
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn tour(&self) -> &[usize] {
+        &self.tour
+    }
+
+    pub fn length(&self) -> f32 {
+        self.tour.evaluate(&self.map)
+    }
+
+    /// Adds a point after the map's last one and returns its index. Fails as [`Change::apply`] does.
+    pub fn insert(&mut self, point: Point) -> Result<usize> {
+        self.change(Change::Insert(point))?;
+        Ok(self.map.size() - 1)
+    }
+
+    /// Removes the point with this index, moving every later point down by one. Fails as [`Change::apply`] does.
+    pub fn remove(&mut self, index: usize) -> Result<()> {
+        self.change(Change::Remove(index))
+    }
+
+    /// Applies the change, carrying the tour over with [`Change::repair`].
+    pub fn change(&mut self, change: Change) -> Result<()> {
+        let map = change.apply(&self.map)?;
+        self.tour = change.repair(&map, &self.tour, self.objective);
+        self.map = map;
+        self.pending += 1;
+        if self.cleanup > 0 && self.pending >= self.cleanup {
+            self.clean_up();
+        }
+        Ok(())
+    }
+
+    /// Brings the tour to a 2-opt local optimum now.
+    pub fn clean_up(&mut self) {
+        let (mut budget, mut reporter) = (Budget::unlimited(), Reporter::silent());
+        two_opt_in_place(&self.map, &mut self.tour, self.objective, |_, _, _| true, &mut budget, &mut reporter);
+        self.pending = 0;
+    }
+}
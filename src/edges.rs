@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::error::{Error, Result};
+
+/// Edges a tour must use and edges it must not, as for a road segment that has to be driven or a road that is
+/// closed. Edges are undirected. Attached to a map with
+/// [`Map::with_edge_constraints`](crate::map::Map::with_edge_constraints).
+#[derive(Clone, Debug)]
+pub struct EdgeConstraints {
+    fixed: Vec<(usize, usize)>,
+    forbidden: Vec<(usize, usize)>,
+    // The points each point is fixed to, at most two
+    fixed_neighbors: Box<[Vec<usize>]>,
+    // The forbidden edges with the lower point first
+    forbidden_set: HashSet<(usize, usize)>
+}
+
+impl EdgeConstraints {
+    /// Checks that every edge joins two different points of a map with `size` points, that no edge is both fixed
+    /// and forbidden, and that the fixed edges can all be on one path: no point has more than two of them and
+    /// they do not close a loop.
+    pub fn new(size: usize, fixed: Vec<(usize, usize)>, forbidden: Vec<(usize, usize)>) -> Result<Self> {
+        let invalid = fixed.iter().chain(forbidden.iter()).find(|&&(a, b)| a >= size || b >= size || a == b);
+        if let Some(&(a, b)) = invalid {
+            return Err(Error::InvalidProblem(format!("({}, {}) is not an edge between two points", a, b)));
+        }
+
+        let forbidden_set: HashSet<(usize, usize)> = forbidden.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+        let mut fixed_neighbors = vec![Vec::new(); size].into_boxed_slice();
+        // Each point's representative among the points joined to it by fixed edges
+        let mut parent: Vec<usize> = (0..size).collect();
+        let find = |parent: &mut Vec<usize>, mut point: usize| {
+            while parent[point] != point {
+                parent[point] = parent[parent[point]];
+                point = parent[point];
+            }
+            point
+        };
+        for &(a, b) in &fixed {
+            if forbidden_set.contains(&(a.min(b), a.max(b))) {
+                return Err(Error::InvalidProblem(format!("the edge ({}, {}) is both fixed and forbidden", a, b)));
+            }
+
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            if root_a == root_b {
+                return Err(Error::InvalidProblem(format!("the fixed edge ({}, {}) closes a loop", a, b)));
+            }
+            parent[root_a] = root_b;
+
+            fixed_neighbors[a].push(b);
+            fixed_neighbors[b].push(a);
+            if let Some(point) = [a, b].iter().copied().find(|&point| fixed_neighbors[point].len() > 2) {
+                return Err(Error::InvalidProblem(format!("point {} has more than two fixed edges", point)));
+            }
+        }
+
+        Ok(EdgeConstraints {
+            fixed,
+            forbidden,
+            fixed_neighbors,
+            forbidden_set
+        })
+    }
+
+    /// These constraints with every edge inside the given segments of `tour` fixed as well, so that the segments
+    /// are kept whole, though possibly reversed, when the tour is optimized again. Each segment is a range of
+    /// positions in the tour. Fails if a segment runs past the end of the tour or if the new fixed edges cannot be
+    /// on one path together with those already fixed.
+    pub fn with_locked(&self, tour: &[usize], segments: &[Range<usize>]) -> Result<Self> {
+        if let Some(segment) = segments.iter().find(|segment| segment.end > tour.len()) {
+            return Err(Error::InvalidProblem(format!(
+                "the segment {:?} runs past the end of a tour of {} points",
+                segment,
+                tour.len()
+            )));
+        }
+
+        let mut fixed = self.fixed.clone();
+        for segment in segments {
+            for edge in tour[segment.clone()].windows(2) {
+                if !self.is_fixed(edge[0], edge[1]) && !fixed.contains(&(edge[0], edge[1])) {
+                    fixed.push((edge[0], edge[1]));
+                }
+            }
+        }
+        Self::new(self.size(), fixed, self.forbidden.clone())
+    }
+
+    pub fn fixed(&self) -> &[(usize, usize)] {
+        &self.fixed
+    }
+
+    pub fn forbidden(&self) -> &[(usize, usize)] {
+        &self.forbidden
+    }
+
+    pub fn size(&self) -> usize {
+        self.fixed_neighbors.len()
+    }
+
+    /// The points the given point must be next to.
+    pub fn fixed_neighbors(&self, point: usize) -> &[usize] {
+        &self.fixed_neighbors[point]
+    }
+
+    #[inline]
+    pub fn is_fixed(&self, a: usize, b: usize) -> bool {
+        self.fixed_neighbors[a].contains(&b)
+    }
+
+    #[inline]
+    pub fn is_forbidden(&self, a: usize, b: usize) -> bool {
+        !self.forbidden_set.is_empty() && self.forbidden_set.contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Checks that the tour uses every fixed edge and no forbidden one. The tour must be a permutation of the
+    /// points.
+    pub fn check(&self, tour: &[usize]) -> Result<()> {
+        if let Some(edge) = tour.windows(2).find(|edge| self.is_forbidden(edge[0], edge[1])) {
+            return Err(Error::InvalidTour(format!("the edge ({}, {}) is forbidden", edge[0], edge[1])));
+        }
+
+        let mut position = vec![0; self.size()];
+        for (i, &point) in tour.iter().enumerate() {
+            position[point] = i;
+        }
+        match self.fixed.iter().find(|&&(a, b)| position[a].abs_diff(position[b]) != 1) {
+            Some((a, b)) => Err(Error::InvalidTour(format!("the fixed edge ({}, {}) is not used", a, b))),
+            None => Ok(())
+        }
+    }
+
+    /// The tour closest to the given one that uses every fixed edge: the points are taken in the order of the
+    /// tour, except that reaching a point joined to others by fixed edges takes the whole chain of them at once,
+    /// from whichever end the tour reaches first. Forbidden edges are not repaired. Tours that already use the
+    /// fixed edges are returned unchanged.
+    pub fn repair(&self, tour: &[usize]) -> Vec<usize> {
+        let mut position = vec![0; self.size()];
+        for (i, &point) in tour.iter().enumerate() {
+            position[point] = i;
+        }
+
+        let mut placed = vec![false; self.size()];
+        let mut repaired = Vec::with_capacity(tour.len());
+        for &point in tour {
+            if placed[point] {
+                continue;
+            }
+
+            let mut chain = self.chain(point);
+            // Infallible: a chain holds at least the point it was built from
+            if position[*chain.last().unwrap()] < position[chain[0]] {
+                chain.reverse();
+            }
+            for &point in &chain {
+                placed[point] = true;
+            }
+            repaired.extend(chain);
+        }
+        repaired
+    }
+
+    // The points joined to the given one by fixed edges, from one end of their chain to the other
+    fn chain(&self, point: usize) -> Vec<usize> {
+        let mut chain = vec![point];
+        for &first in &self.fixed_neighbors[point] {
+            chain.reverse();
+            let (mut previous, mut current) = (point, first);
+            loop {
+                chain.push(current);
+                match self.fixed_neighbors[current].iter().find(|&&next| next != previous) {
+                    Some(&next) => (previous, current) = (current, next),
+                    None => break
+                }
+            }
+        }
+        chain
+    }
+
+    // Whether reversing `tour[i..=j]` keeps every fixed edge the tour uses and joins no forbidden edge
+    #[inline]
+    pub(crate) fn allows_reversal(&self, tour: &[usize], i: usize, j: usize) -> bool {
+        let before = i == 0 || !(self.is_fixed(tour[i - 1], tour[i]) || self.is_forbidden(tour[i - 1], tour[j]));
+        let after = j + 1 == tour.len()
+            || !(self.is_fixed(tour[j], tour[j + 1]) || self.is_forbidden(tour[i], tour[j + 1]));
+        before && after
+    }
+
+    // Follows the map swapping two of its points
+    pub(crate) fn swap(&mut self, first: usize, second: usize) {
+        let relabel = |point: &mut usize| {
+            if *point == first {
+                *point = second;
+            } else if *point == second {
+                *point = first;
+            }
+        };
+
+        for (a, b) in self.fixed.iter_mut().chain(self.forbidden.iter_mut()) {
+            relabel(a);
+            relabel(b);
+        }
+        self.fixed_neighbors.swap(first, second);
+        self.fixed_neighbors.iter_mut().flatten().for_each(relabel);
+        self.forbidden_set = self.forbidden.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+    }
+}
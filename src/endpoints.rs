@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::map::Map;
+use crate::solve::{Objective, Progress, ProgressEvent, SolveOptions, SolveResult, Solver, check_map};
+use crate::time::Instant;
+
+/// The points an open path must start and end at, as when a plotter has to finish where the next sheet begins.
+/// Every tour in this crate is an open path whose ends are free, which is what an endpoint left as `None` stays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Endpoints {
+    pub start: Option<usize>,
+    pub end: Option<usize>
+}
+
+impl Endpoints {
+    /// Checks that the endpoints are points of the map, and that a path of more than one point does not start and
+    /// end at the same one.
+    pub fn validate(&self, map: &Map) -> Result<()> {
+        if let Some(point) = self.start.into_iter().chain(self.end).find(|&point| point >= map.size()) {
+            return Err(Error::InvalidProblem(format!("the endpoint {} is not a point of the map", point)));
+        }
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if start == end && map.size() > 1 => {
+                Err(Error::InvalidProblem(format!("the path cannot both start and end at point {}", start)))
+            },
+            _ => Ok(())
+        }
+    }
+
+    /// Checks that the tour starts and ends at the chosen points.
+    pub fn check_tour(&self, tour: &[usize]) -> Result<()> {
+        if let Some(start) = self.start.filter(|&start| tour.first() != Some(&start)) {
+            return Err(Error::InvalidTour(format!("the path does not start at point {}", start)));
+        }
+        match self.end.filter(|&end| tour.last() != Some(&end)) {
+            Some(end) => Err(Error::InvalidTour(format!("the path does not end at point {}", end))),
+            None => Ok(())
+        }
+    }
+
+    /// The tour turned around if that brings the chosen points nearer their ends, and then with the start moved
+    /// to the front and the end to the back. Tours that already have the right ends are returned unchanged.
+    pub fn repair(&self, tour: &[usize]) -> Vec<usize> {
+        let mut tour = tour.to_vec();
+        if self.displacement(&tour) > self.displacement(&tour.iter().rev().copied().collect::<Vec<_>>()) {
+            tour.reverse();
+        }
+
+        if let Some(start) = self.start {
+            tour.retain(|&point| point != start);
+            tour.insert(0, start);
+        }
+        if let Some(end) = self.end.filter(|&end| Some(end) != self.start) {
+            tour.retain(|&point| point != end);
+            tour.push(end);
+        }
+        tour
+    }
+
+    // How many places the chosen points of the tour are from their ends
+    fn displacement(&self, tour: &[usize]) -> usize {
+        let position = |point: usize| tour.iter().position(|&other| other == point).unwrap_or(0);
+        let start = self.start.map_or(0, position);
+        let end = self.end.map_or(0, |end| tour.len().saturating_sub(position(end) + 1));
+        start + end
+    }
+
+    // How many different points are chosen, each of which a path with the right ends reaches by a single edge
+    fn chosen(&self) -> usize {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if start != end => 2,
+            (None, None) => 0,
+            _ => 1
+        }
+    }
+
+    fn is_chosen(&self, point: usize) -> bool {
+        self.start == Some(point) || self.end == Some(point)
+    }
+}
+
+/// Finds the shortest open path between chosen [endpoints](Endpoints) with any solver, which is what a plotter or
+/// CNC tool that starts and parks at set places needs.
+///
+/// The wrapped solver searches the map with every edge at a chosen point made longer by more than the length of
+/// the shortest path with the right ends, so that the shortest paths reach each chosen point by a single edge, at
+/// an end. An exact solver therefore finds the shortest path between the endpoints. A heuristic's path is turned
+/// around and [repaired](Endpoints::repair) if it ends elsewhere. Lengths in the progress reports, the result and
+/// its lower bound are those without the penalty, though the wrapped solver may report some for paths with other
+/// ends. Fails when maximizing and on maps with [time windows](Map::with_time_windows), where the penalty would
+/// not steer the search but delay every visit.
+#[derive(Clone, Copy)]
+pub struct FixedEndpoints<'a> {
+    pub solver: &'a dyn Solver,
+    pub endpoints: Endpoints
+}
+
+impl Solver for FixedEndpoints<'_> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        self.endpoints.validate(map)?;
+        if self.endpoints.chosen() == 0 || map.size() < 2 {
+            return self.solver.solve(map, options);
+        }
+        if options.objective == Objective::Maximize || map.time_windows().is_some() {
+            return Err(Error::InvalidProblem(
+                "fixed endpoints can only be used when minimizing the length of a path".to_string()
+            ));
+        }
+
+        let endpoints = self.endpoints;
+        let penalty = penalty(map, &endpoints);
+        let charged = penalty * endpoints.chosen() as f32;
+        let penalized = {
+            let map = map.clone();
+            let extra = move |point: usize| if endpoints.is_chosen(point) { penalty } else { 0.0 };
+            map.clone().with_costs(move |i, j| map.dist(i, j) + extra(i) + extra(j))
+        };
+        // A context for the map would fit the penalized one whenever the map has costs of its own
+        let inner = SolveOptions {
+            progress: options.progress.clone().map(|progress| {
+                Arc::new(Unpenalized {
+                    progress,
+                    endpoints,
+                    charged
+                }) as Arc<dyn Progress>
+            }),
+            lower_bound: options.lower_bound.map(|bound| bound + charged),
+            context: None,
+            ..options.clone()
+        };
+
+        let solved = self.solver.solve(&penalized, &inner)?;
+        let mut result = SolveResult::new(map, endpoints.repair(&solved.tour), solved.iterations, start);
+        result.lower_bound = solved.lower_bound.map(|bound| bound - charged);
+        result.termination = solved.termination;
+        Ok(result)
+    }
+}
+
+// The penalty for every edge at a chosen point: twice the length of a path with the right ends, which is more than
+// any path could save by reaching a chosen point by two edges
+fn penalty(map: &Map, endpoints: &Endpoints) -> f32 {
+    let path = endpoints.repair(&(0..map.size()).collect::<Vec<_>>());
+    let length: f32 = path.windows(2).map(|edge| map.dist(edge[0], edge[1])).sum();
+    if length > 0.0 { 2.0 * length } else { 1.0 }
+}
+
+// Passes progress on with the penalty taken off every length, and the tours repaired
+struct Unpenalized {
+    progress: Arc<dyn Progress>,
+    endpoints: Endpoints,
+    charged: f32
+}
+
+impl Progress for Unpenalized {
+    fn report(&self, event: ProgressEvent) {
+        self.progress.report(match event {
+            ProgressEvent::Sample { step, best, mean, elapsed } => ProgressEvent::Sample {
+                step,
+                best: best - self.charged,
+                mean: mean - self.charged,
+                elapsed
+            },
+            ProgressEvent::Improvement { length, elapsed } => ProgressEvent::Improvement {
+                length: length - self.charged,
+                elapsed
+            },
+            event => event
+        });
+    }
+
+    fn tour(&self, tour: &[usize]) {
+        self.progress.tour(&self.endpoints.repair(tour));
+    }
+
+    fn detailed(&self) -> bool {
+        self.progress.detailed()
+    }
+}
@@ -0,0 +1,137 @@
+use itertools::Itertools;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::result;
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Errors produced by fallible operations throughout the crate.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The map has no points
+    EmptyMap,
+    /// The point with this index has a coordinate that is NaN or infinite
+    NonFinitePoint {
+        index: usize
+    },
+    /// The attached distance between these points is NaN
+    NanDistance {
+        from: usize,
+        to: usize
+    },
+    /// A chromosome, tour, or other structure does not match the size of the map
+    DimensionMismatch {
+        expected: usize,
+        found: usize
+    },
+    /// A tour is not a permutation of the map's indices, or cannot be represented
+    InvalidTour(String),
+    /// Input could not be parsed
+    Parse {
+        line: usize,
+        message: String
+    },
+    /// Reading or writing a file failed
+    Io {
+        kind: io::ErrorKind,
+        message: String
+    },
+    /// No GPU is available, or it failed to run a computation
+    Gpu(String),
+    /// The instance is too large for the solver, such as for the memory an exact solver would need
+    TooLarge(String),
+    /// The problem is inconsistent, such as having more salesmen than points to visit
+    InvalidProblem(String),
+    /// A certificate of optimality does not prove what it claims
+    InvalidCertificate(String),
+    /// No solver is registered under this name
+    UnknownSolver(String),
+    /// A solver's settings are out of range or contradict each other, such as more elites than replacements
+    InvalidSettings(String)
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyMap => write!(f, "the map has no points"),
+            Error::NonFinitePoint { index } => {
+                write!(f, "point {} has a coordinate that is not a finite number", index)
+            },
+            Error::NanDistance { from, to } => {
+                write!(f, "the distance between points {} and {} is not a number", from, to)
+            },
+            Error::DimensionMismatch { expected, found } => {
+                write!(f, "dimension mismatch: expected {}, found {}", expected, found)
+            },
+            Error::InvalidTour(reason) => write!(f, "invalid tour: {}", reason),
+            Error::Parse { line, message } => write!(f, "parse error on line {}: {}", line, message),
+            Error::Io { message, .. } => write!(f, "i/o error: {}", message),
+            Error::Gpu(message) => write!(f, "gpu error: {}", message),
+            Error::TooLarge(message) => write!(f, "instance too large: {}", message),
+            Error::InvalidProblem(message) => write!(f, "invalid problem: {}", message),
+            Error::InvalidCertificate(message) => write!(f, "invalid certificate: {}", message),
+            Error::UnknownSolver(name) => write!(f, "unknown solver: {}", name),
+            Error::InvalidSettings(message) => write!(f, "invalid settings: {}", message)
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Everything that keeps a tour from visiting every point of a map exactly once, from
+/// [`Map::validate_tour`](crate::map::Map::validate_tour).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TourError {
+    /// The points the tour never visits, in increasing order
+    pub missing: Vec<usize>,
+    /// The points the tour visits more than once, in increasing order
+    pub duplicates: Vec<usize>,
+    /// The indices of the tour past the last point of the map, in the order they appear
+    pub out_of_range: Vec<usize>
+}
+
+// Lists longer than this are cut short in messages, since a tour for the wrong map can get every point wrong
+const LISTED: usize = 10;
+
+impl Display for TourError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let clauses = [
+            (&self.missing, "point", "points", "missing"),
+            (&self.duplicates, "point", "points", "visited more than once"),
+            (&self.out_of_range, "index", "indices", "out of range")
+        ];
+        let mut first = true;
+        for (values, one, many, what) in clauses.iter().filter(|(values, ..)| !values.is_empty()) {
+            if !first {
+                f.write_str("; ")?;
+            }
+            first = false;
+
+            let (noun, verb) = if values.len() == 1 { (one, "is") } else { (many, "are") };
+            write!(f, "{} {}", noun, values.iter().take(LISTED).join(", "))?;
+            if values.len() > LISTED {
+                write!(f, " and {} more", values.len() - LISTED)?;
+            }
+            write!(f, " {} {}", verb, what)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for TourError {}
+
+impl From<TourError> for Error {
+    fn from(error: TourError) -> Self {
+        Error::InvalidTour(error.to_string())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io {
+            kind: error.kind(),
+            message: error.to_string()
+        }
+    }
+}
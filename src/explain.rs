@@ -0,0 +1,249 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+
+use crate::error::Result;
+use crate::local;
+use crate::map::{Map, check_permutation};
+use crate::search_tree::{Outcome, SearchTree};
+use crate::solve::{self, Budget, Reporter, SolveOptions, SolveResult, check_map};
+use crate::time::Instant;
+
+/// One decision made by a solver, as recorded by the `explain_` functions of this module.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(tag = "step", rename_all = "snake_case"))]
+pub enum Step {
+    /// Nearest neighbor moved from `from` to `chosen`, the nearest unvisited point, passing over the
+    /// `alternatives`: every other unvisited point with its distance, nearest first
+    Choose {
+        from: usize,
+        chosen: usize,
+        distance: f32,
+        alternatives: Vec<(usize, f32)>
+    },
+    /// 2-opt reversed the tour between positions `i` and `j`, which held the points `first` and `last`, changing
+    /// its length by `change` to `length`. On maps with time windows, turn penalties or forbidden edges, the
+    /// length is the cost the search lowers instead.
+    Reverse {
+        i: usize,
+        j: usize,
+        first: usize,
+        last: usize,
+        change: f32,
+        length: f32
+    },
+    /// 2-opt tried every reversal of the tour once and made `moves` of them, leaving it at `length`. The search
+    /// ends after a pass without moves.
+    Pass {
+        moves: u64,
+        length: f32
+    },
+    /// Branch-and-bound searched below the path, whose `bound` on the length of any path completing it was less
+    /// than the best length found so far
+    Branch {
+        path: Vec<usize>,
+        length: f32,
+        bound: f32
+    },
+    /// Branch-and-bound cut off the path, whose `bound` could not beat `best`, the best length found so far
+    Prune {
+        path: Vec<usize>,
+        length: f32,
+        bound: f32,
+        best: f32
+    },
+    /// Branch-and-bound ruled out the path because its last point was not ready or could not be reached
+    Reject {
+        path: Vec<usize>,
+        reason: Rejection
+    },
+    /// Branch-and-bound completed the path, which is `improved` if it was shorter than every path found before it
+    Complete {
+        path: Vec<usize>,
+        length: f32,
+        improved: bool
+    },
+    /// Branch-and-bound ran out of time before looking at the path
+    Stop {
+        path: Vec<usize>
+    }
+}
+
+/// Why branch-and-bound ruled out a path without measuring it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum Rejection {
+    /// Some of the last point's [predecessors](Map::with_precedences) were not on the path yet
+    Precedence,
+    /// The [edge constraints](Map::with_edge_constraints) ruled out the edge to the last point
+    Edge
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let path = |path: &[usize]| path.iter().map(usize::to_string).collect::<Vec<_>>().join(" ");
+        match self {
+            Step::Choose { from, chosen, distance, alternatives } => {
+                write!(f, "from {} go to {} at distance {:.4}", from, chosen, distance)?;
+                if let Some(&(next, next_distance)) = alternatives.first() {
+                    write!(
+                        f,
+                        ", passing over {} other points, the nearest {} at {:.4}",
+                        alternatives.len(),
+                        next,
+                        next_distance
+                    )?;
+                }
+                Ok(())
+            },
+            Step::Reverse { i, j, first, last, change, length } => write!(
+                f,
+                "reverse positions {} to {} (points {} to {}), changing the length by {:.4} to {:.4}",
+                i, j, first, last, change, length
+            ),
+            Step::Pass { moves, length } => {
+                write!(f, "pass finished with {} reversals at length {:.4}", moves, length)
+            },
+            Step::Branch { path: nodes, length, bound } => {
+                write!(f, "branch on [{}] at length {:.4}, bound {:.4}", path(nodes), length, bound)
+            },
+            Step::Prune { path: nodes, length, bound, best } => write!(
+                f,
+                "prune [{}] at length {:.4}: bound {:.4} cannot beat {:.4}",
+                path(nodes), length, bound, best
+            ),
+            Step::Reject { path: nodes, reason: Rejection::Precedence } => {
+                write!(f, "reject [{}]: a predecessor of the last point is missing", path(nodes))
+            },
+            Step::Reject { path: nodes, reason: Rejection::Edge } => {
+                write!(f, "reject [{}]: the edge to the last point is ruled out", path(nodes))
+            },
+            Step::Complete { path: nodes, length, improved } => write!(
+                f,
+                "complete [{}] at length {:.4}{}",
+                path(nodes),
+                length,
+                if *improved { ", the new best" } else { "" }
+            ),
+            Step::Stop { path: nodes } => write!(f, "stop before [{}]: out of time", path(nodes))
+        }
+    }
+}
+
+/// The decisions a solver made on its way to a result, in order, for following a small solve step by step.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Explanation {
+    pub steps: Vec<Step>
+}
+
+impl Explanation {
+    pub fn save_text<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write_text(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes one numbered step per line, in words.
+    pub fn write_text<W: Write>(&self, mut out: W) -> Result<()> {
+        for (index, step) in self.steps.iter().enumerate() {
+            writeln!(out, "{:>5}. {}", index + 1, step)?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Writes one step per line as JSON, tagged with its kind, such as
+    /// `{"step":"pass","moves":2,"length":7.5}`.
+    #[cfg(feature = "serde")]
+    pub fn write_json_lines<W: Write>(&self, mut out: W) -> Result<()> {
+        for step in &self.steps {
+            serde_json::to_writer(&mut out, step).map_err(std::io::Error::from)?;
+            writeln!(out)?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Runs [`nearest_neighbor`](solve::nearest_neighbor), recording every point it moves to and the points it passed
+/// over.
+pub fn explain_nearest_neighbor(map: &Map) -> Result<(SolveResult, Explanation)> {
+    let mut steps = Vec::new();
+    let result = solve::nearest_neighbor_steps(map, Some(&mut steps))?;
+    Ok((result, Explanation { steps }))
+}
+
+/// Runs [`two_opt`](local::two_opt) on a single thread, recording every reversal it makes and every pass over the
+/// tour.
+pub fn explain_two_opt(map: &Map, tour: &[usize], options: &SolveOptions) -> Result<(SolveResult, Explanation)> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_permutation(map, tour)?;
+
+    let mut budget = Budget::new(options, start, 1);
+    let objective = options.objective;
+    let mut reporter = Reporter::new(options, start).objective(objective);
+    let mut tour = tour.to_vec();
+    let mut steps = Vec::new();
+    let any = |_: &[usize], _, _| true;
+    let moves = local::two_opt_steps(map, &mut tour, objective, any, &mut budget, &mut reporter, Some(&mut steps));
+
+    let mut result = SolveResult::new(map, tour, moves, start);
+    result.termination = budget.termination();
+    Ok((result, Explanation { steps }))
+}
+
+/// Runs [`trace_branch_and_bound`](solve::trace_branch_and_bound), recording every path it branched on, pruned,
+/// ruled out or completed, with its length and bound. Fails on instances of more than 10 points.
+pub fn explain_branch_and_bound(map: &Map, options: &SolveOptions) -> Result<(SolveResult, Explanation)> {
+    let (result, tree) = solve::trace_branch_and_bound(map, options)?;
+    Ok((result, explain_tree(&tree)))
+}
+
+// The steps of a search tree, whose nodes are in the order the search reached them
+fn explain_tree(tree: &SearchTree) -> Explanation {
+    let mut paths: Vec<Vec<usize>> = Vec::with_capacity(tree.nodes.len());
+    let mut steps = Vec::with_capacity(tree.nodes.len());
+    for node in &tree.nodes {
+        let mut path = node.parent.map(|parent| paths[parent].clone()).unwrap_or_default();
+        path.extend(node.point);
+        paths.push(path.clone());
+
+        // The root's empty path is not a decision
+        if node.parent.is_none() {
+            continue;
+        }
+        let (length, bound) = (node.length, node.bound);
+        steps.push(match node.outcome {
+            Outcome::Expanded => Step::Branch {
+                path,
+                length,
+                bound
+            },
+            Outcome::Pruned { best } => Step::Prune {
+                path,
+                length,
+                bound,
+                best
+            },
+            Outcome::Precedence => Step::Reject {
+                path,
+                reason: Rejection::Precedence
+            },
+            Outcome::Edge => Step::Reject {
+                path,
+                reason: Rejection::Edge
+            },
+            Outcome::Leaf { improved } => Step::Complete {
+                path,
+                length,
+                improved
+            },
+            Outcome::Stopped => Step::Stop { path }
+        });
+    }
+
+    Explanation { steps }
+}
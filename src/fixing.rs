@@ -0,0 +1,97 @@
+use rand::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::frequencies::EdgeFrequencies;
+use crate::map::Map;
+use crate::solve::{SolveOptions, SolveResult, Solver, Termination, check_map};
+use crate::time::Instant;
+
+/// Closes the last of the gap on medium instances by fixing the edges good tours agree on and solving what is left.
+/// The `solver` runs `runs` times with different seeds, the edges used by at least `threshold` of the tours it
+/// finds are [fixed](EdgeFrequencies::fix_on) on top of any constraints the map has, and `refine` then solves the
+/// reduced map, such as branch-and-bound when few edges are left free, or Lin-Kernighan, which both keep the fixed
+/// edges. The reduced map has the same points and costs, so its tours are tours of the map, and the best of every
+/// tour found is returned. A [context](SolveOptions::context) for the map still fits the reduced one.
+///
+/// A time limit is shared out evenly between the runs and the refinement, each taking its share of whatever time
+/// is left. The seed of every run is derived from that of the options. A lower bound the refinement proves only
+/// holds for the reduced map, so the result carries none.
+#[derive(Clone, Copy)]
+pub struct EdgeFixing<'a> {
+    pub solver: &'a dyn Solver,
+    pub refine: &'a dyn Solver,
+    pub runs: usize,
+    pub threshold: f32
+}
+
+impl Solver for EdgeFixing<'_> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        if self.runs == 0 {
+            return Err(Error::InvalidSettings("edge fixing needs at least one run to fix edges from".to_string()));
+        }
+        if !(self.threshold > 0.0 && self.threshold <= 1.0) {
+            return Err(Error::InvalidSettings(format!(
+                "the threshold is {}, not above 0 and at most 1",
+                self.threshold
+            )));
+        }
+
+        let seed = options.seed.unwrap_or_else(|| options.rng().gen());
+        // Each stage takes an even share of the time left, the refinement all of what remains
+        let share = |stages: usize| SolveOptions {
+            time_limit: options.time_limit.map(|limit| limit.saturating_sub(start.elapsed()) / stages as u32),
+            ..options.clone()
+        };
+        let loss = |result: &SolveResult| options.objective.loss(result.length);
+
+        let mut tours = Vec::with_capacity(self.runs);
+        let mut best: Option<SolveResult> = None;
+        let mut iterations = 0;
+        let mut termination = Termination::Completed;
+        for run in 0..self.runs {
+            let result = self.solver.solve(map, &SolveOptions {
+                seed: Some(seed.wrapping_add(run as u64)),
+                ..share(self.runs - run + 1)
+            })?;
+            iterations += result.iterations;
+            tours.push(result.tour.clone());
+            // A run stopped by its share of the time limit leaves the rest of the time to the others, but one
+            // stopped any other way stops the whole search
+            if !matches!(result.termination, Termination::Completed | Termination::TimeLimit) {
+                termination = result.termination;
+            }
+            if best.as_ref().is_none_or(|best| loss(&result) < loss(best)) {
+                best = Some(result);
+            }
+            if termination != Termination::Completed {
+                break;
+            }
+        }
+
+        if termination == Termination::Completed {
+            let frequencies = EdgeFrequencies::from_tours(map.size(), &tours)?;
+            let constraints = match map.edge_constraints() {
+                Some(edges) => frequencies.fix_on(edges, self.threshold)?,
+                None => frequencies.fixed_edges(self.threshold)?
+            };
+            let reduced = map.clone().with_edge_constraints(constraints)?;
+            let refined = self.refine.solve(&reduced, &SolveOptions {
+                seed: Some(seed.wrapping_add(self.runs as u64)),
+                ..share(1)
+            })?;
+            iterations += refined.iterations;
+            termination = refined.termination;
+            if best.as_ref().is_none_or(|best| loss(&refined) < loss(best)) {
+                best = Some(refined);
+            }
+        }
+
+        // Infallible: there is at least one run
+        let best = best.unwrap();
+        let mut result = SolveResult::new(map, best.tour, iterations, start);
+        result.termination = termination;
+        Ok(result)
+    }
+}
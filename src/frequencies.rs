@@ -0,0 +1,184 @@
+use std::cmp;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+
+use crate::edges::EdgeConstraints;
+use crate::error::{Error, Result};
+use crate::map::{Map, Path};
+
+/// How many of a set of tours use every edge, such as the individuals of a GA population or an archive of good
+/// tours, which shows the edges a search has settled on. Edges are undirected, and the counts are kept for every
+/// pair of points, which takes memory quadratic in the size of the map. The frequencies can be written out as an
+/// n×n matrix for plotting as a heatmap, and the edges nearly every tour agrees on can be
+/// [fixed](Self::fixed_edges) to shrink the search.
+#[derive(Clone, Debug)]
+pub struct EdgeFrequencies {
+    size: usize,
+    // The counts of the pairs above the diagonal, row by row, as a distance matrix keeps its distances
+    counts: Box<[u32]>,
+    tours: u32
+}
+
+impl EdgeFrequencies {
+    /// No tours yet, for a map of `size` points.
+    pub fn new(size: usize) -> Self {
+        EdgeFrequencies {
+            size,
+            counts: vec![0; size * size.saturating_sub(1) / 2].into_boxed_slice(),
+            tours: 0
+        }
+    }
+
+    /// Counts the edges of every tour, failing like [`add`](Self::add) on the first that is not valid.
+    pub fn from_tours<I>(size: usize, tours: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[usize]>
+    {
+        let mut frequencies = Self::new(size);
+        for tour in tours {
+            frequencies.add(tour.as_ref())?;
+        }
+        Ok(frequencies)
+    }
+
+    /// Counts the edges of the tour of every individual of the population.
+    pub fn from_population<P: Path>(map: &Map, population: &[P]) -> Result<Self> {
+        Self::from_tours(map.size(), population.iter().map(|indv| indv.tour(map)))
+    }
+
+    /// Counts the edges of one more tour, which must visit points below the size at most once each. Fails without
+    /// counting anything otherwise.
+    pub fn add(&mut self, tour: &[usize]) -> Result<()> {
+        let mut visited = vec![false; self.size];
+        for &point in tour {
+            if point >= self.size {
+                return Err(Error::InvalidTour(format!("point {} is out of range for {} points", point, self.size)));
+            }
+            if visited[point] {
+                return Err(Error::InvalidTour(format!("point {} is visited more than once", point)));
+            }
+            visited[point] = true;
+        }
+
+        for edge in tour.windows(2) {
+            let index = self.index(edge[0], edge[1]);
+            self.counts[index] += 1;
+        }
+        self.tours += 1;
+        Ok(())
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tours counted.
+    pub fn tours(&self) -> u32 {
+        self.tours
+    }
+
+    /// How many of the tours use the edge between the two points, which is zero from a point to itself.
+    pub fn count(&self, a: usize, b: usize) -> u32 {
+        if a == b { 0 } else { self.counts[self.index(a, b)] }
+    }
+
+    /// The fraction of the tours that use the edge between the two points, or zero before any tour is counted.
+    pub fn frequency(&self, a: usize, b: usize) -> f32 {
+        if self.tours == 0 { 0.0 } else { self.count(a, b) as f32 / self.tours as f32 }
+    }
+
+    /// Every edge some tour uses, with the lower point first, and its count, the most used edges first.
+    pub fn edges(&self) -> Vec<(usize, usize, u32)> {
+        let mut edges: Vec<_> = (0..self.size)
+            .flat_map(|a| (a + 1..self.size).map(move |b| (a, b)))
+            .map(|(a, b)| (a, b, self.count(a, b)))
+            .filter(|&(.., count)| count > 0)
+            .collect();
+        edges.sort_by_key(|&(.., count)| cmp::Reverse(count));
+        edges
+    }
+
+    /// The edges used by at least `threshold` of the tours, most used first, leaving out any that would give a
+    /// point a third edge or close a loop with those before it, so that they can all be on one path.
+    pub fn decided(&self, threshold: f32) -> Vec<(usize, usize)> {
+        self.decide(threshold, None)
+    }
+
+    /// Fixes the [decided](Self::decided) edges, for attaching to the map with
+    /// [`Map::with_edge_constraints`] so that searching it again only rearranges the rest of the tour.
+    pub fn fixed_edges(&self, threshold: f32) -> Result<EdgeConstraints> {
+        EdgeConstraints::new(self.size, self.decided(threshold), Vec::new())
+    }
+
+    /// Like [`fixed_edges`](Self::fixed_edges), but on top of the given constraints, leaving out the decided edges
+    /// they forbid or that cannot be on one path with the edges they fix.
+    pub fn fix_on(&self, edges: &EdgeConstraints, threshold: f32) -> Result<EdgeConstraints> {
+        let fixed = edges.fixed().iter().copied().chain(self.decide(threshold, Some(edges))).collect();
+        EdgeConstraints::new(self.size, fixed, edges.forbidden().to_vec())
+    }
+
+    pub fn save_csv<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write_csv(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes the frequencies as an n×n matrix of comma separated values, a row for every point, which is
+    /// symmetric and zero on the diagonal.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> Result<()> {
+        for a in 0..self.size {
+            for b in 0..self.size {
+                let separator = if b == 0 { "" } else { "," };
+                write!(out, "{}{}", separator, self.frequency(a, b))?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    // The decided edges that can join those the constraints fix, if there are any
+    fn decide(&self, threshold: f32, edges: Option<&EdgeConstraints>) -> Vec<(usize, usize)> {
+        let mut degree = vec![0u8; self.size];
+        // Each point's representative among the points joined to it by the edges taken so far
+        let mut parent: Vec<usize> = (0..self.size).collect();
+        let find = |parent: &mut Vec<usize>, mut point: usize| {
+            while parent[point] != point {
+                parent[point] = parent[parent[point]];
+                point = parent[point];
+            }
+            point
+        };
+        let join = |parent: &mut Vec<usize>, degree: &mut Vec<u8>, a: usize, b: usize| {
+            let (root_a, root_b) = (find(parent, a), find(parent, b));
+            if degree[a] == 2 || degree[b] == 2 || root_a == root_b {
+                return false;
+            }
+            parent[root_a] = root_b;
+            degree[a] += 1;
+            degree[b] += 1;
+            true
+        };
+
+        let fixed = edges.map_or(&[][..], EdgeConstraints::fixed);
+        for &(a, b) in fixed {
+            join(&mut parent, &mut degree, a, b);
+        }
+        let allowed = |a: usize, b: usize| {
+            edges.is_none_or(|edges| !edges.is_fixed(a, b) && !edges.is_forbidden(a, b))
+        };
+        self.edges()
+            .into_iter()
+            .filter(|&(a, b, _)| self.frequency(a, b) >= threshold && allowed(a, b))
+            .filter(|&(a, b, _)| join(&mut parent, &mut degree, a, b))
+            .map(|(a, b, _)| (a, b))
+            .collect()
+    }
+
+    // Row `i` holds the counts of the edges to the points after it, as in a distance matrix
+    fn index(&self, a: usize, b: usize) -> usize {
+        let (i, j) = if a < b { (a, b) } else { (b, a) };
+        assert!(j < self.size, "Point {} is out of range", j);
+        i * (2 * self.size - i - 1) / 2 + (j - i - 1)
+    }
+}
@@ -1,33 +1,359 @@
 use rand::prelude::*;
-use std::cmp;
+use std::mem;
+use std::sync::Mutex;
 
-use crate::map::{Map, Path};
+use crate::context::SolverContext;
+use crate::error::{Error, Result};
+use crate::instrument;
+use crate::map::{Decoder, Map, Path, RandomKeyPath, Tour, check_tour};
+use crate::matrix::check_quadratic;
+use crate::neighbors::Neighbors;
+use crate::solve::{Budget, Objective, Reporter, SolveOptions, SolveResult, Solver, check_map, known_bound};
+use crate::time::Instant;
 
+/// Parameters shared by the roulette wheel GA and the drivers built on top of it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
 pub struct Settings {
     pub replace_percent: f32,
     pub elitist_percent: f32,
     pub crossover_prob: f32,
     pub mutate_prob: f32,
-    pub selection_noise: f32
+    pub selection_noise: f32,
+    pub population_schedule: PopulationSchedule,
+    pub operator_selection: OperatorSelection,
+    /// Repair offspring with up to this many passes of 2-opt over the candidate lists, instead of the cheaper
+    /// repairs of [`Path::fix_with`], whenever they are repaired over candidate lists
+    pub two_opt_passes: u32,
+    /// Estimate the fitness of every individual by the mean of this many evaluations, for maps whose
+    /// [costs](Map::with_costs) are drawn at random, so that the GA works towards the expected length of a tour
+    /// rather than the luck of a single draw. One evaluation is all a map with fixed distances needs
+    pub fitness_samples: u32,
+    /// Evaluate the individuals kept as elites this many more times every generation, and average the extra
+    /// evaluations into their fitness, so that an individual does not stay an elite on a lucky estimate
+    pub elite_samples: u32
 }
 
+/// How the population size changes over the course of a run.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum PopulationSchedule {
+    /// Keep whatever size the population started with
+    Constant,
+    /// Interpolate linearly from `start` to `end` over the given number of generations
+    Linear {
+        start: usize,
+        end: usize,
+        generations: u32
+    },
+    /// Each step is (first generation, size), sorted by generation
+    Stepped {
+        steps: Box<[(u32, usize)]>
+    }
+}
+
+/// How the roulette wheel GA chooses the [operators](Operator) each pair of offspring is made with. Adaptive
+/// selection rewards an operator whenever a child it made is better than both of its parents, and chooses between
+/// the solver's recombinator and one-point, two-point and uniform crossover, and between point and segment
+/// mutation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum OperatorSelection {
+    /// Always recombine with the solver's recombinator and mutate single genes
+    Fixed,
+    /// Choose every operator with a probability of at least `min_prob`, and share the rest in proportion to how
+    /// often each operator improved lately, weighing every new outcome by `adaptation`
+    ProbabilityMatching {
+        min_prob: f32,
+        adaptation: f32
+    },
+    /// Try every operator once, then choose the one with the highest upper confidence bound (UCB1) on its rate of
+    /// improvement, with `exploration` scaling how far the bound reaches above the rate
+    Ucb {
+        exploration: f32
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            replace_percent: 1.0,
+            elitist_percent: 0.25,
+            crossover_prob: 0.9,
+            mutate_prob: 0.05,
+            selection_noise: 0.0,
+            population_schedule: PopulationSchedule::Constant,
+            operator_selection: OperatorSelection::Fixed,
+            two_opt_passes: 0,
+            fitness_samples: 1,
+            elite_samples: 0
+        }
+    }
+}
+
+impl Settings {
+    /// Checks that the settings make sense together, failing with [`Error::InvalidSettings`] on the first that do
+    /// not: the percentages and probabilities must be between zero and one, no more of the population may be kept
+    /// as elites than is replaced, the selection noise may not be negative, the population schedule may not
+    /// shrink the population below two individuals or list its steps out of order, the operator selection must
+    /// leave room for its adaptation, and every fitness needs at least one sample. The GA solvers check their
+    /// settings this way before starting.
+    pub fn validate(&self) -> Result<()> {
+        let fraction = |name: &str, value: f32| {
+            if (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(Error::InvalidSettings(format!("{} is {}, not between 0 and 1", name, value)))
+            }
+        };
+        fraction("replace_percent", self.replace_percent)?;
+        fraction("elitist_percent", self.elitist_percent)?;
+        fraction("crossover_prob", self.crossover_prob)?;
+        fraction("mutate_prob", self.mutate_prob)?;
+        if self.elitist_percent > self.replace_percent {
+            return Err(Error::InvalidSettings(format!(
+                "elitist_percent is {}, more than the replace_percent of {}",
+                self.elitist_percent, self.replace_percent
+            )));
+        }
+        if !(self.selection_noise >= 0.0 && self.selection_noise.is_finite()) {
+            return Err(Error::InvalidSettings(format!(
+                "selection_noise is {}, not a finite number of at least 0",
+                self.selection_noise
+            )));
+        }
+        if self.fitness_samples == 0 {
+            return Err(Error::InvalidSettings("fitness_samples is 0, but every fitness needs a sample".to_string()));
+        }
+        self.population_schedule.validate()?;
+        self.operator_selection.validate()
+    }
+}
+
+impl PopulationSchedule {
+    // Checks that every size is at least two and the steps are in order
+    fn validate(&self) -> Result<()> {
+        let sizes: Vec<usize> = match self {
+            PopulationSchedule::Constant => Vec::new(),
+            &PopulationSchedule::Linear { start, end, .. } => vec![start, end],
+            PopulationSchedule::Stepped { steps } => {
+                if steps.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+                    let message = "the steps of the population schedule are not sorted by generation";
+                    return Err(Error::InvalidSettings(message.to_string()));
+                }
+                steps.iter().map(|&(_, size)| size).collect()
+            }
+        };
+        match sizes.into_iter().find(|&size| size < 2) {
+            Some(size) => Err(Error::InvalidSettings(format!(
+                "the population schedule has a size of {}, but a population needs at least two individuals",
+                size
+            ))),
+            None => Ok(())
+        }
+    }
+
+    /// The population size for the given generation, or `None` if the size should be left alone.
+    pub fn size(&self, generation: u32) -> Option<usize> {
+        match self {
+            PopulationSchedule::Constant => None,
+            &PopulationSchedule::Linear { start, end, generations } => {
+                if generation >= generations {
+                    return Some(end);
+                }
+
+                let t = generation as f32 / generations as f32;
+                Some((start as f32 + (end as f32 - start as f32) * t).round() as usize)
+            },
+            PopulationSchedule::Stepped { steps } => steps.iter()
+                .take_while(|(from, _)| *from <= generation)
+                .last()
+                .map(|&(_, size)| size)
+        }
+    }
+}
+
+impl OperatorSelection {
+    // Checks that the minimum probability leaves some to share between four crossovers, and that the parameters
+    // are in range
+    fn validate(&self) -> Result<()> {
+        match *self {
+            OperatorSelection::Fixed => Ok(()),
+            OperatorSelection::ProbabilityMatching { min_prob, .. } if !(0.0..=0.25).contains(&min_prob) => {
+                Err(Error::InvalidSettings(format!("min_prob is {}, not between 0 and 0.25", min_prob)))
+            },
+            OperatorSelection::ProbabilityMatching { adaptation, .. } if !(adaptation > 0.0 && adaptation <= 1.0) => {
+                Err(Error::InvalidSettings(format!("adaptation is {}, not above 0 and at most 1", adaptation)))
+            },
+            OperatorSelection::Ucb { exploration } if !(exploration >= 0.0 && exploration.is_finite()) => {
+                Err(Error::InvalidSettings(format!(
+                    "exploration is {}, not a finite number of at least 0",
+                    exploration
+                )))
+            },
+            _ => Ok(())
+        }
+    }
+
+    // One of the operators, which are either all crossovers or all mutations, by the statistics so far. Fixed
+    // selection takes the first without drawing from the generator.
+    fn choose(&self, stats: &OperatorStats, operators: &[Operator], rng: &mut impl Rng) -> Operator {
+        match *self {
+            OperatorSelection::Fixed => operators[0],
+            OperatorSelection::ProbabilityMatching { min_prob, .. } => {
+                let total: f32 = operators.iter().map(|&operator| stats.quality[operator as usize]).sum();
+                let shared = 1.0 - min_prob * operators.len() as f32;
+                let mut random = rng.gen::<f32>();
+                for &operator in operators {
+                    let share = if total > 0.0 {
+                        stats.quality[operator as usize] / total
+                    } else {
+                        1.0 / operators.len() as f32
+                    };
+                    random -= min_prob + shared * share;
+                    if random < 0.0 {
+                        return operator;
+                    }
+                }
+                operators[operators.len() - 1]
+            },
+            OperatorSelection::Ucb { exploration } => {
+                if let Some(&untried) = operators.iter().find(|&&operator| stats.applications(operator) == 0) {
+                    return untried;
+                }
+                let total: u64 = operators.iter().map(|&operator| stats.applications(operator)).sum();
+                let bound = |operator: Operator| {
+                    let applications = stats.applications(operator) as f32;
+                    let rate = stats.improvements(operator) as f32 / applications;
+                    rate + exploration * (2.0 * (total as f32).ln() / applications).sqrt()
+                };
+                // Infallible: the operators are never empty
+                operators.iter().copied().max_by(|&a, &b| bound(a).total_cmp(&bound(b))).unwrap()
+            }
+        }
+    }
+}
+
+/// Grows or shrinks the population to the target size. The population is sorted by loss first so that
+/// shrinking only discards the worst individuals, and growing fills the gap with mutated copies of the
+/// current best individuals.
+pub fn resize_population<C: Chromosome>(
+    map: &Map,
+    population: &mut Vec<C>,
+    target: usize,
+    objective: Objective,
+    rng: &mut impl Rng
+) {
+    assert!(target >= 2, "Population must contain at least two individuals");
+
+    if target == population.len() || population.is_empty() {
+        return;
+    }
+
+    let mut ranked: Vec<(f32, C)> = population.drain(..)
+        .map(|indv| (objective.loss(indv.evaluate(map)), indv))
+        .collect();
+    ranked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    population.extend(ranked.into_iter().map(|(_, indv)| indv));
+
+    if target < population.len() {
+        population.truncate(target);
+        return;
+    }
+
+    let parents = population.len();
+    while population.len() < target {
+        let mut child = population[population.len() % parents].clone();
+        child.point_mutation(rng.gen::<usize>() % child.len(), rng);
+        population.push(child);
+    }
+}
+
+/// Creates `size` chromosomes and redraws every gene from `rng`, so that the population depends only on the
+/// generator and not on the randomness the chromosomes were constructed with.
+pub fn random_population<C, F>(map: &Map, size: usize, new_chromosome: F, rng: &mut impl Rng) -> Vec<C>
+where
+    C: Chromosome,
+    F: Fn(&Map) -> C
+{
+    (0..size)
+        .map(|_| {
+            let mut indv = new_chromosome(map);
+            (0..indv.len()).for_each(|index| indv.point_mutation(index, rng));
+            indv
+        })
+        .collect()
+}
+
+/// A path encoding that can be recombined and mutated.
+///
+/// The operations on the genes, which know nothing of the map, are kept apart from the mapping between the genes
+/// and the [tour](Tour) they stand for. An encoding only says how it decodes and encodes tours, and the encodings
+/// of this crate share the evaluation of the decoded tour, its validation, and the repairs for constrained maps,
+/// which encode the repaired tour in place of the old one.
+///
+/// With the `serde` feature, the crate's chromosomes serialize, so that a population can be dumped mid-run and
+/// loaded again later or on another machine. Nothing checks a deserialized chromosome against the map it is used
+/// with; one that does not fit the map can make the GA panic.
 pub trait Chromosome: Path + Clone {
+    /// The number of genes
     fn len(&self) -> usize;
 
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Swaps the genes in `start..end` with those of the other chromosome
     fn crossover(&mut self, other: &mut Self, start: usize, end: usize);
 
+    /// Redraws the gene at `index`
     fn point_mutation(&mut self, index: usize, rng: &mut impl Rng);
+
+    /// Writes the tour the genes stand for into the buffer, replacing its contents, so that decoding many
+    /// chromosomes needs no allocation once the buffer is large enough. Chromosomes that do not
+    /// [validate](Path::validate) against the map decode to meaningless tours or panic.
+    fn decode_into(&self, map: &Map, tour: &mut Vec<usize>);
+
+    /// The tour the genes stand for.
+    fn decode(&self, map: &Map) -> Tour {
+        let mut tour = Vec::with_capacity(map.size());
+        self.decode_into(map, &mut tour);
+        debug_assert_eq!(check_tour(map.size(), &tour), Ok(()));
+        Tour::from(tour)
+    }
+
+    /// Overwrites this chromosome so that it decodes to the given tour over the map's points
+    fn encode(&mut self, map: &Map, tour: &[usize]) -> Result<()>;
+}
+
+/// Seeds the first `copies` individuals of the population with a known tour, for example one from a
+/// previous run or a constructive heuristic.
+pub fn warm_start<C: Chromosome>(map: &Map, population: &mut [C], tour: &[usize], copies: usize) -> Result<()> {
+    population.iter_mut()
+        .take(copies)
+        .try_for_each(|indv| indv.encode(map, tour))
 }
 
 #[inline]
 pub fn slice_crossover<T>(first: &mut [T], second: &mut [T], start: usize, end: usize) {
-    (&mut first[start..end]).swap_with_slice(&mut second[start..end]);
+    first[start..end].swap_with_slice(&mut second[start..end]);
 }
 
+/// A crossover strategy producing two children in place from two parents.
 pub trait Recombinator {
     fn recombine<C: Chromosome>(&self, first: &mut C, second: &mut C, rng: &mut impl Rng);
 }
 
+/// K-point crossover with cut points spread evenly across the chromosome.
+#[derive(Clone, Copy, Debug)]
 pub struct KPoint {
     count: f32
 }
@@ -69,6 +395,8 @@ impl Recombinator for KPoint {
     }
 }
 
+/// Uniform crossover, switching parents with probability `weight` at every gene.
+#[derive(Clone, Copy, Debug)]
 pub struct Uniform {
     weight: f32
 }
@@ -89,6 +417,12 @@ impl Uniform {
     }
 }
 
+impl Default for Uniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Recombinator for Uniform {
     fn recombine<C: Chromosome>(&self, first: &mut C, second: &mut C, rng: &mut impl Rng) {
         assert_eq!(first.len(), second.len(), "Cannot recombine chromosomes of different lengths");
@@ -114,38 +448,399 @@ impl Recombinator for Uniform {
     }
 }
 
+/// Allows the recombination strategy to be chosen at runtime
+#[derive(Clone, Copy, Debug)]
+pub enum AnyRecombinator {
+    KPoint(KPoint),
+    Uniform(Uniform)
+}
+
+impl Recombinator for AnyRecombinator {
+    fn recombine<C: Chromosome>(&self, first: &mut C, second: &mut C, rng: &mut impl Rng) {
+        match self {
+            AnyRecombinator::KPoint(inner) => inner.recombine(first, second, rng),
+            AnyRecombinator::Uniform(inner) => inner.recombine(first, second, rng)
+        }
+    }
+}
+
+impl From<KPoint> for AnyRecombinator {
+    fn from(inner: KPoint) -> Self {
+        AnyRecombinator::KPoint(inner)
+    }
+}
+
+impl From<Uniform> for AnyRecombinator {
+    fn from(inner: Uniform) -> Self {
+        AnyRecombinator::Uniform(inner)
+    }
+}
+
+/// The crossover and mutation operators the roulette wheel GA makes offspring with, which it keeps
+/// [statistics](OperatorStats) for and chooses between as its [operator selection](OperatorSelection) says.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// The recombinator the solver was given, the only crossover unless operators are selected adaptively
+    Recombinator,
+    OnePointCrossover,
+    TwoPointCrossover,
+    UniformCrossover,
+    /// Redraws a single random gene, the only mutation unless operators are selected adaptively
+    PointMutation,
+    /// Redraws every gene of a random run of up to an eighth of the chromosome
+    SegmentMutation
+}
+
+impl Operator {
+    pub const ALL: [Operator; 6] = [
+        Operator::Recombinator,
+        Operator::OnePointCrossover,
+        Operator::TwoPointCrossover,
+        Operator::UniformCrossover,
+        Operator::PointMutation,
+        Operator::SegmentMutation
+    ];
+    const CROSSOVERS: [Operator; 4] = [
+        Operator::Recombinator,
+        Operator::OnePointCrossover,
+        Operator::TwoPointCrossover,
+        Operator::UniformCrossover
+    ];
+    const MUTATIONS: [Operator; 2] = [Operator::PointMutation, Operator::SegmentMutation];
+
+    /// The name the operator's applications are [counted](crate::instrument::Instrumentation::count) under at the
+    /// end of a run, such as `uniform_crossover`. Its improvements are counted under the same name followed by
+    /// `_improvements`.
+    pub fn name(self) -> &'static str {
+        self.names().0
+    }
+
+    fn names(self) -> (&'static str, &'static str) {
+        match self {
+            Operator::Recombinator => ("recombinator", "recombinator_improvements"),
+            Operator::OnePointCrossover => ("one_point_crossover", "one_point_crossover_improvements"),
+            Operator::TwoPointCrossover => ("two_point_crossover", "two_point_crossover_improvements"),
+            Operator::UniformCrossover => ("uniform_crossover", "uniform_crossover_improvements"),
+            Operator::PointMutation => ("point_mutation", "point_mutation_improvements"),
+            Operator::SegmentMutation => ("segment_mutation", "segment_mutation_improvements")
+        }
+    }
+
+    // Recombines the pair with the crossover. Mutations are never chosen as crossovers, and leave the pair alone.
+    fn recombine<C: Chromosome, R: Recombinator>(
+        self,
+        recombinator: &R,
+        first: &mut C,
+        second: &mut C,
+        rng: &mut impl Rng
+    ) {
+        match self {
+            Operator::Recombinator => recombinator.recombine(first, second, rng),
+            Operator::OnePointCrossover => KPoint::new(1).recombine(first, second, rng),
+            Operator::TwoPointCrossover => KPoint::new(2).recombine(first, second, rng),
+            Operator::UniformCrossover => Uniform::new().recombine(first, second, rng),
+            Operator::PointMutation | Operator::SegmentMutation => {}
+        }
+    }
+
+    // Mutates the individual with the mutation. Crossovers are never chosen as mutations, and leave it alone.
+    fn mutate<C: Chromosome>(self, indv: &mut C, rng: &mut impl Rng) {
+        match self {
+            Operator::PointMutation => indv.point_mutation(rng.gen::<usize>() % indv.len(), rng),
+            Operator::SegmentMutation => {
+                let length = rng.gen_range(1, (indv.len() / 8).max(1) + 1);
+                let start = rng.gen_range(0, indv.len() - length + 1);
+                (start..start + length).for_each(|index| indv.point_mutation(index, rng));
+            },
+            Operator::Recombinator
+            | Operator::OnePointCrossover
+            | Operator::TwoPointCrossover
+            | Operator::UniformCrossover => {}
+        }
+    }
+}
+
+/// How often each [operator](Operator) was applied by the generations run with a [`GenerationPool`], and how
+/// often a child it made came out better than both of its parents. A crossover is applied once for each of the
+/// two children it makes, and a child that was also mutated counts for both operators.
+#[derive(Clone, Debug, Default)]
+pub struct OperatorStats {
+    applications: [u64; 6],
+    improvements: [u64; 6],
+    // How often every operator improved lately, for probability matching
+    quality: [f32; 6]
+}
+
+impl OperatorStats {
+    pub fn applications(&self, operator: Operator) -> u64 {
+        self.applications[operator as usize]
+    }
+
+    pub fn improvements(&self, operator: Operator) -> u64 {
+        self.improvements[operator as usize]
+    }
+
+    /// The share of the operator's applications that improved on the parents, or `None` if it was never applied.
+    pub fn success_rate(&self, operator: Operator) -> Option<f32> {
+        match self.applications(operator) {
+            0 => None,
+            applications => Some(self.improvements(operator) as f32 / applications as f32)
+        }
+    }
+
+    // Records the outcome of a child for every operator it was made with
+    fn record(&mut self, operators: [Option<Operator>; 2], improved: bool, selection: &OperatorSelection) {
+        for &operator in operators.iter().flatten() {
+            let index = operator as usize;
+            self.applications[index] += 1;
+            self.improvements[index] += improved as u64;
+            if let OperatorSelection::ProbabilityMatching { adaptation, .. } = *selection {
+                let reward = if improved { 1.0 } else { 0.0 };
+                self.quality[index] += adaptation * (reward - self.quality[index]);
+            }
+        }
+    }
+}
+
+// The number of candidates each point gets in the repairs of the solvers
+const REPAIR_NEIGHBORS: usize = 8;
+
+/// How offspring are repaired before they are evaluated.
+#[derive(Clone, Copy, Debug)]
+pub enum Repair<'a> {
+    /// Leave offspring as they are
+    Off,
+    /// Apply [`Path::fix`]
+    Local,
+    /// Apply [`Path::fix_with`] over these candidate lists
+    Candidates(&'a Neighbors),
+    /// Apply up to this many passes of [`Path::fix_two_opt`] over these candidate lists
+    TwoOpt(&'a Neighbors, u32)
+}
+
+impl Repair<'_> {
+    pub fn apply<P: Path>(self, map: &Map, path: &mut P) {
+        match self {
+            Repair::Off => {},
+            Repair::Local => path.fix(map),
+            Repair::Candidates(neighbors) => path.fix_with(map, neighbors),
+            Repair::TwoOpt(neighbors, passes) => path.fix_two_opt(map, neighbors, passes)
+        }
+    }
+
+    /// The repair with 2-opt passes in place of the candidate repairs of [`Path::fix_with`], if `passes` is not
+    /// zero. Other repairs are returned unchanged.
+    pub fn two_opt(self, passes: u32) -> Self {
+        match self {
+            Repair::Candidates(neighbors) if passes > 0 => Repair::TwoOpt(neighbors, passes),
+            repair => repair
+        }
+    }
+}
+
+impl From<bool> for Repair<'_> {
+    fn from(fix: bool) -> Self {
+        if fix { Repair::Local } else { Repair::Off }
+    }
+}
+
+impl<'a> From<Option<&'a Neighbors>> for Repair<'a> {
+    fn from(neighbors: Option<&'a Neighbors>) -> Self {
+        neighbors.map_or(Repair::Off, Repair::Candidates)
+    }
+}
+
+// Offspring are repaired as they are made, but a map with precedences also needs the individuals that were not
+// made by crossover repaired, or one of them could come out as an infeasible best
+fn repair_population<C: Chromosome>(map: &Map, population: &mut [C], repair: Repair<'_>) {
+    if map.precedences().is_some() {
+        population.iter_mut().for_each(|indv| repair.apply(map, indv));
+    }
+}
+
+/// The candidate lists for the repairs of a run with fixing turned on, built once for the whole run, or taken from
+/// the context if there is one for the map. Turn the result into a [`Repair`] with `.as_ref().into()`.
+pub(crate) fn repair_neighbors(map: &Map, fix: bool, context: Option<&SolverContext>) -> Option<Neighbors> {
+    match context {
+        _ if !fix => None,
+        Some(context) => Some(context.neighbors().clone()),
+        None => Some(Neighbors::candidates(map, REPAIR_NEIGHBORS))
+    }
+}
+
+// Fails with `Error::InvalidSettings` unless the population has at least `least` individuals, which the generations
+// of the solvers assert
+pub(crate) fn check_population(population: usize, least: usize) -> Result<()> {
+    if population < least {
+        return Err(Error::InvalidSettings(format!(
+            "the population is {}, but needs at least {} individuals",
+            population, least
+        )));
+    }
+    Ok(())
+}
+
+/// Buffers kept across the generations of a run: the loss vectors, the individuals ranked for elitism, and
+/// individuals that left the population, whose storage is reused for later offspring. Once the population stops
+/// growing, generations stop allocating. The pool also counts the evaluations and crossovers of its generations,
+/// and keeps the [statistics of the operators](OperatorStats) that adaptive selection works from.
+pub struct GenerationPool<C> {
+    losses: Vec<f32>,
+    // The losses before they are turned into selection weights, to tell whether a child improved on its parents
+    parents: Vec<f32>,
+    ranked: Vec<(f32, f32, C)>,
+    spare: Vec<C>,
+    evaluations: u64,
+    crossovers: u64,
+    operators: OperatorStats
+}
+
+impl<C: Clone> GenerationPool<C> {
+    pub fn new() -> Self {
+        GenerationPool {
+            losses: Vec::new(),
+            parents: Vec::new(),
+            ranked: Vec::new(),
+            spare: Vec::new(),
+            evaluations: 0,
+            crossovers: 0,
+            operators: OperatorStats::default()
+        }
+    }
+
+    /// The individuals evaluated by the generations run with this pool.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// The pairs of parents recombined by the generations run with this pool.
+    pub fn crossovers(&self) -> u64 {
+        self.crossovers
+    }
+
+    /// How often each operator was applied by the generations run with this pool, and how often it improved.
+    pub fn operators(&self) -> &OperatorStats {
+        &self.operators
+    }
+
+    // A copy of the individual, written over a spare one when there is any
+    fn copy_of(&mut self, indv: &C) -> C {
+        match self.spare.pop() {
+            Some(mut copy) => {
+                copy.clone_from(indv);
+                copy
+            },
+            None => indv.clone()
+        }
+    }
+
+    fn recycle(&mut self, indv: C) {
+        self.spare.push(indv);
+    }
+}
+
+impl<C: Clone> Default for GenerationPool<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fitness-proportionate parent selection with optional elitism.
 pub struct RouletteWheelSelection;
 
 impl RouletteWheelSelection {
+    /// Runs a single generation and returns the minimum loss seen.
     pub fn evolve<C, R>(settings: &Settings, map: &Map, population: &mut Vec<C>, recombinator: &R, fix: bool) -> f32
     where
         C: Chromosome,
         R: Recombinator
     {
-        let mut rng = thread_rng();
+        Self::evolve_with_rng(settings, map, population, recombinator, fix.into(), &mut thread_rng())
+    }
 
+    pub fn evolve_with_rng<C, R, G>(
+        settings: &Settings,
+        map: &Map,
+        population: &mut Vec<C>,
+        recombinator: &R,
+        repair: Repair<'_>,
+        rng: &mut G
+    ) -> f32
+    where
+        C: Chromosome,
+        R: Recombinator,
+        G: Rng
+    {
+        let mut pool = GenerationPool::new();
+        Self::evolve_with_pool(settings, map, population, recombinator, repair, Objective::Minimize, &mut pool, rng)
+    }
+
+    /// Like [`evolve_with_rng`](Self::evolve_with_rng), but takes its buffers from a pool that should be kept for
+    /// the whole run, and works towards the given objective. The returned loss is that of
+    /// [`Objective::loss`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn evolve_with_pool<C, R, G>(
+        settings: &Settings,
+        map: &Map,
+        population: &mut Vec<C>,
+        recombinator: &R,
+        repair: Repair<'_>,
+        objective: Objective,
+        pool: &mut GenerationPool<C>,
+        rng: &mut G
+    ) -> f32
+    where
+        C: Chromosome,
+        R: Recombinator,
+        G: Rng
+    {
         // Compute the loss vector
+        let repair = repair.two_opt(settings.two_opt_passes);
         let n = population.len();
-        let mut losses = vec![0.0f32; n];
-        let mut loss_sum: f32 = 0.0;
-        let mut min_loss = f32::MAX;
-        for i in 0..n {
-            let loss = population[i].evaluate(map);
-            losses[i] = loss;
-            loss_sum += loss;
+        let samples = settings.fitness_samples.max(1);
+        let mut losses = mem::take(&mut pool.losses);
+        losses.clear();
+        losses.extend(population.iter().map(|indv| objective.loss(sample_length(map, indv, samples))));
 
-            if loss < min_loss {
-                min_loss = loss;
+        // Resample the individuals that elitism will keep, weighing every sample alike
+        let elites = ((settings.elitist_percent * n as f32) as usize).min(n);
+        if settings.elite_samples > 0 && elites > 0 {
+            let mut order: Vec<usize> = (0..n).collect();
+            order.select_nth_unstable_by(elites - 1, |&a, &b| losses[a].total_cmp(&losses[b]));
+            let total = (samples + settings.elite_samples) as f32;
+            for &index in &order[..elites] {
+                let extra = objective.loss(sample_length(map, &population[index], settings.elite_samples));
+                losses[index] = (losses[index] * samples as f32 + extra * settings.elite_samples as f32) / total;
             }
+            pool.evaluations += (elites as u64) * u64::from(settings.elite_samples);
+        }
+        let mut min_loss = losses.iter().copied().fold(f32::MAX, f32::min);
+        let max_loss = losses.iter().copied().fold(f32::MIN, f32::max);
+        let mut parents = mem::take(&mut pool.parents);
+        parents.clear();
+        parents.extend_from_slice(&losses);
+
+        // Maximizing negates the losses, so reflect them back onto the range of the lengths before weighing them,
+        // which keeps the weights positive and gives every rank the weight its mirror image has when minimizing
+        if objective == Objective::Maximize {
+            let reflection = min_loss + max_loss;
+            losses.iter_mut().for_each(|loss| *loss -= reflection);
         }
+        let loss_sum: f32 = losses.iter().sum();
         losses.iter_mut().for_each(|loss| *loss /= loss_sum);
 
-        // Get elitism cut-off
+        // Get elitism cut-off, sorting the individuals together with their losses so none is evaluated again
         if settings.elitist_percent > 0.0 {
-            losses.sort_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
-            population.sort_by(|a, b| {
-                a.evaluate(map).partial_cmp(&b.evaluate(map)).unwrap_or(cmp::Ordering::Equal)
-            });
+            let mut ranked = mem::take(&mut pool.ranked);
+            let rows = losses.drain(..).zip(parents.drain(..)).zip(population.drain(..));
+            ranked.extend(rows.map(|((loss, parent), indv)| (loss, parent, indv)));
+            ranked.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+            for (loss, parent, indv) in ranked.drain(..) {
+                losses.push(loss);
+                parents.push(parent);
+                population.push(indv);
+            }
+            pool.ranked = ranked;
         }
 
         let mut offspring_count = 0;
@@ -156,45 +851,51 @@ impl RouletteWheelSelection {
             'selector: for i in 0..2 {
                 let noise = settings.selection_noise * (rng.gen::<f32>() * 2.0 - 1.0);
                 let mut random = rng.gen::<f32>() + noise;
-                for j in 0..n {
-                    if random < losses[j] && (i == 0 || selections[0] != j) {
+                for (j, &loss) in losses.iter().enumerate() {
+                    if random < loss && (i == 0 || selections[0] != j) {
                         selections[i] = j;
                         continue 'selector;
                     }
 
-                    random -= losses[j];
+                    random -= loss;
                 }
 
                 selections[i] = n - 1;
             }
 
             // Compute the child chromosomes
-            let mut first = population[selections[0]].clone();
-            let mut second = population[selections[1]].clone();
+            let selection = &settings.operator_selection;
+            let mut first = pool.copy_of(&population[selections[0]]);
+            let mut second = pool.copy_of(&population[selections[1]]);
+            let mut crossover = None;
             if rng.gen::<f32>() < settings.crossover_prob {
-                recombinator.recombine(&mut first, &mut second, &mut rng);
-            }
-            if rng.gen::<f32>() < settings.mutate_prob {
-                first.point_mutation(rng.gen::<usize>() % first.len(), &mut rng);
-            }
-            if rng.gen::<f32>() < settings.mutate_prob {
-                second.point_mutation(rng.gen::<usize>() % second.len(), &mut rng);
+                let operator = selection.choose(&pool.operators, &Operator::CROSSOVERS, rng);
+                operator.recombine(recombinator, &mut first, &mut second, rng);
+                crossover = Some(operator);
+                pool.crossovers += 1;
             }
+            let mutations = [&mut first, &mut second].map(|indv| {
+                if rng.gen::<f32>() < settings.mutate_prob {
+                    let operator = selection.choose(&pool.operators, &Operator::MUTATIONS, rng);
+                    operator.mutate(indv, rng);
+                    Some(operator)
+                } else {
+                    None
+                }
+            });
 
             // Fix trivial errors
-            if fix {
-                first.fix(&map);
-                second.fix(&map);
-            }
+            repair.apply(map, &mut first);
+            repair.apply(map, &mut second);
 
-            // Update minimum loss value
-            let loss = first.evaluate(map);
-            if loss < min_loss {
-                min_loss = loss;
-            }
-            let loss = second.evaluate(map);
-            if loss < min_loss {
-                min_loss = loss;
+            // Update minimum loss value, and credit the operators of every child better than both parents
+            let best_parent = parents[selections[0]].min(parents[selections[1]]);
+            for (indv, &mutation) in [&first, &second].iter().zip(&mutations) {
+                let loss = objective.loss(sample_length(map, *indv, samples));
+                if loss < min_loss {
+                    min_loss = loss;
+                }
+                pool.operators.record([crossover, mutation], loss < best_parent, selection);
             }
             
             // Add them to the population
@@ -204,15 +905,915 @@ impl RouletteWheelSelection {
             if offspring_count < target_offspring_count {
                 population.push(second);
                 offspring_count += 1;
+            } else {
+                pool.recycle(second);
             }
         }
 
-        let mut index = n - 1;
-        while population.len() > n {
-            population.remove(index);
-            index -= 1;
+        // The offspring replace the last individuals of the old population, which are the worst ones when elitism
+        // sorted it. Swapping each into the place of the one it replaces keeps their order without shifting the
+        // survivors, and leaves everything to discard at the end
+        let replaced = (population.len() - n).min(n);
+        for i in 0..replaced {
+            population.swap(n - replaced + i, n + i);
         }
+        pool.spare.extend(population.drain(n..));
+        pool.losses = losses;
+        pool.parents = parents;
+        pool.evaluations += (n + 2 * offspring_count.div_ceil(2)) as u64 * u64::from(samples);
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(min_loss, offspring = offspring_count, "generation complete");
 
         min_loss
     }
-}
\ No newline at end of file
+}
+
+/// The parameters of a roulette wheel GA run.
+pub(crate) struct GaParams<'a, R> {
+    pub settings: &'a Settings,
+    pub recombinator: &'a R,
+    pub repair: Repair<'a>,
+    pub objective: Objective,
+    /// The generation to start from, non-zero when resuming a run
+    pub first_generation: u32,
+    pub generations: u32
+}
+
+/// The best individual seen during a run.
+pub(crate) struct GaOutcome {
+    /// The [loss](Objective::loss) of the best tour
+    pub loss: f32,
+    pub tour: Vec<usize>,
+    /// The generation in which the best individual was found
+    pub best_generation: u32,
+    /// The generation the run reached before it finished or the budget ran out
+    pub generations: u32
+}
+
+/// Runs the roulette wheel GA until the configured number of generations has passed or the budget runs out.
+pub(crate) fn run_generations<C, R, G>(
+    params: &GaParams<'_, R>,
+    map: &Map,
+    population: &mut Vec<C>,
+    budget: &mut Budget,
+    reporter: &mut Reporter<'_>,
+    rng: &mut G
+) -> GaOutcome
+where
+    C: Chromosome,
+    R: Recombinator,
+    G: Rng
+{
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("ga", population = population.len(), generations = params.generations).entered();
+
+    let started = Instant::now();
+    let objective = params.objective;
+    repair_population(map, population, params.repair);
+    let samples = params.settings.fitness_samples;
+    let (mut loss, mut tour) = best_sampled(map, population, objective, samples);
+    reporter.improved(objective.length(loss));
+    reporter.tour(&tour);
+    budget.reached(objective.length(loss));
+    let mut best_generation = params.first_generation;
+    let mut generation = params.first_generation;
+    let mut pool = GenerationPool::new();
+    // The evaluations of the pool already counted toward the budget
+    let mut counted = 0;
+    while generation < params.generations && !budget.exhausted() {
+        if let Some(size) = params.settings.population_schedule.size(generation) {
+            resize_population(map, population, size, objective, rng);
+            repair_population(map, population, params.repair);
+        }
+
+        let current = RouletteWheelSelection::evolve_with_pool(
+            params.settings,
+            map,
+            population,
+            params.recombinator,
+            params.repair,
+            objective,
+            &mut pool,
+            rng
+        );
+        generation += 1;
+        reporter.generation(generation, params.generations);
+        budget.evaluated(pool.evaluations() - counted);
+        counted = pool.evaluations();
+
+        // The reported loss may belong to an individual that was not kept, so re-check the survivors
+        if current < loss {
+            let (current_loss, current_tour) = best_sampled(map, population, objective, samples);
+            if current_loss < loss {
+                loss = current_loss;
+                tour = current_tour;
+                best_generation = generation;
+                reporter.improved(objective.length(loss));
+                reporter.tour(&tour);
+                budget.reached(objective.length(loss));
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(loss, generation, "improved");
+            }
+        }
+
+        reporter.sample(generation as u64, objective.length(loss), || {
+            population.iter().map(|indv| indv.evaluate(map)).sum::<f32>() / population.len() as f32
+        });
+        reporter.diversity(|| diversity(map, population, &tour));
+    }
+
+    reporter.count(instrument::EVALUATIONS, pool.evaluations());
+    reporter.count(instrument::CROSSOVERS, pool.crossovers());
+    for operator in Operator::ALL {
+        let (applications, improvements) = operator.names();
+        reporter.count(applications, pool.operators().applications(operator));
+        reporter.count(improvements, pool.operators().improvements(operator));
+    }
+    reporter.time(instrument::SEARCH, started.elapsed());
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(loss, best_generation, generations = generation, "finished");
+
+    GaOutcome {
+        loss,
+        tour,
+        best_generation,
+        generations: generation
+    }
+}
+
+/// The loss and tour of the best individual in the population.
+pub fn best_of<C: Path>(map: &Map, population: &[C]) -> (f32, Vec<usize>) {
+    best_by(map, population, Objective::Minimize)
+}
+
+/// Like [`best_of`], but the best individual and its loss are those of the objective.
+pub(crate) fn best_by<C: Path>(map: &Map, population: &[C], objective: Objective) -> (f32, Vec<usize>) {
+    best_sampled(map, population, objective, 1)
+}
+
+// Like `best_by`, but estimating every loss from the mean of `samples` evaluations
+fn best_sampled<C: Path>(map: &Map, population: &[C], objective: Objective, samples: u32) -> (f32, Vec<usize>) {
+    population.iter()
+        .map(|indv| (objective.loss(sample_length(map, indv, samples)), indv))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(loss, indv)| (loss, indv.tour(map)))
+        .unwrap_or((f32::MAX, Vec::new()))
+}
+
+// The mean length of `samples` evaluations of the path, which on a map with random costs estimates its expected
+// length
+fn sample_length<P: Path>(map: &Map, path: &P, samples: u32) -> f32 {
+    if samples <= 1 {
+        return path.evaluate(map);
+    }
+    let sum: f64 = (0..samples).map(|_| f64::from(path.evaluate(map))).sum();
+    (sum / f64::from(samples)) as f32
+}
+
+/// The average fraction of edges in which the population's tours differ from `best`, ignoring direction. Zero
+/// means every individual encodes the same tour as `best`.
+pub fn diversity<C: Path>(map: &Map, population: &[C], best: &[usize]) -> f32 {
+    if population.is_empty() || best.len() < 2 {
+        return 0.0;
+    }
+
+    let mut next = vec![usize::MAX; best.len()];
+    for edge in best.windows(2) {
+        next[edge[0]] = edge[1];
+    }
+    let shared = |a: usize, b: usize| next[a] == b || next[b] == a;
+
+    let edges = (best.len() - 1) as f32;
+    let total: f32 = population.iter()
+        .map(|indv| {
+            let tour = indv.tour(map);
+            tour.windows(2).filter(|edge| !shared(edge[0], edge[1])).count() as f32 / edges
+        })
+        .sum();
+    total / population.len() as f32
+}
+
+/// Trades individuals with a running GA, for hybrid schemes in which other threads, such as one running
+/// Lin-Kernighan, feed the population tours of their own and read it back. Hand it to
+/// [`GaSolver::solve_with_exchange`] or [`GaSolver::resume_with_exchange`], and share it with the other threads.
+///
+/// After every generation, the run replaces its worst individuals with whatever was injected since the last one,
+/// the best arrivals first, and then publishes a copy of its population as the [`snapshot`](Self::snapshot).
+#[derive(Debug)]
+pub struct Exchange<C> {
+    // Individuals and tours waiting to join the population at the next generation
+    arrivals: Mutex<(Vec<C>, Vec<Vec<usize>>)>,
+    // The generation the run reached and its population then
+    snapshot: Mutex<(u32, Vec<C>)>
+}
+
+impl<C: Chromosome> Exchange<C> {
+    pub fn new() -> Self {
+        Exchange {
+            arrivals: Mutex::new((Vec::new(), Vec::new())),
+            snapshot: Mutex::new((0, Vec::new()))
+        }
+    }
+
+    /// Queues individuals for the population of the run, after checking that they fit the map it solves.
+    pub fn inject(&self, map: &Map, individuals: Vec<C>) -> Result<()> {
+        individuals.iter().try_for_each(|indv| indv.validate(map))?;
+        self.arrivals.lock().unwrap().0.extend(individuals);
+        Ok(())
+    }
+
+    /// Queues a tour for the population of the run, to be [encoded](Chromosome::encode) in place of one of its
+    /// individuals, after checking that it visits every point of the map it solves once.
+    pub fn inject_tour(&self, map: &Map, tour: &[usize]) -> Result<()> {
+        map.validate_tour(tour)?;
+        self.arrivals.lock().unwrap().1.push(tour.to_vec());
+        Ok(())
+    }
+
+    /// The generation the run last reached and a copy of its population then, which is empty until the run
+    /// starts.
+    pub fn snapshot(&self) -> (u32, Vec<C>) {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    // Replaces the worst individuals of the population with the arrivals, the best of them first. Arrivals that do
+    // not fit the map, having been checked against another one, are dropped.
+    pub(crate) fn admit(&self, map: &Map, population: &mut [C], objective: Objective) {
+        let (individuals, tours) = mem::take(&mut *self.arrivals.lock().unwrap());
+        let template = match population.first() {
+            Some(indv) => indv.clone(),
+            None => return
+        };
+        let encoded = tours.into_iter().filter_map(|tour| {
+            let mut indv = template.clone();
+            indv.encode(map, &tour).ok().map(|_| indv)
+        });
+
+        let by_loss = |a: f32, b: f32| a.total_cmp(&b);
+        let mut arrived: Vec<(f32, C)> = individuals.into_iter()
+            .filter(|indv| indv.validate(map).is_ok())
+            .chain(encoded)
+            .map(|indv| (objective.loss(indv.evaluate(map)), indv))
+            .collect();
+        if arrived.is_empty() {
+            return;
+        }
+        arrived.sort_by(|a, b| by_loss(a.0, b.0));
+        let mut worst: Vec<(f32, usize)> = population.iter()
+            .enumerate()
+            .map(|(index, indv)| (objective.loss(indv.evaluate(map)), index))
+            .collect();
+        worst.sort_by(|a, b| by_loss(b.0, a.0));
+        for ((_, index), (_, indv)) in worst.into_iter().zip(arrived) {
+            population[index] = indv;
+        }
+    }
+
+    pub(crate) fn publish(&self, generation: u32, population: &[C]) {
+        *self.snapshot.lock().unwrap() = (generation, population.to_vec());
+    }
+}
+
+impl<C: Chromosome> Default for Exchange<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs the roulette wheel GA for a fixed number of generations from a random initial population.
+pub struct GaSolver<C> {
+    pub settings: Settings,
+    pub recombinator: AnyRecombinator,
+    pub population: usize,
+    pub generations: u32,
+    pub fix: bool,
+    pub new_chromosome: fn(&Map) -> C
+}
+
+impl<C: Chromosome> Solver for GaSolver<C> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        self.settings.validate()?;
+        check_population(self.population, 2)?;
+        let mut rng = options.rng();
+        let mut population = random_population(map, self.population, self.new_chromosome, &mut rng);
+        population.iter().try_for_each(|indv| indv.validate(map))?;
+
+        let neighbors = repair_neighbors(map, self.fix, SolverContext::of(options, map));
+        let params = GaParams {
+            settings: &self.settings,
+            recombinator: &self.recombinator,
+            repair: neighbors.as_ref().into(),
+            objective: options.objective,
+            first_generation: 0,
+            generations: self.generations
+        };
+        let bound = known_bound(map, options);
+        let mut budget = Budget::new(options, start, 1).with_target(bound, options);
+        let mut reporter = Reporter::new(options, start).objective(options.objective);
+        reporter.time(instrument::CONSTRUCTION, start.elapsed());
+        let outcome = run_generations(&params, map, &mut population, &mut budget, &mut reporter, &mut rng);
+
+        let mut result = SolveResult::new(map, outcome.tour, outcome.generations as u64, start);
+        result.lower_bound = bound;
+        result.evaluations = budget.evaluations();
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+impl GaSolver<RandomKeyPath> {
+    /// A builder starting from a random-key population of 100 run for 1000 generations with uniform crossover.
+    /// The chromosome type can be changed with [`GaSolverBuilder::chromosome`].
+    pub fn builder() -> GaSolverBuilder<RandomKeyPath> {
+        GaSolverBuilder {
+            solver: GaSolver {
+                settings: Settings::default(),
+                recombinator: AnyRecombinator::Uniform(Uniform::new()),
+                population: 100,
+                generations: 1000,
+                fix: false,
+                new_chromosome: RandomKeyPath::new
+            }
+        }
+    }
+}
+
+/// Configures a [`GaSolver`]. See [`GaSolver::builder`].
+pub struct GaSolverBuilder<C> {
+    solver: GaSolver<C>
+}
+
+impl<C> GaSolverBuilder<C> {
+    pub fn chromosome<D>(self, new_chromosome: fn(&Map) -> D) -> GaSolverBuilder<D> {
+        let GaSolver { settings, recombinator, population, generations, fix, .. } = self.solver;
+        GaSolverBuilder {
+            solver: GaSolver {
+                settings,
+                recombinator,
+                population,
+                generations,
+                fix,
+                new_chromosome
+            }
+        }
+    }
+
+    /// Replaces every GA setting at once.
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.solver.settings = settings;
+        self
+    }
+
+    pub fn replace_percent(mut self, replace_percent: f32) -> Self {
+        self.solver.settings.replace_percent = replace_percent;
+        self
+    }
+
+    pub fn elitist_percent(mut self, elitist_percent: f32) -> Self {
+        self.solver.settings.elitist_percent = elitist_percent;
+        self
+    }
+
+    pub fn crossover_prob(mut self, crossover_prob: f32) -> Self {
+        self.solver.settings.crossover_prob = crossover_prob;
+        self
+    }
+
+    pub fn mutate_prob(mut self, mutate_prob: f32) -> Self {
+        self.solver.settings.mutate_prob = mutate_prob;
+        self
+    }
+
+    pub fn selection_noise(mut self, selection_noise: f32) -> Self {
+        self.solver.settings.selection_noise = selection_noise;
+        self
+    }
+
+    pub fn population_schedule(mut self, population_schedule: PopulationSchedule) -> Self {
+        self.solver.settings.population_schedule = population_schedule;
+        self
+    }
+
+    pub fn operator_selection(mut self, operator_selection: OperatorSelection) -> Self {
+        self.solver.settings.operator_selection = operator_selection;
+        self
+    }
+
+    pub fn two_opt_passes(mut self, two_opt_passes: u32) -> Self {
+        self.solver.settings.two_opt_passes = two_opt_passes;
+        self
+    }
+
+    pub fn fitness_samples(mut self, fitness_samples: u32) -> Self {
+        self.solver.settings.fitness_samples = fitness_samples;
+        self
+    }
+
+    pub fn elite_samples(mut self, elite_samples: u32) -> Self {
+        self.solver.settings.elite_samples = elite_samples;
+        self
+    }
+
+    pub fn recombinator<R: Into<AnyRecombinator>>(mut self, recombinator: R) -> Self {
+        self.solver.recombinator = recombinator.into();
+        self
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.solver.population = population;
+        self
+    }
+
+    pub fn generations(mut self, generations: u32) -> Self {
+        self.solver.generations = generations;
+        self
+    }
+
+    pub fn fix(mut self, fix: bool) -> Self {
+        self.solver.fix = fix;
+        self
+    }
+
+    pub fn build(self) -> GaSolver<C> {
+        self.solver
+    }
+}
+
+/// Biased random-key genetic algorithm. Each generation the elites are copied over unchanged, a fraction of
+/// the population is replaced by freshly generated mutants, and the remainder is filled with children of
+/// one elite and one non-elite parent, where each key is inherited from the elite with probability
+/// `elite_bias`. Mutants take the [decoder](Decoder) of the elites.
+pub struct Brkga {
+    pub elite_percent: f32,
+    pub mutant_percent: f32,
+    pub elite_bias: f32
+}
+
+impl Brkga {
+    pub fn evolve(&self, map: &Map, population: &mut Vec<RandomKeyPath>, fix: bool) -> f32 {
+        self.evolve_with_rng(map, population, fix.into(), &mut thread_rng())
+    }
+
+    pub fn evolve_with_rng<G: Rng>(
+        &self,
+        map: &Map,
+        population: &mut Vec<RandomKeyPath>,
+        repair: Repair<'_>,
+        rng: &mut G
+    ) -> f32 {
+        let n = population.len();
+        assert!(n >= 2, "Population must contain at least two individuals");
+
+        let mut ranked: Vec<(f32, RandomKeyPath)> = population.drain(..)
+            .map(|indv| (indv.evaluate(map), indv))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let elite_count = ((self.elite_percent * n as f32) as usize).max(1).min(n - 1);
+        let mutant_count = ((self.mutant_percent * n as f32) as usize).min(n - elite_count);
+        let mut min_loss = ranked[0].0;
+
+        // Elites survive unchanged
+        let (elites, others) = ranked.split_at(elite_count);
+        population.extend(elites.iter().map(|(_, indv)| indv.clone()));
+
+        // Inject mutants to maintain diversity
+        for _ in 0..mutant_count {
+            let mut mutant = RandomKeyPath::with_rng(map, rng).with_decoder(elites[0].1.decoder());
+            repair.apply(map, &mut mutant);
+            min_loss = min_loss.min(mutant.evaluate(map));
+            population.push(mutant);
+        }
+
+        // Biased crossover between an elite and a non-elite parent
+        while population.len() < n {
+            let elite = &elites[rng.gen::<usize>() % elites.len()].1;
+            let other = &others[rng.gen::<usize>() % others.len()].1;
+
+            let mut child = elite.clone();
+            child.inner_mut().iter_mut()
+                .zip(other.inner().iter())
+                .filter(|_| rng.gen::<f32>() >= self.elite_bias)
+                .for_each(|(key, &other_key)| *key = other_key);
+
+            repair.apply(map, &mut child);
+            min_loss = min_loss.min(child.evaluate(map));
+            population.push(child);
+        }
+
+        min_loss
+    }
+}
+
+/// Differential evolution (DE/rand/1/bin) over the continuous key vectors of random-key paths. Every
+/// individual competes against a trial vector built from the weighted difference of two other individuals
+/// added to a third, and is replaced if the trial is at least as good.
+pub struct DifferentialEvolution {
+    pub differential_weight: f32,
+    pub crossover_prob: f32
+}
+
+impl DifferentialEvolution {
+    pub fn evolve(&self, map: &Map, population: &mut [RandomKeyPath]) -> f32 {
+        self.evolve_with_rng(map, population, &mut thread_rng())
+    }
+
+    pub fn evolve_with_rng<G: Rng>(&self, map: &Map, population: &mut [RandomKeyPath], rng: &mut G) -> f32 {
+        let n = population.len();
+        assert!(n >= 4, "Differential evolution requires at least four individuals");
+
+        let mut losses: Vec<f32> = population.iter().map(|indv| indv.evaluate(map)).collect();
+
+        for i in 0..n {
+            // Pick three distinct individuals, all different from the target
+            let mut picks = [i; 3];
+            for k in 0..3 {
+                while picks[k] == i || picks[..k].contains(&picks[k]) {
+                    picks[k] = rng.gen::<usize>() % n;
+                }
+            }
+            let [a, b, c] = picks;
+
+            let len = population[i].len();
+            let forced = rng.gen::<usize>() % len;
+            let mut trial = population[i].clone();
+            {
+                let (base, first, second) = (population[a].inner(), population[b].inner(), population[c].inner());
+                for (j, key) in trial.inner_mut().iter_mut().enumerate() {
+                    if j == forced || rng.gen::<f32>() < self.crossover_prob {
+                        *key = base[j] + self.differential_weight * (first[j] - second[j]);
+                    }
+                }
+            }
+
+            let loss = trial.evaluate(map);
+            if loss <= losses[i] {
+                population[i] = trial;
+                losses[i] = loss;
+            }
+        }
+
+        losses.into_iter().fold(f32::MAX, f32::min)
+    }
+}
+
+/// Estimation-of-distribution algorithm using an edge histogram model. Each generation the elite tours
+/// are tallied into a symmetric edge-frequency matrix, and the rest of the population is replaced by
+/// tours sampled from that matrix. `bias` is the baseline weight given to every edge so that edges absent
+/// from the elites can still be sampled.
+pub struct EdgeHistogram {
+    pub elite_percent: f32,
+    pub bias: f32
+}
+
+impl EdgeHistogram {
+    pub fn evolve(&self, map: &Map, population: &mut [Vec<usize>]) -> f32 {
+        self.evolve_with_rng(map, population, &mut thread_rng())
+    }
+
+    pub fn evolve_with_rng<G: Rng>(&self, map: &Map, population: &mut [Vec<usize>], rng: &mut G) -> f32 {
+        let n = population.len();
+        assert!(n >= 2, "Population must contain at least two individuals");
+
+        let mut ranked: Vec<(f32, Vec<usize>)> = population.iter_mut()
+            .map(|tour| (tour.evaluate(map), mem::take(tour)))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        for (tour, (_, ranked)) in population.iter_mut().zip(ranked) {
+            *tour = ranked;
+        }
+
+        let elite_count = ((self.elite_percent * n as f32) as usize).max(1).min(n - 1);
+        let model = self.build_model(map.size(), &population[..elite_count]);
+
+        let mut min_loss = population[0].evaluate(map);
+        for tour in population.iter_mut().skip(elite_count) {
+            *tour = Self::sample(&model, map.size(), rng);
+            min_loss = min_loss.min(tour.evaluate(map));
+        }
+
+        min_loss
+    }
+
+    fn build_model(&self, size: usize, elites: &[Vec<usize>]) -> Vec<f32> {
+        let mut model = vec![self.bias; size * size];
+        for tour in elites {
+            for edge in tour.windows(2) {
+                model[edge[0] * size + edge[1]] += 1.0;
+                model[edge[1] * size + edge[0]] += 1.0;
+            }
+        }
+        model
+    }
+
+    fn sample(model: &[f32], size: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let mut tour = Vec::with_capacity(size);
+        let mut visited = vec![false; size];
+
+        let mut current = rng.gen::<usize>() % size;
+        visited[current] = true;
+        tour.push(current);
+
+        while tour.len() < size {
+            let row = &model[current * size..(current + 1) * size];
+            let total: f32 = row.iter()
+                .zip(visited.iter())
+                .filter(|(_, &seen)| !seen)
+                .map(|(&weight, _)| weight)
+                .sum();
+
+            let mut random = rng.gen::<f32>() * total;
+            let mut next = None;
+            for (candidate, (&weight, &seen)) in row.iter().zip(visited.iter()).enumerate() {
+                if seen {
+                    continue;
+                }
+
+                next = Some(candidate);
+                if random < weight {
+                    break;
+                }
+                random -= weight;
+            }
+
+            // Infallible: at least one city remains unvisited
+            current = next.unwrap();
+            visited[current] = true;
+            tour.push(current);
+        }
+
+        tour
+    }
+}
+
+/// Runs BRKGA for a fixed number of generations from a random initial population, decoded by `decoder`.
+pub struct BrkgaSolver {
+    pub brkga: Brkga,
+    pub population: usize,
+    pub generations: u32,
+    pub fix: bool,
+    pub decoder: Decoder
+}
+
+impl Solver for BrkgaSolver {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        check_population(self.population, 2)?;
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start);
+        let mut rng = options.rng();
+        let mut population: Vec<RandomKeyPath> = (0..self.population)
+            .map(|_| RandomKeyPath::with_rng(map, &mut rng).with_decoder(self.decoder))
+            .collect();
+        let neighbors = repair_neighbors(map, self.fix, SolverContext::of(options, map));
+        let repair = neighbors.as_ref().into();
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            reporter.improved(self.brkga.evolve_with_rng(map, &mut population, repair, &mut rng));
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        // Elites always survive, so the best individual is still in the population
+        let mut result = SolveResult::new(map, best_of(map, &population).1, generations as u64, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+impl BrkgaSolver {
+    /// A builder starting from the commonly recommended BRKGA parameters, a population of 100 and 1000
+    /// generations, decoding keys in sorted order.
+    pub fn builder() -> BrkgaSolverBuilder {
+        BrkgaSolverBuilder {
+            solver: BrkgaSolver {
+                brkga: Brkga {
+                    elite_percent: 0.2,
+                    mutant_percent: 0.15,
+                    elite_bias: 0.7
+                },
+                population: 100,
+                generations: 1000,
+                fix: false,
+                decoder: Decoder::Sorted
+            }
+        }
+    }
+}
+
+/// Configures a [`BrkgaSolver`]. See [`BrkgaSolver::builder`].
+pub struct BrkgaSolverBuilder {
+    solver: BrkgaSolver
+}
+
+impl BrkgaSolverBuilder {
+    pub fn elite_percent(mut self, elite_percent: f32) -> Self {
+        self.solver.brkga.elite_percent = elite_percent;
+        self
+    }
+
+    pub fn mutant_percent(mut self, mutant_percent: f32) -> Self {
+        self.solver.brkga.mutant_percent = mutant_percent;
+        self
+    }
+
+    pub fn elite_bias(mut self, elite_bias: f32) -> Self {
+        self.solver.brkga.elite_bias = elite_bias;
+        self
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.solver.population = population;
+        self
+    }
+
+    pub fn generations(mut self, generations: u32) -> Self {
+        self.solver.generations = generations;
+        self
+    }
+
+    pub fn fix(mut self, fix: bool) -> Self {
+        self.solver.fix = fix;
+        self
+    }
+
+    pub fn decoder(mut self, decoder: Decoder) -> Self {
+        self.solver.decoder = decoder;
+        self
+    }
+
+    pub fn build(self) -> BrkgaSolver {
+        self.solver
+    }
+}
+
+/// Runs differential evolution for a fixed number of generations from a random initial population.
+pub struct DifferentialEvolutionSolver {
+    pub de: DifferentialEvolution,
+    pub population: usize,
+    pub generations: u32
+}
+
+impl Solver for DifferentialEvolutionSolver {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        check_population(self.population, 4)?;
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start);
+        let mut rng = options.rng();
+        let mut population: Vec<RandomKeyPath> =
+            (0..self.population).map(|_| RandomKeyPath::with_rng(map, &mut rng)).collect();
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            reporter.improved(self.de.evolve_with_rng(map, &mut population, &mut rng));
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        // Individuals are only ever replaced by better ones
+        let mut result = SolveResult::new(map, best_of(map, &population).1, generations as u64, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+impl DifferentialEvolutionSolver {
+    /// A builder starting from the classic DE/rand/1/bin parameters, a population of 100 and 1000 generations.
+    pub fn builder() -> DifferentialEvolutionSolverBuilder {
+        DifferentialEvolutionSolverBuilder {
+            solver: DifferentialEvolutionSolver {
+                de: DifferentialEvolution {
+                    differential_weight: 0.8,
+                    crossover_prob: 0.9
+                },
+                population: 100,
+                generations: 1000
+            }
+        }
+    }
+}
+
+/// Configures a [`DifferentialEvolutionSolver`]. See [`DifferentialEvolutionSolver::builder`].
+pub struct DifferentialEvolutionSolverBuilder {
+    solver: DifferentialEvolutionSolver
+}
+
+impl DifferentialEvolutionSolverBuilder {
+    pub fn differential_weight(mut self, differential_weight: f32) -> Self {
+        self.solver.de.differential_weight = differential_weight;
+        self
+    }
+
+    pub fn crossover_prob(mut self, crossover_prob: f32) -> Self {
+        self.solver.de.crossover_prob = crossover_prob;
+        self
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.solver.population = population;
+        self
+    }
+
+    pub fn generations(mut self, generations: u32) -> Self {
+        self.solver.generations = generations;
+        self
+    }
+
+    pub fn build(self) -> DifferentialEvolutionSolver {
+        self.solver
+    }
+}
+
+/// Runs the edge histogram EDA for a fixed number of generations from random initial tours.
+pub struct EdgeHistogramSolver {
+    pub eda: EdgeHistogram,
+    pub population: usize,
+    pub generations: u32
+}
+
+impl Solver for EdgeHistogramSolver {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        check_population(self.population, 2)?;
+        // The model holds a weight for every ordered pair of points
+        let model = (map.size() as u64).pow(2).saturating_mul(mem::size_of::<f32>() as u64);
+        check_quadratic(model, "the edge histogram", map.size())?;
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start);
+        let mut rng = options.rng();
+        let mut population: Vec<Vec<usize>> = (0..self.population)
+            .map(|_| {
+                let mut tour: Vec<usize> = (0..map.size()).collect();
+                tour.shuffle(&mut rng);
+                tour
+            })
+            .collect();
+
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            reporter.improved(self.eda.evolve_with_rng(map, &mut population, &mut rng));
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        // Elites always survive, so the best tour is still in the population
+        let mut result = SolveResult::new(map, best_of(map, &population).1, generations as u64, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+impl EdgeHistogramSolver {
+    /// A builder starting from a population of 100 run for 1000 generations, modelling the best half of it.
+    pub fn builder() -> EdgeHistogramSolverBuilder {
+        EdgeHistogramSolverBuilder {
+            solver: EdgeHistogramSolver {
+                eda: EdgeHistogram {
+                    elite_percent: 0.5,
+                    bias: 0.1
+                },
+                population: 100,
+                generations: 1000
+            }
+        }
+    }
+}
+
+/// Configures an [`EdgeHistogramSolver`]. See [`EdgeHistogramSolver::builder`].
+pub struct EdgeHistogramSolverBuilder {
+    solver: EdgeHistogramSolver
+}
+
+impl EdgeHistogramSolverBuilder {
+    pub fn elite_percent(mut self, elite_percent: f32) -> Self {
+        self.solver.eda.elite_percent = elite_percent;
+        self
+    }
+
+    pub fn bias(mut self, bias: f32) -> Self {
+        self.solver.eda.bias = bias;
+        self
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.solver.population = population;
+        self
+    }
+
+    pub fn generations(mut self, generations: u32) -> Self {
+        self.solver.generations = generations;
+        self
+    }
+
+    pub fn build(self) -> EdgeHistogramSolver {
+        self.solver
+    }
+}
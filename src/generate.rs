@@ -0,0 +1,219 @@
+use rand::prelude::*;
+use std::f32::consts;
+
+use crate::map::{Map, Point};
+
+/// How the points of a generated instance are spread out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    /// Uniform in polar coordinates on the unit disc, so points bunch up towards the center
+    Disc,
+    /// Uniform over the unit square
+    Uniform,
+    /// Normally distributed around `clusters` centers placed uniformly over the unit square, with standard
+    /// deviation `spread`
+    Clustered {
+        clusters: usize,
+        spread: f32
+    },
+    /// The points of a square grid with unit spacing, filled row by row
+    Grid
+}
+
+impl Distribution {
+    /// The given number of clusters, spread so that neighboring clusters rarely overlap.
+    pub fn clustered(clusters: usize) -> Self {
+        let clusters = clusters.max(1);
+        Distribution::Clustered {
+            clusters,
+            spread: 0.25 / (clusters as f32).sqrt()
+        }
+    }
+}
+
+/// A way of placing the points of a random instance, which [`Map::random`] draws from.
+///
+/// Besides the built-in [`Distribution`]s and the shapes in this module, any closure taking the random number
+/// generator and returning a point is a distribution, which draws every point independently by calling it.
+pub trait PointDistribution {
+    /// Draws `count` points.
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point>;
+}
+
+impl<F: Fn(&mut dyn RngCore) -> Point> PointDistribution for F {
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        (0..count).map(|_| self(rng)).collect()
+    }
+}
+
+impl PointDistribution for Distribution {
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        match *self {
+            Distribution::Disc => (0..count)
+                .map(|_| {
+                    let theta = 2.0 * consts::PI * rng.gen::<f32>();
+                    Point::polar(rng.gen::<f32>(), theta)
+                })
+                .collect(),
+            Distribution::Uniform => (0..count).map(|_| uniform(rng)).collect(),
+            Distribution::Clustered { clusters, spread } => {
+                let centers: Vec<Point> = (0..clusters.max(1)).map(|_| uniform(rng)).collect();
+                (0..count)
+                    .map(|_| {
+                        // Infallible: there is always at least one center
+                        let center = centers.choose(rng).unwrap();
+                        Point {
+                            x: center.x + spread * normal(rng),
+                            y: center.y + spread * normal(rng)
+                        }
+                    })
+                    .collect()
+            },
+            Distribution::Grid => {
+                let side = (count as f32).sqrt().ceil() as usize;
+                (0..count)
+                    .map(|i| Point {
+                        x: (i % side) as f32,
+                        y: (i / side) as f32
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Uniform over the area of a disc, unlike [`Distribution::Disc`], which is uniform in polar coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UniformDisc {
+    pub center: Point,
+    pub radius: f32
+}
+
+impl Default for UniformDisc {
+    /// The unit disc around the origin.
+    fn default() -> Self {
+        UniformDisc {
+            center: Point::new(),
+            radius: 1.0
+        }
+    }
+}
+
+impl PointDistribution for UniformDisc {
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        Ring {
+            center: self.center,
+            inner: 0.0,
+            outer: self.radius
+        }
+        .sample(count, rng)
+    }
+}
+
+/// Uniform over a square with its lowest corner at `corner`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UniformSquare {
+    pub corner: Point,
+    pub side: f32
+}
+
+impl Default for UniformSquare {
+    /// The unit square, the same as [`Distribution::Uniform`].
+    fn default() -> Self {
+        UniformSquare {
+            corner: Point::new(),
+            side: 1.0
+        }
+    }
+}
+
+impl PointDistribution for UniformSquare {
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        (0..count)
+            .map(|_| self.corner + self.side * uniform(rng))
+            .collect()
+    }
+}
+
+/// One of the normal distributions of a [`GaussianMixture`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Component {
+    pub center: Point,
+    /// The standard deviation along either axis
+    pub spread: f32,
+    /// How likely every point is to come from this component, relative to the others
+    pub weight: f32
+}
+
+/// Normally distributed around the centers of its components, each point drawn from a component chosen in
+/// proportion to their weights. Unlike [`Distribution::Clustered`], the centers, spreads and weights are given.
+///
+/// Should no component have a positive weight they are chosen uniformly, and a mixture without any places every
+/// point at the origin.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GaussianMixture {
+    pub components: Vec<Component>
+}
+
+impl PointDistribution for GaussianMixture {
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let weighted = self.components.iter().any(|component| component.weight > 0.0);
+        (0..count)
+            .map(|_| {
+                let component = if weighted {
+                    self.components.choose_weighted(rng, |component| component.weight.max(0.0)).ok()
+                } else {
+                    self.components.choose(rng)
+                };
+                match component {
+                    Some(component) => Point {
+                        x: component.center.x + component.spread * normal(rng),
+                        y: component.center.y + component.spread * normal(rng)
+                    },
+                    None => Point::new()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Uniform over the area of an annulus, between the circles of radius `inner` and `outer` around the center. An
+/// inner radius equal to the outer one places the points on a circle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ring {
+    pub center: Point,
+    pub inner: f32,
+    pub outer: f32
+}
+
+impl PointDistribution for Ring {
+    fn sample(&self, count: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        // The area inside a radius grows with its square, so the square of the radius is drawn uniformly
+        let (low, high) = (self.inner * self.inner, self.outer * self.outer);
+        (0..count)
+            .map(|_| {
+                let radius = (low + (high - low) * rng.gen::<f32>()).sqrt();
+                self.center + Point::polar(radius, 2.0 * consts::PI * rng.gen::<f32>())
+            })
+            .collect()
+    }
+}
+
+/// Generates `count` points with the given distribution. The same as [`Map::random`].
+pub fn generate<R: Rng + ?Sized>(count: usize, distribution: Distribution, rng: &mut R) -> Map {
+    Map::random(count, &distribution, rng)
+}
+
+fn uniform<R: Rng + ?Sized>(rng: &mut R) -> Point {
+    Point {
+        x: rng.gen(),
+        y: rng.gen()
+    }
+}
+
+// A standard normal sample by the Box-Muller transform
+fn normal<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+    let u = 1.0 - rng.gen::<f32>();
+    let v = rng.gen::<f32>();
+    (-2.0 * u.ln()).sqrt() * (2.0 * consts::PI * v).cos()
+}
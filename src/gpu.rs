@@ -0,0 +1,255 @@
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+use crate::error::{Error, Result};
+use crate::map::{Map, Path};
+use crate::matrix::DistanceMatrix;
+use crate::solve::check_map;
+
+// Each invocation sums the edges of one tour, reading distances from the packed upper triangle of
+// `DistanceMatrix`
+const SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read> distances: array<f32>;
+@group(0) @binding(1) var<storage, read> tours: array<u32>;
+@group(0) @binding(2) var<storage, read_write> lengths: array<f32>;
+// The number of points, then the number of tours
+@group(0) @binding(3) var<uniform> shape: vec4<u32>;
+
+fn distance(a: u32, b: u32, size: u32) -> f32 {
+    if (a == b) {
+        return 0.0;
+    }
+    let i = min(a, b);
+    let j = max(a, b);
+    return distances[i * (2u * size - i - 1u) / 2u + (j - i - 1u)];
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = shape.x;
+    if (id.x >= shape.y) {
+        return;
+    }
+
+    let start = id.x * size;
+    var total = 0.0;
+    for (var k = 1u; k < size; k++) {
+        total += distance(tours[start + k - 1u], tours[start + k], size);
+    }
+    lengths[id.x] = total;
+}
+"#;
+
+const WORKGROUP_SIZE: usize = 64;
+
+// The most workgroups a single dispatch may have along one dimension
+const MAX_WORKGROUPS: usize = 65535;
+
+/// Evaluates many tours over one map at once on a GPU. The distances between the map's points are uploaded once
+/// and stay on the device, so each evaluation only uploads the tours, which pays off for large populations over
+/// large maps. Lengths are summed in a different order than on the CPU, so they can differ in the last bits.
+pub struct GpuEvaluator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    distances: wgpu::Buffer,
+    size: usize,
+    // The largest storage buffer the device can bind, which bounds how many tours go in one dispatch
+    max_binding: u64
+}
+
+impl GpuEvaluator {
+    /// Connects to the most powerful GPU available and uploads the map's distances to it. Fails if there is no
+    /// GPU, or if the distances do not fit in a single buffer on it.
+    pub fn new(map: &Map) -> Result<Self> {
+        pollster::block_on(Self::connect(map))
+    }
+
+    async fn connect(map: &Map) -> Result<Self> {
+        check_map(map)?;
+
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            })
+            .await
+            .ok_or_else(|| Error::Gpu("no GPU adapter is available".to_string()))?;
+        // Ask for everything the adapter supports, since the distances of a large map need large buffers
+        let limits = adapter.limits();
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("tsp"),
+                    required_features: wgpu::Features::empty(),
+                    required_limits: limits.clone()
+                },
+                None
+            )
+            .await
+            .map_err(|error| Error::Gpu(error.to_string()))?;
+
+        let matrix = DistanceMatrix::try_new(map)?;
+        // Bindings cannot be empty, so a single point still gets one (unused) distance
+        let packed = if matrix.packed().is_empty() { &[0.0][..] } else { matrix.packed() };
+        let max_binding = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+        if (packed.len() * 4) as u64 > max_binding {
+            return Err(Error::Gpu(format!(
+                "the distances between {} points take {} bytes, but the GPU binds at most {}",
+                map.size(),
+                packed.len() * 4,
+                max_binding
+            )));
+        }
+
+        let distances = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("distances"),
+            contents: &to_bytes(packed.iter().map(|distance| distance.to_ne_bytes())),
+            usage: wgpu::BufferUsages::STORAGE
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("evaluate"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into())
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("evaluate"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            compilation_options: Default::default()
+        });
+
+        Ok(GpuEvaluator {
+            device,
+            queue,
+            pipeline,
+            distances,
+            size: map.size(),
+            max_binding
+        })
+    }
+
+    /// The lengths of the tours, in order. Like [`Path::evaluate`], tours that are not permutations of the map's
+    /// points produce meaningless lengths, but tours of the wrong length or with points out of range are errors.
+    pub fn evaluate<T: AsRef<[usize]>>(&self, tours: &[T]) -> Result<Vec<f32>> {
+        for tour in tours {
+            let tour = tour.as_ref();
+            if tour.len() != self.size {
+                return Err(Error::DimensionMismatch {
+                    expected: self.size,
+                    found: tour.len()
+                });
+            }
+            if let Some(point) = tour.iter().find(|&&point| point >= self.size) {
+                return Err(Error::InvalidTour(format!("point {} is out of range", point)));
+            }
+        }
+
+        let per_tour = (self.size * 4) as u64;
+        let batch = ((self.max_binding / per_tour) as usize).min(MAX_WORKGROUPS * WORKGROUP_SIZE);
+        if batch == 0 {
+            return Err(Error::Gpu(format!("a tour of {} points does not fit in a GPU buffer", self.size)));
+        }
+
+        let mut lengths = Vec::with_capacity(tours.len());
+        for chunk in tours.chunks(batch) {
+            lengths.extend(self.evaluate_batch(chunk)?);
+        }
+        Ok(lengths)
+    }
+
+    /// The lengths of the tours of every individual of the population, decoded on the CPU and evaluated on the
+    /// GPU. The map must be the one the evaluator was created for.
+    pub fn evaluate_population<P: Path>(&self, map: &Map, population: &[P]) -> Result<Vec<f32>> {
+        if map.size() != self.size {
+            return Err(Error::DimensionMismatch {
+                expected: self.size,
+                found: map.size()
+            });
+        }
+
+        let tours: Vec<Vec<usize>> = population.iter().map(|indv| indv.tour(map)).collect();
+        self.evaluate(&tours)
+    }
+
+    fn evaluate_batch<T: AsRef<[usize]>>(&self, tours: &[T]) -> Result<Vec<f32>> {
+        if tours.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let indices = tours.iter()
+            .flat_map(|tour| tour.as_ref().iter())
+            .map(|&point| (point as u32).to_ne_bytes());
+        let tour_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tours"),
+            contents: &to_bytes(indices),
+            usage: wgpu::BufferUsages::STORAGE
+        });
+        let shape = [self.size as u32, tours.len() as u32, 0, 0];
+        let shape_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("shape"),
+            contents: &to_bytes(shape.iter().map(|value| value.to_ne_bytes())),
+            usage: wgpu::BufferUsages::UNIFORM
+        });
+        let output_size = (tours.len() * 4) as u64;
+        let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("lengths"),
+            size: output_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: output_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("evaluate"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: self.distances.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: tour_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: output.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: shape_buffer.as_entire_binding() }
+            ]
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(tours.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, output_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            // The receiver outlives the wait below, so the send cannot fail
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()
+            .map_err(|error| Error::Gpu(error.to_string()))?
+            .map_err(|error| Error::Gpu(error.to_string()))?;
+
+        let lengths = slice.get_mapped_range()
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        readback.unmap();
+        Ok(lengths)
+    }
+}
+
+fn to_bytes<I: Iterator<Item = [u8; 4]>>(words: I) -> Vec<u8> {
+    words.flat_map(IntoIterator::into_iter).collect()
+}
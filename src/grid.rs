@@ -0,0 +1,157 @@
+use crate::error::{Error, Result};
+use crate::map::{Map, Point};
+use crate::matrix::DistanceMatrix;
+
+// Coordinates are kept within this bound so that every squared distance fits in a `u64`
+const MAX_COORDINATE: i64 = 1 << 30;
+
+// The largest integer up to which every integer is exact in an `f32`
+const F32_EXACT: u64 = 1 << 24;
+
+/// A point with integer coordinates, such as a drill hole on a board measured in mils.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GridPoint {
+    pub x: i64,
+    pub y: i64
+}
+
+/// An instance on an integer grid, whose distances are computed with integer arithmetic alone, so that they and
+/// every length summed from them come out bit for bit the same on every platform. A distance is the straight line
+/// distance rounded to the nearest integer, as TSPLIB's `EUC_2D` rounds it, with halves rounded up. Comparisons of
+/// which point is nearest use the exact squared distances instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    points: Vec<GridPoint>
+}
+
+impl Grid {
+    /// Checks that every coordinate lies within ±2^30.
+    pub fn new(points: Vec<GridPoint>) -> Result<Self> {
+        let outside = points.iter().find(|point| point.x.abs() > MAX_COORDINATE || point.y.abs() > MAX_COORDINATE);
+        if let Some(point) = outside {
+            return Err(Error::InvalidProblem(format!(
+                "the point ({}, {}) lies outside the grid of coordinates up to 2^30",
+                point.x, point.y
+            )));
+        }
+
+        Ok(Grid {
+            points
+        })
+    }
+
+    /// The grid of a map whose points all have integer coordinates, as TSPLIB instances of drilling problems do.
+    pub fn from_map(map: &Map) -> Result<Self> {
+        let points = map.iter()
+            .map(|point| match (integer(point.x), integer(point.y)) {
+                (Some(x), Some(y)) => Ok(GridPoint { x, y }),
+                _ => Err(Error::InvalidProblem(format!("the point {} does not have integer coordinates", point)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(points)
+    }
+
+    pub fn points(&self) -> &[GridPoint] {
+        &self.points
+    }
+
+    pub fn size(&self) -> usize {
+        self.points.len()
+    }
+
+    /// The exact square of the distance between two points.
+    #[inline]
+    pub fn squared(&self, a: usize, b: usize) -> u64 {
+        let (a, b) = (self.points[a], self.points[b]);
+        let (x, y) = ((a.x - b.x).unsigned_abs(), (a.y - b.y).unsigned_abs());
+        x * x + y * y
+    }
+
+    /// The distance between two points, rounded to the nearest integer.
+    #[inline]
+    pub fn dist(&self, a: usize, b: usize) -> u64 {
+        let squared = self.squared(a, b);
+        let root = squared.isqrt();
+        // The root rounds up when the square reaches halfway to the next one, `(root + 1/2)^2 = root^2 + root + 1/4`,
+        // which for integers means passing `root^2 + root`
+        if squared - root * root > root { root + 1 } else { root }
+    }
+
+    /// The exact length of the path visiting the points in the given order.
+    pub fn path_length(&self, tour: &[usize]) -> u64 {
+        tour.windows(2).map(|edge| self.dist(edge[0], edge[1])).sum()
+    }
+
+    /// The exact length of the tour, including the edge back from its last point to its first.
+    pub fn tour_length(&self, tour: &[usize]) -> u64 {
+        let closing = match (tour.first(), tour.last()) {
+            (Some(&first), Some(&last)) => self.dist(last, first),
+            _ => 0
+        };
+        self.path_length(tour) + closing
+    }
+
+    /// Builds a path from the first point by always moving to the nearest unvisited point, comparing squared
+    /// distances and breaking ties by the lower index, so that the path is the same on every platform.
+    pub fn nearest_neighbor(&self) -> Vec<usize> {
+        let mut unvisited: Vec<usize> = (1..self.size()).collect();
+        let mut tour = Vec::with_capacity(self.size());
+        if self.points.is_empty() {
+            return tour;
+        }
+
+        let mut last = 0;
+        tour.push(last);
+        while !unvisited.is_empty() {
+            // Infallible: there is an unvisited point
+            let (index, _) = unvisited.iter()
+                .enumerate()
+                .min_by_key(|&(_, &point)| (self.squared(last, point), point))
+                .unwrap();
+            last = unvisited.swap_remove(index);
+            tour.push(last);
+        }
+        tour
+    }
+
+    /// The rounded distances between every pair of points, which are integers and so exact in an `f32`.
+    pub fn distances(&self) -> DistanceMatrix {
+        DistanceMatrix::from_fn(self.size(), |i, j| self.dist(i, j) as f32)
+    }
+
+    /// A map of the points with the rounded [distances](Self::distances) attached, for the solvers. They add up
+    /// lengths in `f32`, which stays exact, and so the same on every platform, as long as no path is longer than
+    /// 2^24. Fails unless the diagonal of the points' bounding box times the number of edges of a path is below
+    /// that, which bounds the length of any path.
+    pub fn map(&self) -> Result<Map> {
+        let (min_x, max_x) = bounds(self.points.iter().map(|point| point.x));
+        let (min_y, max_y) = bounds(self.points.iter().map(|point| point.y));
+        let (width, height) = ((max_x - min_x) as u64, (max_y - min_y) as u64);
+        // One more than the rounded diagonal, so the bound is safe whichever way the diagonal rounds
+        let diagonal = (width * width + height * height).isqrt() + 1;
+        let longest = diagonal.saturating_mul(self.size().saturating_sub(1) as u64);
+        if longest >= F32_EXACT {
+            return Err(Error::TooLarge(format!(
+                "paths may be up to {} long, over the 2^24 below which f32 lengths are exact",
+                longest
+            )));
+        }
+
+        let points = self.points.iter().map(|point| Point { x: point.x as f32, y: point.y as f32 }).collect();
+        Map::from_points(points).with_distances(self.distances())
+    }
+}
+
+// The coordinate as an integer, if it is one
+fn integer(coordinate: f32) -> Option<i64> {
+    (coordinate.fract() == 0.0 && coordinate.abs() <= MAX_COORDINATE as f32).then_some(coordinate as i64)
+}
+
+// The least and greatest values, or zeros if there are none
+fn bounds<I: Iterator<Item = i64>>(values: I) -> (i64, i64) {
+    values.fold(None, |bounds: Option<(i64, i64)>, value| match bounds {
+        Some((min, max)) => Some((min.min(value), max.max(value))),
+        None => Some((value, value))
+    })
+    .unwrap_or((0, 0))
+}
@@ -0,0 +1,405 @@
+use rand::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::cvrp::order_crossover;
+use crate::error::{Error, Result};
+use crate::ga::check_population;
+use crate::map::{Map, Path};
+use crate::solve::{Budget, Reporter, SolveOptions, SolveResult, Termination, check_map};
+use crate::time::Instant;
+
+/// The generalized traveling salesman problem: the points are split into groups, and the tour visits exactly one
+/// point of every group, as when a delivery can be made to any one of several entrances of a building. Like every
+/// tour in this crate the tour is an open path, and its length is that of [`Path::evaluate`].
+#[derive(Clone, Copy, Debug)]
+pub struct Problem<'a> {
+    pub map: &'a Map,
+    /// The group of every point, by index
+    pub groups: &'a [usize]
+}
+
+impl Problem<'_> {
+    /// Checks that there is a group for every point.
+    pub fn validate(&self) -> Result<()> {
+        check_map(self.map)?;
+        if self.groups.len() != self.map.size() {
+            return Err(Error::DimensionMismatch {
+                expected: self.map.size(),
+                found: self.groups.len()
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the tour visits exactly one point of every group.
+    pub fn check_tour(&self, tour: &[usize]) -> Result<()> {
+        let mut visited: BTreeMap<usize, usize> = BTreeMap::new();
+        for &point in tour {
+            let group = *self.groups.get(point)
+                .ok_or_else(|| Error::InvalidTour(format!("point {} is out of range", point)))?;
+            if let Some(other) = visited.insert(group, point) {
+                return Err(Error::InvalidTour(format!("points {} and {} are both in group {}", other, point, group)));
+            }
+        }
+
+        match self.groups.iter().find(|group| !visited.contains_key(group)) {
+            Some(group) => Err(Error::InvalidTour(format!("group {} is not visited", group))),
+            None => Ok(())
+        }
+    }
+
+    // The points of every group, with the groups numbered by the order of their labels and the points ordered by
+    // their indices, and the number of every point's group
+    fn layout(&self) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (point, &group) in self.groups.iter().enumerate() {
+            members.entry(group).or_default().push(point);
+        }
+
+        let members: Vec<Vec<usize>> = members.into_values().collect();
+        let mut group_of = vec![0; self.map.size()];
+        for (group, points) in members.iter().enumerate() {
+            points.iter().for_each(|&point| group_of[point] = group);
+        }
+        (members, group_of)
+    }
+}
+
+/// The tour that visits the groups in the same order as the given one, through the members that make it
+/// shortest. Found by dynamic programming over the groups in order, in `O(m^2)` time per group for groups of `m`
+/// points. The tour must pass [`Problem::check_tour`].
+pub fn choose_members(problem: &Problem<'_>, tour: &[usize]) -> Vec<usize> {
+    let (members, group_of) = problem.layout();
+    let order: Vec<usize> = tour.iter().map(|&point| group_of[point]).collect();
+    best_members(problem.map, &members, &order)
+}
+
+fn best_members(map: &Map, members: &[Vec<usize>], order: &[usize]) -> Vec<usize> {
+    let Some(&first) = order.first() else {
+        return Vec::new();
+    };
+
+    // `best[k][m]` is the shortest path through the first `k + 1` groups ending at their `m`th member, reached
+    // from member `from[k][m]` of the group before
+    let mut best = vec![vec![0f32; members[first].len()]];
+    let mut from = vec![vec![0usize; members[first].len()]];
+    for k in 1..order.len() {
+        let (previous, current) = (&members[order[k - 1]], &members[order[k]]);
+        let (lengths, choices): (Vec<f32>, Vec<usize>) = current.iter()
+            .map(|&point| {
+                (0..previous.len())
+                    .map(|p| (best[k - 1][p] + map.dist(previous[p], point), p))
+                    .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                    // Infallible: every group has a member
+                    .unwrap()
+            })
+            .unzip();
+        best.push(lengths);
+        from.push(choices);
+    }
+
+    // Infallible: there is at least one group, and every group has a member
+    let last = best.last().unwrap();
+    let mut m = (0..last.len())
+        .min_by(|&a, &b| last[a].total_cmp(&last[b]))
+        .unwrap();
+    let mut tour = vec![0; order.len()];
+    for k in (0..order.len()).rev() {
+        tour[k] = members[order[k]][m];
+        m = from[k][m];
+    }
+    tour
+}
+
+/// Builds a tour by starting at point zero and going to the nearest point of a group not yet visited, and then
+/// chooses the members for the order of the groups with [`choose_members`]. The iteration count is the number of
+/// groups.
+pub fn greedy(problem: &Problem<'_>) -> Result<SolveResult> {
+    let start = Instant::now();
+    problem.validate()?;
+
+    let (members, group_of) = problem.layout();
+    let tour = best_members(problem.map, &members, &greedy_order(problem.map, &members, &group_of));
+    let groups = tour.len() as u64;
+    Ok(SolveResult::new(problem.map, tour, groups, start))
+}
+
+// The order in which the nearest neighbor walk from point zero visits the groups
+fn greedy_order(map: &Map, members: &[Vec<usize>], group_of: &[usize]) -> Vec<usize> {
+    let mut visited = vec![false; members.len()];
+    let mut current = 0;
+    let mut order = Vec::with_capacity(members.len());
+    loop {
+        visited[group_of[current]] = true;
+        order.push(group_of[current]);
+        let next = (0..map.size())
+            .filter(|&point| !visited[group_of[point]])
+            .min_by(|&a, &b| map.dist(current, a).total_cmp(&map.dist(current, b)));
+        match next {
+            Some(next) => current = next,
+            None => return order
+        }
+    }
+}
+
+/// A two-layer chromosome: the order in which the groups are visited, and which member of each group is. Groups
+/// are numbered by the order of their labels, and the members of a group by the order of their indices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupChromosome {
+    /// A permutation of the groups
+    pub order: Vec<usize>,
+    /// The member chosen from every group, by group
+    pub members: Vec<usize>
+}
+
+impl GroupChromosome {
+    /// The tour visiting the chosen member of every group, in the order of the groups.
+    pub fn tour(&self, problem: &Problem<'_>) -> Vec<usize> {
+        decode(&problem.layout().0, self)
+    }
+}
+
+fn decode(members: &[Vec<usize>], indv: &GroupChromosome) -> Vec<usize> {
+    indv.order.iter().map(|&group| members[group][indv.members[group]]).collect()
+}
+
+/// A GA over [`GroupChromosome`]s. Each generation the `elites` best individuals survive, and the rest of the
+/// population is filled with children of tournament winners, made by order crossover of the group orders and
+/// uniform crossover of the member choices. Each layer is then mutated on its own, the order by reversing or
+/// swapping and the choices by drawing a new member for one group. The population starts from the [`greedy`] tour
+/// and random individuals, and the members of the best tour are chosen again with [`choose_members`] at the end.
+#[derive(Clone, Debug)]
+pub struct GtspGa {
+    pub population: usize,
+    pub generations: u32,
+    /// The number of individuals competing in each tournament
+    pub tournament: usize,
+    pub elites: usize,
+    pub crossover_prob: f32,
+    pub mutate_prob: f32
+}
+
+impl Default for GtspGa {
+    fn default() -> Self {
+        GtspGa {
+            population: 100,
+            generations: 1000,
+            tournament: 3,
+            elites: 2,
+            crossover_prob: 0.9,
+            mutate_prob: 0.3
+        }
+    }
+}
+
+impl GtspGa {
+    /// Checks that the population has at least two individuals and that tournaments have at least one, failing
+    /// with [`Error::InvalidSettings`] otherwise.
+    pub fn validate(&self) -> Result<()> {
+        check_population(self.population, 2)?;
+        if self.tournament == 0 {
+            return Err(Error::InvalidSettings("the tournament is 0, but needs at least one individual".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Runs the GA for the configured number of generations or until the budget runs out, returning the best tour
+    /// found. The iteration count is the number of generations run.
+    pub fn solve(&self, problem: &Problem<'_>, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        problem.validate()?;
+        self.validate()?;
+
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start);
+        let mut rng = options.rng();
+        let map = problem.map;
+        let (members, group_of) = problem.layout();
+        let count = members.len();
+        let evaluate = |indv: &GroupChromosome| decode(&members, indv).evaluate(map);
+
+        // The greedy tour, with each member found among its group's
+        let order = greedy_order(map, &members, &group_of);
+        let tour = best_members(map, &members, &order);
+        let mut chosen = vec![0; count];
+        for (&group, point) in order.iter().zip(tour) {
+            // Infallible: the tour visits a member of every group
+            chosen[group] = members[group].iter().position(|&member| member == point).unwrap();
+        }
+        let mut population = vec![GroupChromosome {
+            order,
+            members: chosen
+        }];
+        population.extend((1..self.population).map(|_| {
+            let mut order: Vec<usize> = (0..count).collect();
+            order.shuffle(&mut rng);
+            let members = members.iter().map(|points| rng.gen_range(0, points.len())).collect();
+            GroupChromosome {
+                order,
+                members
+            }
+        }));
+        let mut ranked: Vec<(f32, GroupChromosome)> = population.into_iter()
+            .map(|indv| (evaluate(&indv), indv))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        reporter.improved(ranked[0].0);
+
+        let elites = self.elites.clamp(1, self.population - 1);
+        let mut present = vec![false; count];
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            let mut next: Vec<(f32, GroupChromosome)> = ranked[..elites].to_vec();
+            while next.len() < self.population {
+                // The population is ranked, so the lowest index drawn wins
+                let mut tournament = || (0..self.tournament).map(|_| rng.gen_range(0, ranked.len())).min().unwrap();
+                let (first, second) = (tournament(), tournament());
+                let mut child = ranked[first].1.clone();
+                if rng.gen::<f32>() < self.crossover_prob {
+                    let other = &ranked[second].1;
+                    child.order = order_crossover(&child.order, &other.order, &mut present, &mut rng);
+                    for (member, &theirs) in child.members.iter_mut().zip(&other.members) {
+                        if rng.gen() {
+                            *member = theirs;
+                        }
+                    }
+                }
+                if rng.gen::<f32>() < self.mutate_prob && count > 1 {
+                    let (a, b) = (rng.gen_range(0, count), rng.gen_range(0, count));
+                    if rng.gen() {
+                        child.order[a.min(b)..=a.max(b)].reverse();
+                    } else {
+                        child.order.swap(a, b);
+                    }
+                }
+                if rng.gen::<f32>() < self.mutate_prob {
+                    let group = rng.gen_range(0, count);
+                    child.members[group] = rng.gen_range(0, members[group].len());
+                }
+                next.push((evaluate(&child), child));
+            }
+
+            next.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            ranked = next;
+            reporter.improved(ranked[0].0);
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        let tour = best_members(map, &members, &ranked[0].1.order);
+        let mut result = SolveResult::new(map, tour, generations as u64, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+/// An exact search over the groups. The tour is extended one group at a time, trying every member of every group
+/// not yet visited, nearest first, and a partial tour is pruned once its length plus the cheapest way into each
+/// remaining group reaches the best tour found so far. The search starts from the [`greedy`] tour. The iteration
+/// count is the number of partial tours expanded, and the result has a lower bound when the search completes.
+pub fn branch_and_bound(problem: &Problem<'_>, options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    problem.validate()?;
+
+    let map = problem.map;
+    let (members, group_of) = problem.layout();
+    // The shortest edge into each group from a point of another
+    let entry: Vec<f32> = members.iter()
+        .enumerate()
+        .map(|(group, points)| {
+            points.iter()
+                .flat_map(|&point| {
+                    (0..map.size()).filter(|&other| group_of[other] != group).map(move |other| map.dist(other, point))
+                })
+                .fold(f32::INFINITY, f32::min)
+        })
+        .map(|dist| if dist.is_finite() { dist } else { 0.0 })
+        .collect();
+
+    let best = greedy(problem)?;
+    let mut search = GroupSearch {
+        map,
+        members: &members,
+        group_of: &group_of,
+        entry: &entry,
+        visited: vec![false; members.len()],
+        path: Vec::with_capacity(members.len()),
+        best_length: best.length,
+        best: best.tour,
+        nodes: 0,
+        budget: Budget::new(options, start, 256),
+        reporter: Reporter::new(options, start)
+    };
+    search.reporter.improved(search.best_length);
+
+    let remaining: f32 = entry.iter().sum();
+    for (first, &group) in group_of.iter().enumerate() {
+        search.enter(first, 0.0, remaining - entry[group]);
+        if search.budget.exhausted() {
+            break;
+        }
+    }
+
+    let termination = search.budget.termination();
+    let mut result = SolveResult::new(map, search.best, search.nodes, start);
+    result.termination = termination;
+    if termination == Termination::Completed {
+        search.reporter.completion(1.0);
+        result.lower_bound = Some(result.length);
+    }
+    Ok(result)
+}
+
+// The state of a depth-first search for the generalized TSP
+struct GroupSearch<'a> {
+    map: &'a Map,
+    members: &'a [Vec<usize>],
+    group_of: &'a [usize],
+    entry: &'a [f32],
+    visited: Vec<bool>,
+    path: Vec<usize>,
+    best_length: f32,
+    best: Vec<usize>,
+    nodes: u64,
+    budget: Budget,
+    reporter: Reporter<'a>
+}
+
+impl GroupSearch<'_> {
+    // Extends the path with the point, whose group is no longer counted in `remaining`, and searches on from it
+    fn enter(&mut self, point: usize, length: f32, remaining: f32) {
+        if self.budget.exhausted() {
+            return;
+        }
+        self.nodes += 1;
+        self.reporter.nodes(self.nodes);
+
+        let group = self.group_of[point];
+        self.visited[group] = true;
+        self.path.push(point);
+        if self.path.len() == self.members.len() {
+            if length < self.best_length {
+                self.best_length = length;
+                self.best.clone_from(&self.path);
+                self.reporter.improved(length);
+            }
+        } else {
+            let mut next: Vec<(f32, usize)> = self.members.iter()
+                .enumerate()
+                .filter(|&(group, _)| !self.visited[group])
+                .flat_map(|(_, points)| points.iter().map(|&next| (self.map.dist(point, next), next)))
+                .collect();
+            next.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            for (dist, next) in next {
+                let rest = remaining - self.entry[self.group_of[next]];
+                if length + dist + rest < self.best_length {
+                    self.enter(next, length + dist, rest);
+                }
+            }
+        }
+        self.path.pop();
+        self.visited[group] = false;
+    }
+}
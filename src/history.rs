@@ -0,0 +1,128 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::solve::{Progress, ProgressEvent};
+
+/// One point on a convergence curve. See [`ProgressEvent::Sample`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    /// The generation for genetic algorithms, or the iteration for simulated annealing
+    pub step: u64,
+    pub best: f32,
+    pub mean: f32,
+    pub elapsed: Duration
+}
+
+/// A new best length and when it was found. See [`ProgressEvent::Improvement`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Improvement {
+    pub best: f32,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::time::serialize_seconds"))]
+    pub elapsed: Duration
+}
+
+/// The best length among the improvements found by `elapsed`, or `None` if none had been found by then, for
+/// comparing how far different solvers get in the same time. The improvements must be in the order they were
+/// found.
+pub fn best_at(improvements: &[Improvement], elapsed: Duration) -> Option<f32> {
+    improvements.iter()
+        .take_while(|improvement| improvement.elapsed <= elapsed)
+        .last()
+        .map(|improvement| improvement.best)
+}
+
+/// Records the convergence of a solve when attached as its progress observer: the samples of the solvers that
+/// report them, and the improvements of every solver. Solvers that report no progress can record their
+/// improvements themselves with [`record`](History::record).
+#[derive(Debug)]
+pub struct History {
+    samples: Mutex<Vec<Sample>>,
+    improvements: Mutex<Vec<Improvement>>,
+    detailed: bool
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            samples: Mutex::new(Vec::new()),
+            improvements: Mutex::new(Vec::new()),
+            detailed: true
+        }
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A history which only records improvements, so that solvers skip computing samples for it.
+    pub fn improvements_only() -> Self {
+        History {
+            detailed: false,
+            ..Self::default()
+        }
+    }
+
+    pub fn samples(&self) -> Vec<Sample> {
+        self.samples.lock().unwrap().clone()
+    }
+
+    pub fn improvements(&self) -> Vec<Improvement> {
+        self.improvements.lock().unwrap().clone()
+    }
+
+    /// Records a new best length found after `elapsed`.
+    pub fn record(&self, elapsed: Duration, best: f32) {
+        self.improvements.lock().unwrap().push(Improvement {
+            best,
+            elapsed
+        });
+    }
+
+    /// The best length recorded by `elapsed`. See [`best_at`].
+    pub fn best_at(&self, elapsed: Duration) -> Option<f32> {
+        best_at(&self.improvements(), elapsed)
+    }
+
+    pub fn save_csv<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write_csv(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes a `step,best,mean,seconds` header followed by one line per sample.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "step,best,mean,seconds")?;
+        for sample in self.samples() {
+            writeln!(out, "{},{},{},{}", sample.step, sample.best, sample.mean, sample.elapsed.as_secs_f64())?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl Progress for History {
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Sample { step, best, mean, elapsed } => {
+                self.samples.lock().unwrap().push(Sample {
+                    step,
+                    best,
+                    mean,
+                    elapsed
+                });
+            },
+            ProgressEvent::Improvement { length, elapsed } => self.record(elapsed, length),
+            _ => {}
+        }
+    }
+
+    fn detailed(&self) -> bool {
+        self.detailed
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Search tree nodes expanded by the exact searches
+pub const NODES: &str = "nodes";
+/// Subtrees branch-and-bound cut off because their lower bound could not beat the best length
+pub const PRUNES: &str = "prunes";
+/// Tours or individuals evaluated
+pub const EVALUATIONS: &str = "evaluations";
+/// Pairs of parents recombined by the genetic algorithms
+pub const CROSSOVERS: &str = "crossovers";
+/// The time spent building the tour or population a search starts from
+pub const CONSTRUCTION: &str = "construction";
+/// The time spent searching from there
+pub const SEARCH: &str = "search";
+
+/// Receives named counts and timings from a running solver, set in
+/// [`SolveOptions::instrumentation`](crate::solve::SolveOptions::instrumentation). The solvers of this crate use the
+/// names of the constants in this module, and others may add their own. Solvers count in their own variables as
+/// they go and add the totals at the end of a solve, or of each thread's share of it, so a count may arrive in
+/// several parts and the same name may be timed more than once.
+pub trait Instrumentation: Send + Sync {
+    /// Adds `amount` to the named counter.
+    fn count(&self, _name: &'static str, _amount: u64) {}
+
+    /// Adds `elapsed` to the named timer.
+    fn time(&self, _name: &'static str, _elapsed: Duration) {}
+}
+
+impl Debug for dyn Instrumentation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Instrumentation")
+    }
+}
+
+/// A running count, such as of the nodes a search has expanded, kept by a single thread or shared by every thread
+/// of a parallel search, so that progress can be reported for the search as a whole. Threads add to a shared count
+/// in batches rather than one at a time, so that they do not contend for it.
+#[derive(Debug)]
+pub enum Counter<'a> {
+    /// Counted by the thread that holds it alone
+    Local(u64),
+    /// Added to by every thread holding it
+    Shared(&'a AtomicUsize)
+}
+
+impl Counter<'_> {
+    /// Adds `amount` to the count, returning the total after it, which for a shared count includes whatever the
+    /// other threads have added.
+    pub fn add(&mut self, amount: u64) -> u64 {
+        match self {
+            Counter::Local(count) => {
+                *count += amount;
+                *count
+            },
+            Counter::Shared(count) => (count.fetch_add(amount as usize, Ordering::Relaxed) + amount as usize) as u64
+        }
+    }
+
+    pub fn get(&self) -> u64 {
+        match self {
+            Counter::Local(count) => *count,
+            Counter::Shared(count) => count.load(Ordering::Relaxed) as u64
+        }
+    }
+}
+
+/// Ignores everything, for solvers run without instrumentation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoOp;
+
+impl Instrumentation for NoOp {}
+
+/// Sums every count and timing it receives, by name.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    counters: Mutex<BTreeMap<&'static str, u64>>,
+    timers: Mutex<BTreeMap<&'static str, Duration>>
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The total of the named counter, which is zero if nothing was counted under the name.
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters.lock().unwrap().get(name).copied().unwrap_or(0)
+    }
+
+    /// The total time of the named timer, which is zero if nothing was timed under the name.
+    pub fn timer(&self, name: &str) -> Duration {
+        self.timers.lock().unwrap().get(name).copied().unwrap_or_default()
+    }
+
+    /// Every counter, in name order.
+    pub fn counters(&self) -> Vec<(&'static str, u64)> {
+        self.counters.lock().unwrap().iter().map(|(&name, &count)| (name, count)).collect()
+    }
+
+    /// Every timer, in name order.
+    pub fn timers(&self) -> Vec<(&'static str, Duration)> {
+        self.timers.lock().unwrap().iter().map(|(&name, &elapsed)| (name, elapsed)).collect()
+    }
+}
+
+impl Instrumentation for Recorder {
+    fn count(&self, name: &'static str, amount: u64) {
+        *self.counters.lock().unwrap().entry(name).or_insert(0) += amount;
+    }
+
+    fn time(&self, name: &'static str, elapsed: Duration) {
+        *self.timers.lock().unwrap().entry(name).or_default() += elapsed;
+    }
+}
@@ -0,0 +1,365 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path;
+
+use crate::error::{Error, Result};
+use crate::map::{MAX_POINTS, Map, Point, check_permutation};
+
+/// File formats for instances and tours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// TSPLIB 95: `NODE_COORD_SECTION` for instances and `TOUR_SECTION` for tours, with 1-based indices
+    Tsplib,
+    /// One `x,y` pair per line for instances and one 0-based index per line for tours, with an optional header
+    Csv,
+    /// An object with the instance's `name` and either its `points`, as `x` and `y` fields, or its 0-based `tour`
+    #[cfg(feature = "serde")]
+    Json
+}
+
+impl Format {
+    /// Guesses the format from a file extension: `.csv` is CSV, `.json` is JSON and anything else TSPLIB.
+    pub fn from_path<P: AsRef<path::Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Format::Csv,
+            #[cfg(feature = "serde")]
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Tsplib
+        }
+    }
+}
+
+pub fn load_map<P: AsRef<path::Path>>(path: P) -> Result<Map> {
+    let format = Format::from_path(&path);
+    read_map(BufReader::new(File::open(path)?), format)
+}
+
+pub fn save_map<P: AsRef<path::Path>>(path: P, map: &Map, name: &str) -> Result<()> {
+    let format = Format::from_path(&path);
+    write_map(BufWriter::new(File::create(path)?), map, name, format)
+}
+
+pub fn load_tour<P: AsRef<path::Path>>(path: P, map: &Map) -> Result<Vec<usize>> {
+    let format = Format::from_path(&path);
+    read_tour(BufReader::new(File::open(path)?), map, format)
+}
+
+pub fn save_tour<P: AsRef<path::Path>>(path: P, tour: &[usize], name: &str) -> Result<()> {
+    let format = Format::from_path(&path);
+    write_tour(BufWriter::new(File::create(path)?), tour, name, format)
+}
+
+pub fn read_map<R: BufRead>(input: R, format: Format) -> Result<Map> {
+    match format {
+        Format::Tsplib => read_tsplib_map(input),
+        Format::Csv => read_csv_map(input),
+        #[cfg(feature = "serde")]
+        Format::Json => json::read_map(input)
+    }
+}
+
+pub fn write_map<W: Write>(mut out: W, map: &Map, name: &str, format: Format) -> Result<()> {
+    match format {
+        Format::Tsplib => {
+            writeln!(out, "NAME : {}", name)?;
+            writeln!(out, "TYPE : TSP")?;
+            writeln!(out, "DIMENSION : {}", map.size())?;
+            writeln!(out, "EDGE_WEIGHT_TYPE : EUC_2D")?;
+            writeln!(out, "NODE_COORD_SECTION")?;
+            for (i, point) in map.iter().enumerate() {
+                writeln!(out, "{} {} {}", i + 1, point.x, point.y)?;
+            }
+            writeln!(out, "EOF")?;
+        },
+        Format::Csv => {
+            writeln!(out, "x,y")?;
+            for point in map {
+                writeln!(out, "{},{}", point.x, point.y)?;
+            }
+        },
+        #[cfg(feature = "serde")]
+        Format::Json => json::write_map(&mut out, map, name)?
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Reads a tour and checks that it visits every point of the map exactly once.
+pub fn read_tour<R: BufRead>(input: R, map: &Map, format: Format) -> Result<Vec<usize>> {
+    let tour = match format {
+        Format::Tsplib => read_tsplib_tour(input)?,
+        Format::Csv => read_csv_tour(input)?,
+        #[cfg(feature = "serde")]
+        Format::Json => json::read_tour(input)?
+    };
+
+    check_permutation(map, &tour)?;
+    Ok(tour)
+}
+
+pub fn write_tour<W: Write>(mut out: W, tour: &[usize], name: &str, format: Format) -> Result<()> {
+    match format {
+        Format::Tsplib => {
+            writeln!(out, "NAME : {}", name)?;
+            writeln!(out, "TYPE : TOUR")?;
+            writeln!(out, "DIMENSION : {}", tour.len())?;
+            writeln!(out, "TOUR_SECTION")?;
+            for city in tour {
+                writeln!(out, "{}", city + 1)?;
+            }
+            writeln!(out, "-1")?;
+            writeln!(out, "EOF")?;
+        },
+        Format::Csv => {
+            writeln!(out, "index")?;
+            for city in tour {
+                writeln!(out, "{}", city)?;
+            }
+        },
+        #[cfg(feature = "serde")]
+        Format::Json => json::write_tour(&mut out, tour, name)?
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn parse_error(line: usize, message: String) -> Error {
+    Error::Parse {
+        line,
+        message
+    }
+}
+
+// Splits a `KEY : VALUE` specification line
+fn specification(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.splitn(2, ':');
+    let key = parts.next()?.trim();
+    let value = parts.next()?.trim();
+    Some((key, value))
+}
+
+fn read_tsplib_map<R: BufRead>(input: R) -> Result<Map> {
+    let mut dimension = None;
+    let mut points: Vec<Option<Point>> = Vec::new();
+    let mut in_coords = false;
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let line_number = number + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed == "EOF" {
+            break;
+        }
+
+        if in_coords {
+            let fields: Vec<&str> = trimmed.split_whitespace().collect();
+            if fields.len() == 3 {
+                let parsed = (fields[0].parse::<usize>(), fields[1].parse::<f32>(), fields[2].parse::<f32>());
+                if let (Ok(id), Ok(x), Ok(y)) = parsed {
+                    if !(x.is_finite() && y.is_finite()) {
+                        let message = format!("node {} has a coordinate that is not a finite number", id);
+                        return Err(parse_error(line_number, message));
+                    }
+                    match points.get_mut(id.wrapping_sub(1)) {
+                        Some(slot @ None) => *slot = Some(Point { x, y }),
+                        Some(Some(_)) => return Err(parse_error(line_number, format!("node {} is repeated", id))),
+                        None => return Err(parse_error(line_number, format!("node {} is out of range", id)))
+                    }
+                    continue;
+                }
+            }
+
+            // Any other line ends the section
+            in_coords = false;
+        }
+
+        if trimmed.ends_with("_SECTION") {
+            match trimmed {
+                "NODE_COORD_SECTION" => {
+                    let size = dimension.ok_or_else(|| {
+                        parse_error(line_number, "DIMENSION must come before NODE_COORD_SECTION".to_owned())
+                    })?;
+                    points = vec![None; size];
+                    in_coords = true;
+                },
+                "DISPLAY_DATA_SECTION" => {},
+                other => return Err(parse_error(line_number, format!("{} is not supported", other)))
+            }
+            continue;
+        }
+
+        let (key, value) = specification(trimmed)
+            .ok_or_else(|| parse_error(line_number, format!("expected a specification, found {:?}", trimmed)))?;
+        match key {
+            "DIMENSION" => {
+                let size = value.parse()
+                    .map_err(|error| parse_error(line_number, format!("invalid dimension: {}", error)))?;
+                if size > MAX_POINTS {
+                    let message = format!("dimension {} is over the limit of {} points", size, MAX_POINTS);
+                    return Err(parse_error(line_number, message));
+                }
+                dimension = Some(size);
+            },
+            "TYPE" if value != "TSP" => {
+                return Err(parse_error(line_number, format!("problem type {} is not supported", value)));
+            },
+            // Only coordinate based instances can be represented by a map
+            "EDGE_WEIGHT_TYPE" if value == "EXPLICIT" => {
+                return Err(parse_error(line_number, "explicit edge weights are not supported".to_owned()));
+            },
+            _ => {}
+        }
+    }
+
+    let size = points.len();
+    let points: Option<Vec<Point>> = points.into_iter().collect();
+    match points {
+        Some(points) if size > 0 => Map::try_from_points(points),
+        Some(_) => Err(parse_error(0, "the instance has no NODE_COORD_SECTION".to_owned())),
+        None => Err(parse_error(0, "NODE_COORD_SECTION is missing some nodes".to_owned()))
+    }
+}
+
+fn read_tsplib_tour<R: BufRead>(input: R) -> Result<Vec<usize>> {
+    let mut tour = Vec::new();
+    let mut in_tour = false;
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let line_number = number + 1;
+        let trimmed = line.trim();
+        if trimmed == "EOF" {
+            break;
+        }
+        if trimmed == "TOUR_SECTION" {
+            in_tour = true;
+            continue;
+        }
+        if !in_tour {
+            continue;
+        }
+
+        for field in trimmed.split_whitespace() {
+            let id: i64 = field.parse()
+                .map_err(|error| parse_error(line_number, format!("invalid node {:?}: {}", field, error)))?;
+            if id == -1 {
+                return Ok(tour);
+            }
+            if id < 1 {
+                return Err(parse_error(line_number, format!("node {} is out of range", id)));
+            }
+            tour.push(id as usize - 1);
+        }
+    }
+
+    if in_tour {
+        Ok(tour)
+    } else {
+        Err(parse_error(0, "the file has no TOUR_SECTION".to_owned()))
+    }
+}
+
+// Lines which fail to parse are allowed only as the first, header line
+fn read_csv_lines<R, T, F>(input: R, mut parse: F) -> Result<Vec<T>>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Option<T>
+{
+    let mut values = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        match parse(trimmed) {
+            Some(value) => values.push(value),
+            None if number == 0 => {},
+            None => return Err(parse_error(number + 1, format!("invalid row {:?}", trimmed)))
+        }
+    }
+
+    Ok(values)
+}
+
+fn read_csv_map<R: BufRead>(input: R) -> Result<Map> {
+    let points = read_csv_lines(input, |line| {
+        let mut fields = line.split(',').map(str::trim);
+        let x = fields.next()?.parse().ok()?;
+        let y = fields.next()?.parse().ok()?;
+        Some(Point { x, y })
+    })?;
+
+    Map::try_from_points(points)
+}
+
+fn read_csv_tour<R: BufRead>(input: R) -> Result<Vec<usize>> {
+    read_csv_lines(input, |line| line.split(',').next()?.trim().parse().ok())
+}
+
+#[cfg(feature = "serde")]
+mod json {
+    use serde::{Deserialize, Serialize};
+    use std::io::{BufRead, Write};
+
+    use crate::error::{Error, Result};
+    use crate::map::{Map, Point};
+
+    #[derive(Serialize, Deserialize)]
+    struct Instance {
+        #[serde(default)]
+        name: String,
+        points: Vec<Point>
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Tour {
+        #[serde(default)]
+        name: String,
+        tour: Vec<usize>
+    }
+
+    fn convert(error: serde_json::Error) -> Error {
+        if error.is_io() {
+            return Error::from(std::io::Error::from(error));
+        }
+
+        super::parse_error(error.line(), error.to_string())
+    }
+
+    pub fn read_map<R: BufRead>(input: R) -> Result<Map> {
+        let instance: Instance = serde_json::from_reader(input).map_err(convert)?;
+        Map::try_from_points(instance.points)
+    }
+
+    pub fn write_map<W: Write>(out: &mut W, map: &Map, name: &str) -> Result<()> {
+        let instance = Instance {
+            name: name.to_owned(),
+            points: map.clone_to_vec()
+        };
+        serde_json::to_writer(&mut *out, &instance).map_err(convert)?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    pub fn read_tour<R: BufRead>(input: R) -> Result<Vec<usize>> {
+        let tour: Tour = serde_json::from_reader(input).map_err(convert)?;
+        Ok(tour.tour)
+    }
+
+    pub fn write_tour<W: Write>(out: &mut W, tour: &[usize], name: &str) -> Result<()> {
+        let tour = Tour {
+            name: name.to_owned(),
+            tour: tour.to_vec()
+        };
+        serde_json::to_writer(&mut *out, &tour).map_err(convert)?;
+        writeln!(out)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use rand::prelude::*;
+use std::panic;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+use crate::context::SolverContext;
+use crate::error::Result;
+use crate::ga::{AnyRecombinator, Chromosome, GaParams, GaSolver, random_population, repair_neighbors, run_generations};
+use crate::map::Map;
+use crate::solve::{Budget, Objective, Reporter, SharedBest, SolveOptions, SolveResult, Solver, Termination, check_map};
+use crate::time::Instant;
+
+/// Which islands of an [`IslandGa`] send migrants to which.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// Every island sends to the next, and the last to the first. Good tours take the longest to spread, which keeps
+    /// the islands searching apart the longest.
+    Ring,
+    /// The first island is the hub: it sends to every other island, and every other island sends to it.
+    Star,
+    /// Every island sends to every other. Good tours reach every island at the next migration, so the islands soon
+    /// search the same region.
+    Complete,
+    /// Every island sends to `degree` others, chosen anew at every migration.
+    Random {
+        degree: usize
+    }
+}
+
+impl Topology {
+    // The islands that the given one of `count` sends to
+    fn destinations<R: Rng + ?Sized>(self, island: usize, count: usize, rng: &mut R) -> Vec<usize> {
+        match self {
+            _ if count < 2 => Vec::new(),
+            Topology::Ring => vec![(island + 1) % count],
+            Topology::Star if island == 0 => (1..count).collect(),
+            Topology::Star => vec![0],
+            Topology::Complete => (0..count).filter(|&other| other != island).collect(),
+            Topology::Random { degree } => {
+                let others: Vec<usize> = (0..count).filter(|&other| other != island).collect();
+                others.choose_multiple(rng, degree).copied().collect()
+            }
+        }
+    }
+}
+
+/// How an island of an [`IslandGa`] chooses the individuals it sends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrantSelection {
+    /// Its best individuals, which spread good tours the fastest
+    Best,
+    /// Individuals chosen uniformly at random, which carry the most diversity
+    Random,
+    /// For every migrant, the best of `size` individuals chosen uniformly at random, so that larger tournaments
+    /// favor better individuals
+    Tournament {
+        size: usize
+    }
+}
+
+impl MigrantSelection {
+    // Copies of `count` individuals of the population, or of all of them if it is smaller
+    fn select<C, R>(self, map: &Map, population: &[C], count: usize, objective: Objective, rng: &mut R) -> Vec<C>
+    where
+        C: Chromosome,
+        R: Rng + ?Sized
+    {
+        let count = count.min(population.len());
+        let losses: Vec<f32> = population.iter().map(|indv| objective.loss(indv.evaluate(map))).collect();
+        let better = |&a: &usize, &b: &usize| losses[a].total_cmp(&losses[b]);
+        let chosen: Vec<usize> = match self {
+            MigrantSelection::Best => {
+                let mut order: Vec<usize> = (0..population.len()).collect();
+                order.sort_by(better);
+                order.truncate(count);
+                order
+            },
+            MigrantSelection::Random => rand::seq::index::sample(rng, population.len(), count).into_vec(),
+            MigrantSelection::Tournament { size } => (0..count)
+                .map(|_| {
+                    // Infallible: a tournament has at least one entrant
+                    (0..size.max(1)).map(|_| rng.gen_range(0, population.len())).min_by(better).unwrap()
+                })
+                .collect()
+        };
+        chosen.into_iter().map(|index| population[index].clone()).collect()
+    }
+}
+
+/// The island model: several populations evolve apart with the roulette wheel GA of `ga`, exchanging a few
+/// individuals every so often, which keeps more diversity than one population of the same total size.
+///
+/// Every island runs `ga.generations` generations at a time. After each such epoch, every island chooses `migrants`
+/// of its individuals by `selection` and sends copies of them to the islands `topology` connects it to, where the
+/// best of the arrivals replace the worst individuals. The solve runs `epochs` epochs unless the time limit runs out
+/// or the token is cancelled first.
+///
+/// Islands are split across threads by [`parallelism`](SolveOptions::parallelism), and every island draws its own
+/// seed, so that a seeded solve evolves the same populations however many threads run it. The best tour of every
+/// island is shared, and reported if it improves, as soon as the island finishes an epoch. The iteration count is
+/// the number of generations run by all islands together.
+pub struct IslandGa<C> {
+    pub ga: GaSolver<C>,
+    pub islands: usize,
+    pub epochs: u32,
+    pub migrants: usize,
+    pub topology: Topology,
+    pub selection: MigrantSelection
+}
+
+impl<C> IslandGa<C> {
+    /// Four islands of the given GA in a ring, each sending its two best individuals to the next after every one of
+    /// 10 epochs.
+    pub fn new(ga: GaSolver<C>) -> Self {
+        IslandGa {
+            ga,
+            islands: 4,
+            epochs: 10,
+            migrants: 2,
+            topology: Topology::Ring,
+            selection: MigrantSelection::Best
+        }
+    }
+}
+
+// The state an island keeps between epochs
+struct Island<C> {
+    population: Vec<C>,
+    rng: StdRng,
+    budget: Budget
+}
+
+impl<C: Chromosome + Send> Solver for IslandGa<C> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        self.ga.settings.validate()?;
+        let objective = options.objective;
+        let mut rng = options.rng();
+        let count = self.islands.max(1);
+        let mut islands = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut island_rng = StdRng::seed_from_u64(rng.gen());
+            let population = random_population(map, self.ga.population, self.ga.new_chromosome, &mut island_rng);
+            population.iter().try_for_each(|indv| indv.validate(map))?;
+            islands.push(Island {
+                population,
+                rng: island_rng,
+                budget: Budget::new(options, start, 1)
+            });
+        }
+
+        let neighbors = repair_neighbors(map, self.ga.fix, SolverContext::of(options, map));
+        let threads = options.parallelism.threads().min(count).max(1);
+        let mut budget = Budget::new(options, start, 1);
+        let best = SharedBest::new();
+        let reporter = Mutex::new(Reporter::new(options, start).objective(objective));
+        let mut generations;
+        let mut epoch: u32 = 0;
+        // The first epoch always runs, so that there is a tour
+        loop {
+            let params = GaParams {
+                settings: &self.ga.settings,
+                recombinator: &self.ga.recombinator,
+                repair: neighbors.as_ref().into(),
+                objective,
+                first_generation: epoch.saturating_mul(self.ga.generations),
+                generations: (epoch + 1).saturating_mul(self.ga.generations)
+            };
+            generations = self.run_epoch(map, &mut islands, &params, &best, &reporter, threads);
+            epoch += 1;
+            let total = self.epochs.max(1).saturating_mul(self.ga.generations);
+            let reached = epoch.saturating_mul(self.ga.generations);
+            reporter.lock().unwrap_or_else(PoisonError::into_inner).generation(reached, total);
+            if epoch >= self.epochs || budget.exhausted() {
+                break;
+            }
+
+            self.migrate(map, &mut islands, objective, &mut rng);
+        }
+
+        let iterations = generations.iter().map(|&count| count as u64).sum();
+        let (_, tour) = best.into_inner();
+        let mut result = SolveResult::new(map, tour, iterations, start);
+        result.termination = islands
+            .iter()
+            .map(|island| island.budget.termination())
+            .chain([budget.termination()])
+            .find(|&termination| termination != Termination::Completed)
+            .unwrap_or(Termination::Completed);
+        Ok(result)
+    }
+}
+
+impl<C: Chromosome + Send> IslandGa<C> {
+    // Runs every island for the generations of the parameters, splitting them across threads, and offers the best
+    // tour of each as it finishes. Returns the generation every island reached.
+    fn run_epoch(
+        &self,
+        map: &Map,
+        islands: &mut [Island<C>],
+        params: &GaParams<'_, AnyRecombinator>,
+        best: &SharedBest,
+        reporter: &Mutex<Reporter<'_>>,
+        threads: usize
+    ) -> Vec<u32> {
+        let objective = params.objective;
+        let run = |island: &mut Island<C>| {
+            let mut silent = Reporter::silent();
+            let outcome =
+                run_generations(params, map, &mut island.population, &mut island.budget, &mut silent, &mut island.rng);
+            best.offer_with(outcome.loss, &outcome.tour, |loss, tour| {
+                // The reporter runs the progress callbacks, and one that panicked left it whole, so the other
+                // islands keep reporting rather than panic on the poisoned lock
+                let mut reporter = reporter.lock().unwrap_or_else(PoisonError::into_inner);
+                reporter.improved(objective.length(loss));
+                reporter.tour(tour);
+            });
+            outcome.generations
+        };
+
+        let chunk = islands.len().div_ceil(threads);
+        thread::scope(|scope| {
+            let run = &run;
+            let workers: Vec<_> = islands
+                .chunks_mut(chunk)
+                .map(|chunk| scope.spawn(move || chunk.iter_mut().map(run).collect::<Vec<_>>()))
+                .collect();
+            // A worker that panicked passes its panic on to the caller
+            workers.into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+                .collect()
+        })
+    }
+
+    // Sends every island's migrants along the topology, where the best of them replace the worst individuals
+    fn migrate<R: Rng + ?Sized>(&self, map: &Map, islands: &mut [Island<C>], objective: Objective, rng: &mut R) {
+        let count = islands.len();
+        let mut arrivals: Vec<Vec<C>> = vec![Vec::new(); count];
+        for (from, island) in islands.iter_mut().enumerate() {
+            let destinations = self.topology.destinations(from, count, rng);
+            if destinations.is_empty() {
+                continue;
+            }
+            let migrants = self.selection.select(map, &island.population, self.migrants, objective, &mut island.rng);
+            for to in destinations {
+                arrivals[to].extend(migrants.iter().cloned());
+            }
+        }
+
+        let by_loss = |a: f32, b: f32| a.total_cmp(&b);
+        for (island, arrived) in islands.iter_mut().zip(arrivals) {
+            let population = &mut island.population;
+            let mut arrived: Vec<(f32, C)> =
+                arrived.into_iter().map(|indv| (objective.loss(indv.evaluate(map)), indv)).collect();
+            arrived.sort_by(|a, b| by_loss(a.0, b.0));
+            let mut worst: Vec<(f32, usize)> = population
+                .iter()
+                .enumerate()
+                .map(|(index, indv)| (objective.loss(indv.evaluate(map)), index))
+                .collect();
+            worst.sort_by(|a, b| by_loss(b.0, a.0));
+            for ((_, index), (_, migrant)) in worst.into_iter().zip(arrived) {
+                population[index] = migrant;
+            }
+        }
+    }
+}
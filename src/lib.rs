@@ -0,0 +1,163 @@
+//! Solvers for the traveling salesman problem over points in the plane.
+//!
+//! - [`map`] holds the problem instance and the tour encodings used by the genetic algorithms, and converts points to
+//!   and from the types of the `geo` crates, and tours to their line strings, when built with the `geo` feature, and
+//!   to and from `glam` vectors and `nalgebra` points with the `glam` and `nalgebra` features. [`generate`] creates
+//!   random instances, drawing their points from a disc, square, ring, Gaussian mixture or any closure through the
+//!   [`PointDistribution`](generate::PointDistribution) trait. [`grid`] holds instances on an integer grid, whose
+//!   distances and lengths are computed exactly and so are the same on every platform. [`matrix`] precomputes the
+//!   distances between every pair of points, refusing to take more memory than its limit for the largest maps,
+//!   [`obstacles`] finds them around polygons for maps whose paths cannot run straight, [`dubins`] along the curves of
+//!   a vehicle with a minimum turning radius, and `simd` computes many distances at once when built with the `simd`
+//!   feature. `routing` asks a routing service such as OSRM for the travel times or road distances between places when
+//!   built with the `routing` feature. [`neighbors`] lists the nearest points to every point, or those of lowest
+//!   alpha-nearness from minimum 1-trees, the candidates for repairs and moves that should not scan the whole map, and
+//!   `delaunay` triangulates the points to add the few candidates the nearest miss when built with the `geometry`
+//!   feature. [`time_windows`] restricts when each point may be visited, making late tours cost more or rejecting
+//!   them, [`precedence`] which points must be visited before which, [`edges`] which edges a tour must or must not
+//!   use, such as those inside the locked segments of an earlier tour, and [`turns`] charges for the angle of every
+//!   turn. [`scenarios`] holds the costs of the edges under several scenarios, such as traffic, and charges tours
+//!   their mean or worst-case length over them.
+//! - [`solve`] contains exact searches and constructive heuristics, [`tours`] enumerates every tour lazily for
+//!   exhaustive searches of one's own and turns tours into a canonical direction, so that a tour and its reverse
+//!   compare equal, [`search_state`] keeps the path of a depth-first search with visits undone when their guards drop,
+//!   which bounded searches of one's own can build on as branch-and-bound does, and [`search_tree`] records the tree a
+//!   branch-and-bound explores on a small instance and draws it as a Graphviz DOT graph.
+//!   [`certificate`] logs every pruning decision of a branch-and-bound and replays them to check its claim of
+//!   optimality. [`explain`] records every decision of nearest neighbor, 2-opt and branch-and-bound, step by step, as
+//!   text or JSON Lines. `Solver::solve_stream` runs any solver in the background and yields its improving tours as
+//!   they are found, `Solver::solve_batch` solves many small maps side by side, and `SharedBest` holds the best tour
+//!   of parallel solvers, which every thread can read without locking.
+//! - [`context`] builds the distance matrix, candidate lists and minimum spanning tree of a map once, for every solver
+//!   run on it to share.
+//! - [`local`] improves existing tours with 2-opt, Or-opt, Lin-Kernighan, iterated local search and simulated
+//!   annealing, and finds and removes the crossing edges of Euclidean tours.
+//! - [`ga`] contains the genetic algorithm drivers and operators, with statistics on how often each operator improves
+//!   and adaptive selection of the productive ones, and an `Exchange` through which other threads inject individuals
+//!   into a running GA and read its population back, and `gpu` evaluates whole populations at once on a GPU when built
+//!   with the `gpu` feature.
+//!   [`frequencies`] counts how many tours of a population or an archive use every edge, for drawing heatmaps of
+//!   the edges a search has settled on and fixing them, which [`fixing`] does to shrink an instance before solving
+//!   what is left of it exactly or with Lin-Kernighan.
+//! - [`mtsp`] splits the points between several salesmen leaving from a shared depot, or from depots the GA
+//!   assigns them to, with a greedy construction and a GA. [`cvrp`] routes vehicles of limited capacity to points
+//!   with demands, with the savings construction and a GA that splits a single tour into routes. [`orienteering`]
+//!   collects as much prize as a route of limited length can, with a greedy insertion and a GA.
+//! - [`dynamic`] keeps the GA or an iterated local search running while points are added to the map, removed
+//!   from it or moved, carrying the current tours over to every new map, or re-solves warm after a few edits. Its
+//!   `OnlineTour` keeps a tour up to date by cheapest insertion as points stream in.
+//! - [`clustered`] finds tours that visit groups of points one group at a time, ordering the groups and then the
+//!   points within each, and [`gtsp`] tours that visit only one point of every group, with a GA that evolves the
+//!   order of the groups and the choice of points together, and a branch-and-bound over the groups.
+//! - [`endpoints`] finds the shortest open path between a chosen start and end, or from a chosen start or to a
+//!   chosen end, with any solver.
+//! - [`decompose`] splits instances too large to solve whole into clusters, solves each on its own and stitches
+//!   their paths together. [`segments`] splits tours into segments and merges segments back into a tour, choosing
+//!   their order and directions and solving the points around every junction exactly, for editing routes by hand.
+//! - [`multistart`] and [`tune`] run many GAs at once, either to find a better tour or better settings.
+//! - [`island`] evolves several GA populations apart, exchanging migrants between them along a ring, star, complete
+//!   or random topology.
+//! - [`portfolio`] runs the GA, simulated annealing and Lin-Kernighan at once, sharing the best tour between them.
+//! - [`registry`] looks solvers up by name, with the built-in ones under their command line names and room for
+//!   other crates to add their own.
+//! - [`bench`] compares solvers over instances with repeated runs, summarizing the lengths and times and testing
+//!   whether they differ significantly, or runs experiments over generated instances of several sizes that record
+//!   chosen metrics of every solve, and
+//!   [`report`] writes benchmark comparisons, or ones of results gathered elsewhere, as Markdown or CSV with the
+//!   wins and losses of every solver.
+//! - [`io`] reads and writes instances and tours in TSPLIB, CSV and JSON formats, and [`tsplib`] knows the optimal
+//!   lengths of the standard TSPLIB instances. `catalog` embeds generated instances with their optima, or their best
+//!   known tours and lower bounds, rated by difficulty, for regression tests and demos, when built with the
+//!   `catalog` feature. [`display`] formats maps and tours for debug dumps, with a fixed precision, a point per line
+//!   or as TSPLIB sections.
+//! - [`history`] records how the best and mean tour lengths evolve over a run, for plotting convergence, and when
+//!   each improvement was found, which `Solver::solve_with_history` keeps in the result for comparing solvers.
+//!   `telemetry` streams every progress event to a file as JSON Lines when built with the `serde` feature.
+//!   [`instrument`] counts the nodes, prunes, evaluations and crossovers of a solve and times its phases.
+//!   `parquet` writes benchmark cells, experiment tables and recorded telemetry as Parquet, for loading into
+//!   pandas or polars, when built with the `parquet` feature.
+//! - [`render`] draws instances and tours as SVG, and animates the tours a solve improves through, and `plot` draws
+//!   them and convergence charts as PNG when built with the `viz` feature. `live` serves a page that draws the best
+//!   tour of a running solve as it improves when built with the `live` feature.
+//! - [`testing`] has assertions, which panic on the first violation they find, for the invariants of tours,
+//!   chromosomes, distance functions and solver results, so that custom solvers and encodings can be tested, and
+//!   `CrossValidation` sweeps random small instances checking heuristics against an exact solver.
+//! - [`checkpoint`] saves long-running GA and branch-and-bound searches to disk so they can be resumed, and snapshots
+//!   GA runs every few generations, random number generator included, so that any of them can be replayed exactly.
+//!   With the `serde` feature, chromosomes and GA checkpoints serialize, for moving populations between machines.
+//! - `wasm`, `python` and `capi` expose the solvers to JavaScript, Python and C when built with the features of
+//!   the same name. [`time`] supplies the clock used for time limits, which in the browser is read through JavaScript.
+
+pub mod bench;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "catalog")]
+pub mod catalog;
+pub mod certificate;
+pub mod checkpoint;
+pub mod clustered;
+pub mod context;
+pub mod cvrp;
+pub mod decompose;
+#[cfg(feature = "geometry")]
+pub mod delaunay;
+pub mod display;
+pub mod dubins;
+pub mod dynamic;
+pub mod edges;
+pub mod endpoints;
+pub mod error;
+pub mod explain;
+pub mod fixing;
+pub mod frequencies;
+pub mod ga;
+pub mod generate;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod grid;
+pub mod gtsp;
+pub mod history;
+pub mod instrument;
+pub mod io;
+pub mod island;
+#[cfg(feature = "live")]
+pub mod live;
+pub mod local;
+pub mod map;
+pub mod matrix;
+pub mod mtsp;
+pub mod multistart;
+pub mod neighbors;
+pub mod obstacles;
+pub mod orienteering;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "viz")]
+pub mod plot;
+pub mod portfolio;
+pub mod precedence;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod registry;
+pub mod render;
+pub mod report;
+#[cfg(feature = "routing")]
+pub mod routing;
+pub mod scenarios;
+pub mod search_state;
+pub mod search_tree;
+pub mod segments;
+#[cfg(feature = "simd")]
+pub mod simd;
+pub mod solve;
+#[cfg(feature = "serde")]
+pub mod telemetry;
+pub mod testing;
+pub mod time;
+pub mod time_windows;
+pub mod tours;
+pub mod tsplib;
+pub mod tune;
+pub mod turns;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,345 @@
+use serde_json::json;
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use tiny_http::{Header, ReadWrite, Request, Response, Server, StatusCode};
+
+use crate::error::{Error, Result};
+use crate::map::Map;
+use crate::solve::{Progress, ProgressEvent};
+use crate::time::Instant;
+
+// The page served at the root, which connects back to the feed and draws what it receives
+const PAGE: &str = include_str!("live.html");
+
+// Appended to a client's key before hashing it to accept the connection, as RFC 6455 prescribes
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// Stats are sent at most this often, since the GA reports every generation
+const STATS_INTERVAL: Duration = Duration::from_millis(100);
+
+// The messages waiting to be handed out to the clients, beyond which new ones are skipped
+const FEED_QUEUE: usize = 256;
+
+// The messages waiting to be written to one client, beyond which it is dropped as too slow
+const CLIENT_QUEUE: usize = 64;
+
+type Client = Box<dyn ReadWrite + Send>;
+
+// What a client that connects late is sent first: the points and the latest tour
+struct Latest {
+    map: String,
+    tour: Option<String>
+}
+
+// The stats of the run so far, sent together
+struct Stats {
+    best: Option<f32>,
+    generation: Option<(u32, u32)>,
+    nodes: Option<u64>,
+    completion: Option<f32>,
+    sent: Option<Instant>
+}
+
+/// Serves a page at `http://address/` which draws the best tour of a running solve as it improves, when attached
+/// as the solve's progress observer. The page receives the points, every new best tour and the stats of the run
+/// as JSON messages over a WebSocket at `/feed`, which other clients can read as well: a `map` message with the
+/// `points` as `[x, y]` pairs when they connect, then `tour` messages with the `tour`, its `length` and the
+/// `seconds` since the server started, and `stats` messages with the `best` length and, as the solver reports
+/// them, the `generation` and `generations`, the `nodes` and the `completion`. Only solvers that report their
+/// tours, which are the genetic algorithms, send tours.
+///
+/// The server stops when dropped. Messages are queued and written to every client from a thread of its own, so
+/// slow clients do not slow the solve down: messages that come faster than they can be handed out are skipped, and
+/// a client that falls too far behind is dropped.
+pub struct LiveServer {
+    server: Arc<Server>,
+    latest: Arc<Mutex<Latest>>,
+    stats: Mutex<Stats>,
+    // Taken when the server stops, which closes the channel and so stops the broadcasting thread
+    sender: Mutex<Option<SyncSender<String>>>,
+    start: Instant,
+    threads: Vec<JoinHandle<()>>
+}
+
+impl LiveServer {
+    /// Starts listening on the address, such as `127.0.0.1:8080`, for a solve of the map.
+    pub fn start(address: &str, map: &Map) -> Result<Self> {
+        let server = Server::http(address).map_err(|error| Error::Io {
+            kind: std::io::ErrorKind::AddrNotAvailable,
+            message: error.to_string()
+        })?;
+        let server = Arc::new(server);
+        let points: Vec<[f32; 2]> = map.iter().map(|point| [point.x, point.y]).collect();
+        let latest = Arc::new(Mutex::new(Latest {
+            map: json!({ "type": "map", "points": points }).to_string(),
+            tour: None
+        }));
+        let clients: Arc<Mutex<Vec<SyncSender<String>>>> = Arc::default();
+        let (sender, receiver) = mpsc::sync_channel(FEED_QUEUE);
+
+        let accept = {
+            let (server, latest, clients) = (Arc::clone(&server), Arc::clone(&latest), Arc::clone(&clients));
+            thread::spawn(move || accept(&server, &latest, &clients))
+        };
+        let broadcast = thread::spawn(move || broadcast(&receiver, &clients));
+
+        Ok(LiveServer {
+            server,
+            latest,
+            stats: Mutex::new(Stats {
+                best: None,
+                generation: None,
+                nodes: None,
+                completion: None,
+                sent: None
+            }),
+            sender: Mutex::new(Some(sender)),
+            start: Instant::now(),
+            threads: vec![accept, broadcast]
+        })
+    }
+
+    /// The address the server listens on, which has the port chosen by the system if the address asked for port
+    /// zero.
+    pub fn address(&self) -> String {
+        self.server.server_addr().to_string()
+    }
+
+    fn send(&self, message: String) {
+        if let Some(sender) = &*self.sender.lock().unwrap() {
+            // A full queue skips the message rather than wait, and a closed one means the broadcasting thread is
+            // gone, and with it every client
+            sender.try_send(message).ok();
+        }
+    }
+
+    // Sends the stats unless they were sent too recently, or regardless if forced
+    fn send_stats(&self, stats: &mut Stats, force: bool) {
+        if !force && stats.sent.is_some_and(|sent| sent.elapsed() < STATS_INTERVAL) {
+            return;
+        }
+        stats.sent = Some(Instant::now());
+
+        let mut message = json!({ "type": "stats", "seconds": self.start.elapsed().as_secs_f64() });
+        if let Some(best) = stats.best {
+            message["best"] = json!(best);
+        }
+        if let Some((generation, generations)) = stats.generation {
+            message["generation"] = json!(generation);
+            message["generations"] = json!(generations);
+        }
+        if let Some(nodes) = stats.nodes {
+            message["nodes"] = json!(nodes);
+        }
+        if let Some(completion) = stats.completion {
+            message["completion"] = json!(completion);
+        }
+        self.send(message.to_string());
+    }
+}
+
+impl Progress for LiveServer {
+    fn report(&self, event: ProgressEvent) {
+        let mut stats = self.stats.lock().unwrap();
+        let force = match event {
+            ProgressEvent::Improvement { length, .. } => {
+                stats.best = Some(length);
+                true
+            },
+            ProgressEvent::Generation { generation, generations } => {
+                stats.generation = Some((generation, generations));
+                generation == generations
+            },
+            ProgressEvent::Nodes(nodes) => {
+                stats.nodes = Some(nodes);
+                false
+            },
+            ProgressEvent::Completion(completion) => {
+                stats.completion = Some(completion);
+                completion >= 1.0
+            },
+            ProgressEvent::Sample { .. } | ProgressEvent::Diversity(_) => return
+        };
+        self.send_stats(&mut stats, force);
+    }
+
+    fn tour(&self, tour: &[usize]) {
+        let length = self.stats.lock().unwrap().best;
+        let message = json!({
+            "type": "tour",
+            "tour": tour,
+            "length": length,
+            "seconds": self.start.elapsed().as_secs_f64()
+        })
+        .to_string();
+
+        self.latest.lock().unwrap().tour = Some(message.clone());
+        self.send(message);
+    }
+
+    fn detailed(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for LiveServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        self.sender.lock().unwrap().take();
+        for thread in self.threads.drain(..) {
+            thread.join().ok();
+        }
+    }
+}
+
+// Answers requests until the server is unblocked: the page at the root, and the feed at `/feed`
+fn accept(server: &Server, latest: &Mutex<Latest>, clients: &Mutex<Vec<SyncSender<String>>>) {
+    for request in server.incoming_requests() {
+        let path = request.url().split('?').next().unwrap_or("").to_owned();
+        let key = request.headers()
+            .iter()
+            .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+            .map(|header| header.value.as_str().to_owned());
+        match (path.as_str(), key) {
+            ("/feed", Some(key)) => {
+                let client = upgrade(request, &key);
+                // The snapshot is copied out, so that the solve can replace it while the client is written to
+                let (map, tour) = {
+                    let latest = latest.lock().unwrap();
+                    (latest.map.clone(), latest.tour.clone())
+                };
+                let (sender, queue) = mpsc::sync_channel(CLIENT_QUEUE);
+                // Infallible: the queue has room for the snapshot and its writer has not started
+                sender.try_send(map).unwrap();
+                if let Some(tour) = tour {
+                    sender.try_send(tour).unwrap();
+                }
+                thread::spawn(move || write_client(client, &queue));
+                clients.lock().unwrap().push(sender);
+            },
+            ("/", _) => {
+                // Infallible: the header is a constant
+                let header = Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap();
+                request.respond(Response::from_string(PAGE).with_header(header)).ok();
+            },
+            _ => {
+                request.respond(Response::from_string("not found").with_status_code(404)).ok();
+            }
+        }
+    }
+}
+
+// Queues every message for every client until the channel closes, without waiting on any of them, dropping the
+// clients whose queues are full or whose writers have stopped
+fn broadcast(receiver: &Receiver<String>, clients: &Mutex<Vec<SyncSender<String>>>) {
+    for message in receiver {
+        clients.lock().unwrap().retain(|client| client.try_send(message.clone()).is_ok());
+    }
+}
+
+// Writes the messages queued for one client until the client is dropped or cannot be written to. A client that
+// stops reading keeps its thread waiting on the write until the connection fails, but nothing else waits on it
+fn write_client(mut client: Client, queue: &Receiver<String>) {
+    for message in queue {
+        if write_frame(&mut client, &message).is_err() {
+            break;
+        }
+    }
+}
+
+// Completes the WebSocket handshake
+fn upgrade(request: Request, key: &str) -> Client {
+    let accept = base64(&sha1(format!("{}{}", key.trim(), HANDSHAKE_GUID).as_bytes()));
+    // Infallible: the headers are constants or base64
+    let header = |field: &str, value: &str| Header::from_bytes(field, value).unwrap();
+    let response = Response::empty(StatusCode(101))
+        .with_header(header("Upgrade", "websocket"))
+        .with_header(header("Connection", "Upgrade"))
+        .with_header(header("Sec-WebSocket-Accept", &accept));
+    request.upgrade("websocket", response)
+}
+
+// Writes the text as a single unmasked WebSocket text frame
+fn write_frame(client: &mut Client, text: &str) -> std::io::Result<()> {
+    let length = text.len();
+    let mut frame = Vec::with_capacity(length + 10);
+    frame.push(0x81);
+    if length < 126 {
+        frame.push(length as u8);
+    } else if length <= u16::MAX as usize {
+        frame.push(126);
+        frame.extend_from_slice(&(length as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(length as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(text.as_bytes());
+    client.write_all(&frame)?;
+    client.flush()
+}
+
+// SHA-1, which the handshake needs however weak it is
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, &word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6)
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
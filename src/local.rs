@@ -0,0 +1,1058 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use std::collections::BTreeMap;
+use std::iter;
+use std::thread;
+
+use crate::context::SolverContext;
+use crate::error::Result;
+use crate::explain::Step;
+use crate::map::{Map, Path, Point, check_permutation};
+use crate::neighbors::{Candidates, Neighbors};
+use crate::solve::{
+    Budget, Objective, Reporter, SolveOptions, SolveResult, Solver, check_map, known_bound, nearest_neighbor
+};
+use crate::time::Instant;
+
+// Moves must shorten the path by more than this fraction of its length to count as improvements, so that
+// floating point noise cannot make the search cycle
+const TOLERANCE: f32 = 1e-6;
+
+// The number of nearest neighbors considered when choosing the next edge in Lin-Kernighan
+pub(crate) const NEIGHBORS: usize = 10;
+
+// Below this many points a 2-opt scan is too quick to be worth splitting across threads
+const PARALLEL_TWO_OPT_SIZE: usize = 1000;
+
+// The most consecutive points Or-opt moves at once
+const OR_OPT_SEGMENT: usize = 3;
+
+// The longest chain of flips Lin-Kernighan tries before giving up on a starting point
+const MAX_DEPTH: usize = 50;
+
+// Local search counts every forbidden edge a tour uses as this much extra cost, so that moves which take them out
+// of an infeasible tour count as improvements
+const FORBIDDEN_PENALTY: f32 = 1e4;
+
+#[inline]
+fn dist(map: &Map, a: usize, b: usize) -> f32 {
+    map.dist(a, b)
+}
+
+// The change in length from reversing `tour[i..=j]`, with the change in the cost of turning on a map with a turn
+// penalty. The path is open, so reversing a prefix or suffix only replaces a single edge.
+fn reversal_delta(map: &Map, tour: &[usize], i: usize, j: usize) -> f32 {
+    let mut delta = map.turn_penalty().map_or(0.0, |turns| turns.reversal_delta(map, tour, i, j));
+    if i > 0 {
+        delta += dist(map, tour[i - 1], tour[j]) - dist(map, tour[i - 1], tour[i]);
+    }
+    if j + 1 < tour.len() {
+        delta += dist(map, tour[i], tour[j + 1]) - dist(map, tour[j], tour[j + 1]);
+    }
+    delta
+}
+
+// The penalty for the forbidden edges among the given ones
+fn forbidden_penalty<I: IntoIterator<Item = (usize, usize)>>(map: &Map, edges: I) -> f32 {
+    match map.edge_constraints() {
+        Some(constraints) => {
+            let count = edges.into_iter().filter(|&(a, b)| constraints.is_forbidden(a, b)).count();
+            FORBIDDEN_PENALTY * count as f32
+        },
+        None => 0.0
+    }
+}
+
+// The change in search cost from reversing `tour[i..=j]` on a map without time windows
+fn reversal_loss(map: &Map, tour: &[usize], i: usize, j: usize, objective: Objective) -> f32 {
+    let mut loss = objective.loss(reversal_delta(map, tour, i, j));
+    if map.edge_constraints().is_some() {
+        let (before, after) = (i.checked_sub(1).map(|k| tour[k]), tour.get(j + 1).copied());
+        let added = before.map(|a| (a, tour[j])).into_iter().chain(after.map(|b| (tour[i], b)));
+        let removed = before.map(|a| (a, tour[i])).into_iter().chain(after.map(|b| (tour[j], b)));
+        loss += forbidden_penalty(map, added) - forbidden_penalty(map, removed);
+    }
+    loss
+}
+
+// Whether reversing `tour[i..=j]` keeps the fixed edges of the map the tour uses and joins none of its forbidden
+// ones
+#[inline]
+fn reversal_allowed(map: &Map, tour: &[usize], i: usize, j: usize) -> bool {
+    map.edge_constraints().is_none_or(|edges| edges.allows_reversal(tour, i, j))
+}
+
+// The nearest neighbor tour, with the chains of fixed edges the map may have pulled in whole
+pub(crate) fn initial_tour(map: &Map) -> Result<Vec<usize>> {
+    let tour = nearest_neighbor(map)?.tour;
+    Ok(match map.edge_constraints() {
+        Some(edges) => edges.repair(&tour),
+        None => tour
+    })
+}
+
+// The cost local search minimizes: the loss of the length, or with time windows of the cost of the schedule, where
+// lateness that would be rejected is penalized instead so that moves can still make a late tour less late. Any
+// turns are charged and forbidden edges penalized on top.
+fn search_cost(map: &Map, tour: &[usize], objective: Objective) -> f32 {
+    let loss = objective.loss(match map.time_windows() {
+        Some(windows) => {
+            let turns = map.turn_penalty().map_or(0.0, |turns| turns.evaluate(map, tour));
+            windows.search_cost(&windows.schedule(map, tour)) + turns
+        },
+        None => tour.evaluate(map)
+    });
+    loss + forbidden_penalty(map, path_edges(tour))
+}
+
+// How much a move must lower the search cost by to count, measured without the penalties for forbidden edges,
+// which would otherwise swamp the length
+fn tolerance(map: &Map, tour: &[usize], cost: f32) -> f32 {
+    (cost - forbidden_penalty(map, path_edges(tour))).abs() * TOLERANCE
+}
+
+fn path_edges(tour: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    tour.windows(2).map(|edge| (edge[0], edge[1]))
+}
+
+// The change in search cost from reversing `tour[i..=j]`. With time windows, a reversal changes when every later
+// point is served, so the reversed tour is scheduled again in full. The tour is left as it was.
+fn move_delta(map: &Map, tour: &mut [usize], i: usize, j: usize, cost: f32, objective: Objective) -> f32 {
+    if map.time_windows().is_none() {
+        return reversal_loss(map, tour, i, j, objective);
+    }
+
+    tour[i..=j].reverse();
+    let delta = search_cost(map, tour, objective) - cost;
+    tour[i..=j].reverse();
+    delta
+}
+
+/// Applies improving 2-opt moves until none remain or the budget runs out, returning the number of moves made.
+/// Reversing `tour[i..=j]` is only considered when `allowed(tour, i, j)` holds and the map's edge constraints
+/// allow it.
+pub(crate) fn two_opt_in_place<F>(
+    map: &Map,
+    tour: &mut [usize],
+    objective: Objective,
+    allowed: F,
+    budget: &mut Budget,
+    reporter: &mut Reporter<'_>
+) -> u64
+where
+    F: Fn(&[usize], usize, usize) -> bool
+{
+    two_opt_steps(map, tour, objective, allowed, budget, reporter, None)
+}
+
+// 2-opt, recording every reversal and the end of every pass over the tour in the steps if there are any
+pub(crate) fn two_opt_steps<F>(
+    map: &Map,
+    tour: &mut [usize],
+    objective: Objective,
+    allowed: F,
+    budget: &mut Budget,
+    reporter: &mut Reporter<'_>,
+    mut steps: Option<&mut Vec<Step>>
+) -> u64
+where
+    F: Fn(&[usize], usize, usize) -> bool
+{
+    let mut length = search_cost(map, tour, objective);
+    let tolerance = tolerance(map, tour, length);
+    let mut moves = 0;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        let before = moves;
+        for i in 0..tour.len().saturating_sub(1) {
+            if budget.exhausted() {
+                return moves;
+            }
+
+            for j in i + 1..tour.len() {
+                if !allowed(tour, i, j) || !reversal_allowed(map, tour, i, j) {
+                    continue;
+                }
+
+                let delta = move_delta(map, tour, i, j, length, objective);
+                budget.evaluated(1);
+                if delta < -tolerance {
+                    if let Some(steps) = steps.as_deref_mut() {
+                        steps.push(Step::Reverse {
+                            i,
+                            j,
+                            first: tour[i],
+                            last: tour[j],
+                            change: objective.length(length + delta) - objective.length(length),
+                            length: objective.length(length + delta)
+                        });
+                    }
+                    tour[i..=j].reverse();
+                    length += delta;
+                    moves += 1;
+                    improved = true;
+                    reporter.improved(objective.length(length));
+                }
+            }
+        }
+        if let Some(steps) = steps.as_deref_mut() {
+            steps.push(Step::Pass {
+                moves: moves - before,
+                length: objective.length(length)
+            });
+        }
+    }
+
+    moves
+}
+
+// Scans for the best reversal starting at every position, splitting the positions across threads, and then
+// applies the best reversals that are independent of each other, until none improves the tour. The budget is only
+// checked between scans.
+fn parallel_two_opt_in_place(
+    map: &Map,
+    tour: &mut [usize],
+    objective: Objective,
+    budget: &mut Budget,
+    reporter: &mut Reporter<'_>,
+    threads: usize
+) -> u64 {
+    let mut length = search_cost(map, tour, objective);
+    let tolerance = tolerance(map, tour, length);
+    let n = tour.len();
+    let mut moves = 0;
+    while !budget.exhausted() {
+        let scanned: &[usize] = tour;
+        let mut candidates: Vec<(f32, usize, usize)> = thread::scope(|scope| {
+            // Rows get shorter as `i` grows, so interleave them to give every thread a similar share
+            let workers: Vec<_> = (0..threads)
+                .map(|first| scope.spawn(move || {
+                    (first..n.saturating_sub(1))
+                        .step_by(threads)
+                        .filter_map(|i| {
+                            (i + 1..n)
+                                .filter(|&j| reversal_allowed(map, scanned, i, j))
+                                .map(|j| (reversal_loss(map, scanned, i, j, objective), i, j))
+                                .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+                        })
+                        .filter(|&(delta, ..)| delta < -tolerance)
+                        .collect::<Vec<_>>()
+                }))
+                .collect();
+
+            // Infallible: the scan does not panic
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+        budget.evaluated((n * n.saturating_sub(1) / 2) as u64);
+        if candidates.is_empty() {
+            break;
+        }
+
+        // Reversing `tour[i..=j]` reads positions `i - 1` to `j + 1` and moves `i` to `j`, so two reversals are
+        // independent, and their deltas still exact after both are applied, when the ranges `i..=j + 1` of the two
+        // are disjoint
+        candidates.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+        let mut applied: BTreeMap<usize, usize> = BTreeMap::new();
+        for (delta, i, j) in candidates {
+            let overlaps_before = applied.range(..=i).next_back().is_some_and(|(_, &end)| end >= i);
+            let overlaps_after = applied.range(i..=j + 1).next().is_some();
+            if overlaps_before || overlaps_after {
+                continue;
+            }
+
+            applied.insert(i, j + 1);
+            tour[i..=j].reverse();
+            length += delta;
+            moves += 1;
+        }
+        reporter.improved(objective.length(length));
+    }
+
+    moves
+}
+
+/// Improves the tour by reversing segments of it while doing so shortens it, or lengthens it when
+/// [maximizing](Objective::Maximize), until it is 2-optimal. The iteration count is the number of reversals made.
+///
+/// With [`parallelism`](SolveOptions::parallelism), large tours are scanned on several threads, and every scan
+/// applies all the best reversals that do not interfere with each other rather than each improvement as soon as
+/// it is found, so the search can end at a different 2-optimal tour.
+///
+/// On a map with [time windows](Map::with_time_windows), reversals must lower the cost of the schedule instead,
+/// and every candidate is scheduled in full, so each scan is slower by a factor of the tour's length and is never
+/// split across threads. When late tours are rejected, lateness is penalized heavily during the search, so that
+/// it can reach a feasible tour from an infeasible one.
+///
+/// On a map with a [turn penalty](Map::with_turn_penalty), reversals must lower the length and the cost of turning
+/// together. A reversal only changes the turns at its ends and just outside them, so this costs no more than the
+/// length alone, but scans are never split across threads, since reversals close to each other change the same
+/// turns.
+///
+/// On a map with [edge constraints](Map::with_edge_constraints), reversals that would break a fixed edge the tour
+/// uses or join a forbidden one are skipped, so a tour that satisfies the constraints keeps satisfying them.
+/// Forbidden edges the tour starts with are penalized heavily, so that reversals taking them out count as
+/// improvements.
+pub fn two_opt(map: &Map, tour: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_permutation(map, tour)?;
+
+    let mut budget = Budget::new(options, start, 1);
+    let objective = options.objective;
+    let mut reporter = Reporter::new(options, start).objective(objective);
+    let mut tour = tour.to_vec();
+    reporter.improved(objective.length(search_cost(map, &tour, objective)));
+    let threads = options.parallelism.threads();
+    // Reversals next to each other change the same turns, so their turn deltas are not independent
+    let independent = map.time_windows().is_none() && map.turn_penalty().is_none();
+    let moves = if threads > 1 && tour.len() >= PARALLEL_TWO_OPT_SIZE && independent {
+        parallel_two_opt_in_place(map, &mut tour, objective, &mut budget, &mut reporter, threads)
+    } else {
+        two_opt_in_place(map, &mut tour, objective, |_, _, _| true, &mut budget, &mut reporter)
+    };
+
+    let mut result = SolveResult::new(map, tour, moves, start);
+    result.evaluations = budget.evaluations();
+    result.termination = budget.termination();
+    Ok(result)
+}
+
+/// 2-opt local search from the nearest neighbor tour, with any chains of fixed edges pulled in whole. See
+/// [`two_opt`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TwoOpt;
+
+impl Solver for TwoOpt {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        check_map(map)?;
+        two_opt(map, &initial_tour(map)?, options)
+    }
+}
+
+// Twice the signed area of the triangle `a`, `b`, `c`: positive when `c` is to the left of the line from `a` to
+// `b`, negative when it is to the right and zero when the three are collinear
+fn orientation(a: Point, b: Point, c: Point) -> f32 {
+    let (u, v) = (b - a, c - a);
+    u.x * v.y - u.y * v.x
+}
+
+// Whether the segments from `a` to `b` and from `c` to `d` cross at a point inside both. Segments that only touch
+// or run along the same line do not count.
+fn segments_cross(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let opposite = |p: f32, q: f32| (p > 0.0 && q < 0.0) || (p < 0.0 && q > 0.0);
+    opposite(orientation(a, b, c), orientation(a, b, d)) && opposite(orientation(c, d, a), orientation(c, d, b))
+}
+
+// Whether the edge from `tour[i]` to `tour[i + 1]` crosses the one from `tour[j]` to `tour[j + 1]`
+#[inline]
+fn edges_cross(map: &Map, tour: &[usize], i: usize, j: usize) -> bool {
+    segments_cross(map[tour[i]], map[tour[i + 1]], map[tour[j]], map[tour[j + 1]])
+}
+
+/// The pairs of edges of the tour that cross, each as the positions `(i, j)` with `i < j` of the points the two
+/// edges leave from, so that the edge from `tour[i]` to `tour[i + 1]` crosses the one from `tour[j]` to
+/// `tour[j + 1]`. Edges that share a point never count, nor do ones that only touch or overlap along a line.
+///
+/// A Euclidean tour with a crossing is never optimal: reversing the points between the two edges uncrosses them
+/// and, by the triangle inequality, shortens the tour. Crossings are found from the positions of the points, so on
+/// a map with [attached distances](Map::with_distances) or [costs](Map::with_costs) they say nothing about the
+/// length. Every pair of edges is tested, which takes time quadratic in the length of the tour.
+pub fn crossings(map: &Map, tour: &[usize]) -> Vec<(usize, usize)> {
+    let edges = tour.len().saturating_sub(1);
+    let mut crossings = Vec::new();
+    for i in 0..edges {
+        for j in i + 2..edges {
+            if edges_cross(map, tour, i, j) {
+                crossings.push((i, j));
+            }
+        }
+    }
+    crossings
+}
+
+/// Removes the [`crossings`] from the tour, uncrossing each pair of edges by reversing the points between
+/// them, until none is left or the budget runs out. This is the subset of [`two_opt`] that only tries the
+/// reversals whose improvement is plain to see, which makes it a cheap clean-up for a tour from a construction or
+/// a short GA run. The iteration count is the number of reversals made.
+///
+/// Reversals are made only when they lower the cost [`two_opt`] minimizes and keep the map's edge constraints, so
+/// on a map that is not Euclidean, or with time windows, turn penalties or when
+/// [maximizing](Objective::Maximize), some crossings can be left in place.
+pub fn uncross(map: &Map, tour: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_permutation(map, tour)?;
+
+    let mut budget = Budget::new(options, start, 1);
+    let objective = options.objective;
+    let mut reporter = Reporter::new(options, start).objective(objective);
+    let mut tour = tour.to_vec();
+    let mut length = search_cost(map, &tour, objective);
+    let tolerance = tolerance(map, &tour, length);
+    reporter.improved(objective.length(length));
+
+    let mut moves = 0;
+    let mut improved = true;
+    while improved && !budget.exhausted() {
+        improved = false;
+        for (i, j) in crossings(map, &tour) {
+            // An earlier reversal in this pass can have moved the edges, so check that they still cross
+            if !edges_cross(map, &tour, i, j) || !reversal_allowed(map, &tour, i + 1, j) {
+                continue;
+            }
+
+            let delta = move_delta(map, &mut tour, i + 1, j, length, objective);
+            if delta < -tolerance {
+                tour[i + 1..=j].reverse();
+                length += delta;
+                moves += 1;
+                improved = true;
+                reporter.improved(objective.length(length));
+            }
+        }
+    }
+
+    let mut result = SolveResult::new(map, tour, moves, start);
+    result.termination = budget.termination();
+    Ok(result)
+}
+
+// Whether swapping the blocks `tour[a..m]` and `tour[m..=e]` keeps the fixed edges of the map the tour uses and
+// joins none of its forbidden ones
+fn shift_allowed(map: &Map, tour: &[usize], a: usize, m: usize, e: usize) -> bool {
+    let edges = match map.edge_constraints() {
+        Some(edges) => edges,
+        None => return true
+    };
+    let before = a == 0 || !(edges.is_fixed(tour[a - 1], tour[a]) || edges.is_forbidden(tour[a - 1], tour[m]));
+    let after = e + 1 == tour.len()
+        || !(edges.is_fixed(tour[e], tour[e + 1]) || edges.is_forbidden(tour[m - 1], tour[e + 1]));
+    before && after && !edges.is_fixed(tour[m - 1], tour[m]) && !edges.is_forbidden(tour[e], tour[a])
+}
+
+// The change in search cost from swapping the blocks `tour[a..m]` and `tour[m..=e]`. Only the three edges at the
+// ends of the blocks change, unless turns or time windows make the cost depend on more, in which case the swapped
+// tour is evaluated in full. The tour is left as it was.
+fn shift_delta(map: &Map, tour: &mut [usize], a: usize, m: usize, e: usize, cost: f32, objective: Objective) -> f32 {
+    if map.time_windows().is_some() || map.turn_penalty().is_some() {
+        tour[a..=e].rotate_left(m - a);
+        let delta = search_cost(map, tour, objective) - cost;
+        tour[a..=e].rotate_right(m - a);
+        return delta;
+    }
+
+    let (before, after) = (a.checked_sub(1).map(|k| tour[k]), tour.get(e + 1).copied());
+    let added: Vec<(usize, usize)> = before.map(|p| (p, tour[m])).into_iter()
+        .chain([(tour[e], tour[a])])
+        .chain(after.map(|q| (tour[m - 1], q)))
+        .collect();
+    let removed: Vec<(usize, usize)> = before.map(|p| (p, tour[a])).into_iter()
+        .chain([(tour[m - 1], tour[m])])
+        .chain(after.map(|q| (tour[e], q)))
+        .collect();
+    let length = |edges: &[(usize, usize)]| edges.iter().map(|&(p, q)| dist(map, p, q)).sum::<f32>();
+    objective.loss(length(&added) - length(&removed)) + forbidden_penalty(map, added)
+        - forbidden_penalty(map, removed)
+}
+
+/// Improves the tour by moving segments of one to three consecutive points to wherever else in the tour makes it
+/// shortest, or longest when [maximizing](Objective::Maximize), until no such move helps. Segments keep their
+/// direction, which complements [`two_opt`]: a point or short run that is out of place often cannot be moved by
+/// any single reversal. The iteration count is the number of segments moved.
+///
+/// Every scan tries each segment at every position, which takes time quadratic in the length of the tour. As with
+/// [`two_opt`], moves lower the cost of the schedule on a map with [time windows](Map::with_time_windows), and the
+/// cost of turning is counted on a map with a [turn penalty](Map::with_turn_penalty), in both cases by evaluating
+/// the moved tour in full. Moves that would break a fixed edge the tour uses or join a forbidden one are skipped.
+pub fn or_opt(map: &Map, tour: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_permutation(map, tour)?;
+
+    let mut budget = Budget::new(options, start, 1);
+    let objective = options.objective;
+    let mut reporter = Reporter::new(options, start).objective(objective);
+    let mut tour = tour.to_vec();
+    let mut length = search_cost(map, &tour, objective);
+    let tolerance = tolerance(map, &tour, length);
+    reporter.improved(objective.length(length));
+
+    let n = tour.len();
+    let mut moves = 0;
+    let mut improved = true;
+    while improved && !budget.exhausted() {
+        improved = false;
+        for first in 0..n {
+            if budget.exhausted() {
+                break;
+            }
+
+            for count in 1..=OR_OPT_SEGMENT.min(n - first) {
+                let end = first + count;
+                // The segment is the second block when moving it back and the first when moving it forward
+                let back = (0..first).map(|a| (a, first, end - 1));
+                let forward = (end..n).map(|e| (first, end, e));
+                let mut best: Option<(f32, usize, usize, usize)> = None;
+                for (a, m, e) in back.chain(forward) {
+                    if !shift_allowed(map, &tour, a, m, e) {
+                        continue;
+                    }
+                    let delta = shift_delta(map, &mut tour, a, m, e, length, objective);
+                    if delta < -tolerance && best.is_none_or(|(least, ..)| delta < least) {
+                        best = Some((delta, a, m, e));
+                    }
+                }
+                if let Some((delta, a, m, e)) = best {
+                    tour[a..=e].rotate_left(m - a);
+                    length += delta;
+                    moves += 1;
+                    improved = true;
+                    reporter.improved(objective.length(length));
+                    // The segment is somewhere else now, so move on to whatever took its place
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut result = SolveResult::new(map, tour, moves, start);
+    result.termination = budget.termination();
+    Ok(result)
+}
+
+// A closed tour through the map's points and a depot at distance zero from all of them. The best tour through
+// the depot is the best open path once the depot is removed, so moves made for closed tours can be used as is.
+// Distances are the losses of the objective, so that when maximizing the moves that shorten the cycle lengthen
+// the path.
+struct Cycle<'a> {
+    map: &'a Map,
+    objective: Objective,
+    order: Vec<usize>,
+    position: Vec<usize>
+}
+
+impl<'a> Cycle<'a> {
+    fn new(map: &'a Map, tour: &[usize], objective: Objective) -> Self {
+        let mut order = tour.to_vec();
+        order.push(map.size());
+
+        let mut position = vec![0; order.len()];
+        for (i, &city) in order.iter().enumerate() {
+            position[city] = i;
+        }
+
+        Cycle {
+            map,
+            objective,
+            order,
+            position
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    fn dist(&self, a: usize, b: usize) -> f32 {
+        let depot = self.map.size();
+        if a == depot || b == depot {
+            0.0
+        } else {
+            self.objective.loss(dist(self.map, a, b)) + forbidden_penalty(self.map, Some((a, b)))
+        }
+    }
+
+    // Whether the map fixes the edge. The depot's edges are never constrained.
+    fn is_fixed(&self, a: usize, b: usize) -> bool {
+        let depot = self.map.size();
+        a != depot && b != depot && self.map.edge_constraints().is_some_and(|edges| edges.is_fixed(a, b))
+    }
+
+    fn is_forbidden(&self, a: usize, b: usize) -> bool {
+        let depot = self.map.size();
+        a != depot && b != depot && self.map.edge_constraints().is_some_and(|edges| edges.is_forbidden(a, b))
+    }
+
+    fn succ(&self, city: usize) -> usize {
+        self.order[(self.position[city] + 1) % self.len()]
+    }
+
+    fn pred(&self, city: usize) -> usize {
+        self.order[(self.position[city] + self.len() - 1) % self.len()]
+    }
+
+    // Reverses the part of the tour running from `from` to `to` through successors
+    fn reverse(&mut self, from: usize, to: usize) {
+        let n = self.len();
+        let (mut i, mut j) = (self.position[from], self.position[to]);
+        let count = (j + n - i) % n + 1;
+        for _ in 0..count / 2 {
+            self.order.swap(i, j);
+            self.position[self.order[i]] = i;
+            self.position[self.order[j]] = j;
+            i = (i + 1) % n;
+            j = (j + n - 1) % n;
+        }
+    }
+
+    // The candidates of every point, including the depot. The nearest neighbors are ordered by distance, and with
+    // the `geometry` feature a map whose distances are straight lines also gets its Delaunay neighbors, behind the
+    // depot, which is at distance zero from every point and so always the nearest.
+    fn neighbors(&self, candidates: Candidates, context: Option<&SolverContext>) -> Neighbors {
+        if candidates == Candidates::Alpha {
+            return Neighbors::alpha_by(self.len(), NEIGHBORS, |a, b| self.dist(a, b));
+        }
+        let straight = self.map.distances().is_none() && !self.map.has_costs();
+        #[cfg(feature = "geometry")]
+        if self.objective == Objective::Minimize && straight {
+            return match context {
+                Some(context) => self.with_depot(context.neighbors(), NEIGHBORS),
+                None => self.with_depot(&Neighbors::delaunay(self.map, NEIGHBORS), NEIGHBORS)
+            };
+        }
+        // The context's nearest points are the cycle's, after the depot, unless forbidden edges are penalized
+        if let Some(context) = context {
+            if self.objective == Objective::Minimize && !straight && self.map.edge_constraints().is_none() {
+                return self.with_depot(context.neighbors(), NEIGHBORS - 1);
+            }
+        }
+        Neighbors::by(self.len(), NEIGHBORS, |a, b| self.dist(a, b))
+    }
+
+    // The candidates of the map's points with the depot first, followed by at most `count` of their own
+    fn with_depot(&self, neighbors: &Neighbors, count: usize) -> Neighbors {
+        let depot = self.map.size();
+        Neighbors::from_lists(NEIGHBORS, (0..self.len()).map(|city| {
+            if city == depot {
+                // Every point is as near to the depot, and ties go to the lower index
+                (0..NEIGHBORS.min(depot) as u32).collect()
+            } else {
+                iter::once(depot).chain(neighbors.of(city).take(count)).map(|other| other as u32).collect()
+            }
+        }))
+    }
+
+    fn into_path(self) -> Vec<usize> {
+        let depot = self.position[self.map.size()];
+        let mut order = self.order;
+        order.rotate_left(depot);
+        order.remove(0);
+        order
+    }
+
+    // Writes the path `into_path` would return into `path`
+    fn path_into(&self, path: &mut Vec<usize>) {
+        let depot = self.position[self.map.size()];
+        path.clear();
+        path.extend_from_slice(&self.order[depot + 1..]);
+        path.extend_from_slice(&self.order[..depot]);
+    }
+}
+
+// Tries a chain of 2-opt flips which each break the edge after `t1`, keeping the prefix of the chain which
+// shortens the tour the most. Returns the change in length, which is zero if no prefix helped.
+fn lin_kernighan_step(cycle: &mut Cycle<'_>, neighbors: &Neighbors, t1: usize, tolerance: f32) -> f32 {
+    let mut flips: Vec<(usize, usize)> = Vec::new();
+    let mut used: Vec<usize> = Vec::new();
+    let mut gain = cycle.dist(t1, cycle.succ(t1));
+    let (mut delta, mut best_delta, mut best_flips) = (0.0, 0.0, 0);
+    if cycle.is_fixed(t1, cycle.succ(t1)) {
+        return 0.0;
+    }
+
+    while flips.len() < MAX_DEPTH {
+        // Pick the next point so that the edge broken in exchange for joining it is as long as possible,
+        // subject to the total gain staying positive
+        let t2 = cycle.succ(t1);
+        let mut choice: Option<(usize, f32)> = None;
+        for t3 in neighbors.of(t2) {
+            let open_gain = gain - cycle.dist(t2, t3);
+            if open_gain <= 0.0 {
+                break;
+            }
+            if t3 == t1 || t3 == cycle.succ(t2) || used.contains(&t3) {
+                continue;
+            }
+            if cycle.is_forbidden(t2, t3) || cycle.is_fixed(cycle.pred(t3), t3) {
+                continue;
+            }
+
+            let score = cycle.dist(cycle.pred(t3), t3) - cycle.dist(t2, t3);
+            if choice.map(|(_, best)| score > best).unwrap_or(true) {
+                choice = Some((t3, score));
+            }
+        }
+
+        let t3 = match choice {
+            Some((t3, _)) => t3,
+            None => break
+        };
+        let t4 = cycle.pred(t3);
+
+        // Replaces (t1, t2) and (t4, t3) with (t1, t4) and (t2, t3)
+        delta += cycle.dist(t1, t4) + cycle.dist(t2, t3) - cycle.dist(t1, t2) - cycle.dist(t4, t3);
+        gain += cycle.dist(t4, t3) - cycle.dist(t2, t3);
+        cycle.reverse(t2, t4);
+        flips.push((t2, t4));
+        used.push(t3);
+
+        // The edge closing the chain is only kept if the chain stops here
+        if delta < best_delta - tolerance && !cycle.is_forbidden(t1, t4) {
+            best_delta = delta;
+            best_flips = flips.len();
+        }
+    }
+
+    while flips.len() > best_flips {
+        // Infallible: the loop stops once only the kept flips remain
+        let (from, to) = flips.pop().unwrap();
+        cycle.reverse(to, from);
+    }
+
+    best_delta
+}
+
+/// Improves the tour with Lin-Kernighan style chains of 2-opt moves over nearest neighbor candidate lists,
+/// until no chain starting from any point shortens it. The iteration count is the number of chains applied.
+/// When [maximizing](Objective::Maximize), the candidates are the farthest points instead, and chains must
+/// lengthen the tour.
+///
+/// Chains are built from edge lengths alone, so on a map with [time windows](Map::with_time_windows) or a
+/// [turn penalty](Map::with_turn_penalty) a chain is only kept if it also lowers the cost of the schedule or of
+/// turning, penalizing rather than rejecting lateness as in [`two_opt`]. Chains never break a fixed edge or join
+/// a forbidden one, as in [`two_opt`].
+pub fn lin_kernighan(map: &Map, tour: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+    lin_kernighan_with(map, tour, Candidates::Nearest, options)
+}
+
+/// Like [`lin_kernighan`], but tries the given candidates for each point. With [`Candidates::Alpha`], they are
+/// ranked by [alpha-nearness](Neighbors::alpha), which finds better tours from the same number of candidates but
+/// takes time quadratic in the size of the map to compute up front.
+pub fn lin_kernighan_with(
+    map: &Map,
+    tour: &[usize],
+    candidates: Candidates,
+    options: &SolveOptions
+) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_permutation(map, tour)?;
+
+    let mut budget = Budget::new(options, start, 1);
+    let objective = options.objective;
+    let mut reporter = Reporter::new(options, start).objective(objective);
+    let mut length = search_cost(map, tour, objective);
+    reporter.improved(objective.length(length));
+
+    // Chains need at least two edges besides the ones being broken
+    if map.size() < 3 {
+        return Ok(SolveResult::new(map, tour.to_vec(), 0, start));
+    }
+
+    let tolerance = tolerance(map, tour, length);
+    let mut cycle = Cycle::new(map, tour, objective);
+    let neighbors = cycle.neighbors(candidates, SolverContext::of(options, map));
+    let mut path = Vec::with_capacity(map.size());
+    let mut chains = 0;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for t1 in 0..cycle.len() {
+            if budget.exhausted() {
+                break;
+            }
+
+            // The cycle only knows the lengths of its edges, so chains are checked against the full cost when that
+            // depends on more than the edges
+            let delta = if map.time_windows().is_some() || map.turn_penalty().is_some() {
+                let (order, position) = (cycle.order.clone(), cycle.position.clone());
+                lin_kernighan_step(&mut cycle, &neighbors, t1, tolerance);
+                cycle.path_into(&mut path);
+                let delta = search_cost(map, &path, objective) - length;
+                if delta < -tolerance {
+                    delta
+                } else {
+                    cycle.order = order;
+                    cycle.position = position;
+                    0.0
+                }
+            } else {
+                lin_kernighan_step(&mut cycle, &neighbors, t1, tolerance)
+            };
+            if delta < 0.0 {
+                length += delta;
+                chains += 1;
+                improved = true;
+                reporter.improved(objective.length(length));
+            }
+        }
+    }
+
+    let mut result = SolveResult::new(map, cycle.into_path(), chains, start);
+    result.termination = budget.termination();
+    Ok(result)
+}
+
+/// Lin-Kernighan local search from the nearest neighbor tour, with any chains of fixed edges pulled in whole. See
+/// [`lin_kernighan_with`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinKernighan {
+    pub candidates: Candidates
+}
+
+impl Solver for LinKernighan {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        check_map(map)?;
+        lin_kernighan_with(map, &initial_tour(map)?, self.candidates, options)
+    }
+}
+
+// An iterated local search between kicks. The tour is only ever replaced by one no worse, so it is always the best
+// the search has found.
+pub(crate) struct Ils {
+    pub tour: Vec<usize>,
+    pub cost: f32
+}
+
+impl Ils {
+    // Brings the tour to a 2-opt local optimum to start from
+    pub fn new(
+        map: &Map,
+        mut tour: Vec<usize>,
+        objective: Objective,
+        budget: &mut Budget,
+        reporter: &mut Reporter<'_>
+    ) -> Self {
+        two_opt_in_place(map, &mut tour, objective, |_, _, _| true, budget, reporter);
+        let cost = search_cost(map, &tour, objective);
+        Ils {
+            tour,
+            cost
+        }
+    }
+
+    // Kicks the tour with a double bridge, then searches again with 2-opt, keeping the result if it is no worse
+    pub fn kick(
+        &mut self,
+        map: &Map,
+        objective: Objective,
+        rng: &mut impl Rng,
+        budget: &mut Budget,
+        reporter: &mut Reporter<'_>
+    ) {
+        let mut kicked = match double_bridge(map, &self.tour, rng) {
+            Some(kicked) => kicked,
+            None => return
+        };
+        two_opt_in_place(map, &mut kicked, objective, |_, _, _| true, budget, reporter);
+
+        let cost = search_cost(map, &kicked, objective);
+        if cost <= self.cost {
+            self.tour = kicked;
+            self.cost = cost;
+        }
+    }
+}
+
+// Cuts the tour into four segments and swaps the middle two, or returns `None` for tours too short to cut and cuts
+// through fixed edges
+pub(crate) fn double_bridge(map: &Map, tour: &[usize], rng: &mut impl Rng) -> Option<Vec<usize>> {
+    let n = tour.len();
+    if n < 4 {
+        return None;
+    }
+
+    let mut cuts = rand::seq::index::sample(rng, n - 1, 3).into_vec();
+    cuts.sort_unstable();
+    let (a, b, c) = (cuts[0] + 1, cuts[1] + 1, cuts[2] + 1);
+    let fixed = map.edge_constraints()
+        .is_some_and(|edges| [a, b, c].iter().any(|&cut| edges.is_fixed(tour[cut - 1], tour[cut])));
+    if fixed {
+        return None;
+    }
+
+    let mut kicked = Vec::with_capacity(n);
+    kicked.extend_from_slice(&tour[..a]);
+    kicked.extend_from_slice(&tour[b..c]);
+    kicked.extend_from_slice(&tour[a..b]);
+    kicked.extend_from_slice(&tour[c..]);
+    Some(kicked)
+}
+
+/// Iterated local search: 2-opt to a local optimum, then over and over a random double-bridge kick, which
+/// reconnects the tour in a way no single reversal can undo, followed by 2-opt again. A kicked tour replaces the
+/// current one whenever it is no worse. The iteration count is the number of kicks.
+///
+/// Moves and costs are those of [`two_opt`], and kicks never cut a fixed edge.
+#[derive(Clone, Copy, Debug)]
+pub struct IteratedLocalSearch {
+    pub kicks: u64
+}
+
+impl Default for IteratedLocalSearch {
+    fn default() -> Self {
+        IteratedLocalSearch {
+            kicks: 1000
+        }
+    }
+}
+
+impl IteratedLocalSearch {
+    /// Searches from the given tour and returns the best tour found.
+    pub fn improve(&self, map: &Map, tour: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        check_permutation(map, tour)?;
+
+        let bound = known_bound(map, options);
+        let mut budget = Budget::new(options, start, 1).with_target(bound, options);
+        let objective = options.objective;
+        let mut reporter = Reporter::new(options, start).objective(objective);
+        let mut rng = options.rng();
+        reporter.improved(objective.length(search_cost(map, tour, objective)));
+        let mut search = Ils::new(map, tour.to_vec(), objective, &mut budget, &mut reporter);
+        budget.reached(objective.length(search.cost));
+
+        let mut kicks = 0;
+        while kicks < self.kicks && !budget.exhausted() {
+            search.kick(map, objective, &mut rng, &mut budget, &mut reporter);
+            kicks += 1;
+            reporter.sample(kicks, objective.length(search.cost), || objective.length(search.cost));
+            budget.reached(objective.length(search.cost));
+        }
+
+        let mut result = SolveResult::new(map, search.tour, kicks, start);
+        result.lower_bound = bound;
+        result.evaluations = budget.evaluations();
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+impl Solver for IteratedLocalSearch {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        check_map(map)?;
+        self.improve(map, &initial_tour(map)?, options)
+    }
+}
+
+/// Simulated annealing over random segment reversals. The temperature starts where an average uphill move is
+/// accepted with probability `initial_acceptance` and cools geometrically to a thousandth of that over
+/// `iterations` moves, or over the time or evaluation limit if that runs out first.
+///
+/// On a map with [time windows](Map::with_time_windows), moves change the cost of the schedule instead of the
+/// length, with lateness penalized rather than rejected as in [`two_opt`]. When
+/// [maximizing](Objective::Maximize), moves that lengthen the tour are the downhill ones. Moves that would break
+/// or join a constrained edge are skipped as in [`two_opt`].
+#[derive(Clone, Copy, Debug)]
+pub struct SimulatedAnnealing {
+    pub iterations: u64,
+    pub initial_acceptance: f32
+}
+
+impl Default for SimulatedAnnealing {
+    fn default() -> Self {
+        SimulatedAnnealing {
+            iterations: 1_000_000,
+            initial_acceptance: 0.5
+        }
+    }
+}
+
+impl SimulatedAnnealing {
+    /// Anneals from the given tour and returns the best tour seen.
+    pub fn improve(&self, map: &Map, tour: &[usize], options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        check_permutation(map, tour)?;
+
+        let bound = known_bound(map, options);
+        let mut budget = Budget::new(options, start, 1024).with_target(bound, options);
+        let objective = options.objective;
+        let mut reporter = Reporter::new(options, start).objective(objective);
+        let mut rng = options.rng();
+        let mut current = tour.to_vec();
+        let mut length = search_cost(map, &current, objective);
+        reporter.improved(objective.length(length));
+        budget.reached(objective.length(length));
+
+        let n = current.len();
+        if n < 3 {
+            return Ok(SolveResult::new(map, current, 0, start));
+        }
+
+        let random_move = |rng: &mut ChaCha20Rng| {
+            let (a, b) = (rng.gen_range(0, n), rng.gen_range(0, n - 1));
+            let b = if b >= a { b + 1 } else { b };
+            (a.min(b), a.max(b))
+        };
+
+        // Sample uphill moves to find a starting temperature on the scale of the instance
+        let (mut uphill, mut count) = (0.0, 0);
+        for _ in 0..100 {
+            let (i, j) = random_move(&mut rng);
+            if !reversal_allowed(map, &current, i, j) {
+                continue;
+            }
+            let delta = move_delta(map, &mut current, i, j, length, objective);
+            budget.evaluated(1);
+            if delta > 0.0 {
+                uphill += delta;
+                count += 1;
+            }
+        }
+        let acceptance = self.initial_acceptance.clamp(1e-6, 1.0 - 1e-6);
+        let initial = if count > 0 { -(uphill / count as f32) / acceptance.ln() } else { 0.0 };
+        let mut temperature = initial;
+
+        let mut best = current.clone();
+        let mut best_length = length;
+        let mut iterations = 0;
+        while iterations < self.iterations && !budget.exhausted() {
+            let (i, j) = random_move(&mut rng);
+            if reversal_allowed(map, &current, i, j) {
+                let delta = move_delta(map, &mut current, i, j, length, objective);
+                budget.evaluated(1);
+                if delta < 0.0 || (temperature > 0.0 && rng.gen::<f32>() < (-delta / temperature).exp()) {
+                    current[i..=j].reverse();
+                    length += delta;
+                    if length < best_length {
+                        best.copy_from_slice(&current);
+                        best_length = length;
+                        reporter.improved(objective.length(best_length));
+                        budget.reached(objective.length(best_length));
+                    }
+                }
+            }
+
+            iterations += 1;
+            if iterations % 256 == 0 {
+                let mut progress = iterations as f32 / self.iterations.max(1) as f32;
+                if let Some(limit) = options.time_limit {
+                    progress = progress.max(start.elapsed().as_secs_f32() / limit.as_secs_f32().max(f32::EPSILON));
+                }
+                if let Some(limit) = options.max_evaluations {
+                    progress = progress.max(budget.evaluations() as f32 / limit.max(1) as f32);
+                }
+                temperature = initial * 0.001f32.powf(progress.min(1.0));
+            }
+            if iterations % 1024 == 0 {
+                reporter.sample(iterations, objective.length(best_length), || objective.length(length));
+            }
+        }
+
+        let mut result = SolveResult::new(map, best, iterations, start);
+        result.lower_bound = bound;
+        result.evaluations = budget.evaluations();
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
+
+impl Solver for SimulatedAnnealing {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        check_map(map)?;
+        self.improve(map, &initial_tour(map)?, options)
+    }
+}
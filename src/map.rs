@@ -1,15 +1,36 @@
 use itertools::Itertools;
 use rand::prelude::*;
+use std::cell::RefCell;
 use std::cmp;
 use std::convert::AsRef;
-use std::f32::consts;
 use std::fmt::{self, Debug, Display, Formatter};
+use std::iter::FromIterator;
 use std::mem;
-use std::ops::{Index, IndexMut};
-
+use std::ops::{Add, Deref, Index, IndexMut, Mul, Range, Sub};
+use std::result;
+use std::slice;
+use std::sync::{Arc, OnceLock};
+
+use crate::checkpoint::{Persist, parse_values};
+use crate::edges::EdgeConstraints;
+use crate::error::{Error, Result, TourError};
 use crate::ga::{Chromosome, slice_crossover};
-
+use crate::generate::{Distribution, PointDistribution};
+use crate::matrix::DistanceMatrix;
+use crate::neighbors::Neighbors;
+use crate::precedence::Precedences;
+use crate::scenarios::Scenarios;
+use crate::time_windows::{TimeWindow, TimeWindows};
+use crate::turns::TurnPenalty;
+
+/// The most points a map read from a file or checked with [`Map::validate`] may have. Far larger instances are out
+/// of reach of every solver in the crate, and a corrupt size in a file would otherwise be allocated before anything
+/// else about it is checked.
+pub const MAX_POINTS: usize = 1 << 24;
+
+/// A point in the plane.
 #[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: f32,
     pub y: f32
@@ -43,6 +64,19 @@ impl Point {
         let y = self.y - other.y;
         x * x + y * y
     }
+
+    /// The point the fraction `t` of the way from this point to the other: this point at zero, the other at one,
+    /// and beyond either for fractions outside that range.
+    #[inline]
+    pub fn lerp(self, other: Point, t: f32) -> Point {
+        self + (other - self) * t
+    }
+}
+
+impl Default for Point {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Display for Point {
@@ -57,40 +91,428 @@ impl Debug for Point {
     }
 }
 
+impl Add for Point {
+    type Output = Point;
+
+    #[inline]
+    fn add(self, other: Point) -> Point {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y
+        }
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    #[inline]
+    fn sub(self, other: Point) -> Point {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y
+        }
+    }
+}
+
+impl Mul<f32> for Point {
+    type Output = Point;
+
+    #[inline]
+    fn mul(self, scale: f32) -> Point {
+        Point {
+            x: self.x * scale,
+            y: self.y * scale
+        }
+    }
+}
+
+impl Mul<Point> for f32 {
+    type Output = Point;
+
+    #[inline]
+    fn mul(self, point: Point) -> Point {
+        point * self
+    }
+}
+
+impl From<(f32, f32)> for Point {
+    fn from((x, y): (f32, f32)) -> Self {
+        Point {
+            x,
+            y
+        }
+    }
+}
+
+impl From<Point> for (f32, f32) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo_types::Point<f32>> for Point {
+    fn from(point: geo_types::Point<f32>) -> Self {
+        Point {
+            x: point.x(),
+            y: point.y()
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<Point> for geo_types::Point<f32> {
+    fn from(point: Point) -> Self {
+        geo_types::Point::new(point.x, point.y)
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo_types::Coord<f32>> for Point {
+    fn from(coord: geo_types::Coord<f32>) -> Self {
+        Point {
+            x: coord.x,
+            y: coord.y
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<Point> for geo_types::Coord<f32> {
+    fn from(point: Point) -> Self {
+        geo_types::Coord {
+            x: point.x,
+            y: point.y
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec2> for Point {
+    fn from(vector: glam::Vec2) -> Self {
+        Point {
+            x: vector.x,
+            y: vector.y
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Point> for glam::Vec2 {
+    fn from(point: Point) -> Self {
+        glam::Vec2::new(point.x, point.y)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Point2<f32>> for Point {
+    fn from(point: nalgebra::Point2<f32>) -> Self {
+        Point {
+            x: point.x,
+            y: point.y
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<Point> for nalgebra::Point2<f32> {
+    fn from(point: Point) -> Self {
+        nalgebra::Point2::new(point.x, point.y)
+    }
+}
+
+/// A traveling salesman instance: the set of points to visit, and optionally the distances between them if they are
+/// not straight lines, when each may be visited, which must be visited before which, which edges the tour must or
+/// must not use, what turning costs, and under which scenarios the costs between them are known.
 #[derive(Clone)]
 pub struct Map {
-    points: Box<[Point]>
+    points: Box<[Point]>,
+    distances: Option<DistanceMatrix>,
+    windows: Option<TimeWindows>,
+    precedences: Option<Precedences>,
+    edges: Option<EdgeConstraints>,
+    turns: Option<TurnPenalty>,
+    costs: Option<Costs>,
+    scenarios: Option<Scenarios>
+}
+
+// A cost function given by the user, with the index it knows every point by, which swapping points exchanges
+#[derive(Clone)]
+struct Costs {
+    cost: Arc<dyn Fn(usize, usize) -> f32 + Send + Sync>,
+    indices: Box<[usize]>
+}
+
+impl Costs {
+    #[inline]
+    fn get(&self, a: usize, b: usize) -> f32 {
+        let (a, b) = (self.indices[a], self.indices[b]);
+        match a.cmp(&b) {
+            cmp::Ordering::Less => (self.cost)(a, b),
+            cmp::Ordering::Greater => (self.cost)(b, a),
+            cmp::Ordering::Equal => 0.0
+        }
+    }
 }
 
 impl Map {
+    /// Generates `count` points uniformly at random in polar coordinates on the unit disc.
     pub fn new(count: usize) -> Self {
-        let mut rng = thread_rng();
-        let mut points = vec![Point::new(); count].into_boxed_slice();
+        Self::random(count, &Distribution::Disc, &mut thread_rng())
+    }
 
-        for i in 0..count {
-            let theta = 2.0 * consts::PI * rng.gen::<f32>();
-            let radius = rng.gen::<f32>();
+    /// Generates `count` points drawn from the given distribution, which can be one of the generator's
+    /// [`Distribution`]s, one of the shapes of the [`generate`](crate::generate) module, or a closure drawing a
+    /// single point.
+    pub fn random<D, R>(count: usize, distribution: &D, mut rng: &mut R) -> Self
+    where
+        D: PointDistribution + ?Sized,
+        R: Rng + ?Sized
+    {
+        Self::from_points(distribution.sample(count, &mut rng))
+    }
 
-            let point = &mut points[i];
-            point.x = radius * theta.cos();
-            point.y = radius * theta.sin();
+    pub fn from_points(points: Vec<Point>) -> Self {
+        Map {
+            points: points.into_boxed_slice(),
+            distances: None,
+            windows: None,
+            precedences: None,
+            edges: None,
+            turns: None,
+            costs: None,
+            scenarios: None
         }
+    }
 
-        Map {
-            points
+    /// Like [`from_points`](Self::from_points), but fails on the inputs the solvers cannot handle. See
+    /// [`validate`](Self::validate).
+    pub fn try_from_points(points: Vec<Point>) -> Result<Self> {
+        let map = Self::from_points(points);
+        map.validate()?;
+        Ok(map)
+    }
+
+    /// Checks that the tour visits every point of the map exactly once, reporting every point it misses or visits
+    /// more than once and every index past the last point, such as of a tour read from a file. Every tour that
+    /// solvers take as input is checked this way.
+    pub fn validate_tour(&self, tour: &[usize]) -> result::Result<(), TourError> {
+        check_tour(self.size(), tour)
+    }
+
+    /// Checks that the map has at least one point and at most [`MAX_POINTS`], that every coordinate is a finite
+    /// number, and that none of its [attached distances](Self::with_distances) is NaN. Every solver checks its map
+    /// this way before starting, since NaN sorts ahead of or behind every distance depending on its sign, and would
+    /// silently scramble the search.
+    pub fn validate(&self) -> Result<()> {
+        if self.points.is_empty() {
+            return Err(Error::EmptyMap);
+        }
+        if self.points.len() > MAX_POINTS {
+            let message = format!("{} points is over the limit of {}", self.points.len(), MAX_POINTS);
+            return Err(Error::TooLarge(message));
+        }
+        if let Some(index) = self.points.iter().position(|point| !(point.x.is_finite() && point.y.is_finite())) {
+            return Err(Error::NonFinitePoint { index });
+        }
+        match self.distances.as_ref().and_then(DistanceMatrix::find_nan) {
+            Some((from, to)) => Err(Error::NanDistance { from, to }),
+            None => Ok(())
         }
     }
 
-    pub fn from_points(points: Vec<Point>) -> Self {
-        Map {
-            points: points.into_boxed_slice()
+    /// A map of `size` points whose distances are whatever `cost` says they are, such as the overlap between
+    /// fragments to sequence or the setup time between jobs. The cost is computed again every time a solver asks
+    /// for it, so it should be quick; [`from_cached_costs`](Self::from_cached_costs) computes every cost once
+    /// instead. See [`with_costs`](Self::with_costs).
+    ///
+    /// The points are spread evenly around the unit circle, which only matters for drawing the map and for the
+    /// few heuristics that work from positions.
+    pub fn from_costs<F>(size: usize, cost: F) -> Self
+    where
+        F: Fn(usize, usize) -> f32 + Send + Sync + 'static
+    {
+        Self::on_circle(size).with_costs(cost)
+    }
+
+    /// Like [`from_costs`](Self::from_costs), but computes the cost of every pair up front and keeps them in a
+    /// [`DistanceMatrix`], which takes memory quadratic in the size but makes every later lookup cheap.
+    pub fn from_cached_costs<F: Fn(usize, usize) -> f32>(size: usize, cost: F) -> Self {
+        let distances = DistanceMatrix::from_fn(size, cost);
+        // Infallible: the matrix has a distance for every point
+        Self::on_circle(size).with_distances(distances).unwrap()
+    }
+
+    /// Replaces the straight line distances between the points with a function of their indices, which every
+    /// solver then uses, like [attached distances](Self::with_distances) that are computed on demand. The solvers
+    /// treat distances as symmetric, so `cost(i, j)` is only called with `i < j`, and the distance from a point to
+    /// itself is zero. Indices are those of the map as given, even after its points are
+    /// [swapped](Self::swap).
+    pub fn with_costs<F>(mut self, cost: F) -> Self
+    where
+        F: Fn(usize, usize) -> f32 + Send + Sync + 'static
+    {
+        self.distances = None;
+        self.scenarios = None;
+        self.costs = Some(Costs {
+            cost: Arc::new(cost),
+            indices: (0..self.size()).collect()
+        });
+        self
+    }
+
+    /// Whether the map has [costs](Self::with_costs) computed by a function.
+    pub fn has_costs(&self) -> bool {
+        self.costs.is_some()
+    }
+
+    // Points evenly spaced around the unit circle, for maps that only have costs
+    fn on_circle(size: usize) -> Self {
+        let angle = |index: usize| index as f32 / size as f32 * 2.0 * std::f32::consts::PI;
+        let points = (0..size).map(|index| Point { x: angle(index).cos(), y: angle(index).sin() }).collect();
+        Self::from_points(points)
+    }
+
+    /// Replaces the straight line distances between the points with the given ones, as for travel around
+    /// [obstacles](crate::obstacles::Obstacles), which every solver then uses. The points still place the map for
+    /// drawing and for heuristics that work from positions, such as the centroids of
+    /// [`clustered`](crate::clustered::clustered), and moving them afterwards leaves the distances as they are.
+    pub fn with_distances(mut self, distances: DistanceMatrix) -> Result<Self> {
+        check_size(self.size(), distances.size())?;
+        self.costs = None;
+        self.scenarios = None;
+        self.distances = Some(distances);
+        Ok(self)
+    }
+
+    pub fn distances(&self) -> Option<&DistanceMatrix> {
+        self.distances.as_ref()
+    }
+
+    /// Replaces the distances between the points with their costs under several [scenarios](Scenarios), so that
+    /// [`Path::evaluate`] charges every tour its cost over all of them. The map's
+    /// [distances](Self::with_distances) become the mean cost of every edge over the scenarios, which every solver
+    /// that looks at single edges uses.
+    pub fn with_scenarios(mut self, scenarios: Scenarios) -> Result<Self> {
+        check_size(self.size(), scenarios.size())?;
+        self.costs = None;
+        self.distances = Some(scenarios.mean());
+        self.scenarios = Some(scenarios);
+        Ok(self)
+    }
+
+    pub fn scenarios(&self) -> Option<&Scenarios> {
+        self.scenarios.as_ref()
+    }
+
+    /// The distance between two points, which is the attached one if the map has
+    /// [distances](Map::with_distances) or [costs](Map::with_costs), and otherwise the straight line distance.
+    #[inline]
+    pub fn dist(&self, a: usize, b: usize) -> f32 {
+        match (&self.distances, &self.costs) {
+            (Some(distances), _) => distances.get(a, b),
+            (None, Some(costs)) => costs.get(a, b),
+            (None, None) => self.points[a].dist(&self.points[b])
         }
     }
 
+    /// Attaches a time window to every point, which [`Path::evaluate`] and local search then take into account.
+    pub fn with_time_windows(mut self, windows: TimeWindows) -> Result<Self> {
+        check_size(self.size(), windows.len())?;
+        self.windows = Some(windows);
+        Ok(self)
+    }
+
+    pub fn time_windows(&self) -> Option<&TimeWindows> {
+        self.windows.as_ref()
+    }
+
+    /// Requires some points to be visited before others, which brute force, branch-and-bound and the repairs of
+    /// random-key and removal-index GA offspring then respect.
+    pub fn with_precedences(mut self, precedences: Precedences) -> Result<Self> {
+        check_size(self.size(), precedences.size())?;
+        self.precedences = Some(precedences);
+        Ok(self)
+    }
+
+    pub fn precedences(&self) -> Option<&Precedences> {
+        self.precedences.as_ref()
+    }
+
+    /// Requires the tour to use some edges and not others, which branch-and-bound then only branches along, and
+    /// which local search moves never break or join.
+    pub fn with_edge_constraints(mut self, edges: EdgeConstraints) -> Result<Self> {
+        check_size(self.size(), edges.size())?;
+        self.edges = Some(edges);
+        Ok(self)
+    }
+
+    pub fn edge_constraints(&self) -> Option<&EdgeConstraints> {
+        self.edges.as_ref()
+    }
+
+    /// Locks the given segments of `tour`, each a range of positions in it, by fixing the edges inside them on top
+    /// of any [edge constraints](Self::with_edge_constraints) the map has, so that re-optimizing the tour only
+    /// rearranges the rest of it, as when part of a route is already being driven. Local search, its kicks and
+    /// the repairs of GA offspring keep every locked segment whole, though they may run it backwards. See
+    /// [`EdgeConstraints::with_locked`].
+    pub fn lock_segments(self, tour: &[usize], segments: &[Range<usize>]) -> Result<Self> {
+        check_permutation(&self, tour)?;
+        let edges = match &self.edges {
+            Some(edges) => edges.with_locked(tour, segments)?,
+            None => EdgeConstraints::new(self.size(), Vec::new(), Vec::new())?.with_locked(tour, segments)?
+        };
+        self.with_edge_constraints(edges)
+    }
+
+    /// Charges for every turn the tour makes on top of its length, which [`Path::evaluate`], brute force and local
+    /// search then take into account. Branch-and-bound and Held-Karp minimize the length alone, so they refuse
+    /// such maps.
+    pub fn with_turn_penalty(mut self, turns: TurnPenalty) -> Self {
+        self.turns = Some(turns);
+        self
+    }
+
+    pub fn turn_penalty(&self) -> Option<&TurnPenalty> {
+        self.turns.as_ref()
+    }
+
     pub fn size(&self) -> usize {
         self.points.len()
     }
 
+    /// Roughly the bytes the map holds: its points and whatever distances, costs or constraints are attached. The
+    /// points take 8 bytes each, so a million of them take 8 MB, while [attached distances](Self::with_distances)
+    /// grow with the square of the size and are what makes large maps expensive.
+    pub fn memory_estimate(&self) -> usize {
+        let pair = mem::size_of::<(usize, usize)>();
+        let lists = self.size() * mem::size_of::<Vec<usize>>();
+        let mut memory = mem::size_of::<Self>() + self.size() * mem::size_of::<Point>();
+        if let Some(distances) = &self.distances {
+            memory += DistanceMatrix::memory(distances.size()) as usize;
+        }
+        if let Some(costs) = &self.costs {
+            memory += costs.indices.len() * mem::size_of::<usize>();
+        }
+        if let Some(scenarios) = &self.scenarios {
+            memory += scenarios.matrices().len() * DistanceMatrix::memory(scenarios.size()) as usize;
+        }
+        if let Some(windows) = &self.windows {
+            memory += windows.len() * mem::size_of::<TimeWindow>();
+        }
+        if let Some(precedences) = &self.precedences {
+            // Every pair is also kept in the list of predecessors of its second point
+            memory += precedences.pairs().len() * (pair + mem::size_of::<usize>()) + lists;
+        }
+        if let Some(edges) = &self.edges {
+            // Fixed edges are also kept in the lists of both points, and forbidden ones in a set
+            memory += edges.fixed().len() * (pair + 2 * mem::size_of::<usize>()) + lists;
+            memory += edges.forbidden().len() * 2 * pair;
+        }
+        memory
+    }
+
     #[inline]
     pub fn get(&self, index: usize) -> Option<Point> {
         self.points.get(index).cloned()
@@ -101,14 +523,82 @@ impl Map {
     }
 
     pub fn swap(&mut self, first: usize, second: usize) {
-        let (first_mut, second_mut) = self.points.split_at_mut(second);
-        mem::swap(&mut first_mut[first], &mut second_mut[0]);
+        self.points.swap(first, second);
+        if let Some(distances) = &mut self.distances {
+            distances.swap(first, second);
+        }
+        if let Some(costs) = &mut self.costs {
+            costs.indices.swap(first, second);
+        }
+        if let Some(scenarios) = &mut self.scenarios {
+            scenarios.swap(first, second);
+        }
+        if let Some(windows) = &mut self.windows {
+            windows.swap(first, second);
+        }
+        if let Some(precedences) = &mut self.precedences {
+            precedences.swap(first, second);
+        }
+        if let Some(edges) = &mut self.edges {
+            edges.swap(first, second);
+        }
+    }
+
+    // Moves the point at index `tour[i]` to index `i`, with everything attached to it, by swapping along every cycle
+    // of the permutation, so that no point is copied and at most one swap is made per point. The tour must be a
+    // permutation of the map's indices.
+    pub(crate) fn permute(&mut self, tour: &[usize]) {
+        let mut placed = vec![false; tour.len()];
+        for start in 0..tour.len() {
+            let mut current = start;
+            while !placed[current] {
+                placed[current] = true;
+                let next = tour[current];
+                if next == start {
+                    break;
+                }
+                self.swap(current, next);
+                current = next;
+            }
+        }
     }
 
     pub fn clone_to_vec(&self) -> Vec<Point> {
-        let mut result = Vec::with_capacity(self.points.len());
-        result.extend_from_slice(&self.points);
-        result
+        self.points.to_vec()
+    }
+
+    /// The points in index order.
+    pub fn as_slice(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// An iterator over the points in index order.
+    pub fn iter(&self) -> slice::Iter<'_, Point> {
+        self.points.iter()
+    }
+
+    /// A map of the points or coordinates of the `geo` crates, such as those of a `MultiPoint`, checked as by
+    /// [`try_from_points`](Self::try_from_points).
+    #[cfg(feature = "geo")]
+    pub fn from_geo<I>(points: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<Point>
+    {
+        Self::try_from_points(points.into_iter().map(Into::into).collect())
+    }
+
+    /// The map's points as a `geo` `MultiPoint`.
+    #[cfg(feature = "geo")]
+    pub fn to_geo(&self) -> geo_types::MultiPoint<f32> {
+        self.points.iter().map(|&point| geo_types::Point::from(point)).collect()
+    }
+
+    /// The path through the map's points in the order of the tour as a `geo` `LineString`, for drawing it or
+    /// measuring it with the `geo` algorithms.
+    #[cfg(feature = "geo")]
+    pub fn geo_path(&self, tour: &[usize]) -> geo_types::LineString<f32> {
+        tour.iter().map(|&index| geo_types::Coord::from(self.points[index])).collect()
     }
 }
 
@@ -121,6 +611,40 @@ impl Index<usize> for Map {
     }
 }
 
+/// A map of the points in the order the iterator yields them, with nothing attached, like
+/// [`from_points`](Map::from_points).
+impl FromIterator<Point> for Map {
+    fn from_iter<I: IntoIterator<Item = Point>>(points: I) -> Self {
+        Self::from_points(points.into_iter().collect())
+    }
+}
+
+/// Appends the points after the map's own, keeping any turn penalty. Panics if the map has distances, costs, time
+/// windows, precedences or edge constraints attached, which have no values for the new points.
+impl Extend<Point> for Map {
+    fn extend<I: IntoIterator<Item = Point>>(&mut self, points: I) {
+        let attached = self.distances.is_some()
+            || self.costs.is_some()
+            || self.windows.is_some()
+            || self.precedences.is_some()
+            || self.edges.is_some();
+        assert!(!attached, "only maps with nothing attached but a turn penalty can be extended");
+
+        let mut extended = mem::take(&mut self.points).into_vec();
+        extended.extend(points);
+        self.points = extended.into_boxed_slice();
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = &'a Point;
+    type IntoIter = slice::Iter<'a, Point>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl IndexMut<usize> for Map {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
@@ -134,35 +658,353 @@ impl Debug for Map {
     }
 }
 
+/// A representation of a path through every point of a map.
 pub trait Path {
+    /// The length of the path, or on a map with [time windows](Map::with_time_windows) the cost of serving the
+    /// points in its order. Paths that do not [`validate`](Path::validate) against the map produce meaningless
+    /// lengths or panic. The lengths of paths of more than 1024 edges are summed in double precision, so that
+    /// rounding does not decide which of two long tours is the shorter.
     fn evaluate(&self, map: &Map) -> f32;
 
+    /// Like [`evaluate`](Path::evaluate), but uses `scratch` for any working memory the representation needs
+    /// instead of allocating, so that one buffer can serve many evaluations. The buffer's contents on entry do not
+    /// matter and are left unspecified.
+    fn evaluate_with(&self, map: &Map, _scratch: &mut Vec<usize>) -> f32 {
+        self.evaluate(map)
+    }
+
+    /// Rearranges the map's points into the order of this path.
     fn reorder(&self, map: &mut Map);
 
+    /// The order in which the map's points are visited
+    fn tour(&self, map: &Map) -> Vec<usize>;
+
+    /// Checks that the path can be evaluated against the map.
+    fn validate(&self, map: &Map) -> Result<()>;
+
+    /// Applies cheap local repairs to the path, if the representation supports any.
     fn fix(&mut self, _map: &Map) {}
+
+    /// Applies the repairs of [`fix`](Path::fix), plus any that look for improvements among each point's nearest
+    /// neighbors, which can join points that are far apart in the path.
+    fn fix_with(&mut self, map: &Map, _neighbors: &Neighbors) {
+        self.fix(map);
+    }
+
+    /// A stronger alternative to [`fix_with`](Path::fix_with): up to `passes` passes of 2-opt over the candidate
+    /// lists, each reversing the part of the path between a point and one of its candidates whenever joining the
+    /// two shortens it, and stopping early after a pass that finds nothing. The moves only compare distances, so
+    /// like the other repairs they are skipped on maps with time windows or a turn penalty, and the constraints of
+    /// the map are repaired after them. Representations without such a repair fall back to `fix_with`.
+    fn fix_two_opt(&mut self, map: &Map, neighbors: &Neighbors, _passes: u32) {
+        self.fix_with(map, neighbors);
+    }
 }
 
 impl<T: AsRef<[usize]>> Path for T {
     fn evaluate(&self, map: &Map) -> f32 {
-        self.as_ref().windows(2)
-            .flat_map(|segment| Some((map.get(segment[0])?, map.get(segment[1])?)))
-            .map(|(a, b)| a.dist(&b))
-            .sum::<f32>()
+        cost(map, self.as_ref())
     }
 
     fn reorder(&self, map: &mut Map) {
-        let path = self.as_ref();
-        for i in 0..path.len() {
-            map.swap(i, path[i]);
+        map.permute(self.as_ref());
+    }
+
+    fn tour(&self, _map: &Map) -> Vec<usize> {
+        self.as_ref().to_vec()
+    }
+
+    fn validate(&self, map: &Map) -> Result<()> {
+        check_permutation(map, self.as_ref())
+    }
+}
+
+/// The order in which a [chromosome](Chromosome) visits the points of the map, as it
+/// [decodes](Chromosome::decode) it. A tour is a [`Path`] itself, like any slice of indices.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tour(Vec<usize>);
+
+impl Tour {
+    pub fn into_vec(self) -> Vec<usize> {
+        self.0
+    }
+}
+
+impl From<Vec<usize>> for Tour {
+    fn from(tour: Vec<usize>) -> Self {
+        Tour(tour)
+    }
+}
+
+impl From<Tour> for Vec<usize> {
+    fn from(tour: Tour) -> Self {
+        tour.0
+    }
+}
+
+impl Deref for Tour {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl AsRef<[usize]> for Tour {
+    fn as_ref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+// The cost of the tour the chromosome decodes to, decoded into the scratch buffer
+fn decoded_cost<C: Chromosome>(chromosome: &C, map: &Map, scratch: &mut Vec<usize>) -> f32 {
+    chromosome.decode_into(map, scratch);
+    debug_assert_eq!(check_tour(map.size(), scratch), Ok(()));
+    cost(map, scratch)
+}
+
+// 2-opt over the decoded tour as `Path::fix_two_opt` describes, encoding the result in place of the old genes. A
+// chromosome that cannot encode the improved tour is left as it was.
+fn two_opt_decoded<C: Chromosome>(chromosome: &mut C, map: &Map, neighbors: &Neighbors, passes: u32) {
+    let tour = chromosome.decode(map).into_vec();
+    let mut improved = tour.clone();
+    two_opt_passes(map, &mut improved, neighbors, passes);
+    let improved = repair_constraints(map, improved);
+    if improved != tour {
+        let _ = chromosome.encode(map, &improved);
+    }
+}
+
+// At most `passes` passes of 2-opt over the candidate lists, stopping after a pass that finds nothing. Every point
+// is joined to its nearer candidates in place of the edge to its successor, or to its predecessor, by reversing
+// the part of the path in between.
+fn two_opt_passes(map: &Map, path: &mut [usize], neighbors: &Neighbors, passes: u32) {
+    let len = path.len();
+    if len < 3 || map.windows.is_some() || map.turns.is_some() {
+        return;
+    }
+
+    let mut position = vec![0; len];
+    for (i, &city) in path.iter().enumerate() {
+        position[city] = i;
+    }
+    let reverse = |path: &mut [usize], position: &mut [usize], start: usize, end: usize| {
+        path[start..=end].reverse();
+        for (k, &city) in path.iter().enumerate().take(end + 1).skip(start) {
+            position[city] = k;
+        }
+    };
+
+    for _ in 0..passes {
+        let mut improved = false;
+        for i in 0..len {
+            let a = path[i];
+            // Join `a` to a candidate `c` further along, reversing the points from its successor `b` to `c`
+            if let Some(&b) = path.get(i + 1) {
+                let current = map.dist(a, b);
+                for c in neighbors.of(a) {
+                    let joined = map.dist(a, c);
+                    // Candidates are ordered by distance, so no later one can shorten the path either
+                    if joined >= current {
+                        break;
+                    }
+
+                    let j = position[c];
+                    if j <= i + 1 {
+                        continue;
+                    }
+                    let (removed, added) = match path.get(j + 1) {
+                        Some(&d) => (current + map.dist(c, d), joined + map.dist(b, d)),
+                        None => (current, joined)
+                    };
+                    if added < removed {
+                        reverse(path, &mut position, i + 1, j);
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+
+            // Join `a` to a candidate `c` further back, reversing the points from `c` to its predecessor `p`. The
+            // move above only reverses points after `a`, so `a` is still at `i`
+            if i > 0 {
+                let p = path[i - 1];
+                let current = map.dist(p, a);
+                for c in neighbors.of(a) {
+                    let joined = map.dist(c, a);
+                    if joined >= current {
+                        break;
+                    }
+
+                    let j = position[c];
+                    if j + 1 >= i {
+                        continue;
+                    }
+                    let (removed, added) = match j.checked_sub(1).map(|k| path[k]) {
+                        Some(d) => (current + map.dist(d, c), joined + map.dist(d, p)),
+                        None => (current, joined)
+                    };
+                    if added < removed {
+                        reverse(path, &mut position, j, i - 1);
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if !improved {
+            break;
         }
     }
 }
 
-#[derive(Clone)]
+// Encodes the decoded tour again with the chains of fixed edges pulled in whole and every point moved after its
+// predecessors. A chromosome that cannot encode the repaired tour is left as it was.
+fn repair_decoded<C: Chromosome>(chromosome: &mut C, map: &Map) {
+    if map.edges.is_none() && map.precedences.is_none() {
+        return;
+    }
+
+    let tour = chromosome.decode(map).into_vec();
+    let repaired = repair_constraints(map, tour.clone());
+    if repaired != tour {
+        let _ = chromosome.encode(map, &repaired);
+    }
+}
+
+// The length of the tour, or the cost of its schedule on a map with time windows, plus the cost of its turns on a
+// map with a turn penalty
+fn cost(map: &Map, tour: &[usize]) -> f32 {
+    let cost = match &map.windows {
+        Some(windows) => windows.evaluate(map, tour),
+        None => length(map, tour)
+    };
+    match &map.turns {
+        Some(turns) => cost + turns.evaluate(map, tour),
+        None => cost
+    }
+}
+
+fn length(map: &Map, tour: &[usize]) -> f32 {
+    if let Some(scenarios) = &map.scenarios {
+        return scenarios.evaluate(tour);
+    }
+    match (&map.distances, &map.costs) {
+        (Some(distances), _) => distances.evaluate(tour),
+        (None, Some(costs)) => sum_lengths(tour.windows(2).map(|edge| costs.get(edge[0], edge[1]))),
+        (None, None) => straight_length(map, tour)
+    }
+}
+
+// Paths of more edges than this have their lengths summed in double precision. The rounding error of a sum in
+// single precision grows with the number of edges, and beyond a few thousand can decide which of two tours is the
+// shorter, while the sums of shorter paths are left as they have always been
+pub(crate) const PRECISE_EDGES: usize = 1024;
+
+// The sum of the lengths of the edges of a path, in double precision if there are more than `PRECISE_EDGES`
+pub(crate) fn sum_lengths<I: ExactSizeIterator<Item = f32>>(lengths: I) -> f32 {
+    if lengths.len() > PRECISE_EDGES {
+        lengths.map(f64::from).sum::<f64>() as f32
+    } else {
+        lengths.sum()
+    }
+}
+
+#[cfg(feature = "simd")]
+fn straight_length(map: &Map, tour: &[usize]) -> f32 {
+    crate::simd::evaluate(map, tour)
+}
+
+#[cfg(not(feature = "simd"))]
+fn straight_length(map: &Map, tour: &[usize]) -> f32 {
+    sum_lengths(tour.windows(2).map(|segment| match (map.get(segment[0]), map.get(segment[1])) {
+        (Some(a), Some(b)) => a.dist(&b),
+        _ => 0.0
+    }))
+}
+
+// The tour with the chains of fixed edges of the map pulled in whole, and then every point moved after its
+// predecessors
+fn repair_constraints(map: &Map, tour: Vec<usize>) -> Vec<usize> {
+    let tour = match &map.edges {
+        Some(edges) => edges.repair(&tour),
+        None => tour
+    };
+    match &map.precedences {
+        Some(precedences) => precedences.repair(&tour),
+        None => tour
+    }
+}
+
+/// Checks that the tour visits every point of the map exactly once.
+pub(crate) fn check_permutation(map: &Map, tour: &[usize]) -> Result<()> {
+    Ok(map.validate_tour(tour)?)
+}
+
+// Everything that keeps the tour from being a permutation of `0..size`
+pub(crate) fn check_tour(size: usize, tour: &[usize]) -> result::Result<(), TourError> {
+    let mut visits = vec![0u32; size];
+    let mut error = TourError::default();
+    for &city in tour {
+        match visits.get_mut(city) {
+            Some(count) => *count = count.saturating_add(1),
+            None => error.out_of_range.push(city)
+        }
+    }
+    for (city, &count) in visits.iter().enumerate() {
+        match count {
+            0 => error.missing.push(city),
+            1 => (),
+            _ => error.duplicates.push(city)
+        }
+    }
+
+    if error == TourError::default() { Ok(()) } else { Err(error) }
+}
+
+thread_local! {
+    static SCRATCH: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+// Evaluates with this thread's scratch buffer, so that fitness evaluation in a GA's loop does not allocate
+fn with_scratch<F: FnOnce(&mut Vec<usize>) -> f32>(evaluate: F) -> f32 {
+    SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut scratch) => evaluate(&mut scratch),
+        // Only reachable if an evaluation evaluates another path, which then gets a buffer of its own
+        Err(_) => evaluate(&mut Vec::new())
+    })
+}
+
+fn check_size(expected: usize, found: usize) -> Result<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(Error::DimensionMismatch {
+            expected,
+            found
+        })
+    }
+}
+
+/// Encodes a path as a sequence of indices into the list of points not yet visited.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemovalIndex {
     path: Box<[usize]>
 }
 
+// Written out so that `clone_from` reuses the buffer, which lets GA generations recycle retired individuals
+impl Clone for RemovalIndex {
+    fn clone(&self) -> Self {
+        RemovalIndex {
+            path: self.path.clone()
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.path.clone_from(&source.path);
+    }
+}
+
 impl RemovalIndex {
     pub fn new(map: &Map) -> Self {
         let mut rng = thread_rng();
@@ -197,32 +1039,40 @@ impl Debug for RemovalIndex {
 
 impl Path for RemovalIndex {
     fn evaluate(&self, map: &Map) -> f32 {
-        if map.size() != self.path.len() + 1 {
-            return 0.0;
-        }
-
-        let mut cloned = map.clone_to_vec();
-        let mut last: Point = cloned.remove(self.path[0]);
-        let mut total: f32 = 0.0;
-        for i in 1..self.path.len() {
-            let current = cloned.remove(self.path[i]);
-            total += current.dist(&last);
-            last = current;
-        }
+        with_scratch(|scratch| self.evaluate_with(map, scratch))
+    }
 
-        total + last.dist(&cloned[0])
+    fn evaluate_with(&self, map: &Map, scratch: &mut Vec<usize>) -> f32 {
+        decoded_cost(self, map, scratch)
     }
 
     fn reorder(&self, map: &mut Map) {
-        if map.size() != self.path.len() + 1 {
-            return;
-        }
+        let tour = self.decode(map);
+        map.permute(&tour);
+    }
+
+    fn tour(&self, map: &Map) -> Vec<usize> {
+        self.decode(map).into_vec()
+    }
+
+    fn validate(&self, map: &Map) -> Result<()> {
+        check_size(map.size(), self.path.len() + 1)?;
 
-        let mut cloned = map.clone_to_vec();
-        for i in 0..self.path.len() {
-            *map.get_mut(i).unwrap() = cloned.remove(self.path[i]);
+        // Each gene indexes into a list that shrinks by one every step
+        match self.path.iter().enumerate().find(|&(i, &index)| index >= map.size() - i) {
+            Some((i, index)) => {
+                Err(Error::InvalidTour(format!("removal index {} at position {} is out of range", index, i)))
+            },
+            None => Ok(())
         }
-        *map.get_mut(map.size() - 1).unwrap() = cloned[0];
+    }
+
+    fn fix(&mut self, map: &Map) {
+        repair_decoded(self, map);
+    }
+
+    fn fix_two_opt(&mut self, map: &Map, neighbors: &Neighbors, passes: u32) {
+        two_opt_decoded(self, map, neighbors, passes);
     }
 }
 
@@ -238,61 +1088,201 @@ impl Chromosome for RemovalIndex {
     fn point_mutation(&mut self, index: usize, rng: &mut impl Rng) {
         self.path[index] = rng.gen::<usize>() % (self.path.len() - index);
     }
+
+    // Moves each removed point to the end of the decoded prefix, keeping the points not yet removed in order
+    // behind it
+    fn decode_into(&self, _map: &Map, tour: &mut Vec<usize>) {
+        tour.clear();
+        tour.extend(0..self.path.len() + 1);
+        for (i, &index) in self.path.iter().enumerate() {
+            tour[i..=i + index].rotate_right(1);
+        }
+    }
+
+    fn encode(&mut self, map: &Map, tour: &[usize]) -> Result<()> {
+        check_size(map.size(), self.path.len() + 1)?;
+        check_permutation(map, tour)?;
+
+        let mut remaining: Vec<usize> = (0..map.size()).collect();
+        for (gene, city) in self.path.iter_mut().zip(tour.iter()) {
+            // Infallible: the tour is a permutation of the map's indices
+            let position = remaining.iter().position(|other| other == city).unwrap();
+            *gene = position;
+            remaining.remove(position);
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+impl Persist for RemovalIndex {
+    fn persist(&self) -> String {
+        self.path.iter().join(" ")
+    }
+
+    fn restore(_map: &Map, line: &str) -> result::Result<Self, String> {
+        Ok(RemovalIndex {
+            path: parse_values(line)?.into_boxed_slice()
+        })
+    }
+}
+
+/// How a [`RandomKeyPath`] turns its keys into a path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Decoder {
+    /// Visit the points in increasing key order
+    #[default]
+    Sorted,
+    /// Start from the point with the lowest key and move on each time to the nearest unvisited point, with the
+    /// distance to every point scaled by one plus its key, as the heuristic decoders of BRKGA do. The keys only
+    /// bias a nearest neighbor tour, so the paths are far shorter than those of sorted keys, but decoding takes
+    /// time quadratic in the size of the map. A path with fixed edges or precedences is repaired after it is
+    /// built, and time windows are not taken into account.
+    ///
+    /// [Encoding](crate::ga::Chromosome::encode) a tour ranks its points in order, which the decoder follows only
+    /// where the tour moves on to a point near enough, so the decoded path can differ from the tour.
+    Greedy
+}
+
+/// Encodes a path as one key per point, turned into a path by its [decoder](Decoder): by default, visiting the
+/// points in increasing key order.
+///
+/// Decoding sorts the keys, which dominates the time the GA spends evaluating, so the sorted order is kept from the
+/// first decoding until the keys next change, and copied along with them when the path is cloned. Greedy decoding
+/// is kept the same way. The repairs of [`Path::fix`] and [`Path::fix_with`] rearrange the keys of a sorted path
+/// and leave a greedy one alone, since it is built from short edges already.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RandomKeyPath {
-    key: Box<[f32]>
+    key: Box<[f32]>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    decoder: Decoder,
+    // The points in increasing key order, once sorted
+    #[cfg_attr(feature = "serde", serde(skip))]
+    order: OnceLock<Box<[usize]>>,
+    // The path the greedy decoder builds from the keys, once decoded
+    #[cfg_attr(feature = "serde", serde(skip))]
+    greedy: OnceLock<Box<[usize]>>
+}
+
+impl Clone for RandomKeyPath {
+    fn clone(&self) -> Self {
+        RandomKeyPath {
+            key: self.key.clone(),
+            decoder: self.decoder,
+            order: self.order.clone(),
+            greedy: self.greedy.clone()
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.key.clone_from(&source.key);
+        self.decoder = source.decoder;
+        self.order.clone_from(&source.order);
+        self.greedy.clone_from(&source.greedy);
+    }
 }
 
 impl RandomKeyPath {
     pub fn new(map: &Map) -> Self {
+        Self::with_rng(map, &mut thread_rng())
+    }
+
+    /// Draws the keys from the given generator.
+    pub fn with_rng<R: Rng + ?Sized>(map: &Map, rng: &mut R) -> Self {
         let mut key = vec![0.0f32; map.size()].into_boxed_slice();
-        let mut rng = thread_rng();
         key.iter_mut().for_each(|element| *element = rng.gen());
 
         RandomKeyPath {
-            key
+            key,
+            decoder: Decoder::Sorted,
+            order: OnceLock::new(),
+            greedy: OnceLock::new()
         }
     }
 
-    fn as_index_path(&self) -> Vec<usize> {
-        self.key.iter()
-            .enumerate()
-            .sorted_by(|(_, a), (_, b)| {
-                a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal)
-            })
-            .map(|(index, _)| index)
-            .collect()
+    /// Random keys decoded [greedily](Decoder::Greedy), for
+    /// [`GaSolverBuilder::chromosome`](crate::ga::GaSolverBuilder::chromosome).
+    pub fn greedy(map: &Map) -> Self {
+        Self::new(map).with_decoder(Decoder::Greedy)
     }
-}
 
-impl Debug for RandomKeyPath {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Debug::fmt(&self.key, f)
+    pub fn with_decoder(mut self, decoder: Decoder) -> Self {
+        self.decoder = decoder;
+        self.greedy.take();
+        self
     }
-}
 
-impl Path for RandomKeyPath {
-    fn evaluate(&self, map: &Map) -> f32 {
-        self.as_index_path().evaluate(map)
+    pub fn decoder(&self) -> Decoder {
+        self.decoder
     }
 
-    fn reorder(&self, map: &mut Map) {
-        self.as_index_path().reorder(map)
+    pub fn inner(&self) -> &[f32] {
+        &self.key
     }
 
-    fn fix(&mut self, map: &Map) {
+    /// The keys, for changing them in place. The cached order is dropped, whether or not they are changed.
+    pub fn inner_mut(&mut self) -> &mut Box<[f32]> {
+        self.changed();
+        &mut self.key
+    }
+
+    /// The points in increasing key order, sorting the keys only if they have changed since the last decoding.
+    /// Equal keys keep their index order.
+    pub fn order(&self) -> &[usize] {
+        self.order.get_or_init(|| {
+            let mut path: Vec<usize> = (0..self.key.len()).collect();
+            path.sort_by(|&a, &b| self.key[a].total_cmp(&self.key[b]));
+            debug_assert_eq!(check_tour(path.len(), &path), Ok(()));
+            path.into_boxed_slice()
+        })
+    }
+
+    // The path the decoder makes of the keys
+    fn path(&self, map: &Map) -> &[usize] {
+        match self.decoder {
+            Decoder::Sorted => self.order(),
+            Decoder::Greedy => self.greedy.get_or_init(|| self.decode_greedy(map))
+        }
+    }
+
+    fn decode_greedy(&self, map: &Map) -> Box<[usize]> {
+        let mut unvisited: Vec<usize> = (0..self.key.len()).collect();
+        let mut path = Vec::with_capacity(unvisited.len());
+        let mut next = unvisited.iter()
+            .enumerate()
+            .min_by(|(_, &a), (_, &b)| self.key[a].total_cmp(&self.key[b]))
+            .map(|(index, _)| index);
+        while let Some(index) = next {
+            let current = unvisited.swap_remove(index);
+            path.push(current);
+            next = unvisited.iter()
+                .map(|&point| map.dist(current, point) * (1.0 + self.key[point]))
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index);
+        }
+        repair_constraints(map, path).into_boxed_slice()
+    }
+
+    // Drops the decoded paths after the keys change
+    fn changed(&mut self) {
+        self.order.take();
+        self.greedy.take();
+    }
+
+    // Swaps neighboring points whose order makes a crossing, and the points at either end of the path
+    fn fix_edges(&mut self, map: &Map) {
         let len = self.key.len();
-        if len < 3 {
+        // The repairs only compare distances, which can make a tour later or turn more even as they shorten it
+        if len < 3 || map.windows.is_some() || map.turns.is_some() {
             return;
         }
 
+        self.order.take();
         let mut index_path: Vec<_> = self.key.iter_mut()
             .enumerate()
-            .sorted_by(|(_, a), (_, b)| {
-                a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal)
-            })
+            .sorted_by(|(_, a), (_, b)| a.total_cmp(b))
             .collect();
 
         // Fix the middle
@@ -301,8 +1291,8 @@ impl Path for RandomKeyPath {
             while j < index_path.len() - 3 {
                 // Take a group of four and evaluate the current and alternative path lengths
                 let section = &mut index_path[j..j + 4];
-                let current = map[section[0].0].dist(&map[section[1].0]) + map[section[2].0].dist(&map[section[3].0]);
-                let alternative = map[section[0].0].dist(&map[section[2].0]) + map[section[1].0].dist(&map[section[3].0]);
+                let current = map.dist(section[0].0, section[1].0) + map.dist(section[2].0, section[3].0);
+                let alternative = map.dist(section[0].0, section[2].0) + map.dist(section[1].0, section[3].0);
 
                 // If the mid-swap improved the path length, apply it
                 if alternative < current {
@@ -320,9 +1310,9 @@ impl Path for RandomKeyPath {
         }
 
         // Fix the start point
-        let anchor = &map[index_path[2].0];
-        let current = map[index_path[1].0].dist_sq(anchor);
-        let alternative = map[index_path[0].0].dist_sq(anchor);
+        let anchor = index_path[2].0;
+        let current = map.dist(index_path[1].0, anchor);
+        let alternative = map.dist(index_path[0].0, anchor);
         if alternative < current {
             let (first, second) = index_path.split_at_mut(1);
             mem::swap(first[0].1, second[0].1);
@@ -330,15 +1320,149 @@ impl Path for RandomKeyPath {
 
         // Fix the end point
         if len > 5 {
-            let anchor = &map[index_path[len - 3].0];
-            let current = map[index_path[len - 2].0].dist_sq(anchor);
-            let alternative = map[index_path[len - 1].0].dist_sq(anchor);
+            let anchor = index_path[len - 3].0;
+            let current = map.dist(index_path[len - 2].0, anchor);
+            let alternative = map.dist(index_path[len - 1].0, anchor);
             if alternative < current {
                 let (first, second) = index_path.split_at_mut(len - 1);
                 mem::swap(first[len - 2].1, second[0].1);
             }
         }
     }
+
+    // Looks for shortcuts to each point's nearest neighbors further along the path
+    fn join_neighbors(&mut self, map: &Map, neighbors: &Neighbors) {
+        let len = self.key.len();
+        if len < 3 || map.windows.is_some() || map.turns.is_some() {
+            return;
+        }
+
+        let mut path = self.as_index_path();
+        let keys: Vec<f32> = path.iter().map(|&city| self.key[city]).collect();
+        let mut position = vec![0; len];
+        for (i, &city) in path.iter().enumerate() {
+            position[city] = i;
+        }
+
+        // Make one pass of 2-opt moves, each joining a point to a candidate further along the path
+        for i in 0..len - 1 {
+            let (a, b) = (path[i], path[i + 1]);
+            let current = map.dist(a, b);
+            for c in neighbors.of(a) {
+                let joined = map.dist(a, c);
+                // Candidates are ordered by distance, so no later one can shorten the path either
+                if joined >= current {
+                    break;
+                }
+
+                let j = position[c];
+                if j <= i + 1 {
+                    continue;
+                }
+
+                let (removed, added) = match path.get(j + 1) {
+                    Some(&d) => (current + map.dist(c, d), joined + map.dist(b, d)),
+                    None => (current, joined)
+                };
+                if added < removed {
+                    path[i + 1..=j].reverse();
+                    for (k, &city) in path.iter().enumerate().take(j + 1).skip(i + 1) {
+                        position[city] = k;
+                    }
+                    break;
+                }
+            }
+        }
+
+        self.hand_out_keys(&path, &keys);
+    }
+
+    // Hands the keys, sorted, to the points of the path in order
+    fn hand_out_keys(&mut self, path: &[usize], keys: &[f32]) {
+        for (&city, &key) in path.iter().zip(keys.iter()) {
+            self.key[city] = key;
+        }
+        self.order.take();
+    }
+
+    // Hands the keys out again so that the path takes every chain of fixed edges whole and visits every point
+    // after its predecessors
+    fn fix_constraints(&mut self, map: &Map) {
+        if map.edges.is_none() && map.precedences.is_none() {
+            return;
+        }
+
+        let path = self.as_index_path();
+        let repaired = repair_constraints(map, path.clone());
+        if repaired != path {
+            // Crossover can leave equal keys, which would sort by index rather than in the repaired order, so
+            // nudge each key above the one before it
+            let keys: Vec<f32> = path.iter().map(|&city| self.key[city]).collect();
+            let mut previous = f32::NEG_INFINITY;
+            for (&city, &key) in repaired.iter().zip(keys.iter()) {
+                previous = if key > previous { key } else { previous.next_up() };
+                self.key[city] = previous;
+            }
+            // The keys now increase strictly along the repaired path, so it is their order
+            self.order = OnceLock::from(repaired.into_boxed_slice());
+        }
+    }
+
+    fn as_index_path(&self) -> Vec<usize> {
+        self.order().to_vec()
+    }
+}
+
+impl Debug for RandomKeyPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.key, f)
+    }
+}
+
+impl Path for RandomKeyPath {
+    fn evaluate(&self, map: &Map) -> f32 {
+        cost(map, self.path(map))
+    }
+
+    fn reorder(&self, map: &mut Map) {
+        self.path(map).reorder(map)
+    }
+
+    fn tour(&self, map: &Map) -> Vec<usize> {
+        self.path(map).to_vec()
+    }
+
+    fn validate(&self, map: &Map) -> Result<()> {
+        check_size(map.size(), self.key.len())
+    }
+
+    fn fix(&mut self, map: &Map) {
+        if self.decoder == Decoder::Sorted {
+            self.fix_edges(map);
+            self.fix_constraints(map);
+        }
+    }
+
+    fn fix_with(&mut self, map: &Map, neighbors: &Neighbors) {
+        if self.decoder == Decoder::Sorted {
+            self.fix_edges(map);
+            self.join_neighbors(map, neighbors);
+            self.fix_constraints(map);
+        }
+    }
+
+    fn fix_two_opt(&mut self, map: &Map, neighbors: &Neighbors, passes: u32) {
+        if self.decoder == Decoder::Sorted {
+            let order = self.as_index_path();
+            let mut path = order.clone();
+            two_opt_passes(map, &mut path, neighbors, passes);
+            if path != order {
+                let keys: Vec<f32> = order.iter().map(|&city| self.key[city]).collect();
+                self.hand_out_keys(&path, &keys);
+            }
+            self.fix_constraints(map);
+        }
+    }
 }
 
 impl Chromosome for RandomKeyPath {
@@ -348,19 +1472,79 @@ impl Chromosome for RandomKeyPath {
 
     fn crossover(&mut self, other: &mut Self, start: usize, end: usize) {
         slice_crossover(&mut self.key, &mut other.key, start, end);
+        self.changed();
+        other.changed();
     }
 
     fn point_mutation(&mut self, index: usize, rng: &mut impl Rng) {
         self.key[index] = rng.gen();
+        self.changed();
+    }
+
+    fn decode_into(&self, map: &Map, tour: &mut Vec<usize>) {
+        tour.clear();
+        tour.extend_from_slice(self.path(map));
+    }
+
+    fn encode(&mut self, map: &Map, tour: &[usize]) -> Result<()> {
+        check_size(map.size(), self.key.len())?;
+        check_permutation(map, tour)?;
+
+        let len = tour.len() as f32;
+        for (position, &city) in tour.iter().enumerate() {
+            self.key[city] = position as f32 / len;
+        }
+        self.changed();
+
+        Ok(())
     }
 }
 
-#[derive(Clone)]
+impl Persist for RandomKeyPath {
+    // Greedy paths are marked by a leading `greedy`, which leaves the lines of sorted ones as they were
+    fn persist(&self) -> String {
+        // f32's Display output parses back to the same value, so keys survive the round trip exactly
+        match self.decoder {
+            Decoder::Sorted => self.key.iter().join(" "),
+            Decoder::Greedy => format!("greedy {}", self.key.iter().join(" "))
+        }
+    }
+
+    fn restore(_map: &Map, line: &str) -> result::Result<Self, String> {
+        let (decoder, keys) = match line.strip_prefix("greedy ") {
+            Some(keys) => (Decoder::Greedy, keys),
+            None => (Decoder::Sorted, line)
+        };
+        Ok(RandomKeyPath {
+            key: parse_values(keys)?.into_boxed_slice(),
+            decoder,
+            order: OnceLock::new(),
+            greedy: OnceLock::new()
+        })
+    }
+}
+
+/// Encodes a path as a sequence of swaps applied to the map's own ordering.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SwapPath {
     swaps: Box<[usize]>,
     map_size: usize
 }
 
+impl Clone for SwapPath {
+    fn clone(&self) -> Self {
+        SwapPath {
+            swaps: self.swaps.clone(),
+            map_size: self.map_size
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.swaps.clone_from(&source.swaps);
+        self.map_size = source.map_size;
+    }
+}
+
 impl SwapPath {
     pub fn new(map: &Map, swap_count: usize) -> Self {
         let map_size = map.size();
@@ -386,19 +1570,38 @@ impl Debug for SwapPath {
 
 impl Path for SwapPath {
     fn evaluate(&self, map: &Map) -> f32 {
-        let mut cloned = map.clone_to_vec();
-        for swap in self.swaps.chunks(2) {
-            cloned.swap(swap[0], swap[1]);
-        }
+        with_scratch(|scratch| self.evaluate_with(map, scratch))
+    }
 
-        cloned.windows(2).map(|points| points[0].dist(&points[1])).sum::<f32>()
+    fn evaluate_with(&self, map: &Map, scratch: &mut Vec<usize>) -> f32 {
+        decoded_cost(self, map, scratch)
     }
 
     fn reorder(&self, map: &mut Map) {
-        for swap in self.swaps.chunks(2) {
-            map.swap(swap[0], swap[1]);
+        let tour = self.decode(map);
+        map.permute(&tour);
+    }
+
+    fn tour(&self, map: &Map) -> Vec<usize> {
+        self.decode(map).into_vec()
+    }
+
+    fn validate(&self, map: &Map) -> Result<()> {
+        check_size(map.size(), self.map_size)?;
+
+        match self.swaps.iter().find(|&&index| index >= self.map_size) {
+            Some(index) => Err(Error::InvalidTour(format!("swap index {} is out of range", index))),
+            None => Ok(())
         }
     }
+
+    fn fix(&mut self, map: &Map) {
+        repair_decoded(self, map);
+    }
+
+    fn fix_two_opt(&mut self, map: &Map, neighbors: &Neighbors, passes: u32) {
+        two_opt_decoded(self, map, neighbors, passes);
+    }
 }
 
 impl Chromosome for SwapPath {
@@ -413,4 +1616,61 @@ impl Chromosome for SwapPath {
     fn point_mutation(&mut self, index: usize, rng: &mut impl Rng) {
         self.swaps[index] = rng.gen::<usize>() % self.map_size;
     }
-}
\ No newline at end of file
+
+    // Applies the swaps to the identity ordering
+    fn decode_into(&self, _map: &Map, tour: &mut Vec<usize>) {
+        tour.clear();
+        tour.extend(0..self.map_size);
+        for swap in self.swaps.chunks(2) {
+            tour.swap(swap[0], swap[1]);
+        }
+    }
+
+    fn encode(&mut self, map: &Map, tour: &[usize]) -> Result<()> {
+        check_size(map.size(), self.map_size)?;
+        check_permutation(map, tour)?;
+
+        // Selection sort the identity ordering into the tour, recording each swap
+        let mut order: Vec<usize> = (0..map.size()).collect();
+        let mut written = 0;
+        for (i, city) in tour.iter().enumerate() {
+            // Infallible: the tour is a permutation of the map's indices
+            let position = order.iter().position(|other| other == city).unwrap();
+            if position == i {
+                continue;
+            }
+
+            if written + 2 > self.swaps.len() {
+                let reason = format!("more than {} swaps are needed to encode the tour", self.swaps.len() / 2);
+                return Err(Error::InvalidTour(reason));
+            }
+
+            self.swaps[written] = i;
+            self.swaps[written + 1] = position;
+            order.swap(i, position);
+            written += 2;
+        }
+
+        // Pad with no-op swaps
+        self.swaps[written..].iter_mut().for_each(|swap| *swap = 0);
+        Ok(())
+    }
+}
+
+impl Persist for SwapPath {
+    fn persist(&self) -> String {
+        self.swaps.iter().join(" ")
+    }
+
+    fn restore(map: &Map, line: &str) -> result::Result<Self, String> {
+        let swaps: Vec<usize> = parse_values(line)?;
+        if !swaps.len().is_multiple_of(2) {
+            return Err(format!("expected pairs of swap indices, found {} values", swaps.len()));
+        }
+
+        Ok(SwapPath {
+            swaps: swaps.into_boxed_slice(),
+            map_size: map.size()
+        })
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::{self, Debug, Formatter};
+use std::mem;
+use std::ops::Index;
+
+use crate::error::{Error, Result};
+use crate::map::{Map, sum_lengths};
+
+/// The most memory [`DistanceMatrix::try_new`] and the solvers that build a structure quadratic in the size of the
+/// map take for it, in bytes, so that a large instance fails with [`Error::TooLarge`] instead of exhausting the
+/// machine's memory. About 46,000 points fit in a distance matrix of this size.
+pub const QUADRATIC_MEMORY_LIMIT: u64 = 4 << 30;
+
+/// Precomputed distances between every pair of points of a symmetric instance. Only the pairs above the diagonal
+/// are stored, row by row in one allocation, which takes half the memory of a full matrix and keeps the distances
+/// from a point to those after it contiguous.
+#[derive(Clone)]
+pub struct DistanceMatrix {
+    size: usize,
+    distances: Box<[f32]>
+}
+
+impl DistanceMatrix {
+    /// The distances between the map's points: its own if it [has them](Map::with_distances), its
+    /// [costs](Map::with_costs) if it has those, and otherwise the straight line distances.
+    #[cfg(not(feature = "simd"))]
+    pub fn new(map: &Map) -> Self {
+        match map.distances() {
+            Some(distances) => distances.clone(),
+            None => Self::from_fn(map.size(), |i, j| map.dist(i, j))
+        }
+    }
+
+    /// The distances between the map's points: its own if it [has them](Map::with_distances), its
+    /// [costs](Map::with_costs) if it has those, and otherwise the straight line distances, computed a row at a
+    /// time.
+    #[cfg(feature = "simd")]
+    pub fn new(map: &Map) -> Self {
+        if let Some(distances) = map.distances() {
+            return distances.clone();
+        }
+        if map.has_costs() {
+            return Self::from_fn(map.size(), |i, j| map.dist(i, j));
+        }
+
+        let size = map.size();
+        let points = map.as_slice();
+        let mut distances = vec![0f32; size * size.saturating_sub(1) / 2];
+        let mut row = &mut distances[..];
+        for i in 0..size {
+            let (current, rest) = row.split_at_mut(size - i - 1);
+            crate::simd::distances(points[i], &points[i + 1..], current);
+            row = rest;
+        }
+
+        DistanceMatrix {
+            size,
+            distances: distances.into_boxed_slice()
+        }
+    }
+
+    /// Like [`new`](Self::new), but fails unless the matrix fits in [`QUADRATIC_MEMORY_LIMIT`].
+    pub fn try_new(map: &Map) -> Result<Self> {
+        check_quadratic(Self::memory(map.size()), "a distance matrix", map.size())?;
+        Ok(Self::new(map))
+    }
+
+    /// The bytes a matrix of `size` points takes.
+    pub fn memory(size: usize) -> u64 {
+        let pairs = size as u64 * (size as u64).saturating_sub(1) / 2;
+        pairs.saturating_mul(mem::size_of::<f32>() as u64)
+    }
+
+    /// Stores `distance(i, j)` for every pair of points with `i < j`, which is all that a symmetric instance needs.
+    pub fn from_fn<F: Fn(usize, usize) -> f32>(size: usize, distance: F) -> Self {
+        let mut distances = Vec::with_capacity(size * size.saturating_sub(1) / 2);
+        for i in 0..size {
+            distances.extend((i + 1..size).map(|j| distance(i, j)));
+        }
+
+        DistanceMatrix {
+            size,
+            distances: distances.into_boxed_slice()
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The distance between two points, which is zero from a point to itself.
+    #[inline]
+    pub fn get(&self, i: usize, j: usize) -> f32 {
+        if i == j {
+            0.0
+        } else {
+            self.distances[self.index(i, j)]
+        }
+    }
+
+    /// The length of the path visiting the points in the given order, like [`Path::evaluate`](crate::map::Path).
+    pub fn evaluate(&self, tour: &[usize]) -> f32 {
+        sum_lengths(tour.windows(2).map(|edge| self.get(edge[0], edge[1])))
+    }
+
+    // The first pair of points whose distance is NaN, if any
+    pub(crate) fn find_nan(&self) -> Option<(usize, usize)> {
+        let mut position = self.distances.iter().position(|distance| distance.is_nan())?;
+        for i in 0..self.size {
+            let row = self.size - i - 1;
+            if position < row {
+                return Some((i, i + 1 + position));
+            }
+            position -= row;
+        }
+        None
+    }
+
+    // Follows the map swapping two of its points. The distance between the two stays, and every other point's
+    // distances to them trade places.
+    pub(crate) fn swap(&mut self, first: usize, second: usize) {
+        if first == second {
+            return;
+        }
+        for other in (0..self.size).filter(|&other| other != first && other != second) {
+            let (a, b) = (self.index(first, other), self.index(second, other));
+            self.distances.swap(a, b);
+        }
+    }
+
+    // The packed distances, row by row, for uploading elsewhere
+    #[cfg(feature = "gpu")]
+    pub(crate) fn packed(&self) -> &[f32] {
+        &self.distances
+    }
+
+    // Row `i` holds the distances to the points after it, so it starts after the `i` rows above it, which hold
+    // `(size - 1) + (size - 2) + ... + (size - i)` distances
+    #[inline]
+    fn index(&self, i: usize, j: usize) -> usize {
+        let (i, j) = if i < j { (i, j) } else { (j, i) };
+        debug_assert!(j < self.size, "Point {} is out of range", j);
+        i * (2 * self.size - i - 1) / 2 + (j - i - 1)
+    }
+}
+
+/// Fails with [`Error::TooLarge`] if `memory` bytes for `what`, a structure quadratic in the `size` of the map, are
+/// over [`QUADRATIC_MEMORY_LIMIT`].
+pub(crate) fn check_quadratic(memory: u64, what: &str, size: usize) -> Result<()> {
+    if memory <= QUADRATIC_MEMORY_LIMIT {
+        return Ok(());
+    }
+
+    Err(Error::TooLarge(format!(
+        "{} for {} points takes {} MiB, over the limit of {} MiB",
+        what,
+        size,
+        memory.div_ceil(1 << 20),
+        QUADRATIC_MEMORY_LIMIT >> 20
+    )))
+}
+
+impl Index<(usize, usize)> for DistanceMatrix {
+    type Output = f32;
+
+    #[inline]
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        if i == j {
+            &0.0
+        } else {
+            &self.distances[self.index(i, j)]
+        }
+    }
+}
+
+impl Debug for DistanceMatrix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DistanceMatrix").field("size", &self.size).finish()
+    }
+}
@@ -0,0 +1,614 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use std::mem;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::ga::check_population;
+use crate::map::Map;
+use crate::solve::{Budget, Reporter, SolveOptions, Termination, check_map};
+use crate::time::Instant;
+
+/// What the routes of a multiple traveling salesmen problem are judged by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Objective {
+    /// The sum of the lengths of every route
+    #[default]
+    Total,
+    /// The length of the longest route, which spreads the work evenly between the salesmen
+    Longest
+}
+
+/// The multiple traveling salesmen problem: `salesmen` closed routes, each leaving the depot and returning to it,
+/// which between them visit every other point of the map exactly once. Every salesman visits at least one point.
+#[derive(Clone, Copy, Debug)]
+pub struct Problem<'a> {
+    pub map: &'a Map,
+    pub depot: usize,
+    pub salesmen: usize,
+    pub objective: Objective
+}
+
+impl Problem<'_> {
+    /// Checks that the depot is a point of the map and that there are enough other points for every salesman.
+    pub fn validate(&self) -> Result<()> {
+        check_map(self.map)?;
+        if self.depot >= self.map.size() {
+            return Err(Error::InvalidProblem(format!("the depot {} is not a point of the map", self.depot)));
+        }
+        if self.salesmen == 0 || self.salesmen > self.map.size() - 1 {
+            return Err(Error::InvalidProblem(format!(
+                "{} salesmen cannot each visit one of the {} points besides the depot",
+                self.salesmen,
+                self.map.size() - 1
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The length of the route from the depot through the points in order and back to the depot.
+    pub fn route_length(&self, route: &[usize]) -> f32 {
+        route_length(self.map, self.depot, route)
+    }
+
+    /// The cost of the routes under the problem's objective.
+    pub fn cost<R: AsRef<[usize]>>(&self, routes: &[R]) -> f32 {
+        let lengths = routes.iter().map(|route| self.route_length(route.as_ref()));
+        match self.objective {
+            Objective::Total => lengths.sum(),
+            Objective::Longest => lengths.fold(0.0, f32::max)
+        }
+    }
+
+    /// Checks that there is one non-empty route per salesman and that together they visit every point but the
+    /// depot exactly once.
+    pub fn check_routes<R: AsRef<[usize]>>(&self, routes: &[R]) -> Result<()> {
+        check_routes(self.map, &[self.depot], self.salesmen, routes)
+    }
+}
+
+/// The multiple traveling salesmen problem with several depots: every salesman leaves one of the `depots` and
+/// returns to it, and which depot each salesman uses is part of the solution, as when technicians may start from
+/// any of a company's offices. Salesmen may share a depot and depots may go unused. Every point that is not a
+/// depot is visited exactly once, and every salesman visits at least one point.
+#[derive(Clone, Copy, Debug)]
+pub struct MultiDepotProblem<'a> {
+    pub map: &'a Map,
+    pub depots: &'a [usize],
+    pub salesmen: usize,
+    pub objective: Objective
+}
+
+impl MultiDepotProblem<'_> {
+    /// Checks that there is at least one depot, that the depots are distinct points of the map, and that there are
+    /// enough other points for every salesman.
+    pub fn validate(&self) -> Result<()> {
+        check_map(self.map)?;
+        if self.depots.is_empty() {
+            return Err(Error::InvalidProblem("there are no depots".to_string()));
+        }
+        if let Some(depot) = self.depots.iter().find(|&&depot| depot >= self.map.size()) {
+            return Err(Error::InvalidProblem(format!("the depot {} is not a point of the map", depot)));
+        }
+        let mut seen = vec![false; self.map.size()];
+        for &depot in self.depots {
+            if mem::replace(&mut seen[depot], true) {
+                return Err(Error::InvalidProblem(format!("the depot {} is listed more than once", depot)));
+            }
+        }
+
+        let others = self.map.size() - self.depots.len();
+        if self.salesmen == 0 || self.salesmen > others {
+            return Err(Error::InvalidProblem(format!(
+                "{} salesmen cannot each visit one of the {} points besides the depots",
+                self.salesmen,
+                others
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The length of the route from the depot through the points in order and back to the depot.
+    pub fn route_length(&self, depot: usize, route: &[usize]) -> f32 {
+        route_length(self.map, depot, route)
+    }
+
+    /// The cost of the routes under the problem's objective, where `depots` holds the depot of every route.
+    pub fn cost<R: AsRef<[usize]>>(&self, depots: &[usize], routes: &[R]) -> f32 {
+        let lengths = depots.iter().zip(routes).map(|(&depot, route)| self.route_length(depot, route.as_ref()));
+        match self.objective {
+            Objective::Total => lengths.sum(),
+            Objective::Longest => lengths.fold(0.0, f32::max)
+        }
+    }
+
+    /// Checks that there is one depot of the problem and one non-empty route per salesman, and that together the
+    /// routes visit every point but the depots exactly once.
+    pub fn check_routes<R: AsRef<[usize]>>(&self, depots: &[usize], routes: &[R]) -> Result<()> {
+        if depots.len() != self.salesmen {
+            return Err(Error::DimensionMismatch {
+                expected: self.salesmen,
+                found: depots.len()
+            });
+        }
+        if let Some(depot) = depots.iter().find(|depot| !self.depots.contains(depot)) {
+            return Err(Error::InvalidTour(format!("{} is not one of the depots", depot)));
+        }
+
+        check_routes(self.map, self.depots, self.salesmen, routes)
+    }
+
+    // The points every salesman visits between them
+    fn customers(&self) -> Vec<usize> {
+        (0..self.map.size()).filter(|point| !self.depots.contains(point)).collect()
+    }
+}
+
+fn check_routes<R: AsRef<[usize]>>(map: &Map, depots: &[usize], salesmen: usize, routes: &[R]) -> Result<()> {
+    if routes.len() != salesmen {
+        return Err(Error::DimensionMismatch {
+            expected: salesmen,
+            found: routes.len()
+        });
+    }
+
+    let mut seen = vec![false; map.size()];
+    depots.iter().for_each(|&depot| seen[depot] = true);
+    for (salesman, route) in routes.iter().enumerate() {
+        let route = route.as_ref();
+        if route.is_empty() {
+            return Err(Error::InvalidTour(format!("salesman {} visits no points", salesman)));
+        }
+
+        for &point in route {
+            match seen.get_mut(point) {
+                Some(true) if depots.contains(&point) => {
+                    return Err(Error::InvalidTour(format!("salesman {} visits the depot {}", salesman, point)));
+                },
+                Some(true) => return Err(Error::InvalidTour(format!("point {} is visited more than once", point))),
+                Some(flag) => *flag = true,
+                None => return Err(Error::InvalidTour(format!("point {} is out of range", point)))
+            }
+        }
+    }
+
+    match seen.iter().position(|&flag| !flag) {
+        Some(point) => Err(Error::InvalidTour(format!("point {} is not visited", point))),
+        None => Ok(())
+    }
+}
+
+// The length of a closed route leaving the depot and returning to it, which is zero for an empty route
+pub(crate) fn route_length(map: &Map, depot: usize, route: &[usize]) -> f32 {
+    match (route.first(), route.last()) {
+        (Some(&first), Some(&last)) => {
+            let inner: f32 = route.windows(2).map(|edge| map.dist(edge[0], edge[1])).sum();
+            map.dist(depot, first) + inner + map.dist(last, depot)
+        },
+        _ => 0.0
+    }
+}
+
+/// Routes for a multiple traveling salesmen problem, or for a vehicle routing problem. Serializes with the
+/// elapsed time in seconds.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MultiSolution {
+    /// The points each salesman visits in order, leaving out the depot at both ends
+    pub routes: Vec<Vec<usize>>,
+    /// The depot each route leaves and returns to
+    pub depots: Vec<usize>,
+    /// The length of each route, including the edges to and from the depot
+    pub lengths: Vec<f32>,
+    /// The total or longest length, depending on the objective, and always the total for vehicle routing
+    pub cost: f32,
+    /// Generations run, or points placed by a constructive heuristic
+    pub iterations: u64,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::time::serialize_seconds"))]
+    pub elapsed: Duration,
+    pub termination: Termination
+}
+
+impl MultiSolution {
+    pub fn new(problem: &Problem<'_>, routes: Vec<Vec<usize>>, iterations: u64, start: Instant) -> Self {
+        MultiSolution {
+            lengths: routes.iter().map(|route| problem.route_length(route)).collect(),
+            cost: problem.cost(&routes),
+            depots: vec![problem.depot; routes.len()],
+            routes,
+            iterations,
+            elapsed: start.elapsed(),
+            termination: Termination::Completed
+        }
+    }
+
+    /// A solution to a [`MultiDepotProblem`], where `depots` holds the depot of every route.
+    pub fn multi_depot(
+        problem: &MultiDepotProblem<'_>,
+        depots: Vec<usize>,
+        routes: Vec<Vec<usize>>,
+        iterations: u64,
+        start: Instant
+    ) -> Self {
+        MultiSolution {
+            lengths: depots.iter().zip(&routes).map(|(&depot, route)| problem.route_length(depot, route)).collect(),
+            cost: problem.cost(&depots, &routes),
+            routes,
+            depots,
+            iterations,
+            elapsed: start.elapsed(),
+            termination: Termination::Completed
+        }
+    }
+}
+
+/// Builds routes greedily. Each salesman first gets the nearest free point to the depot, in turn, and then the
+/// routes are extended one point at a time: for the total length by the cheapest extension of any route, and for
+/// the longest route by extending the currently shortest route to its nearest free point.
+pub fn greedy(problem: &Problem<'_>) -> Result<MultiSolution> {
+    let start = Instant::now();
+    problem.validate()?;
+
+    let free: Vec<usize> = (0..problem.map.size()).filter(|&point| point != problem.depot).collect();
+    let routes = greedy_routes(problem.map, &vec![problem.depot; problem.salesmen], free, problem.objective);
+    let placed = problem.map.size() - 1;
+    Ok(MultiSolution::new(problem, routes, placed as u64, start))
+}
+
+/// Builds routes greedily as [`greedy`] does, after handing the depots out to the salesmen in turn, so that every
+/// depot is used if there are at least as many salesmen as depots.
+pub fn greedy_multi_depot(problem: &MultiDepotProblem<'_>) -> Result<MultiSolution> {
+    let start = Instant::now();
+    problem.validate()?;
+
+    let depots: Vec<usize> = (0..problem.salesmen)
+        .map(|salesman| problem.depots[salesman % problem.depots.len()])
+        .collect();
+    let free = problem.customers();
+    let placed = free.len();
+    let routes = greedy_routes(problem.map, &depots, free, problem.objective);
+    Ok(MultiSolution::multi_depot(problem, depots, routes, placed as u64, start))
+}
+
+// The greedy routes of salesmen leaving the given depots, one per salesman, through the free points
+fn greedy_routes(map: &Map, depots: &[usize], mut free: Vec<usize>, objective: Objective) -> Vec<Vec<usize>> {
+    let salesmen = depots.len();
+    let mut routes: Vec<Vec<usize>> = vec![Vec::new(); salesmen];
+    // The open length of each route, from the depot to its last point
+    let mut lengths = vec![0f32; salesmen];
+
+    let nearest = |from: usize, free: &[usize]| {
+        free.iter()
+            .enumerate()
+            .map(|(index, &point)| (index, map.dist(from, point)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    };
+
+    let mut placed = 0;
+    while !free.is_empty() {
+        let salesman = if placed < salesmen {
+            placed
+        } else {
+            match objective {
+                Objective::Total => (0..salesmen)
+                    // Infallible: there are free points
+                    .map(|salesman| (salesman, nearest(*routes[salesman].last().unwrap(), &free).unwrap().1))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(salesman, _)| salesman)
+                    .unwrap(),
+                Objective::Longest => (0..salesmen)
+                    .min_by(|&a, &b| lengths[a].total_cmp(&lengths[b]))
+                    .unwrap()
+            }
+        };
+
+        let from = routes[salesman].last().copied().unwrap_or(depots[salesman]);
+        // Infallible: there are free points
+        let (index, distance) = nearest(from, &free).unwrap();
+        routes[salesman].push(free.swap_remove(index));
+        lengths[salesman] += distance;
+        placed += 1;
+    }
+    routes
+}
+
+/// A multi-chromosome encoding: one list of points per salesman, the order in which that salesman visits them.
+/// Mutations reorder points within a route and move them between routes, never leaving a route empty.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiChromosome {
+    routes: Vec<Vec<usize>>
+}
+
+impl MultiChromosome {
+    /// Shuffles the points besides the depot and cuts them into one non-empty route per salesman at random.
+    pub fn random<R: Rng + ?Sized>(problem: &Problem<'_>, rng: &mut R) -> Self {
+        let points: Vec<usize> = (0..problem.map.size()).filter(|&point| point != problem.depot).collect();
+        Self::shuffled(points, problem.salesmen, rng)
+    }
+
+    // Shuffles the points and cuts them into one non-empty route per salesman at random
+    fn shuffled<R: Rng + ?Sized>(mut points: Vec<usize>, salesmen: usize, rng: &mut R) -> Self {
+        points.shuffle(rng);
+
+        let mut cuts = rand::seq::index::sample(rng, points.len() - 1, salesmen - 1).into_vec();
+        cuts.iter_mut().for_each(|cut| *cut += 1);
+        cuts.sort_unstable();
+        cuts.push(points.len());
+
+        let mut routes = Vec::with_capacity(salesmen);
+        let mut from = 0;
+        for cut in cuts {
+            routes.push(points[from..cut].to_vec());
+            from = cut;
+        }
+
+        MultiChromosome {
+            routes
+        }
+    }
+
+    /// Wraps routes such as those of a [`MultiSolution`], which should pass [`Problem::check_routes`].
+    pub fn from_routes(routes: Vec<Vec<usize>>) -> Self {
+        MultiChromosome {
+            routes
+        }
+    }
+
+    pub fn routes(&self) -> &[Vec<usize>] {
+        &self.routes
+    }
+
+    pub fn into_routes(self) -> Vec<Vec<usize>> {
+        self.routes
+    }
+
+    /// Applies one mutation chosen at random: reversing or swapping within a route, or, with more than one route,
+    /// moving a segment to another route or exchanging segments between two routes.
+    pub fn mutate<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let count = self.routes.len();
+        let kinds = if count > 1 { 4 } else { 2 };
+        match rng.gen_range(0, kinds) {
+            0 => {
+                let route = &mut self.routes[rng.gen_range(0, count)];
+                let (from, to) = segment(route.len(), rng);
+                route[from..to].reverse();
+            },
+            1 => {
+                let route = &mut self.routes[rng.gen_range(0, count)];
+                let (a, b) = (rng.gen_range(0, route.len()), rng.gen_range(0, route.len()));
+                route.swap(a, b);
+            },
+            2 => {
+                let (source, target) = two_routes(count, rng);
+                // Leave at least one point behind
+                if self.routes[source].len() < 2 {
+                    return;
+                }
+                let (from, to) = segment(self.routes[source].len() - 1, rng);
+                let moved: Vec<usize> = self.routes[source].drain(from..to).collect();
+                let at = rng.gen_range(0, self.routes[target].len() + 1);
+                self.routes[target].splice(at..at, moved);
+            },
+            _ => {
+                let (first, second) = two_routes(count, rng);
+                let (first_from, first_to) = segment(self.routes[first].len(), rng);
+                let (second_from, second_to) = segment(self.routes[second].len(), rng);
+                let from_first: Vec<usize> = self.routes[first].drain(first_from..first_to).collect();
+                let from_second: Vec<usize> = self.routes[second].splice(second_from..second_to, from_first).collect();
+                self.routes[first].splice(first_from..first_from, from_second);
+            }
+        }
+    }
+}
+
+// A random non-empty range `from..to` within `0..len`, for `len` at least one
+fn segment<R: Rng + ?Sized>(len: usize, rng: &mut R) -> (usize, usize) {
+    let (a, b) = (rng.gen_range(0, len), rng.gen_range(0, len));
+    (a.min(b), a.max(b) + 1)
+}
+
+// Two different routes, for at least two routes
+fn two_routes<R: Rng + ?Sized>(count: usize, rng: &mut R) -> (usize, usize) {
+    let first = rng.gen_range(0, count);
+    let second = (first + rng.gen_range(1, count)) % count;
+    (first, second)
+}
+
+/// A [`MultiChromosome`] together with the depot of every route, for a [`MultiDepotProblem`]. Besides the
+/// mutations of the routes, which move points between salesmen and so between depots, a salesman can be moved to
+/// another depot, so that the assignment evolves with the routes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiDepotChromosome {
+    depots: Vec<usize>,
+    routes: MultiChromosome
+}
+
+impl MultiDepotChromosome {
+    /// Shuffles the points besides the depots and cuts them into one non-empty route per salesman at random, each
+    /// leaving a depot drawn at random.
+    pub fn random<R: Rng + ?Sized>(problem: &MultiDepotProblem<'_>, rng: &mut R) -> Self {
+        let routes = MultiChromosome::shuffled(problem.customers(), problem.salesmen, rng);
+        let depots = (0..problem.salesmen).map(|_| problem.depots[rng.gen_range(0, problem.depots.len())]).collect();
+        MultiDepotChromosome {
+            depots,
+            routes
+        }
+    }
+
+    /// Wraps depots and routes such as those of a [`MultiSolution`], which should pass
+    /// [`MultiDepotProblem::check_routes`].
+    pub fn from_routes(depots: Vec<usize>, routes: Vec<Vec<usize>>) -> Self {
+        MultiDepotChromosome {
+            depots,
+            routes: MultiChromosome::from_routes(routes)
+        }
+    }
+
+    pub fn depots(&self) -> &[usize] {
+        &self.depots
+    }
+
+    pub fn routes(&self) -> &[Vec<usize>] {
+        self.routes.routes()
+    }
+
+    pub fn into_parts(self) -> (Vec<usize>, Vec<Vec<usize>>) {
+        (self.depots, self.routes.into_routes())
+    }
+
+    /// Applies one mutation chosen at random: one of [`MultiChromosome::mutate`], or, with more than one depot,
+    /// moving a salesman to another depot.
+    pub fn mutate<R: Rng + ?Sized>(&mut self, problem: &MultiDepotProblem<'_>, rng: &mut R) {
+        let count = problem.depots.len();
+        if count > 1 && rng.gen_range(0, 5) == 0 {
+            let salesman = rng.gen_range(0, self.depots.len());
+            self.depots[salesman] = problem.depots[rng.gen_range(0, count)];
+        } else {
+            self.routes.mutate(rng);
+        }
+    }
+}
+
+/// A mutation-only GA over [`MultiChromosome`]s, which suits the encoding better than crossover, since two parents
+/// rarely split the points between salesmen compatibly. Each generation the `elites` best individuals survive,
+/// and the rest of the population is replaced by mutated copies of tournament winners. The population starts
+/// from the [`greedy`] routes and random individuals.
+#[derive(Clone, Debug)]
+pub struct MultiGa {
+    pub population: usize,
+    pub generations: u32,
+    /// The number of individuals competing in each tournament
+    pub tournament: usize,
+    pub elites: usize,
+    /// The most mutations applied to each copy, drawn uniformly from one up to this
+    pub mutations: usize
+}
+
+impl Default for MultiGa {
+    fn default() -> Self {
+        MultiGa {
+            population: 100,
+            generations: 2000,
+            tournament: 4,
+            elites: 2,
+            mutations: 3
+        }
+    }
+}
+
+impl MultiGa {
+    /// Runs the GA for the configured number of generations or until the budget runs out, returning the best
+    /// routes found. The iteration count is the number of generations run.
+    pub fn solve(&self, problem: &Problem<'_>, options: &SolveOptions) -> Result<MultiSolution> {
+        let start = Instant::now();
+        problem.validate()?;
+        self.validate()?;
+
+        let greedy = MultiChromosome::from_routes(greedy(problem)?.routes);
+        let (best, generations, termination) = self.evolve(
+            greedy,
+            |rng| MultiChromosome::random(problem, rng),
+            |indv| problem.cost(indv.routes()),
+            |indv, rng| indv.mutate(rng),
+            options,
+            start
+        );
+
+        let mut result = MultiSolution::new(problem, best.into_routes(), generations as u64, start);
+        result.termination = termination;
+        Ok(result)
+    }
+
+    /// Like [`solve`](Self::solve), but over [`MultiDepotChromosome`]s, choosing the depot of every salesman
+    /// along with the routes. The population starts from the [`greedy_multi_depot`] routes and random
+    /// individuals.
+    pub fn solve_multi_depot(&self, problem: &MultiDepotProblem<'_>, options: &SolveOptions) -> Result<MultiSolution> {
+        let start = Instant::now();
+        problem.validate()?;
+        self.validate()?;
+
+        let greedy = greedy_multi_depot(problem)?;
+        let (best, generations, termination) = self.evolve(
+            MultiDepotChromosome::from_routes(greedy.depots, greedy.routes),
+            |rng| MultiDepotChromosome::random(problem, rng),
+            |indv| problem.cost(indv.depots(), indv.routes()),
+            |indv, rng| indv.mutate(problem, rng),
+            options,
+            start
+        );
+
+        let (depots, routes) = best.into_parts();
+        let mut result = MultiSolution::multi_depot(problem, depots, routes, generations as u64, start);
+        result.termination = termination;
+        Ok(result)
+    }
+
+    /// Checks that the population has at least two individuals and that tournaments have at least one, failing
+    /// with [`Error::InvalidSettings`] otherwise.
+    pub fn validate(&self) -> Result<()> {
+        check_population(self.population, 2)?;
+        if self.tournament == 0 {
+            return Err(Error::InvalidSettings("the tournament is 0, but needs at least one individual".to_string()));
+        }
+        Ok(())
+    }
+
+    // Evolves a population started from `first` and random individuals, returning the best individual, the number
+    // of generations run, and why the run stopped. The settings have been validated
+    fn evolve<T, N, C, M>(
+        &self,
+        first: T,
+        random: N,
+        cost: C,
+        mutate: M,
+        options: &SolveOptions,
+        start: Instant
+    ) -> (T, u32, Termination)
+    where
+        T: Clone,
+        N: Fn(&mut ChaCha20Rng) -> T,
+        C: Fn(&T) -> f32,
+        M: Fn(&mut T, &mut ChaCha20Rng)
+    {
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start);
+        let mut rng = options.rng();
+
+        let mut population = vec![first];
+        population.extend((1..self.population).map(|_| random(&mut rng)));
+        let mut ranked: Vec<(f32, T)> = population.into_iter()
+            .map(|indv| (cost(&indv), indv))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        reporter.improved(ranked[0].0);
+
+        let elites = self.elites.clamp(1, self.population - 1);
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            let mut next: Vec<(f32, T)> = ranked[..elites].to_vec();
+            while next.len() < self.population {
+                // The population is ranked, so the lowest index drawn wins
+                // Infallible: tournaments have at least one individual
+                let winner = (0..self.tournament).map(|_| rng.gen_range(0, ranked.len())).min().unwrap();
+                let mut child = ranked[winner].1.clone();
+                for _ in 0..rng.gen_range(1, self.mutations.max(1) + 1) {
+                    mutate(&mut child, &mut rng);
+                }
+                next.push((cost(&child), child));
+            }
+
+            next.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            ranked = next;
+            reporter.improved(ranked[0].0);
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        (ranked.swap_remove(0).1, generations, budget.termination())
+    }
+}
@@ -0,0 +1,174 @@
+use rand::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::context::SolverContext;
+use crate::ga::{
+    AnyRecombinator, Chromosome, GaParams, Repair, Settings, random_population, repair_neighbors, run_generations
+};
+use crate::map::{Map, Path};
+use crate::neighbors::Neighbors;
+use crate::error::Result;
+use crate::solve::{Budget, Objective, Reporter, SolveOptions, SolveResult, Solver, Termination, check_map};
+use crate::time::Instant;
+
+/// A single independent GA run. The seed drives selection, recombination, and mutation.
+#[derive(Clone, Debug)]
+pub struct RunConfig {
+    pub settings: Settings,
+    pub recombinator: AnyRecombinator,
+    pub population: usize,
+    pub generations: u32,
+    pub fix: bool,
+    pub seed: u64
+}
+
+/// The outcome of a single run.
+#[derive(Clone, Debug)]
+pub struct RunSummary {
+    pub seed: u64,
+    /// The [loss](crate::solve::Objective::loss) of the best tour, which is its length unless maximizing
+    pub best_loss: f32,
+    /// The generation in which the best loss was first reached
+    pub best_generation: u32,
+    pub elapsed: Duration,
+    pub termination: Termination
+}
+
+/// The best tour across all runs.
+#[derive(Clone, Debug)]
+pub struct MultiStartResult {
+    pub tour: Vec<usize>,
+    pub length: f32,
+    /// One summary per run, in the same order as the run configurations
+    pub runs: Vec<RunSummary>
+}
+
+type RunOutcome = (RunSummary, Vec<usize>);
+
+/// Executes every run on as many threads as [`parallelism`](SolveOptions::parallelism) allows and returns the
+/// best tour found by any of them.
+/// Each run reports improvements on its own best tour to the progress callback.
+pub fn multi_start<C, F>(
+    map: &Map,
+    runs: &[RunConfig],
+    options: &SolveOptions,
+    new_chromosome: F
+) -> Result<MultiStartResult>
+where
+    C: Chromosome + Send,
+    F: Fn(&Map) -> C + Sync
+{
+    assert!(!runs.is_empty(), "At least one run is required");
+    check_map(map)?;
+    runs.iter().try_for_each(|config| config.settings.validate())?;
+    let start = Instant::now();
+
+    let threads = options.parallelism.threads().min(runs.len());
+    // Shared by every run that repairs its offspring
+    let neighbors = repair_neighbors(map, runs.iter().any(|config| config.fix), SolverContext::of(options, map));
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<RunOutcome>>> = Mutex::new(vec![None; runs.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let config = match runs.get(index) {
+                    Some(config) => config,
+                    None => break
+                };
+
+                let budget = Budget::new(options, start, 1);
+                let reporter = Reporter::new(options, start).objective(options.objective);
+                let neighbors = neighbors.as_ref();
+                let result = single_run(map, config, neighbors, options.objective, budget, reporter, &new_chromosome);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    let results: Vec<RunOutcome> = results.into_inner()
+        .unwrap()
+        .into_iter()
+        // Infallible: every index is claimed by exactly one worker
+        .map(Option::unwrap)
+        .collect();
+
+    let best = results.iter()
+        .min_by(|(a, _), (b, _)| a.best_loss.total_cmp(&b.best_loss))
+        .unwrap();
+    let tour = best.1.clone();
+    let length = tour.evaluate(map);
+
+    Ok(MultiStartResult {
+        tour,
+        length,
+        runs: results.into_iter().map(|(summary, _)| summary).collect()
+    })
+}
+
+fn single_run<C, F>(
+    map: &Map,
+    config: &RunConfig,
+    neighbors: Option<&Neighbors>,
+    objective: Objective,
+    mut budget: Budget,
+    mut reporter: Reporter<'_>,
+    new_chromosome: &F
+) -> RunOutcome
+where
+    C: Chromosome,
+    F: Fn(&Map) -> C
+{
+    let start = Instant::now();
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("run", seed = config.seed).entered();
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut population = random_population(map, config.population, new_chromosome, &mut rng);
+
+    let params = GaParams {
+        settings: &config.settings,
+        recombinator: &config.recombinator,
+        repair: if config.fix { neighbors.into() } else { Repair::Off },
+        objective,
+        first_generation: 0,
+        generations: config.generations
+    };
+    let outcome = run_generations(&params, map, &mut population, &mut budget, &mut reporter, &mut rng);
+
+    let summary = RunSummary {
+        seed: config.seed,
+        best_loss: outcome.loss,
+        best_generation: outcome.best_generation,
+        elapsed: start.elapsed(),
+        termination: budget.termination()
+    };
+
+    (summary, outcome.tour)
+}
+
+/// Runs a fixed set of GA configurations in parallel. See [`multi_start`].
+pub struct MultiStart<C> {
+    pub runs: Vec<RunConfig>,
+    pub new_chromosome: fn(&Map) -> C
+}
+
+impl<C: Chromosome + Send> Solver for MultiStart<C> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        let result = multi_start(map, &self.runs, options, self.new_chromosome)?;
+        let generations = self.runs.iter().map(|run| run.generations as u64).sum();
+
+        let mut solve_result = SolveResult::new(map, result.tour, generations, start);
+        if let Some(run) = result.runs.iter().find(|run| run.termination != Termination::Completed) {
+            solve_result.termination = run.termination;
+        }
+        Ok(solve_result)
+    }
+}
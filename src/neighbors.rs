@@ -0,0 +1,377 @@
+use std::fmt::{self, Debug, Formatter};
+use std::mem;
+
+use crate::map::Map;
+
+// Rounds of subgradient ascent on the penalties of the points before their alpha-nearness is measured
+const ASCENT_ROUNDS: usize = 50;
+
+// Rounds without a better lower bound after which the ascent takes smaller steps
+const ASCENT_PATIENCE: usize = 5;
+
+/// Which points local search tries joining each point to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Candidates {
+    /// The nearest points, see [`Neighbors::candidates`]
+    #[default]
+    Nearest,
+    /// The points of lowest alpha-nearness, see [`Neighbors::alpha`]
+    Alpha
+}
+
+/// The nearest points to every point of a map, ordered from nearest to farthest. Restricting moves to candidates
+/// from these lists lets repairs and local searches find the edges that matter without scanning the whole map.
+/// With the `geometry` feature, [`delaunay`](Self::delaunay) adds the points that share a Delaunay edge with each.
+/// The lists hold `u32` indices, which take half the memory of `usize` ones on large instances; maps have at most
+/// [`MAX_POINTS`](crate::map::MAX_POINTS) points when they are solved, well within range.
+#[derive(Clone)]
+pub struct Neighbors {
+    count: usize,
+    starts: Box<[usize]>,
+    lists: Box<[u32]>
+}
+
+impl Neighbors {
+    /// The `count` nearest points to each point of the map, or every other point if the map is smaller than that.
+    pub fn new(map: &Map, count: usize) -> Self {
+        Self::by(map.size(), count, |a, b| map.dist(a, b))
+    }
+
+    // The `count` points nearest to each of `size` points by the distance function
+    pub(crate) fn by<F: Fn(usize, usize) -> f32>(size: usize, count: usize, dist: F) -> Self {
+        debug_assert!(size <= u32::MAX as usize, "{} points do not fit in u32 indices", size);
+        let count = count.min(size.saturating_sub(1));
+        let mut lists = Vec::with_capacity(size * count);
+        let mut others = Vec::with_capacity(size);
+        for city in 0..size {
+            others.clear();
+            others.extend((0..size as u32).filter(|&other| other as usize != city));
+            // Ties go to the lower index, so that the lists do not depend on how the selection shuffles
+            let by_distance = |a: &u32, b: &u32| {
+                dist(city, *a as usize)
+                    .total_cmp(&dist(city, *b as usize))
+                    .then(a.cmp(b))
+            };
+
+            // Only the nearest need to be in order
+            if count < others.len() {
+                others.select_nth_unstable_by(count, by_distance);
+            }
+            others.truncate(count);
+            others.sort_by(by_distance);
+            lists.extend_from_slice(&others);
+        }
+
+        Neighbors {
+            count,
+            starts: (0..=size).map(|city| city * count).collect(),
+            lists: lists.into_boxed_slice()
+        }
+    }
+
+    /// The `count` nearest points to each point of the map together with the points it shares an edge with in the
+    /// [Delaunay triangulation](crate::delaunay), nearest first. The triangulation adds the few points that are
+    /// close in a direction the nearest ones all miss, such as across a gap between clusters, so the lists cover
+    /// nearly every edge of an optimal tour while staying short: a point has six Delaunay neighbors on average.
+    ///
+    /// The triangulation is of the points' positions, so the lists only make sense for a map without
+    /// [attached distances](Map::with_distances) or [costs](Map::with_costs).
+    #[cfg(feature = "geometry")]
+    pub fn delaunay(map: &Map, count: usize) -> Self {
+        let nearest = Self::new(map, count);
+        let mut adjacent = vec![Vec::new(); map.size()];
+        for (a, b) in crate::delaunay::edges(map) {
+            adjacent[a].push(b as u32);
+            adjacent[b].push(a as u32);
+        }
+
+        Self::from_lists(count, adjacent.into_iter().enumerate().map(|(city, mut list)| {
+            list.extend(nearest.of(city).map(|other| other as u32));
+            list.sort_by(|a, b| {
+                map.dist(city, *a as usize)
+                    .total_cmp(&map.dist(city, *b as usize))
+                    .then(a.cmp(b))
+            });
+            list.dedup();
+            list
+        }))
+    }
+
+    /// The `count` points nearest to each point by alpha-nearness, as in LKH: how much longer the minimum 1-tree
+    /// gets when it is made to include the edge to them. A 1-tree is a spanning tree of all but one point, joined
+    /// to that point by its two shortest edges, and so a tour is a 1-tree whose points all have degree two. Edges of
+    /// the minimum 1-tree have an alpha of zero, and edges of an optimal tour have low ones far more often than they
+    /// join nearest neighbors, so five alpha-nearest candidates cover an optimal tour about as well as ten or more
+    /// nearest ones.
+    ///
+    /// Before measuring, the points are given penalties, added to the lengths of their edges, by
+    /// [subgradient ascent](https://en.wikipedia.org/wiki/Subgradient_method) towards a 1-tree whose points all
+    /// have degree two, which is what makes the values informative. Every round of the ascent and the measurement
+    /// takes time quadratic in the size of the map, but only linear memory.
+    pub fn alpha(map: &Map, count: usize) -> Self {
+        Self::alpha_by(map.size(), count, |a, b| map.dist(a, b))
+    }
+
+    // The `count` points of lowest alpha-nearness to each of `size` points by the distance function
+    pub(crate) fn alpha_by<F: Fn(usize, usize) -> f32>(size: usize, count: usize, dist: F) -> Self {
+        if size < 3 {
+            return Self::by(size, count, dist);
+        }
+
+        let count = count.min(size - 1);
+        let penalties = ascent(size, &dist);
+        let cost = |a: usize, b: usize| dist(a, b) as f64 + penalties[a] + penalties[b];
+        let tree = OneTree::new(size, &cost);
+
+        let mut lists = Vec::with_capacity(size * count);
+        let mut alphas = vec![0.0; size];
+        let mut beta = vec![f64::NEG_INFINITY; size];
+        let mut mark = vec![usize::MAX; size];
+        let mut others = Vec::with_capacity(size);
+        for city in 0..size {
+            tree.alphas(city, &cost, &mut alphas, &mut beta, &mut mark);
+            others.clear();
+            others.extend((0..size as u32).filter(|&other| other as usize != city));
+            // Ties go to the nearer point, and then to the lower index
+            let by_alpha = |a: &u32, b: &u32| {
+                let (a, b) = (*a as usize, *b as usize);
+                alphas[a]
+                    .total_cmp(&alphas[b])
+                    .then(cost(city, a).total_cmp(&cost(city, b)))
+                    .then(a.cmp(&b))
+            };
+
+            if count < others.len() {
+                others.select_nth_unstable_by(count, by_alpha);
+            }
+            others.truncate(count);
+            others.sort_by(by_alpha);
+            lists.extend_from_slice(&others);
+        }
+
+        Neighbors {
+            count,
+            starts: (0..=size).map(|city| city * count).collect(),
+            lists: lists.into_boxed_slice()
+        }
+    }
+
+    /// The candidates the solvers use for a map: [`delaunay`](Self::delaunay) when built with the `geometry`
+    /// feature and the map's distances are straight lines, and otherwise the `count` [nearest](Self::new).
+    pub fn candidates(map: &Map, count: usize) -> Self {
+        #[cfg(feature = "geometry")]
+        if map.distances().is_none() && !map.has_costs() {
+            return Self::delaunay(map, count);
+        }
+        Self::new(map, count)
+    }
+
+    // Lists of any length, one for each point in order
+    pub(crate) fn from_lists<I: IntoIterator<Item = Vec<u32>>>(count: usize, lists: I) -> Self {
+        let mut starts = vec![0];
+        let mut all = Vec::new();
+        for list in lists {
+            all.extend_from_slice(&list);
+            starts.push(all.len());
+        }
+
+        Neighbors {
+            count,
+            starts: starts.into_boxed_slice(),
+            lists: all.into_boxed_slice()
+        }
+    }
+
+    /// The number of nearest points in every list. Lists with [Delaunay](Self::delaunay) neighbors can hold more.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The candidates of `city`, nearest first.
+    #[inline]
+    pub fn of(&self, city: usize) -> impl Iterator<Item = usize> + '_ {
+        self.lists[self.starts[city]..self.starts[city + 1]].iter().map(|&other| other as usize)
+    }
+
+    /// The bytes the lists take.
+    pub fn memory(&self) -> usize {
+        self.lists.len() * mem::size_of::<u32>() + self.starts.len() * mem::size_of::<usize>()
+    }
+}
+
+// A minimum 1-tree: a minimum spanning tree of every point but the first, grown by Prim's algorithm from the
+// second, with the first point joined by its two cheapest edges
+struct OneTree {
+    // The point each one hangs from in the spanning tree, and `usize::MAX` for the first two
+    parent: Vec<usize>,
+    // The points of the spanning tree in the order they joined it, so every one comes after its parent
+    order: Vec<usize>,
+    degrees: Vec<i64>,
+    length: f64,
+    // The costs of the first point's cheapest and second cheapest edges, with the points at their other ends
+    nearest: (usize, f64),
+    second: (usize, f64)
+}
+
+impl OneTree {
+    fn new<F: Fn(usize, usize) -> f64>(size: usize, cost: &F) -> Self {
+        let mut parent = vec![usize::MAX; size];
+        let mut key = vec![f64::INFINITY; size];
+        let mut inside = vec![false; size];
+        let mut order = Vec::with_capacity(size - 1);
+        let mut degrees = vec![0; size];
+        let mut length = 0.0;
+        key[1] = 0.0;
+        for _ in 1..size {
+            // Infallible: some point is outside the tree until every one is in
+            let next = (1..size)
+                .filter(|&point| !inside[point])
+                .min_by(|&a, &b| key[a].total_cmp(&key[b]))
+                .unwrap();
+            inside[next] = true;
+            order.push(next);
+            if parent[next] != usize::MAX {
+                length += key[next];
+                degrees[next] += 1;
+                degrees[parent[next]] += 1;
+            }
+            for point in 1..size {
+                if !inside[point] {
+                    let cost = cost(next, point);
+                    if cost < key[point] {
+                        key[point] = cost;
+                        parent[point] = next;
+                    }
+                }
+            }
+        }
+
+        let (mut nearest, mut second) = ((usize::MAX, f64::INFINITY), (usize::MAX, f64::INFINITY));
+        for point in 1..size {
+            let cost = cost(0, point);
+            if cost < nearest.1 {
+                second = nearest;
+                nearest = (point, cost);
+            } else if cost < second.1 {
+                second = (point, cost);
+            }
+        }
+        length += nearest.1 + second.1;
+        degrees[0] = 2;
+        degrees[nearest.0] += 1;
+        degrees[second.0] += 1;
+
+        OneTree {
+            parent,
+            order,
+            degrees,
+            length,
+            nearest,
+            second
+        }
+    }
+
+    // Writes the alpha-nearness of every point to `city` into `alphas`. For two points of the spanning tree, it is
+    // the cost of their edge less that of the costliest edge on the path between them in the tree, which the edge
+    // would replace; `beta` holds those costliest edges along the path from `city`, found in the order the tree
+    // was grown so that every point's parent is done first. `mark` records which points are on the path from
+    // `city` to the root.
+    fn alphas<F: Fn(usize, usize) -> f64>(
+        &self,
+        city: usize,
+        cost: &F,
+        alphas: &mut [f64],
+        beta: &mut [f64],
+        mark: &mut [usize]
+    ) {
+        // The first point's edges replace the costlier of its two in the 1-tree
+        let joined = |other: usize| {
+            if other == self.nearest.0 || other == self.second.0 { 0.0 } else { cost(0, other) - self.second.1 }
+        };
+        if city == 0 {
+            (1..alphas.len()).for_each(|other| alphas[other] = joined(other));
+            return;
+        }
+        alphas[0] = joined(city);
+
+        beta[city] = f64::NEG_INFINITY;
+        mark[city] = city;
+        let mut point = city;
+        while self.parent[point] != usize::MAX {
+            let parent = self.parent[point];
+            beta[parent] = beta[point].max(cost(point, parent));
+            mark[parent] = city;
+            point = parent;
+        }
+        for &other in &self.order {
+            if other == city {
+                continue;
+            }
+            if mark[other] != city {
+                let parent = self.parent[other];
+                beta[other] = beta[parent].max(cost(other, parent));
+            }
+            alphas[other] = cost(city, other) - beta[other];
+        }
+    }
+}
+
+// Penalties for the points that bring the minimum 1-tree closer to a tour, found by subgradient ascent on the
+// Held-Karp lower bound: points of degree above two are made costlier and leaves cheaper, with steps scaled by how
+// far the bound is from the length of a nearest neighbor tour. Returns the penalties of the best bound seen.
+fn ascent<F: Fn(usize, usize) -> f32>(size: usize, dist: &F) -> Vec<f64> {
+    let upper = nearest_neighbor_length(size, dist);
+    let mut penalties = vec![0.0; size];
+    let mut best = (f64::NEG_INFINITY, penalties.clone());
+    let (mut scale, mut stale) = (2.0, 0);
+    for _ in 0..ASCENT_ROUNDS {
+        let tree = OneTree::new(size, &|a: usize, b: usize| dist(a, b) as f64 + penalties[a] + penalties[b]);
+        let bound = tree.length - 2.0 * penalties.iter().sum::<f64>();
+        if bound > best.0 {
+            best = (bound, penalties.clone());
+            stale = 0;
+        } else {
+            stale += 1;
+            if stale >= ASCENT_PATIENCE {
+                scale /= 2.0;
+                stale = 0;
+            }
+        }
+
+        // A 1-tree whose points all have degree two is an optimal tour, and no penalty can raise the bound further
+        let norm: i64 = tree.degrees.iter().map(|&degree| (degree - 2) * (degree - 2)).sum();
+        let step = scale * (upper - bound).max(0.0) / norm.max(1) as f64;
+        if norm == 0 || step <= 0.0 {
+            break;
+        }
+        for (penalty, &degree) in penalties.iter_mut().zip(&tree.degrees) {
+            *penalty += step * (degree - 2) as f64;
+        }
+    }
+
+    best.1
+}
+
+// The length of the closed tour built by always moving to the nearest unvisited point from the first
+fn nearest_neighbor_length<F: Fn(usize, usize) -> f32>(size: usize, dist: &F) -> f64 {
+    let mut visited = vec![false; size];
+    visited[0] = true;
+    let (mut current, mut length) = (0, 0.0);
+    for _ in 1..size {
+        // Infallible: some point is unvisited until every one is
+        let next = (0..size)
+            .filter(|&point| !visited[point])
+            .min_by(|&a, &b| dist(current, a).total_cmp(&dist(current, b)))
+            .unwrap();
+        visited[next] = true;
+        length += dist(current, next) as f64;
+        current = next;
+    }
+    length + dist(current, 0) as f64
+}
+
+impl Debug for Neighbors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Neighbors").field("count", &self.count).finish()
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::{Error, Result};
+use crate::map::{Map, Point};
+use crate::matrix::DistanceMatrix;
+
+// Three points count as collinear when the sine of the angle they make is below this
+const COLLINEAR: f64 = 1e-9;
+
+type Vector = (f64, f64);
+
+fn vector(point: Point) -> Vector {
+    (point.x as f64, point.y as f64)
+}
+
+fn length((x, y): Vector) -> f64 {
+    x.hypot(y)
+}
+
+// Which side of the line through `origin` and `a` the point `b` is on: positive to the left, negative to the
+// right, and zero on the line
+fn orientation(origin: Vector, a: Vector, b: Vector) -> f64 {
+    let (ax, ay) = (a.0 - origin.0, a.1 - origin.1);
+    let (bx, by) = (b.0 - origin.0, b.1 - origin.1);
+    let cross = ax * by - ay * bx;
+    if cross.abs() <= COLLINEAR * length((ax, ay)) * length((bx, by)) { 0.0 } else { cross }
+}
+
+// Where `point` lies along the segment from `from` to `to`, as a fraction of its length, if it lies on it at all
+fn along(point: Vector, from: Vector, to: Vector) -> Option<f64> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let squared = dx * dx + dy * dy;
+    if squared == 0.0 || orientation(from, to, point) != 0.0 {
+        return None;
+    }
+
+    let t = ((point.0 - from.0) * dx + (point.1 - from.1) * dy) / squared;
+    if (-COLLINEAR..=1.0 + COLLINEAR).contains(&t) { Some(t.clamp(0.0, 1.0)) } else { None }
+}
+
+// Whether the segments cross at a point inside both, rather than touching or overlapping
+fn cross(a: Vector, b: Vector, p: Vector, q: Vector) -> bool {
+    orientation(p, q, a) * orientation(p, q, b) < 0.0 && orientation(a, b, p) * orientation(a, b, q) < 0.0
+}
+
+/// Polygons that paths between points must go around, as walls or furniture for a robot crossing a floor. Paths
+/// may run along an obstacle's edges and touch its corners, but not cross its inside.
+#[derive(Clone, Debug)]
+pub struct Obstacles {
+    polygons: Vec<Vec<Point>>
+}
+
+impl Obstacles {
+    /// Checks that every polygon has at least three corners, all at finite coordinates. The corners of a polygon
+    /// go around it in either direction.
+    pub fn new(polygons: Vec<Vec<Point>>) -> Result<Self> {
+        if let Some(index) = polygons.iter().position(|polygon| polygon.len() < 3) {
+            return Err(Error::InvalidProblem(format!("obstacle {} has fewer than three corners", index)));
+        }
+        let finite = |polygon: &Vec<Point>| polygon.iter().all(|corner| corner.x.is_finite() && corner.y.is_finite());
+        if let Some(index) = polygons.iter().position(|polygon| !finite(polygon)) {
+            return Err(Error::InvalidProblem(format!("obstacle {} has a corner that is not finite", index)));
+        }
+
+        Ok(Obstacles { polygons })
+    }
+
+    pub fn polygons(&self) -> &[Vec<Point>] {
+        &self.polygons
+    }
+
+    /// Whether the point lies inside an obstacle. Points on an obstacle's edge are outside it.
+    pub fn contains(&self, point: Point) -> bool {
+        self.contains_vector(vector(point))
+    }
+
+    /// Whether the straight segment between the points stays out of every obstacle.
+    pub fn visible(&self, a: Point, b: Point) -> bool {
+        self.visible_vectors(vector(a), vector(b))
+    }
+
+    /// The lengths of the shortest paths around the obstacles between every pair of the map's points, for
+    /// [`Map::with_distances`]. The paths are found with Dijkstra's algorithm over the visibility graph, which joins
+    /// the points and the obstacles' corners wherever they can see each other, since a shortest path only ever bends
+    /// at corners.
+    ///
+    /// Fails if a point lies inside an obstacle, or if the obstacles wall some points off from the others.
+    pub fn distances(&self, map: &Map) -> Result<DistanceMatrix> {
+        if let Some(point) = (0..map.size()).find(|&point| self.contains(map[point])) {
+            return Err(Error::InvalidProblem(format!("point {} is inside an obstacle", point)));
+        }
+
+        let nodes: Vec<Vector> = map.iter()
+            .copied()
+            .chain(self.polygons.iter().flatten().copied())
+            .map(vector)
+            .collect();
+        let count = nodes.len();
+        let mut edges = vec![f64::INFINITY; count * count];
+        for i in 0..count {
+            for j in i + 1..count {
+                if self.visible_vectors(nodes[i], nodes[j]) {
+                    let dist = length((nodes[j].0 - nodes[i].0, nodes[j].1 - nodes[i].1));
+                    edges[i * count + j] = dist;
+                    edges[j * count + i] = dist;
+                }
+            }
+        }
+
+        // Dijkstra's algorithm from every point over the dense graph, which needs no heap
+        let size = map.size();
+        let mut rows = Vec::with_capacity(size);
+        for source in 0..size {
+            let mut dist = vec![f64::INFINITY; count];
+            let mut done = vec![false; count];
+            dist[source] = 0.0;
+            while let Some(node) = (0..count)
+                .filter(|&node| !done[node] && dist[node].is_finite())
+                .min_by(|&a, &b| dist[a].total_cmp(&dist[b]))
+            {
+                done[node] = true;
+                for next in 0..count {
+                    let through = dist[node] + edges[node * count + next];
+                    if through < dist[next] {
+                        dist[next] = through;
+                    }
+                }
+            }
+
+            if let Some(point) = (0..size).find(|&point| dist[point].is_infinite()) {
+                return Err(Error::InvalidProblem(format!("obstacles wall point {} off from point {}", point, source)));
+            }
+            dist.truncate(size);
+            rows.push(dist);
+        }
+
+        Ok(DistanceMatrix::from_fn(size, |i, j| rows[i][j] as f32))
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vector, Vector)> + '_ {
+        self.polygons.iter().flat_map(|polygon| {
+            polygon.iter().zip(polygon.iter().cycle().skip(1)).map(|(&from, &to)| (vector(from), vector(to)))
+        })
+    }
+
+    fn contains_vector(&self, point: Vector) -> bool {
+        self.polygons.iter().any(|polygon| {
+            let corners: Vec<Vector> = polygon.iter().copied().map(vector).collect();
+            let sides = corners.iter().zip(corners.iter().cycle().skip(1));
+            if sides.clone().any(|(&from, &to)| along(point, from, to).is_some()) {
+                return false;
+            }
+
+            // Count the sides a ray to the right of the point crosses
+            let crossings = sides
+                .filter(|&(&from, &to)| {
+                    (from.1 > point.1) != (to.1 > point.1)
+                        && point.0 < from.0 + (point.1 - from.1) * (to.0 - from.0) / (to.1 - from.1)
+                })
+                .count();
+            crossings % 2 == 1
+        })
+    }
+
+    fn visible_vectors(&self, a: Vector, b: Vector) -> bool {
+        if self.edges().any(|(from, to)| cross(a, b, from, to)) {
+            return false;
+        }
+
+        // Without crossing a side, the segment can still enter an obstacle through its corners, so check the
+        // middle of every piece between the corners it passes through
+        let mut stops: Vec<f64> = self.polygons.iter()
+            .flatten()
+            .filter_map(|&corner| along(vector(corner), a, b))
+            .chain([0.0, 1.0].iter().copied())
+            .collect();
+        stops.sort_by(|x, y| x.total_cmp(y));
+        stops.windows(2)
+            .filter(|stop| stop[1] - stop[0] > COLLINEAR)
+            .all(|stop| {
+                let t = (stop[0] + stop[1]) / 2.0;
+                !self.contains_vector((a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)))
+            })
+    }
+}
@@ -0,0 +1,353 @@
+use rand::prelude::*;
+use std::time::Duration;
+
+use crate::cvrp::order_crossover;
+use crate::error::{Error, Result};
+use crate::ga::check_population;
+use crate::map::Map;
+use crate::mtsp::route_length;
+use crate::solve::{Budget, Objective, Reporter, SolveOptions, Termination, check_map};
+use crate::time::Instant;
+
+/// The orienteering problem: a closed route leaving the depot and returning to it, no longer than `budget`, which
+/// collects the prize of every point it visits and should collect as much as it can. Unlike the traveling salesman
+/// problem, points may be left out.
+#[derive(Clone, Copy, Debug)]
+pub struct Problem<'a> {
+    pub map: &'a Map,
+    pub depot: usize,
+    /// The prize of every point, by index. The depot's is ignored
+    pub prizes: &'a [f32],
+    /// The longest the route may be, including the edges to and from the depot
+    pub budget: f32
+}
+
+impl Problem<'_> {
+    /// Checks that there is a prize for every point, that no prize is negative, and that the budget is not
+    /// negative.
+    pub fn validate(&self) -> Result<()> {
+        check_map(self.map)?;
+        if self.prizes.len() != self.map.size() {
+            return Err(Error::DimensionMismatch {
+                expected: self.map.size(),
+                found: self.prizes.len()
+            });
+        }
+        if self.depot >= self.map.size() {
+            return Err(Error::InvalidProblem(format!("the depot {} is not a point of the map", self.depot)));
+        }
+        if let Some(point) = self.prizes.iter().position(|&prize| !(prize.is_finite() && prize >= 0.0)) {
+            return Err(Error::InvalidProblem(format!("point {} has the prize {}", point, self.prizes[point])));
+        }
+        if self.budget.is_nan() || self.budget < 0.0 {
+            return Err(Error::InvalidProblem(format!("the budget {} is negative", self.budget)));
+        }
+
+        Ok(())
+    }
+
+    /// The total prize of the points of a route.
+    pub fn prize(&self, route: &[usize]) -> f32 {
+        route.iter().fold(0.0, |total, &point| total + self.prizes[point])
+    }
+
+    /// The length of the route from the depot through the points in order and back to the depot.
+    pub fn length(&self, route: &[usize]) -> f32 {
+        route_length(self.map, self.depot, route)
+    }
+
+    /// Checks that the route visits points of the map other than the depot at most once each, and that it fits
+    /// in the budget.
+    pub fn check_route(&self, route: &[usize]) -> Result<()> {
+        let mut seen = vec![false; self.map.size()];
+        seen[self.depot] = true;
+        for &point in route {
+            match seen.get_mut(point) {
+                Some(true) if point == self.depot => {
+                    return Err(Error::InvalidTour("the route visits the depot".to_string()));
+                },
+                Some(true) => return Err(Error::InvalidTour(format!("point {} is visited more than once", point))),
+                Some(flag) => *flag = true,
+                None => return Err(Error::InvalidTour(format!("point {} is out of range", point)))
+            }
+        }
+
+        let length = self.length(route);
+        if length > self.budget {
+            let message = format!("the route is {} long, over the budget of {}", length, self.budget);
+            return Err(Error::InvalidTour(message));
+        }
+        Ok(())
+    }
+
+    // The points other than the depot
+    fn customers(&self) -> Vec<usize> {
+        (0..self.map.size()).filter(|&point| point != self.depot).collect()
+    }
+
+    // The points either side of position `i` of the route, where a point inserted there would go, with the depot
+    // before the first point and after the last
+    fn around(&self, route: &[usize], i: usize) -> (usize, usize) {
+        let before = if i == 0 { self.depot } else { route[i - 1] };
+        let after = route.get(i).copied().unwrap_or(self.depot);
+        (before, after)
+    }
+
+    #[inline]
+    fn dist(&self, a: usize, b: usize) -> f32 {
+        self.map.dist(a, b)
+    }
+}
+
+/// A route for an orienteering problem. Serializes with the elapsed time in seconds.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Solution {
+    /// The points visited in order, leaving out the depot at both ends
+    pub route: Vec<usize>,
+    pub prize: f32,
+    /// The length of the route, including the edges to and from the depot
+    pub length: f32,
+    /// Generations run, or points inserted by a constructive heuristic
+    pub iterations: u64,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::time::serialize_seconds"))]
+    pub elapsed: Duration,
+    pub termination: Termination
+}
+
+impl Solution {
+    pub fn new(problem: &Problem<'_>, route: Vec<usize>, iterations: u64, start: Instant) -> Self {
+        Solution {
+            prize: problem.prize(&route),
+            length: problem.length(&route),
+            route,
+            iterations,
+            elapsed: start.elapsed(),
+            termination: Termination::Completed
+        }
+    }
+}
+
+/// Builds a route by cheapest insertion weighed by prize. Every round, the point with the highest prize per unit of
+/// added length, over the cheapest place to insert it, joins the route, as long as the route still fits in the
+/// budget. Once no point fits, the route is shortened with 2-opt and the insertions start again, until neither
+/// helps. The iteration count is the number of points inserted.
+pub fn greedy(problem: &Problem<'_>) -> Result<Solution> {
+    let start = Instant::now();
+    problem.validate()?;
+
+    let mut route = Vec::new();
+    let mut length = problem.length(&route);
+    let mut free = problem.customers();
+    let mut inserted = 0;
+    loop {
+        while let Some((index, position)) = best_insertion(problem, &route, &free, length) {
+            route.insert(position, free.swap_remove(index));
+            length = problem.length(&route);
+            // The added length can round under the budget while the route's own sum rounds over it
+            if length > problem.budget {
+                route.remove(position);
+                length = problem.length(&route);
+                continue;
+            }
+            inserted += 1;
+        }
+
+        if !shorten(problem, &mut route) {
+            break;
+        }
+        length = problem.length(&route);
+    }
+
+    Ok(Solution::new(problem, route, inserted, start))
+}
+
+// The free point with the highest prize per unit of added length that fits in the budget, and where it goes
+fn best_insertion(problem: &Problem<'_>, route: &[usize], free: &[usize], length: f32) -> Option<(usize, usize)> {
+    let mut best: Option<(f32, usize, usize)> = None;
+    for (index, &point) in free.iter().enumerate() {
+        let (position, added) = (0..=route.len())
+            .map(|i| {
+                let (before, after) = problem.around(route, i);
+                (i, problem.dist(before, point) + problem.dist(point, after) - problem.dist(before, after))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            // Infallible: there is always at least one position
+            .unwrap();
+        if length + added > problem.budget {
+            continue;
+        }
+
+        let ratio = problem.prizes[point] / added.max(f32::EPSILON);
+        if best.is_none_or(|(best_ratio, ..)| ratio > best_ratio) {
+            best = Some((ratio, index, position));
+        }
+    }
+    best.map(|(_, index, position)| (index, position))
+}
+
+// Applies improving 2-opt moves to the closed route through the depot, returning whether any was made
+fn shorten(problem: &Problem<'_>, route: &mut [usize]) -> bool {
+    let tolerance = problem.length(route) * 1e-6;
+    let mut shortened = false;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..route.len() {
+            for j in i + 1..route.len() {
+                let (before, _) = problem.around(route, i);
+                let (_, after) = problem.around(route, j + 1);
+                let delta = problem.dist(before, route[j]) + problem.dist(route[i], after)
+                    - problem.dist(before, route[i])
+                    - problem.dist(route[j], after);
+                if delta < -tolerance {
+                    route[i..=j].reverse();
+                    improved = true;
+                    shortened = true;
+                }
+            }
+        }
+    }
+    shortened
+}
+
+/// Takes the points of a giant tour through every point but the depot in order, adding each to the route if it
+/// still fits in the budget and skipping it otherwise. This is the decoder of [`OrienteeringGa`], and runs in
+/// `O(n)` time.
+pub fn decode(problem: &Problem<'_>, giant: &[usize]) -> Vec<usize> {
+    let mut route = Vec::new();
+    // The length from the depot to the last point of the route
+    let mut open = 0.0;
+    let mut last = problem.depot;
+    for &point in giant {
+        let reached = open + problem.dist(last, point);
+        if reached + problem.dist(point, problem.depot) <= problem.budget {
+            route.push(point);
+            open = reached;
+            last = point;
+        }
+    }
+
+    // Summing the route in another order can round it over the budget, so drop points from the end until the
+    // length the problem checks fits
+    while problem.length(&route) > problem.budget {
+        route.pop();
+    }
+    route
+}
+
+/// A GA over giant tours, decoded into routes by [`decode`], which ranks routes by their prize and then by their
+/// length. Each generation the `elites` best tours survive, and the rest of the population is filled with children
+/// of tournament winners, made by order crossover and then mutated by reversing or swapping. The population starts
+/// from the [`greedy`] route followed by the points it leaves out, and random tours. The best route is shortened
+/// with 2-opt at the end, which keeps its prize.
+#[derive(Clone, Debug)]
+pub struct OrienteeringGa {
+    pub population: usize,
+    pub generations: u32,
+    /// The number of individuals competing in each tournament
+    pub tournament: usize,
+    pub elites: usize,
+    pub crossover_prob: f32,
+    pub mutate_prob: f32
+}
+
+impl Default for OrienteeringGa {
+    fn default() -> Self {
+        OrienteeringGa {
+            population: 100,
+            generations: 1000,
+            tournament: 3,
+            elites: 2,
+            crossover_prob: 0.9,
+            mutate_prob: 0.3
+        }
+    }
+}
+
+impl OrienteeringGa {
+    /// Checks that the population has at least two individuals and that tournaments have at least one, failing
+    /// with [`Error::InvalidSettings`] otherwise.
+    pub fn validate(&self) -> Result<()> {
+        check_population(self.population, 2)?;
+        if self.tournament == 0 {
+            return Err(Error::InvalidSettings("the tournament is 0, but needs at least one individual".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Runs the GA for the configured number of generations or until the budget runs out, returning the route of
+    /// the best giant tour found. The iteration count is the number of generations run.
+    pub fn solve(&self, problem: &Problem<'_>, options: &SolveOptions) -> Result<Solution> {
+        let start = Instant::now();
+        problem.validate()?;
+        self.validate()?;
+
+        let mut budget = Budget::new(options, start, 1);
+        let mut reporter = Reporter::new(options, start).objective(Objective::Maximize);
+        let mut rng = options.rng();
+        let evaluate = |giant: &[usize]| {
+            let route = decode(problem, giant);
+            (problem.prize(&route), problem.length(&route))
+        };
+        // More prize first, then less length
+        let rank = |(a, _): &((f32, f32), Vec<usize>), (b, _): &((f32, f32), Vec<usize>)| {
+            b.0.total_cmp(&a.0).then(a.1.total_cmp(&b.1))
+        };
+
+        let customers = problem.customers();
+        let mut seeded = greedy(problem)?.route;
+        let mut visited = vec![false; problem.map.size()];
+        seeded.iter().for_each(|&point| visited[point] = true);
+        seeded.extend(customers.iter().filter(|&&point| !visited[point]));
+        let mut population: Vec<Vec<usize>> = vec![seeded];
+        population.extend((1..self.population).map(|_| {
+            let mut giant = customers.clone();
+            giant.shuffle(&mut rng);
+            giant
+        }));
+        let mut ranked: Vec<((f32, f32), Vec<usize>)> = population.into_iter()
+            .map(|giant| (evaluate(&giant), giant))
+            .collect();
+        ranked.sort_by(rank);
+        reporter.improved(ranked[0].0 .0);
+
+        let elites = self.elites.clamp(1, self.population - 1);
+        let mut present = vec![false; problem.map.size()];
+        let mut generations = 0;
+        while generations < self.generations && !budget.exhausted() {
+            let mut next: Vec<((f32, f32), Vec<usize>)> = ranked[..elites].to_vec();
+            while next.len() < self.population {
+                // The population is ranked, so the lowest index drawn wins
+                let mut tournament = || (0..self.tournament).map(|_| rng.gen_range(0, ranked.len())).min().unwrap();
+                let (first, second) = (tournament(), tournament());
+                let mut child = if rng.gen::<f32>() < self.crossover_prob {
+                    order_crossover(&ranked[first].1, &ranked[second].1, &mut present, &mut rng)
+                } else {
+                    ranked[first].1.clone()
+                };
+                if rng.gen::<f32>() < self.mutate_prob && child.len() > 1 {
+                    let (a, b) = (rng.gen_range(0, child.len()), rng.gen_range(0, child.len()));
+                    if rng.gen() {
+                        child[a.min(b)..=a.max(b)].reverse();
+                    } else {
+                        child.swap(a, b);
+                    }
+                }
+                next.push((evaluate(&child), child));
+            }
+
+            next.sort_by(rank);
+            ranked = next;
+            reporter.improved(ranked[0].0 .0);
+            generations += 1;
+            reporter.generation(generations, self.generations);
+        }
+
+        let mut route = decode(problem, &ranked[0].1);
+        shorten(problem, &mut route);
+        let mut result = Solution::new(problem, route, generations as u64, start);
+        result.termination = budget.termination();
+        Ok(result)
+    }
+}
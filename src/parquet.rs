@@ -0,0 +1,260 @@
+use arrow_array::types::Float64Type;
+use arrow_array::{ArrayRef, Float32Array, Float64Array, ListArray, RecordBatch, StringArray, UInt32Array, UInt64Array};
+use ::parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path;
+use std::sync::{Arc, Mutex};
+
+use crate::bench::{Cell, Metric, Summary, Table};
+use crate::error::{Error, Result};
+use crate::solve::{Progress, ProgressEvent};
+use crate::time::Instant;
+
+fn convert<E: ToString>(error: E) -> Error {
+    Error::Io {
+        kind: io::ErrorKind::Other,
+        message: error.to_string()
+    }
+}
+
+// Writes the columns as a single row group
+fn write_columns<W: Write + Send>(out: W, columns: Vec<(String, ArrayRef)>) -> Result<()> {
+    let batch = RecordBatch::try_from_iter(columns).map_err(convert)?;
+    let mut writer = ArrowWriter::try_new(out, batch.schema(), None).map_err(convert)?;
+    writer.write(&batch).map_err(convert)?;
+    writer.close().map_err(convert)?;
+    Ok(())
+}
+
+fn floats<I: IntoIterator<Item = f64>>(values: I) -> ArrayRef {
+    Arc::new(values.into_iter().collect::<Float64Array>())
+}
+
+fn strings<'a, I: IntoIterator<Item = &'a str>>(values: I) -> ArrayRef {
+    Arc::new(values.into_iter().map(Some).collect::<StringArray>())
+}
+
+// A column for every statistic of the summaries, named after the quantity they summarize
+fn summary_columns(name: &str, summaries: &[Summary]) -> Vec<(String, ArrayRef)> {
+    let column = |statistic: &str, value: fn(&Summary) -> f64| {
+        (format!("{}_{}", name, statistic), floats(summaries.iter().map(value)))
+    };
+    vec![
+        column("mean", |summary| summary.mean),
+        column("median", |summary| summary.median),
+        column("std_dev", |summary| summary.std_dev),
+        column("min", |summary| summary.min),
+        column("max", |summary| summary.max),
+        column("ci95", |summary| summary.ci95)
+    ]
+}
+
+/// Writes the cells of a [`Benchmark`](crate::bench::Benchmark) as Parquet, one row per cell: the instance,
+/// its number of points and the solver, every statistic of the length, seconds and iterations as columns such as
+/// `length_mean` and `seconds_ci95`, the length of every repetition as a list, the gap and the fraction above the
+/// optimum, which is null for instances without a known one.
+pub fn write_cells<W: Write + Send>(out: W, cells: &[Cell]) -> Result<()> {
+    let lengths: Vec<Summary> = cells.iter().map(|cell| cell.length).collect();
+    let seconds: Vec<Summary> = cells.iter().map(|cell| cell.seconds).collect();
+    let iterations: Vec<Summary> = cells.iter().map(|cell| cell.iterations).collect();
+    let repetitions = ListArray::from_iter_primitive::<Float64Type, _, _>(
+        cells.iter().map(|cell| Some(cell.lengths.iter().copied().map(Some)))
+    );
+
+    let mut columns = vec![
+        ("instance".to_owned(), strings(cells.iter().map(|cell| cell.instance.as_str()))),
+        ("points".to_owned(), Arc::new(cells.iter().map(|cell| cell.points as u64).collect::<UInt64Array>()) as _),
+        ("solver".to_owned(), strings(cells.iter().map(|cell| cell.solver.as_str())))
+    ];
+    columns.extend(summary_columns("length", &lengths));
+    columns.extend(summary_columns("seconds", &seconds));
+    columns.extend(summary_columns("iterations", &iterations));
+    columns.push(("lengths".to_owned(), Arc::new(repetitions)));
+    columns.push(("gap".to_owned(), floats(cells.iter().map(|cell| cell.gap))));
+    let above = cells.iter().map(|cell| cell.above_optimal).collect::<Float64Array>();
+    columns.push(("above_optimal".to_owned(), Arc::new(above)));
+    write_columns(out, columns)
+}
+
+pub fn save_cells<P: AsRef<path::Path>>(path: P, cells: &[Cell]) -> Result<()> {
+    write_cells(File::create(path)?, cells)
+}
+
+// The column an experiment's metric is written to
+fn metric_name(metric: Metric) -> String {
+    match metric {
+        Metric::Length => "length".to_owned(),
+        Metric::Seconds => "seconds".to_owned(),
+        Metric::Iterations => "iterations".to_owned(),
+        Metric::Gap => "gap".to_owned(),
+        Metric::Counter(name) => name.to_owned()
+    }
+}
+
+/// Writes the table of an [`Experiment`](crate::bench::Experiment) as Parquet, one row per solve: the size,
+/// instance, solver and repetition, then a column for each metric, named `length`, `seconds`, `iterations`,
+/// `gap`, or the name of the counter.
+pub fn write_table<W: Write + Send>(out: W, table: &Table) -> Result<()> {
+    let rows = &table.rows;
+    let mut columns = vec![
+        ("size".to_owned(), Arc::new(rows.iter().map(|row| row.size as u64).collect::<UInt64Array>()) as ArrayRef),
+        ("instance".to_owned(), Arc::new(rows.iter().map(|row| row.instance as u64).collect::<UInt64Array>())),
+        ("solver".to_owned(), strings(rows.iter().map(|row| row.solver.as_str()))),
+        ("repetition".to_owned(), Arc::new(rows.iter().map(|row| row.repetition).collect::<UInt32Array>()))
+    ];
+    for (index, &metric) in table.metrics.iter().enumerate() {
+        columns.push((metric_name(metric), floats(rows.iter().map(|row| row.values[index]))));
+    }
+    write_columns(out, columns)
+}
+
+pub fn save_table<P: AsRef<path::Path>>(path: P, table: &Table) -> Result<()> {
+    write_table(File::create(path)?, table)
+}
+
+// One event, with the fields it does not have left empty
+#[derive(Default)]
+struct Event {
+    event: &'static str,
+    seconds: f64,
+    fraction: Option<f32>,
+    nodes: Option<u64>,
+    generation: Option<u32>,
+    generations: Option<u32>,
+    step: Option<u64>,
+    best: Option<f32>,
+    mean: Option<f32>,
+    diversity: Option<f32>,
+    length: Option<f32>
+}
+
+/// Records the progress of a solve when attached as its progress observer, to be written as a Parquet table once
+/// it has finished. Parquet files are written whole, so unlike `Telemetry`, which
+/// streams the same events as JSON Lines, nothing is written during the solve.
+///
+/// Every event is a row with its `event` in snake case and the `seconds` since the table was created, and a
+/// column for every field of every event, which is null in the rows of the events without it. Samples and
+/// diversity are only recorded by [`with_samples`](TelemetryTable::with_samples).
+pub struct TelemetryTable {
+    events: Mutex<Vec<Event>>,
+    start: Instant,
+    samples: bool
+}
+
+impl Default for TelemetryTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TelemetryTable {
+    /// An empty table, with the clock started now.
+    pub fn new() -> Self {
+        TelemetryTable {
+            events: Mutex::new(Vec::new()),
+            start: Instant::now(),
+            samples: false
+        }
+    }
+
+    /// Also records the [`Sample`](ProgressEvent::Sample) and [`Diversity`](ProgressEvent::Diversity) events,
+    /// which makes the solvers compute them.
+    pub fn with_samples(self) -> Self {
+        TelemetryTable {
+            samples: true,
+            ..self
+        }
+    }
+
+    /// The number of events recorded so far.
+    pub fn len(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write(File::create(path)?)
+    }
+
+    pub fn write<W: Write + Send>(&self, out: W) -> Result<()> {
+        let events = self.events.lock().unwrap();
+        let singles = |field: fn(&Event) -> Option<f32>| {
+            Arc::new(events.iter().map(field).collect::<Float32Array>()) as ArrayRef
+        };
+        let counts = |field: fn(&Event) -> Option<u32>| {
+            Arc::new(events.iter().map(field).collect::<UInt32Array>()) as ArrayRef
+        };
+        let longs = |field: fn(&Event) -> Option<u64>| {
+            Arc::new(events.iter().map(field).collect::<UInt64Array>()) as ArrayRef
+        };
+
+        let columns = vec![
+            ("event".to_owned(), strings(events.iter().map(|event| event.event))),
+            ("seconds".to_owned(), floats(events.iter().map(|event| event.seconds))),
+            ("fraction".to_owned(), singles(|event| event.fraction)),
+            ("nodes".to_owned(), longs(|event| event.nodes)),
+            ("generation".to_owned(), counts(|event| event.generation)),
+            ("generations".to_owned(), counts(|event| event.generations)),
+            ("step".to_owned(), longs(|event| event.step)),
+            ("best".to_owned(), singles(|event| event.best)),
+            ("mean".to_owned(), singles(|event| event.mean)),
+            ("diversity".to_owned(), singles(|event| event.diversity)),
+            ("length".to_owned(), singles(|event| event.length))
+        ];
+        write_columns(out, columns)
+    }
+}
+
+impl Progress for TelemetryTable {
+    fn report(&self, event: ProgressEvent) {
+        let seconds = self.start.elapsed().as_secs_f64();
+        let event = match event {
+            ProgressEvent::Completion(fraction) => Event {
+                event: "completion",
+                fraction: Some(fraction),
+                ..Event::default()
+            },
+            ProgressEvent::Nodes(nodes) => Event {
+                event: "nodes",
+                nodes: Some(nodes),
+                ..Event::default()
+            },
+            ProgressEvent::Generation { generation, generations } => Event {
+                event: "generation",
+                generation: Some(generation),
+                generations: Some(generations),
+                ..Event::default()
+            },
+            ProgressEvent::Sample { step, best, mean, .. } if self.samples => Event {
+                event: "sample",
+                step: Some(step),
+                best: Some(best),
+                mean: Some(mean),
+                ..Event::default()
+            },
+            ProgressEvent::Diversity(diversity) if self.samples => Event {
+                event: "diversity",
+                diversity: Some(diversity),
+                ..Event::default()
+            },
+            ProgressEvent::Improvement { length, .. } => Event {
+                event: "improvement",
+                length: Some(length),
+                ..Event::default()
+            },
+            ProgressEvent::Sample { .. } | ProgressEvent::Diversity(_) => return
+        };
+        self.events.lock().unwrap().push(Event {
+            seconds,
+            ..event
+        });
+    }
+
+    fn detailed(&self) -> bool {
+        self.samples
+    }
+}
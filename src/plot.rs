@@ -0,0 +1,89 @@
+use plotters::prelude::*;
+use std::fmt::Display;
+use std::io;
+use std::ops::Range;
+use std::path;
+
+use crate::error::{Error, Result};
+use crate::history::Sample;
+use crate::map::Map;
+use crate::render::{SvgOptions, worst_edges};
+
+// Neither chart has text, since plotters can only draw it with system font libraries
+fn convert<E: Display>(error: E) -> Error {
+    Error::Io {
+        kind: io::ErrorKind::Other,
+        message: error.to_string()
+    }
+}
+
+// Pads a range so that nothing is drawn on the edge of the chart, and so that it is never empty
+fn padded(min: f32, max: f32) -> Range<f32> {
+    let pad = ((max - min) * 0.05).max(f32::EPSILON);
+    min - pad..max + pad
+}
+
+/// Draws the map and tour like [`render::write_svg`](crate::render::write_svg), but as a PNG.
+pub fn save_png<P: AsRef<path::Path>>(path: P, map: &Map, tour: Option<&[usize]>, options: &SvgOptions) -> Result<()> {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for point in map {
+        min_x = min_x.min(point.x);
+        min_y = min_y.min(point.y);
+        max_x = max_x.max(point.x);
+        max_y = max_y.max(point.y);
+    }
+
+    // Keep the aspect ratio by giving both axes the larger extent
+    let extent = (max_x - min_x).max(max_y - min_y);
+    let root = BitMapBackend::new(path.as_ref(), (options.size, options.size)).into_drawing_area();
+    root.fill(&WHITE).map_err(convert)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(options.size / 20)
+        .build_cartesian_2d(padded(min_x, min_x + extent), padded(min_y, min_y + extent))
+        .map_err(convert)?;
+
+    if let Some(tour) = tour {
+        let steelblue = RGBColor(70, 130, 180);
+        let line = tour.iter().map(|&city| (map[city].x, map[city].y));
+        chart.draw_series(LineSeries::new(line, steelblue.stroke_width(2))).map_err(convert)?;
+
+        let crimson = RGBColor(220, 20, 60);
+        for (from, to) in worst_edges(map, tour, options.worst_edges) {
+            let edge = vec![(map[from].x, map[from].y), (map[to].x, map[to].y)];
+            chart.draw_series(LineSeries::new(edge, crimson.stroke_width(3))).map_err(convert)?;
+        }
+    }
+
+    chart.draw_series(map.iter().map(|point| Circle::new((point.x, point.y), 3, BLACK.filled())))
+        .map_err(convert)?;
+    root.present().map_err(convert)?;
+    Ok(())
+}
+
+/// Plots the best length in blue and the mean length in grey against the step.
+pub fn save_convergence_png<P: AsRef<path::Path>>(path: P, samples: &[Sample], size: (u32, u32)) -> Result<()> {
+    let last_step = samples.iter().map(|sample| sample.step).max().unwrap_or(1).max(1);
+    let (mut low, mut high) = (f32::MAX, f32::MIN);
+    for sample in samples {
+        low = low.min(sample.best);
+        high = high.max(sample.mean);
+    }
+    if samples.is_empty() {
+        low = 0.0;
+        high = 1.0;
+    }
+
+    let root = BitMapBackend::new(path.as_ref(), size).into_drawing_area();
+    root.fill(&WHITE).map_err(convert)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(size.0.min(size.1) / 20)
+        .build_cartesian_2d(0f32..last_step as f32, padded(low, high))
+        .map_err(convert)?;
+
+    let mean = samples.iter().map(|sample| (sample.step as f32, sample.mean));
+    chart.draw_series(LineSeries::new(mean, RGBColor(160, 160, 160))).map_err(convert)?;
+    let best = samples.iter().map(|sample| (sample.step as f32, sample.best));
+    chart.draw_series(LineSeries::new(best, BLUE.stroke_width(2))).map_err(convert)?;
+    root.present().map_err(convert)?;
+    Ok(())
+}
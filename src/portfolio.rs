@@ -0,0 +1,230 @@
+use rand::prelude::*;
+use std::panic;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+use crate::context::SolverContext;
+use crate::error::Result;
+use crate::ga::{Chromosome, GaParams, GaSolver, best_by, random_population, repair_neighbors, run_generations};
+use crate::local::{SimulatedAnnealing, double_bridge, initial_tour, lin_kernighan};
+use crate::map::{Map, Path};
+use crate::solve::{Budget, Objective, Reporter, SharedBest, SolveOptions, SolveResult, Solver, Termination, check_map};
+use crate::time::Instant;
+
+/// Runs the roulette wheel GA, simulated annealing and Lin-Kernighan at once, each on its own thread whatever the
+/// [`parallelism`](SolveOptions::parallelism), and returns the best tour any of them found.
+///
+/// The members share the best tour found so far, the incumbent, and take it up whenever it beats their own at the
+/// start of a round: the GA puts it in place of its worst individual, annealing starts from it, and Lin-Kernighan
+/// searches from it, or otherwise from a double bridge kick of its own best tour. A round is the GA's
+/// `generations`, one run of annealing's `iterations`, or one Lin-Kernighan search. Every member runs `rounds`
+/// rounds unless the time limit runs out or the token is cancelled first. The iteration count is the number of
+/// rounds run by all members together.
+pub struct Portfolio<C> {
+    pub ga: GaSolver<C>,
+    pub annealing: SimulatedAnnealing,
+    pub rounds: u32
+}
+
+impl<C> Portfolio<C> {
+    /// A portfolio of the two solvers and Lin-Kernighan running 10 rounds each.
+    pub fn new(ga: GaSolver<C>, annealing: SimulatedAnnealing) -> Self {
+        Portfolio {
+            ga,
+            annealing,
+            rounds: 10
+        }
+    }
+}
+
+impl<C: Chromosome> Solver for Portfolio<C> {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let start = Instant::now();
+        check_map(map)?;
+        self.ga.settings.validate()?;
+        let objective = options.objective;
+        let tour = initial_tour(map)?;
+        let incumbent = Incumbent::new(map, tour, Reporter::new(options, start).objective(objective), objective);
+
+        // Each member draws its own seeds, so that a seeded solve still seeds every member
+        let mut rng = options.rng();
+        let seeds = [rng.gen(), rng.gen(), rng.gen()];
+        let outcomes: Vec<Result<(u64, Termination)>> = thread::scope(|scope| {
+            let incumbent = &incumbent;
+            let members = vec![
+                scope.spawn(move || self.run_ga(map, options, incumbent, start, seeds[0])),
+                scope.spawn(move || self.run_annealing(map, options, incumbent, start, seeds[1])),
+                scope.spawn(move || self.run_lin_kernighan(map, options, incumbent, start, seeds[2]))
+            ];
+            // A member that panicked passes its panic on to the caller
+            members.into_iter()
+                .map(|member| member.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+                .collect()
+        });
+
+        let mut rounds = 0;
+        let mut termination = Termination::Completed;
+        for outcome in outcomes {
+            let (count, ended) = outcome?;
+            rounds += count;
+            if ended != Termination::Completed {
+                termination = ended;
+            }
+        }
+
+        let (_, tour) = incumbent.best.into_inner();
+        let mut result = SolveResult::new(map, tour, rounds, start);
+        result.termination = termination;
+        Ok(result)
+    }
+}
+
+impl<C: Chromosome> Portfolio<C> {
+    fn run_ga(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        incumbent: &Incumbent<'_>,
+        start: Instant,
+        seed: u64
+    ) -> Result<(u64, Termination)> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut population = random_population(map, self.ga.population, self.ga.new_chromosome, &mut rng);
+        population.iter().try_for_each(|indv| indv.validate(map))?;
+
+        let neighbors = repair_neighbors(map, self.ga.fix, SolverContext::of(options, map));
+        let mut budget = Budget::new(options, start, 1);
+        let mut rounds = 0;
+        while rounds < self.rounds && !budget.exhausted() {
+            let (loss, _) = best_by(map, &population, options.objective);
+            if let Some(tour) = incumbent.take(loss) {
+                let losses: Vec<f32> = population.iter()
+                    .map(|indv| options.objective.loss(indv.evaluate(map)))
+                    .collect();
+                let worst = (0..population.len())
+                    .max_by(|&a, &b| losses[a].total_cmp(&losses[b]));
+                if let Some(worst) = worst {
+                    population[worst].encode(map, &tour)?;
+                }
+            }
+
+            let params = GaParams {
+                settings: &self.ga.settings,
+                recombinator: &self.ga.recombinator,
+                repair: neighbors.as_ref().into(),
+                objective: options.objective,
+                first_generation: rounds.saturating_mul(self.ga.generations),
+                generations: (rounds + 1).saturating_mul(self.ga.generations)
+            };
+            let mut reporter = Reporter::silent();
+            let outcome = run_generations(&params, map, &mut population, &mut budget, &mut reporter, &mut rng);
+            incumbent.offer(outcome.loss, &outcome.tour);
+            rounds += 1;
+        }
+        Ok((rounds as u64, budget.termination()))
+    }
+
+    fn run_annealing(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        incumbent: &Incumbent<'_>,
+        start: Instant,
+        seed: u64
+    ) -> Result<(u64, Termination)> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tour = initial_tour(map)?;
+        let mut budget = Budget::new(options, start, 1);
+        let mut rounds = 0;
+        while rounds < self.rounds && !budget.exhausted() {
+            if let Some(better) = incumbent.take(options.objective.loss(tour.evaluate(map))) {
+                tour = better;
+            }
+
+            let result = self.annealing.improve(map, &tour, &member_options(options, start, rng.gen()))?;
+            incumbent.offer(options.objective.loss(result.length), &result.tour);
+            tour = result.tour;
+            rounds += 1;
+        }
+        Ok((rounds as u64, budget.termination()))
+    }
+
+    fn run_lin_kernighan(
+        &self,
+        map: &Map,
+        options: &SolveOptions,
+        incumbent: &Incumbent<'_>,
+        start: Instant,
+        seed: u64
+    ) -> Result<(u64, Termination)> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut best = initial_tour(map)?;
+        let mut loss = options.objective.loss(best.evaluate(map));
+        let mut budget = Budget::new(options, start, 1);
+        let mut rounds = 0;
+        while rounds < self.rounds && !budget.exhausted() {
+            let from = match incumbent.take(loss) {
+                Some(tour) => tour,
+                // The first round searches from the starting tour itself
+                None if rounds == 0 => best.clone(),
+                None => double_bridge(map, &best, &mut rng).unwrap_or_else(|| best.clone())
+            };
+
+            let result = lin_kernighan(map, &from, &member_options(options, start, rng.gen()))?;
+            let found = options.objective.loss(result.length);
+            if found <= loss {
+                incumbent.offer(found, &result.tour);
+                best = result.tour;
+                loss = found;
+            }
+            rounds += 1;
+        }
+        Ok((rounds as u64, budget.termination()))
+    }
+}
+
+// The options for one round of a member: what is left of the time limit, and no observers, since improvements are
+// reported through the incumbent
+pub(crate) fn member_options(options: &SolveOptions, start: Instant, seed: u64) -> SolveOptions {
+    SolveOptions {
+        time_limit: options.time_limit.map(|limit| limit.saturating_sub(start.elapsed())),
+        progress: None,
+        instrumentation: None,
+        seed: Some(seed),
+        ..options.clone()
+    }
+}
+
+// The best tour found by any member, which reports every improvement as it is offered
+struct Incumbent<'a> {
+    best: SharedBest,
+    reporter: Mutex<Reporter<'a>>,
+    objective: Objective
+}
+
+impl<'a> Incumbent<'a> {
+    fn new(map: &Map, tour: Vec<usize>, mut reporter: Reporter<'a>, objective: Objective) -> Self {
+        let loss = objective.loss(tour.evaluate(map));
+        reporter.improved(objective.length(loss));
+        reporter.tour(&tour);
+        Incumbent {
+            best: SharedBest::with_tour(loss, tour),
+            reporter: Mutex::new(reporter),
+            objective
+        }
+    }
+
+    // A copy of the incumbent if it is better than a tour of the given loss
+    fn take(&self, loss: f32) -> Option<Vec<usize>> {
+        self.best.better_than(loss)
+    }
+
+    // Replaces the incumbent with the tour if it is better, reporting the improvement
+    fn offer(&self, loss: f32, tour: &[usize]) {
+        self.best.offer_with(loss, tour, |loss, tour| {
+            let mut reporter = self.reporter.lock().unwrap_or_else(PoisonError::into_inner);
+            reporter.improved(self.objective.length(loss));
+            reporter.tour(tour);
+        });
+    }
+}
@@ -0,0 +1,120 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::error::{Error, Result};
+
+/// Ordered pairs of points, where the first of each pair must be visited before the second, as when a passenger
+/// must be picked up before being dropped off. Attached to a map with
+/// [`Map::with_precedences`](crate::map::Map::with_precedences).
+#[derive(Clone, Debug)]
+pub struct Precedences {
+    pairs: Vec<(usize, usize)>,
+    // The points that must come before each point
+    predecessors: Box<[Vec<usize>]>
+}
+
+impl Precedences {
+    /// Checks that every pair names two different points of a map with `size` points, and that no chain of pairs
+    /// loops back on itself, which would make every tour infeasible.
+    pub fn new(size: usize, pairs: Vec<(usize, usize)>) -> Result<Self> {
+        let mut predecessors = vec![Vec::new(); size].into_boxed_slice();
+        for &(before, after) in &pairs {
+            if before >= size || after >= size {
+                return Err(Error::InvalidProblem(format!("the pair ({}, {}) is out of range", before, after)));
+            }
+            if before == after {
+                return Err(Error::InvalidProblem(format!("point {} cannot precede itself", before)));
+            }
+            predecessors[after].push(before);
+        }
+
+        let precedences = Precedences {
+            pairs,
+            predecessors
+        };
+        let identity: Vec<usize> = (0..size).collect();
+        if precedences.repair(&identity).len() < size {
+            return Err(Error::InvalidProblem("the precedences form a cycle".to_string()));
+        }
+        Ok(precedences)
+    }
+
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+
+    pub fn size(&self) -> usize {
+        self.predecessors.len()
+    }
+
+    /// The points that must be visited before the given point.
+    pub fn predecessors(&self, point: usize) -> &[usize] {
+        &self.predecessors[point]
+    }
+
+    /// Checks that the tour visits the first point of every pair before the second. The tour must be a
+    /// permutation of the points.
+    pub fn check(&self, tour: &[usize]) -> Result<()> {
+        let mut position = vec![0; self.size()];
+        for (i, &point) in tour.iter().enumerate() {
+            position[point] = i;
+        }
+
+        match self.pairs.iter().find(|&&(before, after)| position[before] > position[after]) {
+            Some((before, after)) => Err(Error::InvalidTour(format!("point {} is visited after {}", before, after))),
+            None => Ok(())
+        }
+    }
+
+    /// The tour closest to the given one that satisfies every pair: the points are taken in the order of the
+    /// tour, except that a point whose predecessors have not all been taken waits until they have. Tours that
+    /// already satisfy the pairs are returned unchanged.
+    pub fn repair(&self, tour: &[usize]) -> Vec<usize> {
+        let mut position = vec![0; self.size()];
+        for (i, &point) in tour.iter().enumerate() {
+            position[point] = i;
+        }
+
+        let mut waiting: Vec<usize> = self.predecessors.iter().map(Vec::len).collect();
+        let mut successors = vec![Vec::new(); self.size()];
+        for &(before, after) in &self.pairs {
+            successors[before].push(after);
+        }
+
+        // Points are taken by their position in the tour once nothing holds them back
+        let mut ready: BinaryHeap<Reverse<usize>> = tour.iter()
+            .filter(|&&point| waiting[point] == 0)
+            .map(|&point| Reverse(position[point]))
+            .collect();
+        let mut repaired = Vec::with_capacity(tour.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            let point = tour[i];
+            repaired.push(point);
+            for &after in &successors[point] {
+                waiting[after] -= 1;
+                if waiting[after] == 0 {
+                    ready.push(Reverse(position[after]));
+                }
+            }
+        }
+        repaired
+    }
+
+    // Follows the map swapping two of its points
+    pub(crate) fn swap(&mut self, first: usize, second: usize) {
+        let relabel = |point: &mut usize| {
+            if *point == first {
+                *point = second;
+            } else if *point == second {
+                *point = first;
+            }
+        };
+
+        for (before, after) in &mut self.pairs {
+            relabel(before);
+            relabel(after);
+        }
+        self.predecessors.swap(first, second);
+        self.predecessors.iter_mut().flatten().for_each(relabel);
+    }
+}
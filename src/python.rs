@@ -0,0 +1,408 @@
+// The code generated by pyo3's macros converts the error of every wrapped function, even when it is already a PyErr
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::ga::{
+    AnyRecombinator,
+    Brkga,
+    BrkgaSolver,
+    DifferentialEvolution,
+    DifferentialEvolutionSolver,
+    EdgeHistogram,
+    EdgeHistogramSolver,
+    GaSolver,
+    KPoint,
+    OperatorSelection,
+    PopulationSchedule,
+    Settings,
+    Uniform
+};
+use crate::map::{Decoder, Map, Point, RandomKeyPath, RemovalIndex, SwapPath};
+use crate::solve::{self, SolveOptions, SolveResult, Solver, Termination};
+
+impl From<Error> for PyErr {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io { .. } => PyOSError::new_err(error.to_string()),
+            _ => PyValueError::new_err(error.to_string())
+        }
+    }
+}
+
+/// The points to visit.
+#[pyclass(name = "Map", module = "traveling_salesman")]
+#[derive(Clone)]
+pub struct PyMap {
+    map: Map
+}
+
+#[pymethods]
+impl PyMap {
+    /// Builds a map from a sequence of `(x, y)` pairs.
+    #[new]
+    fn new(points: Vec<(f32, f32)>) -> Self {
+        PyMap {
+            map: points.into_iter().map(Point::from).collect()
+        }
+    }
+
+    /// `count` random points on the unit disc.
+    #[staticmethod]
+    fn random(count: usize) -> Self {
+        PyMap {
+            map: Map::new(count)
+        }
+    }
+
+    fn points(&self) -> Vec<(f32, f32)> {
+        self.map.iter().map(|&point| point.into()).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.map.size()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Map(<{} points>)", self.map.size())
+    }
+}
+
+/// The outcome of a solve.
+#[pyclass(name = "SolveResult", module = "traveling_salesman", frozen)]
+pub struct PySolveResult {
+    #[pyo3(get)]
+    tour: Vec<usize>,
+    #[pyo3(get)]
+    length: f32,
+    #[pyo3(get)]
+    lower_bound: Option<f32>,
+    #[pyo3(get)]
+    iterations: u64,
+    /// Wall-clock time in seconds
+    #[pyo3(get)]
+    elapsed: f64,
+    /// One of `"completed"`, `"time_limit"`, `"cancelled"`, `"target_gap"` or `"evaluation_limit"`
+    #[pyo3(get)]
+    termination: &'static str,
+    #[pyo3(get)]
+    gap: Option<f32>,
+    /// Whether the tour is proven optimal
+    #[pyo3(get)]
+    optimal: bool
+}
+
+impl From<SolveResult> for PySolveResult {
+    fn from(result: SolveResult) -> Self {
+        PySolveResult {
+            optimal: result.is_optimal(),
+            gap: result.gap(),
+            termination: match result.termination {
+                Termination::Completed => "completed",
+                Termination::TimeLimit => "time_limit",
+                Termination::Cancelled => "cancelled",
+                Termination::TargetGap => "target_gap",
+                Termination::EvaluationLimit => "evaluation_limit"
+            },
+            elapsed: result.elapsed.as_secs_f64(),
+            iterations: result.iterations,
+            lower_bound: result.lower_bound,
+            length: result.length,
+            tour: result.tour
+        }
+    }
+}
+
+#[pymethods]
+impl PySolveResult {
+    fn __repr__(&self) -> String {
+        format!("SolveResult(length={}, termination={:?})", self.length, self.termination)
+    }
+}
+
+// A time limit in seconds, where negative ones stop at once and ones too long to represent are rejected
+fn duration(seconds: Option<f64>) -> PyResult<Option<Duration>> {
+    seconds
+        .map(|seconds| {
+            Duration::try_from_secs_f64(seconds.max(0.0))
+                .map_err(|_| PyValueError::new_err(format!("invalid time limit {}", seconds)))
+        })
+        .transpose()
+}
+
+// The solve itself runs without the GIL so that other Python threads keep going
+fn solve_with<S: Solver + Sync>(
+    py: Python<'_>,
+    solver: &S,
+    map: &PyMap,
+    time_limit: Option<f64>
+) -> PyResult<PySolveResult> {
+    let options = SolveOptions {
+        time_limit: duration(time_limit)?,
+        ..SolveOptions::default()
+    };
+    let result = py.allow_threads(|| solver.solve(&map.map, &options))?;
+    Ok(result.into())
+}
+
+#[pyfunction]
+fn nearest_neighbor(map: &PyMap) -> PyResult<PySolveResult> {
+    Ok(solve::nearest_neighbor(&map.map)?.into())
+}
+
+/// Exact depth-first branch-and-bound, stopping early after `time_limit` seconds. The search starts from `tour` if
+/// given, such as the tour of a heuristic, and otherwise from the nearest neighbor tour.
+#[pyfunction]
+#[pyo3(signature = (map, time_limit = None, tour = None))]
+fn branch_and_bound(
+    py: Python<'_>,
+    map: &PyMap,
+    time_limit: Option<f64>,
+    tour: Option<Vec<usize>>
+) -> PyResult<PySolveResult> {
+    let tour = match tour {
+        Some(tour) => tour,
+        None => return solve_with(py, &solve::BranchAndBound, map, time_limit)
+    };
+    let options = SolveOptions {
+        time_limit: duration(time_limit)?,
+        ..SolveOptions::default()
+    };
+    let result = py.allow_threads(|| {
+        solve::branch_and_bound_from(&map.map, &options, &solve::NearestNeighborBound, &tour)
+    })?;
+    Ok(result.into())
+}
+
+/// Exhaustive search, stopping early after `time_limit` seconds.
+#[pyfunction]
+#[pyo3(signature = (map, time_limit = None))]
+fn brute_force(py: Python<'_>, map: &PyMap, time_limit: Option<f64>) -> PyResult<PySolveResult> {
+    solve_with(py, &solve::BruteForce, map, time_limit)
+}
+
+/// The roulette wheel GA. `chromosome` is one of `"random_key"`, `"greedy_key"` (random keys decoded greedily),
+/// `"removal_index"` or `"swap"`, `crossover_points` selects k-point crossover instead of uniform crossover, and
+/// `two_opt_passes` repairs fixed offspring with that many passes of 2-opt instead.
+#[pyclass(name = "GaSolver", module = "traveling_salesman")]
+#[derive(Clone)]
+pub struct PyGaSolver {
+    #[pyo3(get, set)]
+    chromosome: String,
+    #[pyo3(get, set)]
+    population: usize,
+    #[pyo3(get, set)]
+    generations: u32,
+    #[pyo3(get, set)]
+    replace_percent: f32,
+    #[pyo3(get, set)]
+    elitist_percent: f32,
+    #[pyo3(get, set)]
+    crossover_prob: f32,
+    #[pyo3(get, set)]
+    mutate_prob: f32,
+    #[pyo3(get, set)]
+    selection_noise: f32,
+    #[pyo3(get, set)]
+    crossover_points: Option<usize>,
+    #[pyo3(get, set)]
+    fix: bool,
+    #[pyo3(get, set)]
+    two_opt_passes: u32
+}
+
+impl PyGaSolver {
+    fn solver<C>(&self, new_chromosome: fn(&Map) -> C) -> GaSolver<C> {
+        let recombinator = match self.crossover_points {
+            Some(count) => AnyRecombinator::KPoint(KPoint::new(count.max(1))),
+            None => AnyRecombinator::Uniform(Uniform::new())
+        };
+
+        GaSolver {
+            settings: Settings {
+                replace_percent: self.replace_percent,
+                elitist_percent: self.elitist_percent,
+                crossover_prob: self.crossover_prob,
+                mutate_prob: self.mutate_prob,
+                selection_noise: self.selection_noise,
+                population_schedule: PopulationSchedule::Constant,
+                operator_selection: OperatorSelection::Fixed,
+                two_opt_passes: self.two_opt_passes,
+                fitness_samples: 1,
+                elite_samples: 0
+            },
+            recombinator,
+            population: self.population,
+            generations: self.generations,
+            fix: self.fix,
+            new_chromosome
+        }
+    }
+}
+
+#[pymethods]
+impl PyGaSolver {
+    #[new]
+    #[pyo3(signature = (
+        chromosome = "random_key".to_owned(),
+        population = 100,
+        generations = 1000,
+        replace_percent = 1.0,
+        elitist_percent = 0.25,
+        crossover_prob = 0.9,
+        mutate_prob = 0.05,
+        selection_noise = 0.0,
+        crossover_points = None,
+        fix = false,
+        two_opt_passes = 0
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        chromosome: String,
+        population: usize,
+        generations: u32,
+        replace_percent: f32,
+        elitist_percent: f32,
+        crossover_prob: f32,
+        mutate_prob: f32,
+        selection_noise: f32,
+        crossover_points: Option<usize>,
+        fix: bool,
+        two_opt_passes: u32
+    ) -> Self {
+        PyGaSolver {
+            chromosome,
+            population,
+            generations,
+            replace_percent,
+            elitist_percent,
+            crossover_prob,
+            mutate_prob,
+            selection_noise,
+            crossover_points,
+            fix,
+            two_opt_passes
+        }
+    }
+
+    #[pyo3(signature = (map, time_limit = None))]
+    fn solve(&self, py: Python<'_>, map: &PyMap, time_limit: Option<f64>) -> PyResult<PySolveResult> {
+        match self.chromosome.as_str() {
+            "random_key" => solve_with(py, &self.solver(RandomKeyPath::new), map, time_limit),
+            "greedy_key" => solve_with(py, &self.solver(RandomKeyPath::greedy), map, time_limit),
+            "removal_index" => solve_with(py, &self.solver(RemovalIndex::new), map, time_limit),
+            "swap" => solve_with(py, &self.solver(|map| SwapPath::new(map, map.size())), map, time_limit),
+            other => Err(PyValueError::new_err(format!("unknown chromosome {:?}", other)))
+        }
+    }
+}
+
+/// Biased random-key GA. `greedy` decodes the keys as biases of a nearest neighbor tour instead of sorting them.
+#[pyfunction]
+#[pyo3(signature = (
+    map,
+    population = 100,
+    generations = 1000,
+    elite_percent = 0.2,
+    mutant_percent = 0.15,
+    elite_bias = 0.7,
+    fix = false,
+    greedy = false,
+    time_limit = None
+))]
+#[allow(clippy::too_many_arguments)]
+fn brkga(
+    py: Python<'_>,
+    map: &PyMap,
+    population: usize,
+    generations: u32,
+    elite_percent: f32,
+    mutant_percent: f32,
+    elite_bias: f32,
+    fix: bool,
+    greedy: bool,
+    time_limit: Option<f64>
+) -> PyResult<PySolveResult> {
+    let solver = BrkgaSolver {
+        brkga: Brkga {
+            elite_percent,
+            mutant_percent,
+            elite_bias
+        },
+        population,
+        generations,
+        fix,
+        decoder: if greedy { Decoder::Greedy } else { Decoder::Sorted }
+    };
+    solve_with(py, &solver, map, time_limit)
+}
+
+/// Differential evolution over random keys.
+#[pyfunction]
+#[pyo3(signature = (
+    map,
+    population = 100,
+    generations = 1000,
+    differential_weight = 0.8,
+    crossover_prob = 0.9,
+    time_limit = None
+))]
+fn differential_evolution(
+    py: Python<'_>,
+    map: &PyMap,
+    population: usize,
+    generations: u32,
+    differential_weight: f32,
+    crossover_prob: f32,
+    time_limit: Option<f64>
+) -> PyResult<PySolveResult> {
+    let solver = DifferentialEvolutionSolver {
+        de: DifferentialEvolution {
+            differential_weight,
+            crossover_prob
+        },
+        population,
+        generations
+    };
+    solve_with(py, &solver, map, time_limit)
+}
+
+/// Edge histogram estimation-of-distribution algorithm.
+#[pyfunction]
+#[pyo3(signature = (map, population = 100, generations = 1000, elite_percent = 0.5, bias = 0.1, time_limit = None))]
+fn edge_histogram(
+    py: Python<'_>,
+    map: &PyMap,
+    population: usize,
+    generations: u32,
+    elite_percent: f32,
+    bias: f32,
+    time_limit: Option<f64>
+) -> PyResult<PySolveResult> {
+    let solver = EdgeHistogramSolver {
+        eda: EdgeHistogram {
+            elite_percent,
+            bias
+        },
+        population,
+        generations
+    };
+    solve_with(py, &solver, map, time_limit)
+}
+
+#[pymodule]
+fn traveling_salesman(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMap>()?;
+    module.add_class::<PySolveResult>()?;
+    module.add_class::<PyGaSolver>()?;
+    module.add_function(wrap_pyfunction!(nearest_neighbor, module)?)?;
+    module.add_function(wrap_pyfunction!(branch_and_bound, module)?)?;
+    module.add_function(wrap_pyfunction!(brute_force, module)?)?;
+    module.add_function(wrap_pyfunction!(brkga, module)?)?;
+    module.add_function(wrap_pyfunction!(differential_evolution, module)?)?;
+    module.add_function(wrap_pyfunction!(edge_histogram, module)?)?;
+    Ok(())
+}
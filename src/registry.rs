@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+use crate::ga::{BrkgaSolver, DifferentialEvolutionSolver, EdgeHistogramSolver, GaSolver};
+use crate::island::IslandGa;
+use crate::local::{LinKernighan, SimulatedAnnealing, TwoOpt};
+use crate::map::Map;
+use crate::neighbors::Candidates;
+use crate::portfolio::Portfolio;
+use crate::solve::{BranchAndBound, BruteForce, HeldKarp, NearestNeighbor, SolveOptions, SolveResult, Solver};
+
+type Factory = Box<dyn Fn() -> Box<dyn Solver + Send + Sync> + Send + Sync>;
+
+struct Entry {
+    description: String,
+    factory: Factory
+}
+
+/// Solvers by name, so that they can be chosen at run time from a string, such as a command line argument or a
+/// column of a benchmark configuration, and so that other crates can add their own algorithms alongside the built-in
+/// ones.
+///
+/// Every solver is registered as a function making a new one, since solvers are neither `Clone` nor shared between
+/// benchmarks. Names are listed in alphabetical order, and registering a name again replaces the solver it stood for.
+#[derive(Default)]
+pub struct Registry {
+    entries: BTreeMap<String, Entry>
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The solvers of this crate with their default parameters, under the names the `tsp` command line selects them
+    /// by: `bb`, `bf`, `hk`, `nn`, `2opt`, `lk`, `lk-alpha`, `sa`, `ga`, `island`, `brkga`, `de`, `eda` and
+    /// `portfolio`.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register("bb", "Branch-and-bound (exact)", || BranchAndBound)
+            .register("bf", "Brute force (exact)", || BruteForce)
+            .register("hk", "Held-Karp dynamic programming (exact, 25 points in 1 GiB, 32 at most)", HeldKarp::default)
+            .register("nn", "Nearest neighbor construction", || NearestNeighbor)
+            .register("2opt", "2-opt local search from the nearest neighbor tour", || TwoOpt)
+            .register("lk", "Lin-Kernighan local search from the nearest neighbor tour", LinKernighan::default)
+            .register("lk-alpha", "Lin-Kernighan over alpha-nearness candidates from minimum 1-trees", || {
+                LinKernighan {
+                    candidates: Candidates::Alpha
+                }
+            })
+            .register("sa", "Simulated annealing from the nearest neighbor tour", SimulatedAnnealing::default)
+            .register("ga", "Roulette wheel GA over random keys", || GaSolver::builder().build())
+            .register("island", "Island model GA, populations in a ring exchanging their best", || {
+                IslandGa::new(GaSolver::builder().build())
+            })
+            .register("brkga", "Biased random-key GA", || BrkgaSolver::builder().build())
+            .register("de", "Differential evolution", || DifferentialEvolutionSolver::builder().build())
+            .register("eda", "Edge histogram EDA", || EdgeHistogramSolver::builder().build())
+            .register("portfolio", "The GA, simulated annealing and Lin-Kernighan at once, sharing the best tour", || {
+                Portfolio::new(GaSolver::builder().build(), SimulatedAnnealing::default())
+            });
+        registry
+    }
+
+    /// Registers the solver made by `factory` under the given name, with a one-line description for listings.
+    pub fn register<S, F>(&mut self, name: &str, description: &str, factory: F) -> &mut Self
+    where
+        S: Solver + Send + Sync + 'static,
+        F: Fn() -> S + Send + Sync + 'static
+    {
+        let entry = Entry {
+            description: description.to_owned(),
+            factory: Box::new(move || Box::new(factory()))
+        };
+        self.entries.insert(name.to_owned(), entry);
+        self
+    }
+
+    /// Removes the named solver, returning whether it was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// The names of every registered solver, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// The description the named solver was registered with.
+    pub fn description(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(|entry| entry.description.as_str())
+    }
+
+    /// A new instance of the named solver, or [`Error::UnknownSolver`] if there is none.
+    pub fn create(&self, name: &str) -> Result<Box<dyn Solver + Send + Sync>> {
+        match self.entries.get(name) {
+            Some(entry) => Ok((entry.factory)()),
+            None => Err(Error::UnknownSolver(name.to_owned()))
+        }
+    }
+
+    /// Solves the map with a new instance of the named solver.
+    pub fn solve(&self, name: &str, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        self.create(name)?.solve(map, options)
+    }
+
+    /// The named solvers, each labelled with its name, as the [`Benchmark`](crate::bench::Benchmark) and
+    /// [`Experiment`](crate::bench::Experiment) take them.
+    pub fn solvers<N: AsRef<str>>(&self, names: &[N]) -> Result<Vec<(String, Box<dyn Solver + Sync>)>> {
+        names
+            .iter()
+            .map(|name| {
+                let name = name.as_ref();
+                Ok((name.to_owned(), self.create(name)? as Box<dyn Solver + Sync>))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,256 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::map::Map;
+use crate::solve::{Progress, ProgressEvent};
+
+/// How to draw a map and tour as SVG.
+#[derive(Clone, Copy, Debug)]
+pub struct SvgOptions {
+    /// Width and height of the image in pixels
+    pub size: u32,
+    /// How many of the tour's longest edges to draw in a warning color
+    pub worst_edges: usize
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            size: 800,
+            worst_edges: 0
+        }
+    }
+}
+
+pub fn save_svg<P: AsRef<path::Path>>(path: P, map: &Map, tour: Option<&[usize]>, options: &SvgOptions) -> Result<()> {
+    write_svg(BufWriter::new(File::create(path)?), map, tour, options)
+}
+
+/// Draws the map's points and, if given, the tour's edges, scaled to fit the image.
+pub fn write_svg<W: Write>(mut out: W, map: &Map, tour: Option<&[usize]>, options: &SvgOptions) -> Result<()> {
+    let project = projection(map, options.size);
+    write_header(&mut out, options.size)?;
+
+    if let Some(tour) = tour {
+        write_tour(&mut out, tour, &project)?;
+
+        // Drawn over the tour so they stand out
+        for (from, to) in worst_edges(map, tour, options.worst_edges) {
+            let ((x1, y1), (x2, y2)) = (project(from), project(to));
+            writeln!(
+                out,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="crimson" stroke-width="2.5"/>"#,
+                x1,
+                y1,
+                x2,
+                y2
+            )?;
+        }
+    }
+
+    write_points(&mut out, map, &project)?;
+    writeln!(out, "</svg>")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// The best tour at one point during a solve. See [`Animation`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub elapsed: Duration,
+    pub length: f32,
+    pub tour: Vec<usize>
+}
+
+// The last improvement reported, waiting for its tour
+#[derive(Default)]
+struct Recording {
+    frames: Vec<Frame>,
+    improvement: Option<(f32, Duration)>
+}
+
+/// Records every new best tour of a solve when attached as its progress observer, to draw them one after another
+/// as SVG frames or an animated SVG. Only solvers that report their tours, which are the genetic algorithms, give
+/// frames; tours found otherwise can be added with [`push`](Animation::push).
+#[derive(Default)]
+pub struct Animation {
+    recording: Mutex<Recording>
+}
+
+impl Animation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a frame for a tour found after `elapsed`.
+    pub fn push(&self, elapsed: Duration, length: f32, tour: &[usize]) {
+        self.recording.lock().unwrap().frames.push(Frame {
+            elapsed,
+            length,
+            tour: tour.to_vec()
+        });
+    }
+
+    pub fn frames(&self) -> Vec<Frame> {
+        self.recording.lock().unwrap().frames.clone()
+    }
+
+    /// Writes every frame to its own SVG file in `directory`, which must exist, named `frame_0000.svg`,
+    /// `frame_0001.svg` and so on, and returns how many were written. Tools such as ffmpeg or ImageMagick can
+    /// turn them into a video or GIF.
+    pub fn save_frames<P: AsRef<path::Path>>(&self, directory: P, map: &Map, options: &SvgOptions) -> Result<usize> {
+        let frames = self.frames();
+        for (index, frame) in frames.iter().enumerate() {
+            let path = directory.as_ref().join(format!("frame_{:04}.svg", index));
+            let mut out = BufWriter::new(File::create(path)?);
+            write_frame(&mut out, map, frame, options)?;
+        }
+        Ok(frames.len())
+    }
+
+    pub fn save_svg<P>(&self, path: P, map: &Map, options: &SvgOptions, delay: Duration) -> Result<()>
+    where
+        P: AsRef<path::Path>
+    {
+        self.write_svg(BufWriter::new(File::create(path)?), map, options, delay)
+    }
+
+    /// Draws the frames as a single SVG which shows each for `delay` and then stays on the last, with the length of
+    /// the tour shown in the corner. Browsers play it when it is opened.
+    pub fn write_svg<W: Write>(&self, mut out: W, map: &Map, options: &SvgOptions, delay: Duration) -> Result<()> {
+        let frames = self.frames();
+        let project = projection(map, options.size);
+        let delay = delay.as_secs_f64();
+        write_header(&mut out, options.size)?;
+
+        for (index, frame) in frames.iter().enumerate() {
+            // The last frame stays once shown, and every other one is replaced by the next
+            let shown = if index + 1 == frames.len() {
+                format!(r#"begin="{:.3}s" fill="freeze""#, index as f64 * delay)
+            } else {
+                format!(r#"begin="{:.3}s" dur="{:.3}s""#, index as f64 * delay, delay)
+            };
+            writeln!(out, r#"<g visibility="hidden">"#)?;
+            writeln!(out, r#"<set attributeName="visibility" to="visible" {}/>"#, shown)?;
+            write_tour(&mut out, &frame.tour, &project)?;
+            write_label(&mut out, frame)?;
+            writeln!(out, "</g>")?;
+        }
+
+        write_points(&mut out, map, &project)?;
+        writeln!(out, "</svg>")?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl Progress for Animation {
+    fn report(&self, event: ProgressEvent) {
+        if let ProgressEvent::Improvement { length, elapsed } = event {
+            self.recording.lock().unwrap().improvement = Some((length, elapsed));
+        }
+    }
+
+    fn tour(&self, tour: &[usize]) {
+        let mut recording = self.recording.lock().unwrap();
+        if let Some((length, elapsed)) = recording.improvement.take() {
+            recording.frames.push(Frame {
+                elapsed,
+                length,
+                tour: tour.to_vec()
+            });
+        }
+    }
+
+    fn detailed(&self) -> bool {
+        false
+    }
+}
+
+// One frame on its own, with its length in the corner
+fn write_frame<W: Write>(out: &mut W, map: &Map, frame: &Frame, options: &SvgOptions) -> Result<()> {
+    let project = projection(map, options.size);
+    write_header(out, options.size)?;
+    write_tour(out, &frame.tour, &project)?;
+    write_label(out, frame)?;
+    write_points(out, map, &project)?;
+    writeln!(out, "</svg>")?;
+    out.flush()?;
+    Ok(())
+}
+
+// Maps the index of a point to its position in the image, scaled to fit with the aspect ratio kept
+fn projection(map: &Map, size: u32) -> impl Fn(usize) -> (f32, f32) + '_ {
+    let points = map.as_slice();
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for point in points {
+        min_x = min_x.min(point.x);
+        min_y = min_y.min(point.y);
+        max_x = max_x.max(point.x);
+        max_y = max_y.max(point.y);
+    }
+
+    // Keep the aspect ratio and leave a margin so points on the edge are not clipped
+    let margin = size as f32 * 0.05;
+    let extent = (max_x - min_x).max(max_y - min_y).max(f32::EPSILON);
+    let scale = (size as f32 - 2.0 * margin) / extent;
+    move |index: usize| {
+        let point = points[index];
+        (margin + (point.x - min_x) * scale, size as f32 - margin - (point.y - min_y) * scale)
+    }
+}
+
+fn write_header<W: Write>(out: &mut W, size: u32) -> Result<()> {
+    writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, size)?;
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+    Ok(())
+}
+
+fn write_tour<W, F>(out: &mut W, tour: &[usize], project: &F) -> Result<()>
+where
+    W: Write,
+    F: Fn(usize) -> (f32, f32)
+{
+    write!(out, r#"<polyline fill="none" stroke="steelblue" stroke-width="1.5" points=""#)?;
+    for &city in tour {
+        let (x, y) = project(city);
+        write!(out, "{:.2},{:.2} ", x, y)?;
+    }
+    writeln!(out, r#""/>"#)?;
+    Ok(())
+}
+
+fn write_label<W: Write>(out: &mut W, frame: &Frame) -> Result<()> {
+    writeln!(
+        out,
+        r#"<text x="8" y="20" font-family="sans-serif" font-size="14">{:.4} after {:.2?}</text>"#,
+        frame.length,
+        frame.elapsed
+    )?;
+    Ok(())
+}
+
+fn write_points<W, F>(out: &mut W, map: &Map, project: &F) -> Result<()>
+where
+    W: Write,
+    F: Fn(usize) -> (f32, f32)
+{
+    for index in 0..map.size() {
+        let (x, y) = project(index);
+        writeln!(out, r#"<circle cx="{:.2}" cy="{:.2}" r="3" fill="black"/>"#, x, y)?;
+    }
+    Ok(())
+}
+
+/// The `count` longest edges of the tour, longest first.
+pub fn worst_edges(map: &Map, tour: &[usize], count: usize) -> Vec<(usize, usize)> {
+    let mut edges: Vec<(usize, usize)> = tour.windows(2).map(|edge| (edge[0], edge[1])).collect();
+    let length = |&(from, to): &(usize, usize)| map.dist(from, to);
+    edges.sort_by(|a, b| length(b).total_cmp(&length(a)));
+    edges.truncate(count);
+    edges
+}
@@ -0,0 +1,226 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+use std::time::Duration;
+
+use crate::bench::{self, Cell, Summary};
+use crate::error::Result;
+use crate::solve::SolveResult;
+
+// Mean lengths closer than this, relative to the shorter, tie
+const TIE: f64 = 1e-6;
+
+/// One solve to report on: the result of the named solver on the named instance.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry<'a> {
+    pub instance: &'a str,
+    pub solver: &'a str,
+    pub result: &'a SolveResult
+}
+
+/// How one solver fared against the others over every instance, comparing the mean lengths of its runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Standing {
+    pub solver: String,
+    /// Instances on which no other solver came as close
+    pub wins: u32,
+    /// Instances on which the solver shared the shortest mean length with others
+    pub ties: u32,
+    /// Instances on which another solver did better
+    pub losses: u32
+}
+
+/// A comparison of solvers over a set of instances, written as Markdown or CSV: one row per instance and solver
+/// with the best and mean lengths, the gaps and the times, followed by every solver's wins and losses.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Report {
+    /// Grouped by instance
+    pub cells: Vec<Cell>,
+    pub standings: Vec<Standing>
+}
+
+impl Report {
+    /// Summarizes the solves, of which there may be several for each instance and solver. Instances and solvers
+    /// appear in the order they are first met, and the points of an instance are counted from its first tour.
+    pub fn from_results(entries: &[Entry<'_>]) -> Self {
+        let mut instances: Vec<&str> = Vec::new();
+        let mut solvers: Vec<&str> = Vec::new();
+        for entry in entries {
+            if !instances.contains(&entry.instance) {
+                instances.push(entry.instance);
+            }
+            if !solvers.contains(&entry.solver) {
+                solvers.push(entry.solver);
+            }
+        }
+
+        let mut cells = Vec::new();
+        for &instance in &instances {
+            let first = cells.len();
+            for &solver in &solvers {
+                let results: Vec<&SolveResult> = entries.iter()
+                    .filter(|entry| entry.instance == instance && entry.solver == solver)
+                    .map(|entry| entry.result)
+                    .collect();
+                if results.is_empty() {
+                    continue;
+                }
+
+                let summarize = |field: fn(&SolveResult) -> f64| {
+                    Summary::of(&results.iter().map(|result| field(result)).collect::<Vec<_>>())
+                };
+                cells.push(Cell {
+                    instance: instance.to_owned(),
+                    points: results[0].tour.len(),
+                    solver: solver.to_owned(),
+                    length: summarize(|result| result.length as f64),
+                    seconds: summarize(|result| result.elapsed.as_secs_f64()),
+                    iterations: summarize(|result| result.iterations as f64),
+                    lengths: results.iter().map(|result| result.length as f64).collect(),
+                    gap: 0.0,
+                    above_optimal: None
+                });
+            }
+            bench::compare(&mut cells[first..]);
+        }
+
+        Self::from_cells(cells)
+    }
+
+    /// Tallies the wins and losses of the solvers in cells grouped by instance, as [`Benchmark::run`]
+    /// returns them.
+    ///
+    /// [`Benchmark::run`]: crate::bench::Benchmark::run
+    pub fn from_cells(cells: Vec<Cell>) -> Self {
+        let mut standings: Vec<Standing> = Vec::new();
+        for cell in &cells {
+            if standings.iter().all(|standing| standing.solver != cell.solver) {
+                standings.push(Standing {
+                    solver: cell.solver.clone(),
+                    wins: 0,
+                    ties: 0,
+                    losses: 0
+                });
+            }
+        }
+
+        let mut first = 0;
+        while first < cells.len() {
+            let count = cells[first..].iter().take_while(|cell| cell.instance == cells[first].instance).count();
+            let group = &cells[first..first + count];
+            let shortest = group.iter().map(|cell| cell.length.mean).fold(f64::MAX, f64::min);
+            let tied = |cell: &Cell| cell.length.mean - shortest <= shortest.abs() * TIE;
+            let leaders = group.iter().filter(|cell| tied(cell)).count();
+
+            for cell in group {
+                // Infallible: every solver with a cell has a standing
+                let standing = standings.iter_mut().find(|standing| standing.solver == cell.solver).unwrap();
+                match (tied(cell), leaders) {
+                    (false, _) => standing.losses += 1,
+                    (true, 1) => standing.wins += 1,
+                    (true, _) => standing.ties += 1
+                }
+            }
+            first += count;
+        }
+
+        Report {
+            cells,
+            standings
+        }
+    }
+
+    pub fn save_markdown<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write_markdown(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes a table of the cells and a table of the standings.
+    pub fn write_markdown<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(
+            out,
+            "| instance | points | algo | best | mean | median | 95% CI | gap | above optimal | mean time | \
+             mean nodes |"
+        )?;
+        writeln!(out, "|---|--:|---|--:|--:|--:|--:|--:|--:|--:|--:|")?;
+        for cell in &self.cells {
+            let above_optimal = cell.above_optimal
+                .map(|above| format!("{:.2}%", above * 100.0))
+                .unwrap_or_else(|| "-".to_owned());
+            writeln!(
+                out,
+                "| {} | {} | {} | {:.4} | {:.4} | {:.4} | ±{:.4} | {:.2}% | {} | {:.2?} ±{:.2?} | {:.0} |",
+                cell.instance,
+                cell.points,
+                cell.solver,
+                cell.length.min,
+                cell.length.mean,
+                cell.length.median,
+                cell.length.ci95,
+                cell.gap * 100.0,
+                above_optimal,
+                Duration::from_secs_f64(cell.seconds.mean),
+                Duration::from_secs_f64(cell.seconds.ci95),
+                cell.iterations.mean
+            )?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "| algo | wins | ties | losses |")?;
+        writeln!(out, "|---|--:|--:|--:|")?;
+        for standing in &self.standings {
+            writeln!(out, "| {} | {} | {} | {} |", standing.solver, standing.wins, standing.ties, standing.losses)?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn save_csv<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write_csv(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes one line per cell, with a header. The standings are left to
+    /// [`write_standings_csv`](Self::write_standings_csv), since they have other columns.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(
+            out,
+            "instance,points,algo,best,mean,median,ci95,gap,above_optimal,\
+             mean_seconds,median_seconds,seconds_ci95,mean_nodes"
+        )?;
+        for cell in &self.cells {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                cell.instance,
+                cell.points,
+                cell.solver,
+                cell.length.min,
+                cell.length.mean,
+                cell.length.median,
+                cell.length.ci95,
+                cell.gap,
+                cell.above_optimal.map(|above| above.to_string()).unwrap_or_default(),
+                cell.seconds.mean,
+                cell.seconds.median,
+                cell.seconds.ci95,
+                cell.iterations.mean
+            )?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Writes an `algo,wins,ties,losses` header followed by one line per solver.
+    pub fn write_standings_csv<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "algo,wins,ties,losses")?;
+        for standing in &self.standings {
+            writeln!(out, "{},{},{},{}", standing.solver, standing.wins, standing.ties, standing.losses)?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+}
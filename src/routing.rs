@@ -0,0 +1,246 @@
+use serde::Deserialize;
+use std::future::Future;
+use std::io;
+
+use crate::error::{Error, Result};
+use crate::map::{Map, Point};
+use crate::matrix::DistanceMatrix;
+
+// The Distance Matrix API answers at most this many origins times destinations at once
+const GOOGLE_BLOCK: usize = 10;
+
+/// A place on the earth, in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64
+}
+
+/// What the loaded matrix measures between two locations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// The travel time in seconds
+    Duration,
+    /// The road distance in meters
+    Distance
+}
+
+/// A routing service with a distance matrix endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Service {
+    /// The table service of an OSRM server at `url`, such as `http://localhost:5000`, routing with `profile`, such
+    /// as `driving`. Servers answer for at most 100 locations unless started with a larger `--max-table-size`.
+    Osrm {
+        url: String,
+        profile: String
+    },
+    /// Google's Distance Matrix API with an API key, which is queried ten origins by ten destinations at a time
+    Google {
+        key: String
+    }
+}
+
+/// Sends the GET requests of the loaders in this module, so that they run on whichever HTTP client and async
+/// runtime the application already uses.
+pub trait HttpClient {
+    /// The body of the response to a GET request of the URL, or an error if the request failed or was not answered
+    /// with success.
+    fn get(&self, url: &str) -> impl Future<Output = Result<String>>;
+}
+
+/// Asks the service for the travel times or distances between every pair of locations. Road networks are not
+/// symmetric, but the solvers are, so the matrix holds the mean of the two directions between each pair. Fails if
+/// the service reports an error or finds no route between two of the locations.
+pub async fn load_matrix<C: HttpClient>(
+    client: &C,
+    service: &Service,
+    locations: &[Location],
+    metric: Metric
+) -> Result<DistanceMatrix> {
+    if locations.is_empty() {
+        return Err(Error::EmptyMap);
+    }
+
+    let costs = match service {
+        Service::Osrm { url, profile } => osrm(client, url, profile, locations, metric).await?,
+        Service::Google { key } => google(client, key, locations, metric).await?
+    };
+
+    let size = locations.len();
+    for i in 0..size {
+        for j in 0..size {
+            if i != j && costs[i * size + j].is_none() {
+                return Err(Error::InvalidProblem(format!("the service found no route from location {} to {}", i, j)));
+            }
+        }
+    }
+    // Infallible: every cost off the diagonal was checked
+    Ok(DistanceMatrix::from_fn(size, |i, j| (costs[i * size + j].unwrap() + costs[j * size + i].unwrap()) / 2.0))
+}
+
+/// A map of the locations with the service's matrix [attached](Map::with_distances), so that every solver uses
+/// the road costs. The points are the longitudes and latitudes, for drawing and for heuristics that work from
+/// positions.
+pub async fn load_map<C: HttpClient>(
+    client: &C,
+    service: &Service,
+    locations: &[Location],
+    metric: Metric
+) -> Result<Map> {
+    let distances = load_matrix(client, service, locations, metric).await?;
+    let points = locations.iter()
+        .map(|location| Point {
+            x: location.longitude as f32,
+            y: location.latitude as f32
+        })
+        .collect();
+    Map::from_points(points).with_distances(distances)
+}
+
+#[derive(Deserialize)]
+struct OsrmTable {
+    code: String,
+    message: Option<String>,
+    durations: Option<Vec<Vec<Option<f32>>>>,
+    distances: Option<Vec<Vec<Option<f32>>>>
+}
+
+// The costs between every pair of locations, row by row, from a single table request
+async fn osrm<C: HttpClient>(
+    client: &C,
+    url: &str,
+    profile: &str,
+    locations: &[Location],
+    metric: Metric
+) -> Result<Vec<Option<f32>>> {
+    let coordinates: Vec<String> = locations.iter()
+        .map(|location| format!("{},{}", location.longitude, location.latitude))
+        .collect();
+    let annotation = match metric {
+        Metric::Duration => "duration",
+        Metric::Distance => "distance"
+    };
+    let request = format!(
+        "{}/table/v1/{}/{}?annotations={}",
+        url.trim_end_matches('/'),
+        profile,
+        coordinates.join(";"),
+        annotation
+    );
+
+    let table: OsrmTable = parse(&client.get(&request).await?)?;
+    if table.code != "Ok" {
+        return Err(service_error(&table.code, table.message));
+    }
+    let rows = match metric {
+        Metric::Duration => table.durations,
+        Metric::Distance => table.distances
+    };
+    let rows = rows.ok_or_else(|| service_error("missing table", None))?;
+    check_table(rows.len(), rows.iter().map(Vec::len), (locations.len(), locations.len()))?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+#[derive(Deserialize)]
+struct GoogleMatrix {
+    status: String,
+    error_message: Option<String>,
+    #[serde(default)]
+    rows: Vec<GoogleRow>
+}
+
+#[derive(Deserialize)]
+struct GoogleRow {
+    elements: Vec<GoogleElement>
+}
+
+#[derive(Deserialize)]
+struct GoogleElement {
+    status: String,
+    duration: Option<GoogleValue>,
+    distance: Option<GoogleValue>
+}
+
+#[derive(Deserialize)]
+struct GoogleValue {
+    value: f32
+}
+
+// The costs between every pair of locations, row by row, requested a block of origins and destinations at a time
+async fn google<C: HttpClient>(
+    client: &C,
+    key: &str,
+    locations: &[Location],
+    metric: Metric
+) -> Result<Vec<Option<f32>>> {
+    let size = locations.len();
+    let mut costs = vec![None; size * size];
+    for origins in (0..size).step_by(GOOGLE_BLOCK) {
+        for destinations in (0..size).step_by(GOOGLE_BLOCK) {
+            let origins = origins..(origins + GOOGLE_BLOCK).min(size);
+            let destinations = destinations..(destinations + GOOGLE_BLOCK).min(size);
+            let join = |range: std::ops::Range<usize>| {
+                range.map(|i| format!("{},{}", locations[i].latitude, locations[i].longitude))
+                    .collect::<Vec<_>>()
+                    .join("%7C")
+            };
+            let request = format!(
+                "https://maps.googleapis.com/maps/api/distancematrix/json?origins={}&destinations={}&key={}",
+                join(origins.clone()),
+                join(destinations.clone()),
+                key
+            );
+
+            let matrix: GoogleMatrix = parse(&client.get(&request).await?)?;
+            if matrix.status != "OK" {
+                return Err(service_error(&matrix.status, matrix.error_message));
+            }
+            let columns = matrix.rows.iter().map(|row| row.elements.len());
+            check_table(matrix.rows.len(), columns, (origins.len(), destinations.len()))?;
+            for (i, row) in origins.zip(matrix.rows) {
+                for (j, element) in destinations.clone().zip(row.elements) {
+                    let value = match metric {
+                        Metric::Duration => element.duration,
+                        Metric::Distance => element.distance
+                    };
+                    // Pairs without a route have a status such as `ZERO_RESULTS`
+                    if element.status == "OK" {
+                        costs[i * size + j] = value.map(|value| value.value);
+                    }
+                }
+            }
+        }
+    }
+    Ok(costs)
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T> {
+    Ok(serde_json::from_str(body).map_err(io::Error::from)?)
+}
+
+// Whether the service answered with a table of the expected rows and columns
+fn check_table<I: Iterator<Item = usize>>(rows: usize, mut columns: I, expected: (usize, usize)) -> Result<()> {
+    if rows != expected.0 {
+        return Err(Error::DimensionMismatch {
+            expected: expected.0,
+            found: rows
+        });
+    }
+    match columns.find(|&found| found != expected.1) {
+        Some(found) => Err(Error::DimensionMismatch {
+            expected: expected.1,
+            found
+        }),
+        None => Ok(())
+    }
+}
+
+fn service_error(status: &str, message: Option<String>) -> Error {
+    Error::Io {
+        kind: io::ErrorKind::Other,
+        message: match message {
+            Some(message) => format!("routing service answered {}: {}", status, message),
+            None => format!("routing service answered {}", status)
+        }
+    }
+}
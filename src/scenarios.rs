@@ -0,0 +1,91 @@
+use crate::error::{Error, Result};
+use crate::matrix::DistanceMatrix;
+
+/// How the lengths of a tour under every [scenario](Scenarios) combine into its cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Robustness {
+    /// The mean length over the scenarios, for a tour that is short on average
+    #[default]
+    Mean,
+    /// The longest length under any scenario, for a tour that must do well however things turn out
+    WorstCase
+}
+
+/// The costs between the points under several scenarios, such as travel times in different traffic, for planning a
+/// tour that holds up across all of them. Attached to a map with
+/// [`Map::with_scenarios`](crate::map::Map::with_scenarios), whose [`Path::evaluate`](crate::map::Path::evaluate)
+/// then charges every tour its [robust](Robustness) cost over the scenarios, which is what the GA solvers minimize,
+/// evaluating every chromosome under each scenario. Every other use of a distance between two points takes the mean
+/// cost of the edge over the scenarios, so that local search and the constructive and exact solvers optimize the
+/// mean length, which for the worst case is only a guide.
+#[derive(Clone, Debug)]
+pub struct Scenarios {
+    matrices: Box<[DistanceMatrix]>,
+    robustness: Robustness
+}
+
+impl Scenarios {
+    /// Fails unless there is at least one scenario and every scenario has costs for the same number of points.
+    pub fn new(matrices: Vec<DistanceMatrix>, robustness: Robustness) -> Result<Self> {
+        let size = match matrices.first() {
+            Some(first) => first.size(),
+            None => return Err(Error::InvalidProblem("there must be at least one scenario".to_string()))
+        };
+        if let Some((index, matrix)) = matrices.iter().enumerate().find(|(_, matrix)| matrix.size() != size) {
+            return Err(Error::InvalidProblem(format!(
+                "scenario {} has costs for {} points, but the first has them for {}",
+                index,
+                matrix.size(),
+                size
+            )));
+        }
+
+        Ok(Scenarios {
+            matrices: matrices.into_boxed_slice(),
+            robustness
+        })
+    }
+
+    /// The number of points every scenario has costs for.
+    pub fn size(&self) -> usize {
+        self.matrices[0].size()
+    }
+
+    pub fn matrices(&self) -> &[DistanceMatrix] {
+        &self.matrices
+    }
+
+    pub fn robustness(&self) -> Robustness {
+        self.robustness
+    }
+
+    /// The length of the tour under every scenario, in their order.
+    pub fn lengths<'a>(&'a self, tour: &'a [usize]) -> impl Iterator<Item = f32> + 'a {
+        self.matrices.iter().map(move |matrix| matrix.evaluate(tour))
+    }
+
+    /// The cost of the tour over the scenarios, by the [robustness](Robustness) they were built with.
+    pub fn evaluate(&self, tour: &[usize]) -> f32 {
+        match self.robustness {
+            Robustness::Mean => {
+                let sum: f64 = self.lengths(tour).map(f64::from).sum();
+                (sum / self.matrices.len() as f64) as f32
+            },
+            Robustness::WorstCase => self.lengths(tour).fold(0.0, f32::max)
+        }
+    }
+
+    /// The mean cost of every edge over the scenarios.
+    pub fn mean(&self) -> DistanceMatrix {
+        let count = self.matrices.len() as f32;
+        DistanceMatrix::from_fn(self.size(), |i, j| {
+            self.matrices.iter().map(|matrix| matrix.get(i, j)).sum::<f32>() / count
+        })
+    }
+
+    // Follows the map swapping two of its points
+    pub(crate) fn swap(&mut self, first: usize, second: usize) {
+        self.matrices.iter_mut().for_each(|matrix| matrix.swap(first, second));
+    }
+}
@@ -0,0 +1,215 @@
+use std::cell::Cell;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+
+/// What a [`SearchState`] keeps for every point. The weights of the points off the current path are summed in
+/// [`SearchState::remaining`], such as the distance from every point to its nearest neighbor, whose sum bounds how
+/// much longer the path has to get.
+pub trait NodeData {
+    /// What the point adds to the remaining total while it is off the path.
+    fn weight(&self) -> f64 {
+        0.0
+    }
+}
+
+impl NodeData for () {}
+
+impl NodeData for f32 {
+    fn weight(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl NodeData for f64 {
+    fn weight(&self) -> f64 {
+        *self
+    }
+}
+
+/// The state of a depth-first search over paths through a set of points, for building exhaustive or bounded
+/// searches of one's own the way [`branch_and_bound`](crate::solve::branch_and_bound) is built on it.
+///
+/// The state knows which points are on the current path and the total [weight](NodeData::weight) of those that are
+/// not. Visiting a point hands out a [`Visit`] guard which takes the point off the path again when it is dropped,
+/// so a recursive search undoes its visits simply by returning. Guards must be dropped in the reverse order of the
+/// visits, as returning from a recursive search does, or the remaining total drifts.
+///
+/// Visits change the state through a shared reference, so that every guard of the path can be held at once, which
+/// makes the state unfit to share between threads: every thread of a parallel search needs its own.
+pub struct SearchState<D> {
+    data: Box<[D]>,
+    // One bit per point, set while the point is not on the current path
+    unvisited: Box<[Cell<u64>]>,
+    // Each visit saves the sum it replaces and puts it back when it is undone, so the sum never drifts
+    remaining: Cell<f64>
+}
+
+impl<D: NodeData> SearchState<D> {
+    /// A state with no point on the path, and the given data for every point.
+    pub fn new(data: Vec<D>) -> Self {
+        let mut unvisited = vec![Cell::new(u64::MAX); data.len().div_ceil(64)].into_boxed_slice();
+        if let Some(last) = unvisited.last_mut() {
+            // Leave the bits past the last point clear so that they are never visited
+            let used = data.len() % 64;
+            if used != 0 {
+                last.set((1 << used) - 1);
+            }
+        }
+
+        let remaining = data.iter().map(NodeData::weight).sum();
+        SearchState {
+            data: data.into_boxed_slice(),
+            unvisited,
+            remaining: Cell::new(remaining)
+        }
+    }
+
+    /// The number of points, on the path or not.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The data of the point. Panics if there is no such point.
+    #[inline]
+    pub fn data(&self, index: usize) -> &D {
+        &self.data[index]
+    }
+
+    /// Whether the point is on the current path. Panics if there is no such point.
+    #[inline]
+    pub fn is_visited(&self, index: usize) -> bool {
+        assert!(index < self.data.len(), "point {} is out of range", index);
+        self.unvisited[index / 64].get() & (1 << (index % 64)) == 0
+    }
+
+    /// The sum of the weights of the points off the path.
+    #[inline]
+    pub fn remaining(&self) -> f64 {
+        self.remaining.get()
+    }
+
+    /// The lowest point off the path, if any is.
+    #[inline]
+    pub fn first_unvisited(&self) -> Option<usize> {
+        self.unvisited.iter()
+            .enumerate()
+            .find(|(_, word)| word.get() != 0)
+            .map(|(word_index, word)| word_index * 64 + word.get().trailing_zeros() as usize)
+    }
+
+    /// Puts the point on the path, unless it is on it already or there is no such point.
+    pub fn visit(&self, index: usize) -> Option<Visit<'_, D>> {
+        if index >= self.data.len() || self.is_visited(index) {
+            return None;
+        }
+        let mut from = index;
+        self.visit_next(&mut from)
+    }
+
+    /// Puts the lowest point off the path at or after `from` on it, and moves `from` past the point, so that
+    /// calling this again visits the next one.
+    #[inline]
+    pub fn visit_next(&self, from: &mut usize) -> Option<Visit<'_, D>> {
+        // Find the lowest unvisited point at or after the index
+        let mut word_index = *from / 64;
+        let mut bits = self.unvisited.get(word_index)?.get() & (u64::MAX << (*from % 64));
+        while bits == 0 {
+            word_index += 1;
+            bits = self.unvisited.get(word_index)?.get();
+        }
+        let index = word_index * 64 + bits.trailing_zeros() as usize;
+        *from = index + 1;
+
+        // Infallible: the bit is set, so the index is in range
+        let (word, data) = unsafe { (self.unvisited.get_unchecked(word_index), self.data.get_unchecked(index)) };
+        word.set(word.get() & !(1 << (index % 64)));
+        let remaining = self.remaining.replace(self.remaining.get() - data.weight());
+        Some(Visit {
+            state: self,
+            index,
+            remaining
+        })
+    }
+
+    /// Visits every point off the path in turn, from the lowest. Each point is on the path from when the iterator
+    /// yields it until its guard is dropped, which should be before the next is taken.
+    pub const fn unvisited(&self) -> Unvisited<'_, D> {
+        Unvisited {
+            state: self,
+            from: 0
+        }
+    }
+}
+
+impl<D> Debug for SearchState<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let visited = self.unvisited.iter().map(|word| word.get().count_zeros() as usize).sum::<usize>()
+            - (self.unvisited.len() * 64 - self.data.len());
+        f.debug_struct("SearchState")
+            .field("points", &self.data.len())
+            .field("visited", &visited)
+            .field("remaining", &self.remaining.get())
+            .finish()
+    }
+}
+
+/// The iterator of [`SearchState::unvisited`].
+pub struct Unvisited<'a, D> {
+    state: &'a SearchState<D>,
+    from: usize
+}
+
+impl<'a, D: NodeData> Iterator for Unvisited<'a, D> {
+    type Item = Visit<'a, D>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.state.visit_next(&mut self.from)
+    }
+}
+
+/// A point on the path of a [`SearchState`], which takes it off the path again when dropped. Dereferences to the
+/// data of the point.
+pub struct Visit<'a, D> {
+    state: &'a SearchState<D>,
+    index: usize,
+    // The state's remaining total before this point was visited
+    remaining: f64
+}
+
+impl<D> Visit<'_, D> {
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<D> Deref for Visit<'_, D> {
+    type Target = D;
+
+    #[inline]
+    fn deref(&self) -> &D {
+        // Infallible: a visit is only made of a point in range
+        unsafe { self.state.data.get_unchecked(self.index) }
+    }
+}
+
+impl<D> Drop for Visit<'_, D> {
+    #[inline]
+    fn drop(&mut self) {
+        // Infallible: a visit is only made of a point in range
+        let word = unsafe { self.state.unvisited.get_unchecked(self.index / 64) };
+        word.set(word.get() | 1 << (self.index % 64));
+        self.state.remaining.set(self.remaining);
+    }
+}
+
+impl<D: Debug> Debug for Visit<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Visit").field("index", &self.index).field("data", &**self).finish()
+    }
+}
@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+
+use crate::error::Result;
+
+/// What branch-and-bound did with a node of its search tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// Its children were searched
+    Expanded,
+    /// Its lower bound could not beat `best`, the shortest length found when it was reached
+    Pruned { best: f32 },
+    /// Some of the point's predecessors were not on the path yet
+    Precedence,
+    /// An edge constraint ruled out the edge to the point
+    Edge,
+    /// A complete path, which is `improved` if it was shorter than every path found before it
+    Leaf { improved: bool },
+    /// The time limit or a cancellation stopped the search before the node was looked at
+    Stopped
+}
+
+/// A node of the search tree: the path of its parent extended by one point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// The index of the parent in [`SearchTree::nodes`], which is `None` only for the root
+    pub parent: Option<usize>,
+    /// The point added to the path, which is `None` only for the root's empty path
+    pub point: Option<usize>,
+    /// The length of the path so far
+    pub length: f32,
+    /// The lower bound on the length of any path completing this one
+    pub bound: f32,
+    pub outcome: Outcome
+}
+
+/// The search tree explored by [`trace_branch_and_bound`](crate::solve::trace_branch_and_bound), in the order
+/// the nodes were reached, which can be written as a Graphviz DOT graph to see how well the bound prunes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchTree {
+    /// Starts with the root
+    pub nodes: Vec<Node>
+}
+
+impl SearchTree {
+    /// The nodes whose subtrees were cut off by the bound.
+    pub fn prunes(&self) -> usize {
+        self.nodes.iter().filter(|node| matches!(node.outcome, Outcome::Pruned { .. })).count()
+    }
+
+    pub fn save_dot<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        self.write_dot(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes the tree as a DOT digraph, for `dot -Tsvg`. Every node is labeled with its point, length and bound,
+    /// pruned nodes are red and name the length they could not beat, nodes ruled out by constraints are grey,
+    /// and complete paths that improved on the best are green.
+    pub fn write_dot<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "digraph search {{")?;
+        writeln!(out, "    node [shape=box, fontname=\"monospace\"];")?;
+        for (index, node) in self.nodes.iter().enumerate() {
+            let point = node.point.map(|point| point.to_string()).unwrap_or_else(|| "start".to_owned());
+            let (note, style) = match node.outcome {
+                Outcome::Expanded => (String::new(), ""),
+                Outcome::Pruned { best } => (format!("\\npruned: bound >= {:.4}", best), ", color=red"),
+                Outcome::Precedence => ("\\nprecedence".to_owned(), ", color=grey, fontcolor=grey, style=dashed"),
+                Outcome::Edge => ("\\nedge constraint".to_owned(), ", color=grey, fontcolor=grey, style=dashed"),
+                Outcome::Leaf { improved: true } => ("\\nnew best".to_owned(), ", style=filled, fillcolor=palegreen"),
+                Outcome::Leaf { improved: false } => ("\\ncomplete".to_owned(), ""),
+                Outcome::Stopped => ("\\nstopped".to_owned(), ", style=dotted")
+            };
+            writeln!(
+                out,
+                "    n{} [label=\"{}\\nlength {:.4}\\nbound {:.4}{}\"{}];",
+                index, point, node.length, node.bound, note, style
+            )?;
+            if let Some(parent) = node.parent {
+                writeln!(out, "    n{} -> n{};", parent, index)?;
+            }
+        }
+        writeln!(out, "}}")?;
+
+        out.flush()?;
+        Ok(())
+    }
+}
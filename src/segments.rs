@@ -0,0 +1,214 @@
+use crate::endpoints::{Endpoints, FixedEndpoints};
+use crate::error::{Error, Result};
+use crate::map::{Map, Path, check_permutation};
+use crate::solve::{Budget, HeldKarp, SolveOptions, SolveResult, Solver, Termination, check_map};
+use crate::time::Instant;
+
+// The most segments whose order `merge` searches exhaustively, over every subset of them
+const REORDER_SEGMENTS: usize = 12;
+
+/// Cuts the tour before every given position, so that `cuts` of `[2, 5]` split a tour of 8 points into its first 2,
+/// the next 3 and the last 3. Fails unless the cuts are in increasing order and inside the tour, past its first
+/// point.
+pub fn split(tour: &[usize], cuts: &[usize]) -> Result<Vec<Vec<usize>>> {
+    let mut start = 0;
+    let mut segments = Vec::with_capacity(cuts.len() + 1);
+    for &cut in cuts {
+        if cut <= start || cut >= tour.len() {
+            return Err(Error::InvalidTour(format!(
+                "cannot cut a tour of {} points at position {} after cutting it at {}",
+                tour.len(),
+                cut,
+                start
+            )));
+        }
+        segments.push(tour[start..cut].to_vec());
+        start = cut;
+    }
+    segments.push(tour[start..].to_vec());
+    Ok(segments)
+}
+
+/// Cuts the longest edges of the tour, as many as it takes to make `count` segments, or every edge if the tour
+/// has fewer points than that. Each segment keeps the order of the tour.
+pub fn split_at_longest(map: &Map, tour: &[usize], count: usize) -> Vec<Vec<usize>> {
+    let mut edges: Vec<usize> = (1..tour.len()).collect();
+    edges.sort_by(|&a, &b| map.dist(tour[b - 1], tour[b]).total_cmp(&map.dist(tour[a - 1], tour[a])));
+    edges.truncate(count.saturating_sub(1));
+    edges.sort_unstable();
+    // Infallible: the cuts are different positions inside the tour, in increasing order
+    split(tour, &edges).unwrap()
+}
+
+/// How [`merge`] joins segments back into a tour.
+#[derive(Clone, Copy, Debug)]
+pub struct Merge {
+    /// Choose the order of the segments as well as their directions, which is exhaustive for up to 12 segments.
+    /// More segments, or segments whose order should stay, keep the order they are given in
+    pub reorder: bool,
+    /// The points on either side of every junction that are rearranged by an exact solve of their own
+    pub window: usize
+}
+
+impl Default for Merge {
+    /// Reorders the segments, and rearranges 4 points on either side of every junction.
+    fn default() -> Self {
+        Merge {
+            reorder: true,
+            window: 4
+        }
+    }
+}
+
+/// Joins the segments into one tour, running each forwards or backwards and, unless told to keep their order,
+/// ordering them so that the edges between them are as short as possible. The points around every junction are
+/// then rearranged by a small exact solve that keeps the points just outside the window where they are, which can
+/// move points across the junction. Together the segments must visit every point of the map once.
+///
+/// The junctions are solved with Held-Karp, which takes time exponential in twice the window, so windows beyond
+/// about 8 points are slow. The iteration count is the sum of those of the junction solves. Only the length of the
+/// tour is minimized, so the map may not have time windows, precedences, edge constraints or a turn penalty.
+pub fn merge(map: &Map, segments: &[Vec<usize>], merge: &Merge, options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_permutation(map, &segments.concat())?;
+    let constrained = map.time_windows().is_some()
+        || map.precedences().is_some()
+        || map.edge_constraints().is_some()
+        || map.turn_penalty().is_some();
+    if constrained {
+        return Err(Error::InvalidProblem("segments can only be merged on maps without constraints".to_string()));
+    }
+
+    let mut paths: Vec<Vec<usize>> = segments.iter().filter(|segment| !segment.is_empty()).cloned().collect();
+    if merge.reorder && paths.len() <= REORDER_SEGMENTS {
+        paths = best_order(map, paths);
+    } else {
+        crate::clustered::orient(map, &mut paths);
+    }
+
+    let junctions: Vec<usize> = paths.iter()
+        .scan(0, |end, path| {
+            *end += path.len();
+            Some(*end)
+        })
+        .collect();
+    let mut tour = paths.concat();
+    let mut budget = Budget::new(options, start, 1);
+    let mut iterations = 0;
+    let mut termination = Termination::Completed;
+    for &junction in &junctions[..junctions.len().saturating_sub(1)] {
+        if budget.exhausted() {
+            termination = budget.termination();
+            break;
+        }
+        iterations += smooth(map, &mut tour, junction, merge.window)?;
+    }
+
+    let mut result = SolveResult::new(map, tour, iterations, start);
+    result.termination = termination;
+    Ok(result)
+}
+
+// The segments in the order and directions that make the edges between them shortest, by dynamic programming over
+// the sets of segments joined so far, the last of them and its direction
+fn best_order(map: &Map, mut paths: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    let count = paths.len();
+    if count < 2 {
+        return paths;
+    }
+    // The first and last point of every segment in each direction
+    let ends = |segment: usize, reversed: usize| {
+        let path = &paths[segment];
+        // Infallible: empty segments are left out
+        let (first, last) = (*path.first().unwrap(), *path.last().unwrap());
+        if reversed == 1 { (last, first) } else { (first, last) }
+    };
+
+    // `best[set][segment][direction]` is the shortest joining length of the segments in the set ending with the
+    // given one, reached from the state in `from`
+    let states = 1usize << count;
+    let mut best = vec![[[f32::INFINITY; 2]; REORDER_SEGMENTS]; states];
+    let mut from = vec![[[(usize::MAX, 0); 2]; REORDER_SEGMENTS]; states];
+    for segment in 0..count {
+        best[1 << segment][segment] = [0.0; 2];
+    }
+    for set in 1..states {
+        for last in (0..count).filter(|&last| set & (1 << last) != 0) {
+            for direction in 0..2 {
+                let length = best[set][last][direction];
+                if length == f32::INFINITY {
+                    continue;
+                }
+                let (_, exit) = ends(last, direction);
+                for next in (0..count).filter(|&next| set & (1 << next) == 0) {
+                    for next_direction in 0..2 {
+                        let (entry, _) = ends(next, next_direction);
+                        let joined = length + map.dist(exit, entry);
+                        let state = set | (1 << next);
+                        if joined < best[state][next][next_direction] {
+                            best[state][next][next_direction] = joined;
+                            from[state][next][next_direction] = (last, direction);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let full = states - 1;
+    let (mut last, mut direction) = (0..count)
+        .flat_map(|segment| [(segment, 0), (segment, 1)])
+        .min_by(|&(a, x), &(b, y)| best[full][a][x].total_cmp(&best[full][b][y]))
+        .unwrap_or((0, 0));
+    let mut order = Vec::with_capacity(count);
+    let mut set = full;
+    while last != usize::MAX {
+        order.push((last, direction));
+        let previous = from[set][last][direction];
+        set &= !(1 << last);
+        (last, direction) = previous;
+    }
+    order.reverse();
+
+    order.into_iter()
+        .map(|(segment, direction)| {
+            let mut path = std::mem::take(&mut paths[segment]);
+            if direction == 1 {
+                path.reverse();
+            }
+            path
+        })
+        .collect()
+}
+
+// Rearranges the `window` points on either side of the junction before position `junction` of the tour by solving
+// them exactly between the points just outside, returning the iterations of the solve
+fn smooth(map: &Map, tour: &mut [usize], junction: usize, window: usize) -> Result<u64> {
+    let (low, high) = (junction.saturating_sub(window), (junction + window).min(tour.len()));
+    if high - low < 3 {
+        return Ok(0);
+    }
+    let before = low.checked_sub(1).map(|index| tour[index]);
+    let after = tour.get(high).copied();
+    let points: Vec<usize> = before.into_iter().chain(tour[low..high].iter().copied()).chain(after).collect();
+
+    let local = Map::from_cached_costs(points.len(), |i, j| map.dist(points[i], points[j]));
+    let endpoints = Endpoints {
+        start: before.map(|_| 0),
+        end: after.map(|_| points.len() - 1)
+    };
+    let solver = FixedEndpoints {
+        solver: &HeldKarp::default(),
+        endpoints
+    };
+    let solved = solver.solve(&local, &SolveOptions::default())?;
+    let current: Vec<usize> = (0..points.len()).collect();
+    if solved.length < current.evaluate(&local) {
+        let inner = &solved.tour[before.map_or(0, |_| 1)..solved.tour.len() - after.map_or(0, |_| 1)];
+        for (slot, &index) in tour[low..high].iter_mut().zip(inner) {
+            *slot = points[index];
+        }
+    }
+    Ok(solved.iterations)
+}
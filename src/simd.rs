@@ -0,0 +1,89 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use crate::map::{Map, PRECISE_EDGES, Point};
+
+// Distances are computed four at a time, as `sqrt(dx * dx + dy * dy)` rather than with `hypot`, so they can
+// differ from `Point::dist` in the last bit, and sums come out in a different order than a sequential sum.
+const LANES: usize = 4;
+
+/// Writes the distance from `from` to each of `points` into the same position of `out`, which must be at least as
+/// long as `points`.
+pub fn distances(from: Point, points: &[Point], out: &mut [f32]) {
+    assert!(out.len() >= points.len(), "The output must have room for every distance");
+
+    let split = points.len() - points.len() % LANES;
+    for (chunk, out) in points[..split].chunks_exact(LANES).zip(out.chunks_exact_mut(LANES)) {
+        out.copy_from_slice(&dist4(from, [chunk[0], chunk[1], chunk[2], chunk[3]]));
+    }
+    for (point, out) in points[split..].iter().zip(out[split..].iter_mut()) {
+        *out = from.dist(point);
+    }
+}
+
+/// The length of the path visiting the map's points in the order of `tour`. Indices out of range panic. Like
+/// [`Path::evaluate`](crate::map::Path::evaluate), long paths are summed in double precision: in blocks of 1024
+/// edges, each summed as a short path is.
+pub fn evaluate(map: &Map, tour: &[usize]) -> f32 {
+    let edges = tour.len().saturating_sub(1);
+    if edges <= PRECISE_EDGES {
+        return evaluate_block(map, tour);
+    }
+    (0..edges)
+        .step_by(PRECISE_EDGES)
+        .map(|start| evaluate_block(map, &tour[start..(start + PRECISE_EDGES + 1).min(tour.len())]) as f64)
+        .sum::<f64>() as f32
+}
+
+fn evaluate_block(map: &Map, tour: &[usize]) -> f32 {
+    let edges = tour.len().saturating_sub(1);
+    let split = edges - edges % LANES;
+
+    let mut sums = [0f32; LANES];
+    for start in (0..split).step_by(LANES) {
+        let from = [map[tour[start]], map[tour[start + 1]], map[tour[start + 2]], map[tour[start + 3]]];
+        let to = [map[tour[start + 1]], map[tour[start + 2]], map[tour[start + 3]], map[tour[start + 4]]];
+        let lengths = edge4(from, to);
+        for (sum, length) in sums.iter_mut().zip(lengths.iter()) {
+            *sum += length;
+        }
+    }
+
+    let tail: f32 = tour[split..].windows(2).map(|edge| map[edge[0]].dist(&map[edge[1]])).sum();
+    sums.iter().sum::<f32>() + tail
+}
+
+fn dist4(from: Point, to: [Point; LANES]) -> [f32; LANES] {
+    edge4([from; LANES], to)
+}
+
+// SSE2 is part of the x86_64 baseline, so it needs no runtime detection
+#[cfg(target_arch = "x86_64")]
+fn edge4(from: [Point; LANES], to: [Point; LANES]) -> [f32; LANES] {
+    let mut out = [0f32; LANES];
+    // Safe: SSE2 is always available on x86_64, and `out` has room for four lanes
+    unsafe {
+        let dx = _mm_sub_ps(
+            _mm_setr_ps(from[0].x, from[1].x, from[2].x, from[3].x),
+            _mm_setr_ps(to[0].x, to[1].x, to[2].x, to[3].x)
+        );
+        let dy = _mm_sub_ps(
+            _mm_setr_ps(from[0].y, from[1].y, from[2].y, from[3].y),
+            _mm_setr_ps(to[0].y, to[1].y, to[2].y, to[3].y)
+        );
+        let squared = _mm_add_ps(_mm_mul_ps(dx, dx), _mm_mul_ps(dy, dy));
+        _mm_storeu_ps(out.as_mut_ptr(), _mm_sqrt_ps(squared));
+    }
+    out
+}
+
+// Written lane by lane so that the compiler can vectorize it for the target
+#[cfg(not(target_arch = "x86_64"))]
+fn edge4(from: [Point; LANES], to: [Point; LANES]) -> [f32; LANES] {
+    let mut out = [0f32; LANES];
+    for lane in 0..LANES {
+        let (dx, dy) = (from[lane].x - to[lane].x, from[lane].y - to[lane].y);
+        out[lane] = (dx * dx + dy * dy).sqrt();
+    }
+    out
+}
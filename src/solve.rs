@@ -1,274 +1,1926 @@
-use crate::map::{Map, Path, Point};
-use std::cell::Cell;
-use std::cmp;
+use crate::certificate::{Certificate, Prune, path_length};
+use crate::checkpoint::BranchCheckpoint;
+use crate::context::SolverContext;
+use crate::edges::EdgeConstraints;
+use crate::error::{Error, Result};
+use crate::explain::Step;
+use crate::history::{History, Improvement};
+use crate::instrument::{self, Counter, Instrumentation};
+use crate::map::{Map, Path, Point, check_permutation};
+use crate::matrix::DistanceMatrix;
+use crate::search_state::{SearchState, Unvisited, Visit};
+use crate::search_tree::{Node, Outcome, SearchTree};
+use crate::time::Instant;
+use crate::tours::Tours;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::collections::HashSet;
 use std::fmt::{self, Debug, Formatter};
-use std::ops::Deref;
+use std::result;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
 
-pub struct Counter<'a>(Option<&'a mut usize>);
+/// Options shared by every solver. Only the time limit, evaluation limit, target gap, beam width, parallelism and
+/// objective can be read from a configuration file.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct SolveOptions {
+    /// Stop and return the best tour found so far once this much time has passed
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::time::deserialize_duration"))]
+    pub time_limit: Option<Duration>,
+    /// Stop and return the best tour found so far once this many tours or moves have been evaluated, the budget
+    /// metaheuristics are usually compared by. Only the [genetic algorithm](crate::ga::GaSolver), simulated
+    /// annealing, iterated local search and 2-opt count their evaluations, and drivers running several searches at
+    /// once, such as the island GA, count those of each search on their own.
+    pub max_evaluations: Option<u64>,
+    /// Stop and return the best tour found so far once the token is cancelled
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub cancel: Option<CancelToken>,
+    /// Receives progress updates while the solver runs
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub progress: Option<Arc<dyn Progress>>,
+    /// Receives counts and timings of the work the solver does
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub instrumentation: Option<Arc<dyn Instrumentation>>,
+    /// Seeds every random choice the solver makes, so that a run can be repeated exactly. Runs stopped by a time
+    /// limit or cancellation still end at different points.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub seed: Option<u64>,
+    /// How many threads the solver may use
+    pub parallelism: Parallelism,
+    /// Whether to look for the shortest or the longest tour
+    pub objective: Objective,
+    /// A known lower bound on the optimal length, such as the optimum itself, which the genetic algorithm,
+    /// simulated annealing and iterated local search report their [gap](SolveResult::gap) against
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub lower_bound: Option<f32>,
+    /// Stop the genetic algorithm, simulated annealing or iterated local search once the best tour is within this
+    /// fraction of the lower bound, so `0.01` stops within 1% of it. The bound is [`lower_bound`](Self::lower_bound)
+    /// if given, else that of the [context](Self::context), else the length of a minimum spanning tree.
+    pub target_gap: Option<f32>,
+    /// Run [`branch_and_bound`] as a beam search instead, which extends its paths a point at a time and keeps only
+    /// this many of those with the lowest bounds at every depth, on a single thread. It takes time polynomial in the
+    /// size of the map, and memory linear in the width, but no longer proves its tour optimal. Checkpoints,
+    /// certificates and traced searches always search in full.
+    pub beam_width: Option<usize>,
+    /// Structures built once for the map being solved, which solvers take instead of building their own
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub context: Option<Arc<SolverContext>>
+}
+
+impl SolveOptions {
+    /// A random number generator seeded with [`seed`](Self::seed), or randomly if there is none.
+    pub fn rng(&self) -> ChaCha20Rng {
+        ChaCha20Rng::seed_from_u64(self.seed.unwrap_or_else(rand::random))
+    }
+}
+
+/// How many threads a solver may use. Only solvers with a parallel implementation use more than one: the
+/// multi-start and island GAs, branch-and-bound, and benchmarks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Parallelism {
+    /// Run on the calling thread
+    #[default]
+    Off,
+    /// Use at most this many threads
+    Threads(usize),
+    /// Use one thread per core
+    All
+}
+
+impl Parallelism {
+    /// The number of threads to use, which is at least one. Browsers cannot spawn threads, so there it is always
+    /// one.
+    pub fn threads(self) -> usize {
+        if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+            return 1;
+        }
+
+        match self {
+            Parallelism::Off => 1,
+            Parallelism::Threads(count) => count.max(1),
+            Parallelism::All => thread::available_parallelism().map(|count| count.get()).unwrap_or(1)
+        }
+    }
+}
+
+/// Whether solvers look for the shortest tour or the longest. Only the roulette wheel GA and the drivers built on
+/// it, and local search, can maximize; every other solver always minimizes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Objective {
+    #[default]
+    Minimize,
+    /// Look for the longest tour, as in dispersion problems. Also a check on selection, since a GA whose selection
+    /// pressure points the wrong way does better at the opposite objective
+    Maximize
+}
+
+impl Objective {
+    /// The loss of a tour of the given length, which is lower for better tours: the length itself when
+    /// minimizing, and its negation when maximizing. A length that is NaN, which a [cost](Map::with_costs)
+    /// function can produce, has an infinite loss, so that the tour ranks below every other.
+    #[inline]
+    pub fn loss(self, length: f32) -> f32 {
+        if length.is_nan() {
+            return f32::INFINITY;
+        }
+        match self {
+            Objective::Minimize => length,
+            Objective::Maximize => -length
+        }
+    }
+
+    /// The length of a tour with the given loss, undoing [`loss`](Self::loss).
+    #[inline]
+    pub fn length(self, loss: f32) -> f32 {
+        self.loss(loss)
+    }
+}
+
+/// Parses `off`, `all`, or a number of threads.
+impl FromStr for Parallelism {
+    type Err = String;
+
+    fn from_str(text: &str) -> result::Result<Self, String> {
+        match text.trim() {
+            "off" => Ok(Parallelism::Off),
+            "all" => Ok(Parallelism::All),
+            count => count.parse()
+                .map(Parallelism::Threads)
+                .map_err(|_| format!("expected off, all or a number of threads, found {:?}", text))
+        }
+    }
+}
+
+/// An update on the state of a running solve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProgressEvent {
+    /// The fraction of the search space covered so far, reported by exhaustive solvers
+    Completion(f32),
+    /// The number of search tree nodes expanded so far, reported by branch-and-bound alongside its completion
+    Nodes(u64),
+    /// A generation finished, reported by the genetic algorithms
+    Generation {
+        generation: u32,
+        generations: u32
+    },
+    /// A point on the convergence curve: the best length so far and the mean length of the current population,
+    /// or of the current tour for single-tour searches. Only computed for [detailed](Progress::detailed) observers.
+    Sample {
+        step: u64,
+        best: f32,
+        mean: f32,
+        elapsed: Duration
+    },
+    /// The average fraction of edges in which the population's tours differ from the best tour, reported by the
+    /// genetic algorithms. Only computed for [detailed](Progress::detailed) observers.
+    Diversity(f32),
+    /// A shorter tour was found
+    Improvement {
+        length: f32,
+        elapsed: Duration
+    }
+}
+
+/// Receives [`ProgressEvent`]s from a running solver. Implemented for any suitable closure.
+pub trait Progress: Send + Sync {
+    fn report(&self, event: ProgressEvent);
+
+    /// Receives the tour behind an improvement, from solvers which track it alongside its length.
+    fn tour(&self, _tour: &[usize]) {}
+
+    /// Whether to compute the [`Sample`](ProgressEvent::Sample) and [`Diversity`](ProgressEvent::Diversity)
+    /// events, which can cost as much as the search itself.
+    fn detailed(&self) -> bool {
+        true
+    }
+}
+
+impl<F: Fn(ProgressEvent) + Send + Sync> Progress for F {
+    fn report(&self, event: ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Forwards every event to each of several observers.
+pub struct Broadcast(pub Vec<Arc<dyn Progress>>);
+
+impl Progress for Broadcast {
+    fn report(&self, event: ProgressEvent) {
+        self.0.iter().for_each(|progress| progress.report(event));
+    }
+
+    fn tour(&self, tour: &[usize]) {
+        self.0.iter().for_each(|progress| progress.tour(tour));
+    }
+
+    fn detailed(&self) -> bool {
+        self.0.iter().any(|progress| progress.detailed())
+    }
+}
+
+impl Debug for dyn Progress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Progress")
+    }
+}
+
+/// Forwards progress to the callback in [`SolveOptions`], if there is one, only reporting strict
+/// improvements.
+pub(crate) struct Reporter<'a> {
+    progress: Option<&'a dyn Progress>,
+    instrumentation: Option<&'a dyn Instrumentation>,
+    start: Instant,
+    objective: Objective,
+    // The loss of the best length reported so far
+    best: f32
+}
+
+impl<'a> Reporter<'a> {
+    pub fn new(options: &'a SolveOptions, start: Instant) -> Self {
+        Reporter {
+            progress: options.progress.as_deref(),
+            instrumentation: options.instrumentation.as_deref(),
+            start,
+            objective: Objective::Minimize,
+            best: f32::MAX
+        }
+    }
+
+    // Judges improvements by the objective, for solvers that honor it
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn silent() -> Self {
+        Reporter {
+            progress: None,
+            instrumentation: None,
+            start: Instant::now(),
+            objective: Objective::Minimize,
+            best: f32::MAX
+        }
+    }
+
+    pub fn improved(&mut self, length: f32) {
+        let loss = self.objective.loss(length);
+        if loss >= self.best {
+            return;
+        }
+        self.best = loss;
+
+        if let Some(progress) = self.progress {
+            progress.report(ProgressEvent::Improvement {
+                length,
+                elapsed: self.start.elapsed()
+            });
+        }
+    }
+
+    pub fn completion(&self, fraction: f32) {
+        if let Some(progress) = self.progress {
+            progress.report(ProgressEvent::Completion(fraction));
+        }
+    }
+
+    pub fn nodes(&self, nodes: u64) {
+        if let Some(progress) = self.progress {
+            progress.report(ProgressEvent::Nodes(nodes));
+        }
+    }
+
+    pub fn tour(&self, tour: &[usize]) {
+        if let Some(progress) = self.progress {
+            progress.tour(tour);
+        }
+    }
+
+    // The mean is computed lazily since it can cost as much as a step of the search
+    pub fn sample<F: FnOnce() -> f32>(&self, step: u64, best: f32, mean: F) {
+        if let Some(progress) = self.progress.filter(|progress| progress.detailed()) {
+            progress.report(ProgressEvent::Sample {
+                step,
+                best,
+                mean: mean(),
+                elapsed: self.start.elapsed()
+            });
+        }
+    }
+
+    pub fn diversity<F: FnOnce() -> f32>(&self, diversity: F) {
+        if let Some(progress) = self.progress.filter(|progress| progress.detailed()) {
+            progress.report(ProgressEvent::Diversity(diversity()));
+        }
+    }
+
+    pub fn generation(&self, generation: u32, generations: u32) {
+        if let Some(progress) = self.progress {
+            progress.report(ProgressEvent::Generation {
+                generation,
+                generations
+            });
+        }
+    }
+
+    pub fn count(&self, name: &'static str, amount: u64) {
+        if let Some(instrumentation) = self.instrumentation {
+            instrumentation.count(name, amount);
+        }
+    }
+
+    pub fn time(&self, name: &'static str, elapsed: Duration) {
+        if let Some(instrumentation) = self.instrumentation {
+            instrumentation.time(name, elapsed);
+        }
+    }
+}
+
+/// A flag shared between a running solver and the code that may want to stop it early.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancelToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        CancelToken(flag)
+    }
+}
+
+/// The best tour found by any of several threads, which parallel solvers share so that each prunes against, or
+/// restarts from, the best that any has found. Tours are judged by their [loss](Objective::loss), lower being
+/// better.
+///
+/// The loss is kept in an atomic as well as beside the tour, so that reading it and turning down worse tours take
+/// no lock; the lock is only taken to hand a better tour over, or a copy of it out.
+#[derive(Debug)]
+pub struct SharedBest {
+    // The ordered bits of the best loss
+    loss: AtomicU32,
+    best: Mutex<(f32, Vec<usize>)>
+}
+
+impl Default for SharedBest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedBest {
+    /// No tour yet, with an infinite loss that every tour beats.
+    pub fn new() -> Self {
+        Self::with_tour(f32::INFINITY, Vec::new())
+    }
+
+    pub fn with_tour(loss: f32, tour: Vec<usize>) -> Self {
+        SharedBest {
+            loss: AtomicU32::new(ordered(loss)),
+            best: Mutex::new((loss, tour))
+        }
+    }
+
+    /// The loss of the best tour, without locking.
+    pub fn loss(&self) -> f32 {
+        unordered(self.loss.load(Ordering::Relaxed))
+    }
+
+    /// Replaces the best tour with the given one if its loss is lower, returning whether it did.
+    pub fn offer(&self, loss: f32, tour: &[usize]) -> bool {
+        self.offer_with(loss, tour, |_, _| ())
+    }
 
-impl<'a> Counter<'a> {
-    pub fn increment(&mut self) {
-        if let Some(inner) = self.0.as_deref_mut() {
-            *inner += 1;
+    /// Like [`offer`](SharedBest::offer), but calls `improved` with the new best while still holding the lock,
+    /// so that whatever it reports to sees the improvements in the order they were made.
+    pub fn offer_with<F: FnOnce(f32, &[usize])>(&self, loss: f32, tour: &[usize], improved: F) -> bool {
+        if self.loss.load(Ordering::Relaxed) <= ordered(loss) {
+            return false;
+        }
+        let mut best = self.lock();
+        if loss >= best.0 {
+            return false;
         }
+        best.0 = loss;
+        best.1.clear();
+        best.1.extend_from_slice(tour);
+        self.loss.store(ordered(loss), Ordering::Relaxed);
+        improved(loss, tour);
+        true
+    }
+
+    /// A copy of the best tour if its loss is lower than the given one.
+    pub fn better_than(&self, loss: f32) -> Option<Vec<usize>> {
+        if self.loss.load(Ordering::Relaxed) >= ordered(loss) {
+            return None;
+        }
+        let best = self.lock();
+        (best.0 < loss).then(|| best.1.clone())
+    }
+
+    /// The loss and a copy of the best tour, which is empty if none has been offered.
+    pub fn get(&self) -> (f32, Vec<usize>) {
+        self.lock().clone()
+    }
+
+    pub fn into_inner(self) -> (f32, Vec<usize>) {
+        self.best.into_inner().unwrap_or_else(PoisonError::into_inner)
     }
+
+    fn lock(&self) -> MutexGuard<'_, (f32, Vec<usize>)> {
+        // A callback that panicked while the lock was held ran after the tour was replaced, so it is still whole
+        self.best.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Bits of the loss that order the same way as the losses do, negative ones included
+fn ordered(loss: f32) -> u32 {
+    let bits = loss.to_bits();
+    if bits >> 31 == 1 { !bits } else { bits | 1 << 31 }
 }
 
-impl<'a> From<Option<&'a mut usize>> for Counter<'a> {
-    fn from(x: Option<&'a mut usize>) -> Self {
-        Counter(x)
+fn unordered(bits: u32) -> f32 {
+    f32::from_bits(if bits >> 31 == 1 { bits & !(1 << 31) } else { !bits })
+}
+
+/// Why a solver stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum Termination {
+    /// The solver ran to completion
+    Completed,
+    /// The time limit was reached, so the tour is the best found so far
+    TimeLimit,
+    /// The solve was cancelled, so the tour is the best found so far
+    Cancelled,
+    /// The tour came within the [target gap](SolveOptions::target_gap) of the lower bound
+    TargetGap,
+    /// The [evaluation limit](SolveOptions::max_evaluations) was reached, so the tour is the best found so far
+    EvaluationLimit
+}
+
+/// Tracks the stopping criteria in [`SolveOptions`] for a single solve.
+pub(crate) struct Budget {
+    deadline: Option<Instant>,
+    cancel: Option<CancelToken>,
+    stride: u32,
+    calls: u32,
+    target: Option<f32>,
+    max_evaluations: Option<u64>,
+    evaluations: u64,
+    termination: Termination
+}
+
+impl Budget {
+    /// The clock and cancellation token are only read once every `stride` calls to [`exhausted`](Budget::exhausted), so that
+    /// checking the budget in hot loops stays cheap.
+    pub fn new(options: &SolveOptions, start: Instant, stride: u32) -> Self {
+        Budget {
+            // A limit too long to reach is no limit at all
+            deadline: options.time_limit.and_then(|limit| start.checked_add(limit)),
+            cancel: options.cancel.clone(),
+            stride: stride.max(1),
+            calls: 0,
+            target: None,
+            max_evaluations: options.max_evaluations,
+            evaluations: 0,
+            termination: Termination::Completed
+        }
+    }
+
+    /// Stops once [`reached`](Budget::reached) sees a length within the target gap of the options above `bound`,
+    /// if there are both.
+    pub fn with_target(mut self, bound: Option<f32>, options: &SolveOptions) -> Self {
+        self.target = bound.zip(options.target_gap).map(|(bound, gap)| bound * (1.0 + gap.max(0.0)));
+        self
+    }
+
+    pub fn unlimited() -> Self {
+        Self::new(&SolveOptions::default(), Instant::now(), 1)
+    }
+
+    pub fn exhausted(&mut self) -> bool {
+        if self.termination != Termination::Completed {
+            return true;
+        }
+
+        if self.max_evaluations.is_some_and(|limit| self.evaluations >= limit) {
+            self.termination = Termination::EvaluationLimit;
+            return true;
+        }
+
+        self.calls += 1;
+        if self.calls < self.stride {
+            return false;
+        }
+        self.calls = 0;
+
+        if self.cancel.as_ref().map(CancelToken::is_cancelled).unwrap_or(false) {
+            self.termination = Termination::Cancelled;
+            return true;
+        }
+
+        if self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false) {
+            self.termination = Termination::TimeLimit;
+            return true;
+        }
+
+        false
+    }
+
+    /// Counts evaluations toward the limit of the options, which [`exhausted`](Budget::exhausted) checks on every
+    /// call.
+    pub fn evaluated(&mut self, count: u64) {
+        self.evaluations += count;
+    }
+
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Whether the length is within the target, in which case the budget is exhausted from then on.
+    pub fn reached(&mut self, length: f32) -> bool {
+        if self.termination == Termination::Completed && self.target.is_some_and(|target| length <= target) {
+            self.termination = Termination::TargetGap;
+        }
+        self.termination == Termination::TargetGap
+    }
+
+    pub fn termination(&self) -> Termination {
+        self.termination
     }
 }
 
-impl<'a> From<&'a mut usize> for Counter<'a> {
-    fn from(x: &'a mut usize) -> Self {
-        Counter(Some(x))
+/// A lower bound on the optimal length of the map to measure a heuristic's gap against: the one given in the
+/// options, else that of their context, else the length of a minimum spanning tree if a target gap needs one.
+/// Maximizing and maps with time windows, whose cost is not a length, have none.
+pub(crate) fn known_bound(map: &Map, options: &SolveOptions) -> Option<f32> {
+    if options.objective == Objective::Maximize || map.time_windows().is_some() {
+        return None;
     }
+
+    options.lower_bound
+        .or_else(|| SolverContext::of(options, map).map(SolverContext::lower_bound))
+        .or_else(|| options.target_gap.map(|_| crate::context::spanning_tree(map.size(), |i, j| map.dist(i, j)).1))
 }
 
-pub fn brute_force<'a, C: Into<Counter<'a>>>(map: &Map, counter: C) -> (Vec<usize>, f32) {
-    let mut counter = counter.into();
-    let mut current = vec![0usize; map.size()];
-    current.iter_mut().enumerate().for_each(|(index, ele)| *ele = index);
-    let mut solution = current.clone();
-    let mut shortest_dist = current.evaluate(map);
+/// The outcome of a solve. Serializes with the elapsed time in seconds.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SolveResult {
+    /// The order in which the map's points are visited
+    pub tour: Vec<usize>,
+    pub length: f32,
+    /// A proven lower bound on the optimal length, if the solver produces one
+    pub lower_bound: Option<f32>,
+    /// Permutations checked, nodes expanded, or generations run, depending on the solver
+    pub iterations: u64,
+    /// The tours and moves evaluated, by the solvers that count them toward the
+    /// [evaluation limit](SolveOptions::max_evaluations), and zero for the others
+    pub evaluations: u64,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::time::serialize_seconds"))]
+    pub elapsed: Duration,
+    pub termination: Termination,
+    /// Every shorter tour length found over the run and when, recorded by
+    /// [`Solver::solve_with_history`] and empty otherwise
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub history: Vec<Improvement>
+}
 
-    let max = map.size() - 1;
-    let mut increase: usize = 0;
-    while increase != max {
-        counter.increment();
+impl SolveResult {
+    pub fn new(map: &Map, tour: Vec<usize>, iterations: u64, start: Instant) -> Self {
+        let length = tour.evaluate(map);
+        SolveResult {
+            tour,
+            length,
+            lower_bound: None,
+            iterations,
+            evaluations: 0,
+            elapsed: start.elapsed(),
+            termination: Termination::Completed,
+            history: Vec::new()
+        }
+    }
+
+    /// The relative gap between the length and the lower bound, if there is one.
+    pub fn gap(&self) -> Option<f32> {
+        self.lower_bound.map(|bound| if bound > 0.0 { (self.length - bound) / bound } else { 0.0 })
+    }
+
+    /// Whether the tour is proven to be optimal, its lower bound having reached its length, as when an exact
+    /// search runs to completion.
+    pub fn is_optimal(&self) -> bool {
+        self.lower_bound.is_some_and(|bound| bound >= self.length)
+    }
+}
+
+/// A common interface over every algorithm in the crate, so they can be used interchangeably.
+pub trait Solver {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult>;
 
-        if increase == 0 {
-            current.swap(increase, increase + 1);
-            increase += 1;
-            while increase < max && current[increase] > current[increase + 1] {
-                increase += 1;
+    /// Solves like [`solve`](Solver::solve), recording every improvement the solver reports in the result's
+    /// [`history`](SolveResult::history). The progress observer of the options, if any, still receives every
+    /// event.
+    fn solve_with_history(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        let history = Arc::new(History::improvements_only());
+        let mut observers: Vec<Arc<dyn Progress>> = vec![Arc::clone(&history) as Arc<dyn Progress>];
+        observers.extend(options.progress.clone());
+        let options = SolveOptions {
+            progress: Some(Arc::new(Broadcast(observers))),
+            ..options.clone()
+        };
+
+        let mut result = self.solve(map, &options)?;
+        result.history = history.improvements();
+        Ok(result)
+    }
+
+    /// Solves every map, up to [`parallelism`](SolveOptions::parallelism) of them at once and each on a single thread,
+    /// and returns their results in the order of the maps, for the thousands of small instances a simulation can
+    /// generate. With a seed in the options, each map is seeded with the next integer after the last. The time limit
+    /// is that of every solve, and cancelling the token stops them all. The progress observer of the options, if any,
+    /// receives the fraction of the maps solved as their [completion](ProgressEvent::Completion) instead of the events
+    /// of the solves.
+    fn solve_batch(&self, maps: &[Map], options: &SolveOptions) -> Vec<Result<SolveResult>>
+    where
+        Self: Sync
+    {
+        let solved = AtomicUsize::new(0);
+        run_tasks(maps.len(), options.parallelism, |index| {
+            let result = self.solve(&maps[index], &SolveOptions {
+                seed: options.seed.map(|seed| seed.wrapping_add(index as u64)),
+                parallelism: Parallelism::Off,
+                progress: None,
+                ..options.clone()
+            });
+            if let Some(progress) = &options.progress {
+                let solved = solved.fetch_add(1, Ordering::Relaxed) + 1;
+                progress.report(ProgressEvent::Completion(solved as f32 / maps.len() as f32));
             }
-        } else {
-            if current[increase + 1] > current[0] {
-                current.swap(increase + 1, 0);
-            } else {
-                let mut start = 0;
-                let mut end = increase;
-                let mut mid = (start + end) / 2;
-                let top = current[increase + 1];
-                while !(current[mid] < top && current[mid - 1] > top) {
-                    if current[mid] < top {
-                        end = mid - 1;
-                    } else {
-                        start = mid + 1;
-                    }
-                    mid = (start + end) / 2;
+            result
+        })
+    }
+
+    /// Solves on a background thread, streaming results as they are found: every improving tour the solver
+    /// reports while it runs, then its final result, or its error. Intermediate results come from solvers that
+    /// report their tours alongside their improvements, such as the genetic algorithms and the
+    /// [portfolio](crate::portfolio::Portfolio); they carry no iteration count. Other solvers only send their
+    /// final result. The progress observer of the options, if any, still receives every event.
+    ///
+    /// Dropping the stream does not stop the solve; cancel it through the options' token instead.
+    fn solve_stream(self: Arc<Self>, map: Map, options: SolveOptions) -> SolveStream
+    where
+        Self: Send + Sync + 'static
+    {
+        let (sender, receiver) = mpsc::channel();
+        let start = Instant::now();
+        let stream = Arc::new(Streamer {
+            map: map.clone(),
+            sender: sender.clone(),
+            start,
+            objective: options.objective,
+            best: Mutex::new(f32::MAX)
+        });
+        let mut observers: Vec<Arc<dyn Progress>> = vec![stream as Arc<dyn Progress>];
+        observers.extend(options.progress.clone());
+        let options = SolveOptions {
+            progress: Some(Arc::new(Broadcast(observers))),
+            ..options
+        };
+
+        thread::spawn(move || {
+            // The stream may have been dropped, in which case nobody is waiting for the result
+            let _ = sender.send(self.solve(&map, &options));
+        });
+        SolveStream {
+            receiver
+        }
+    }
+}
+
+// Runs the tasks on up to the given number of threads, each task once, and returns their results in task order
+pub(crate) fn run_tasks<T, F>(tasks: usize, parallelism: Parallelism, task: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync
+{
+    let threads = parallelism.threads().min(tasks);
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<T>>> = Mutex::new((0..tasks).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= tasks {
+                    break;
                 }
 
-                current.swap(increase + 1, mid);
-            }
+                let result = task(index);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
 
-            for i in 0..=increase / 2 {
-                current.swap(i, increase - i);
-            }
-            increase = 0;
+    // Infallible: every task is claimed by exactly one worker
+    results.into_inner()
+        .unwrap()
+        .into_iter()
+        .map(Option::unwrap)
+        .collect()
+}
+
+/// The results of a [`Solver::solve_stream`], in the order they were found. Iterating blocks until the next
+/// result arrives and ends after the final one; [`receiver`](Self::receiver) polls without blocking.
+#[derive(Debug)]
+pub struct SolveStream {
+    receiver: Receiver<Result<SolveResult>>
+}
+
+impl SolveStream {
+    pub fn receiver(&self) -> &Receiver<Result<SolveResult>> {
+        &self.receiver
+    }
+}
+
+impl Iterator for SolveStream {
+    type Item = Result<SolveResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+// Sends every tour reported for an improvement over the last one sent down a stream
+struct Streamer {
+    map: Map,
+    sender: Sender<Result<SolveResult>>,
+    start: Instant,
+    objective: Objective,
+    // The loss of the last tour sent
+    best: Mutex<f32>
+}
+
+impl Progress for Streamer {
+    fn report(&self, _event: ProgressEvent) {}
+
+    fn tour(&self, tour: &[usize]) {
+        let loss = self.objective.loss(tour.evaluate(&self.map));
+        let mut best = self.best.lock().unwrap();
+        if loss < *best {
+            *best = loss;
+            let _ = self.sender.send(Ok(SolveResult::new(&self.map, tour.to_vec(), 0, self.start)));
+        }
+    }
+
+    fn detailed(&self) -> bool {
+        false
+    }
+}
+
+/// Checks that the map has points to visit and that they are valid. See [`Map::validate`].
+pub(crate) fn check_map(map: &Map) -> Result<()> {
+    map.validate()
+}
+
+// Fails if the map charges for turns, which the exact solvers that minimize the length alone cannot prove optimal
+fn check_no_turns(map: &Map, solver: &str) -> Result<()> {
+    if map.turn_penalty().is_some() {
+        return Err(Error::InvalidProblem(format!("{} cannot solve maps with a turn penalty", solver)));
+    }
+    Ok(())
+}
+
+/// Exhaustive search over every permutation. See [`brute_force`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BruteForce;
+
+impl Solver for BruteForce {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        brute_force(map, options)
+    }
+}
+
+/// Depth-first branch-and-bound. See [`branch_and_bound`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BranchAndBound;
+
+impl Solver for BranchAndBound {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        branch_and_bound(map, options, &NearestNeighborBound)
+    }
+}
+
+/// The lower bound [`branch_and_bound`] prunes with: a length no tour completing the current path can be shorter
+/// than, from the [state](SearchState) of the search, whose data is the distance from every point to its nearest
+/// neighbor, and the length of the path so far. Implemented for any suitable closure.
+///
+/// The higher the bound, the more of the tree is pruned, but a bound above the length of some tour completing the
+/// path can prune away the shortest tour. Constraints the search does not know of can raise the bound: a path
+/// that cannot be completed at all may be given an infinite one.
+pub trait Bound: Sync {
+    fn bound(&self, state: &SearchState<f32>, accumulated: f32) -> f32;
+}
+
+impl<F: Fn(&SearchState<f32>, f32) -> f32 + Sync> Bound for F {
+    fn bound(&self, state: &SearchState<f32>, accumulated: f32) -> f32 {
+        self(state, accumulated)
+    }
+}
+
+/// The bound of [`BranchAndBound`]: the length of the path, plus the distance from every point off it but the
+/// lowest to its nearest neighbor.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NearestNeighborBound;
+
+impl Bound for NearestNeighborBound {
+    #[inline]
+    fn bound(&self, state: &SearchState<f32>, accumulated: f32) -> f32 {
+        match state.first_unvisited() {
+            Some(first) => accumulated + (state.remaining() - *state.data(first) as f64) as f32,
+            None => accumulated
+        }
+    }
+}
+
+/// Held-Karp dynamic programming, refusing instances whose tables would take more than `memory_limit` bytes. See
+/// [`held_karp`].
+#[derive(Clone, Copy, Debug)]
+pub struct HeldKarp {
+    pub memory_limit: u64
+}
+
+impl Default for HeldKarp {
+    /// Allows up to 1 GiB, enough for 25 points. Raising the limit admits more, up to the 32 points
+    /// [`held_karp`] handles at all.
+    fn default() -> Self {
+        HeldKarp {
+            memory_limit: 1 << 30
+        }
+    }
+}
+
+impl Solver for HeldKarp {
+    fn solve(&self, map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+        held_karp(map, options, self.memory_limit)
+    }
+}
+
+/// Greedy nearest neighbor construction. See [`nearest_neighbor`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NearestNeighbor;
+
+impl Solver for NearestNeighbor {
+    fn solve(&self, map: &Map, _options: &SolveOptions) -> Result<SolveResult> {
+        nearest_neighbor(map)
+    }
+}
+
+/// Finds the shortest path by enumerating every permutation of the points, skipping those that visit points
+/// before their [predecessors](Map::with_precedences) or break the map's
+/// [edge constraints](Map::with_edge_constraints). If every permutation is skipped, the search fails.
+pub fn brute_force(map: &Map, options: &SolveOptions) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("brute_force", size = map.size()).entered();
+
+    let mut budget = Budget::new(options, start, 1024);
+    let mut reporter = Reporter::new(options, start);
+    let mut iterations = 0u64;
+    let mut tours = Tours::new(map.size());
+    // Infallible: the first tour is always generated
+    let mut solution = start_tour(map, tours.next_tour().unwrap().to_vec());
+    let mut shortest_dist = start_length(map, &solution);
+    reporter.improved(shortest_dist);
+
+    let permutations = (1..=map.size()).map(|n| n as f64).product::<f64>();
+
+    while let Some(current) = tours.next_tour() {
+        if budget.exhausted() {
+            break;
+        }
+
+        iterations += 1;
+        if iterations.is_multiple_of(1 << 16) {
+            reporter.completion((iterations as f64 / permutations) as f32);
         }
 
         let dist = current.evaluate(map);
-        if dist < shortest_dist {
+        if dist < shortest_dist && feasible(map, current) {
             shortest_dist = dist;
-            solution = current.clone();
+            solution = current.to_vec();
+            reporter.improved(dist);
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(length = dist, iterations, "improved");
         }
     }
 
-    (solution, shortest_dist)
+    reporter.count(instrument::NODES, iterations);
+    reporter.count(instrument::EVALUATIONS, iterations);
+    reporter.time(instrument::SEARCH, start.elapsed());
+    if budget.termination() == Termination::Completed {
+        check_found(map, &solution)?;
+        reporter.completion(1.0);
+    }
+
+    let mut result = SolveResult::new(map, solution, iterations, start);
+    result.termination = budget.termination();
+    if result.termination == Termination::Completed {
+        result.lower_bound = Some(result.length);
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(length = result.length, iterations, termination = ?result.termination, "finished");
+
+    Ok(result)
 }
 
-pub fn nearest_neighbor(map: &Map) -> f32 {
-    let mut points = map.clone_to_vec();
-    let mut point = points.remove(0);
-    let mut total: f32 = 0.0;
-    while points.len() > 0 {
-        let (index, nearest) = points.iter().enumerate().min_by(|(_, a), (_, b)| {
-            let da = a.dist_sq(&point);
-            let db = b.dist_sq(&point);
-            da.partial_cmp(&db).unwrap_or(cmp::Ordering::Equal)
-        }).unwrap();
-        total += nearest.dist(&point);
-        point = points.remove(index);
+// Sets of visited points are `u32` masks
+const HELD_KARP_MAX_POINTS: usize = 32;
+
+/// The memory in bytes that [`held_karp`] needs for its tables over the given number of points, or `None` if it
+/// cannot handle that many points at all.
+pub fn held_karp_memory(points: usize) -> Option<u64> {
+    if points > HELD_KARP_MAX_POINTS {
+        return None;
     }
-    total
+
+    // Every layer keeps its parents, but only two layers of lengths are alive at once
+    let binomials = binomials(points);
+    let layer = |size: usize| binomials[points][size] * size as u64;
+    let parents: u64 = (1..=points).map(layer).sum();
+    let lengths = (1..=points).map(|size| (layer(size) + layer(size - 1)) * 4).max().unwrap_or(0);
+    Some(parents + lengths)
 }
 
-pub fn branch_and_bound<'a, C: Into<Counter<'a>>>(map: &Map, counter: C) -> f32 {
-    let mut counter = counter.into();
-    let data = PathData::new(map);
-    let mut min_dist = nearest_neighbor(map);
+/// Finds the shortest path with the Held-Karp dynamic program, which computes the shortest path through every set
+/// of points ending at each of them, in `O(2^n n^2)` time. The sets are processed a layer of equal size at a time,
+/// keeping the lengths of only the previous layer and a one byte parent for every entry, so the tables take about
+/// `n 2^(n-1)` bytes. Fails before allocating anything if that is more than `memory_limit`, or if there are more
+/// than 32 points. Only the length is minimized, so maps with a [turn penalty](Map::with_turn_penalty), time
+/// windows, precedences, edge constraints or scenarios are refused.
+///
+/// Stopping early leaves no complete path, so the nearest neighbor tour is returned instead. The iteration count is
+/// the number of table entries filled.
+pub fn held_karp(map: &Map, options: &SolveOptions, memory_limit: u64) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_no_turns(map, "Held-Karp")?;
+    let constrained = map.time_windows().is_some()
+        || map.precedences().is_some()
+        || map.edge_constraints().is_some()
+        || map.scenarios().is_some();
+    if constrained {
+        return Err(Error::InvalidProblem(
+            "Held-Karp cannot solve maps with time windows, precedences, edge constraints or scenarios".to_string()
+        ));
+    }
 
-    let mut iter = data.iter();
-    while let Some(point) = iter.next() {
-        branch_and_bound_internal(data.iter(), &point, 0f32, &mut min_dist, &mut counter);
+    let n = map.size();
+    let required = held_karp_memory(n).ok_or_else(|| {
+        Error::TooLarge(format!("Held-Karp handles at most {} points, not {}", HELD_KARP_MAX_POINTS, n))
+    })?;
+    if required > memory_limit {
+        return Err(Error::TooLarge(format!(
+            "Held-Karp needs {} MiB for {} points, over the limit of {} MiB",
+            required.div_ceil(1 << 20),
+            n,
+            memory_limit >> 20
+        )));
+    }
 
-        // Explicit for clarity
-        drop(point);
-    } 
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("held_karp", size = n, memory = required).entered();
+
+    let matrix = match SolverContext::of(options, map) {
+        Some(context) => context.distances().clone(),
+        None => DistanceMatrix::new(map)
+    };
+    let binomials = binomials(n);
+    let mut budget = Budget::new(options, start, 256);
+    let reporter = Reporter::new(options, start);
+    let mut iterations = 0u64;
+
+    // The sets of each size are numbered in colexicographic order, in which Gosper's hack enumerates them, and an
+    // entry for every point of a set follows in increasing order. The single point paths all have length zero
+    let mut previous = vec![0f32; n];
+    let mut parents: Vec<Vec<u8>> = vec![Vec::new()];
+    for size in 2..=n {
+        let count = binomials[n][size] as usize;
+        let mut lengths = vec![f32::MAX; count * size];
+        let mut parent = vec![0u8; count * size];
+        let mut set = u32::MAX >> (32 - size);
+        for rank in 0..count {
+            if budget.exhausted() {
+                let mut result = nearest_neighbor(map)?;
+                result.iterations = iterations;
+                result.elapsed = start.elapsed();
+                result.termination = budget.termination();
+                return Ok(result);
+            }
 
-    min_dist
+            for (entry, last) in points_of(set).enumerate() {
+                let rest = set & !(1 << last);
+                let base = colex_rank(rest, &binomials) * (size - 1);
+                for (index, before) in points_of(rest).enumerate() {
+                    let length = previous[base + index] + matrix.get(before, last);
+                    if length < lengths[rank * size + entry] {
+                        lengths[rank * size + entry] = length;
+                        parent[rank * size + entry] = before as u8;
+                    }
+                }
+            }
+            iterations += size as u64;
+
+            // Gosper's hack: the next larger set of the same size
+            if rank + 1 < count {
+                let lowest = set & set.wrapping_neg();
+                let ripple = set + lowest;
+                set = (((ripple ^ set) >> 2) / lowest) | ripple;
+            }
+        }
+
+        previous = lengths;
+        parents.push(parent);
+        reporter.completion((1u64 << size) as f32 / (1u64 << n) as f32);
+    }
+
+    // Walk the parents back from the best end of the path through every point
+    let mut set = u32::MAX >> (32 - n);
+    let mut last = (0..n)
+        .min_by(|&a, &b| previous[a].total_cmp(&previous[b]))
+        .unwrap();
+    let mut tour = vec![last];
+    for size in (2..=n).rev() {
+        let entry = (set & ((1 << last) - 1)).count_ones() as usize;
+        let before = parents[size - 1][colex_rank(set, &binomials) * size + entry] as usize;
+        set &= !(1 << last);
+        last = before;
+        tour.push(last);
+    }
+    tour.reverse();
+
+    let mut result = SolveResult::new(map, tour, iterations, start);
+    result.lower_bound = Some(result.length);
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(length = result.length, iterations, "finished");
+
+    Ok(result)
 }
 
+// `binomials[n][k]` is n choose k, for every n and k up to `points`
+fn binomials(points: usize) -> Vec<Vec<u64>> {
+    let mut binomials = vec![vec![0u64; points + 1]; points + 1];
+    for n in 0..=points {
+        binomials[n][0] = 1;
+        for k in 1..=n {
+            binomials[n][k] = binomials[n - 1][k - 1] + binomials[n - 1][k];
+        }
+    }
+    binomials
+}
 
-fn branch_and_bound_internal(
-    mut points: PathDataIter<'_>,
-    last: &Point,
-    accumulated: f32,
-    min_dist: &mut f32,
-    counter: &mut Counter<'_>
-) {
-    let mut count = 0;
-    while let Some(point) = points.next() {
-        count += 1;
-        counter.increment();
-        
-        let new_accumulated = accumulated + point.dist(last);
-        if points.lower_bound(new_accumulated) < *min_dist {
-            branch_and_bound_internal(points.clone_reset(), &point, new_accumulated, min_dist, counter);
+// The position of the set among the sets of its size in colexicographic order, which is the sum of `c choose i`
+// over its points `c`, counting `i` from one in increasing order
+fn colex_rank(set: u32, binomials: &[Vec<u64>]) -> usize {
+    points_of(set).enumerate().map(|(i, point)| binomials[point][i + 1] as usize).sum()
+}
+
+// The points of the set in increasing order
+fn points_of(mut set: u32) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if set == 0 {
+            None
+        } else {
+            let point = set.trailing_zeros() as usize;
+            set &= set - 1;
+            Some(point)
+        }
+    })
+}
+
+/// Builds a path greedily by always moving to the nearest unvisited point, starting from the first point.
+pub fn nearest_neighbor(map: &Map) -> Result<SolveResult> {
+    nearest_neighbor_steps(map, None)
+}
+
+// Nearest neighbor, recording every choice among the unvisited points in the steps if there are any
+pub(crate) fn nearest_neighbor_steps(map: &Map, mut steps: Option<&mut Vec<Step>>) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    let mut points: Vec<usize> = (1..map.size()).collect();
+    let mut last = 0;
+    let mut tour = Vec::with_capacity(map.size());
+    tour.push(last);
+    while !points.is_empty() {
+        let (index, _) = points
+            .iter()
+            .enumerate()
+            .min_by(|(_, &a), (_, &b)| map.dist(last, a).total_cmp(&map.dist(last, b)))
+            .unwrap();
+        let chosen = points.remove(index);
+        if let Some(steps) = steps.as_deref_mut() {
+            let mut alternatives: Vec<(usize, f32)> =
+                points.iter().map(|&point| (point, map.dist(last, point))).collect();
+            alternatives.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+            steps.push(Step::Choose {
+                from: last,
+                chosen,
+                distance: map.dist(last, chosen),
+                alternatives
+            });
+        }
+        last = chosen;
+        tour.push(last);
+    }
+    Ok(SolveResult::new(map, tour, map.size() as u64, start))
+}
+
+/// Finds the shortest path with a depth-first branch-and-bound search, pruning every path whose lower bound is no
+/// shorter than the best path found so far, such as that of [`NearestNeighborBound`]. With
+/// [`parallelism`](SolveOptions::parallelism), the search is split between threads by the first point of the path, and
+/// every thread prunes with the shortest length any of them has found. Paths are never extended by a point whose
+/// [predecessors](Map::with_precedences) are not all on them already, nor by an edge the map's [edge
+/// constraints](Map::with_edge_constraints) rule out: a forbidden one, or any other than a fixed edge leaving the last
+/// point. If no path satisfies the constraints, the search fails once it has tried them all. Maps with a [turn
+/// penalty](Map::with_turn_penalty) are refused, since only the length is minimized. With a
+/// [beam width](SolveOptions::beam_width), the search keeps only the most promising paths at every depth instead.
+pub fn branch_and_bound<B: Bound>(map: &Map, options: &SolveOptions, bound: &B) -> Result<SolveResult> {
+    let threads = options.parallelism.threads().min(map.size());
+    if let Some(width) = options.beam_width {
+        beam_search(map, options, bound, width, None)
+    } else if threads > 1 {
+        parallel_branch_and_bound(map, options, bound, threads, None)
+    } else {
+        search(map, options, bound, &mut None, None, None, None)
+    }
+}
+
+/// Like [`branch_and_bound`], but starts from the given tour, such as one Lin-Kernighan found, instead of the
+/// nearest neighbor tour, which prunes far more of the tree from the start the shorter the tour is. The tour is
+/// repaired to respect the map's constraints first, and is returned if the search finds nothing shorter. Whether
+/// the search proved its tour optimal before it stopped is [`SolveResult::is_optimal`]. Fails if the tour is not a
+/// permutation of the map's points.
+pub fn branch_and_bound_from<B: Bound>(
+    map: &Map,
+    options: &SolveOptions,
+    bound: &B,
+    tour: &[usize]
+) -> Result<SolveResult> {
+    check_permutation(map, tour)?;
+    let threads = options.parallelism.threads().min(map.size());
+    if let Some(width) = options.beam_width {
+        beam_search(map, options, bound, width, Some(tour))
+    } else if threads > 1 {
+        parallel_branch_and_bound(map, options, bound, threads, Some(tour))
+    } else {
+        search(map, options, bound, &mut None, None, None, Some(tour))
+    }
+}
+
+/// Runs [`branch_and_bound`] with the [`NearestNeighborBound`], continuing from the checkpoint if there is one. If the
+/// search stops before it completes, the checkpoint is replaced with one recording where it stopped, otherwise it is
+/// cleared. A checkpoint describes where a single search stopped, so this always runs on the calling thread.
+pub fn resume_branch_and_bound(
+    map: &Map,
+    options: &SolveOptions,
+    checkpoint: &mut Option<BranchCheckpoint>
+) -> Result<SolveResult> {
+    search(map, options, &NearestNeighborBound, checkpoint, None, None, None)
+}
+
+/// Runs [`branch_and_bound`] with the [`NearestNeighborBound`] on a single thread, which is the bound
+/// [`Certificate::verify`] recomputes, logging every path it prunes with the bound that cut it off
+/// and the length of the best tour at the time, in a [`Certificate`] that [`Certificate::verify`] checks
+/// independently of the search. Certificates of large instances hold millions of prunes. If the search stops
+/// before it completes, the certificate claims the best tour it found, and verifying it walks the unexplored
+/// rest of the tree in full.
+pub fn certify_branch_and_bound(map: &Map, options: &SolveOptions) -> Result<(SolveResult, Certificate)> {
+    let mut prunes = Vec::new();
+    let result = search(map, options, &NearestNeighborBound, &mut None, None, Some(&mut prunes), None)?;
+    let certificate = Certificate {
+        tour: result.tour.clone(),
+        length: path_length(map, &result.tour) as f32,
+        prunes
+    };
+    Ok((result, certificate))
+}
+
+// Searches with branch-and-bound on a single thread, recording the nodes it reaches in the tree if there is one,
+// and the paths it prunes in the log if there is one. The search starts from the initial tour if there is one and
+// no checkpoint, and otherwise from the nearest neighbor tour
+fn search<B: Bound>(
+    map: &Map,
+    options: &SolveOptions,
+    bound: &B,
+    checkpoint: &mut Option<BranchCheckpoint>,
+    tree: Option<&mut SearchTree>,
+    log: Option<&mut Vec<Prune>>,
+    initial: Option<&[usize]>
+) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_no_turns(map, "branch-and-bound")?;
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("branch_and_bound", size = map.size()).entered();
+
+    let data = PathData::new(map, bound);
+    let (solution, resume, iterations) = match checkpoint.take() {
+        Some(checkpoint) => {
+            checkpoint.validate(map)?;
+            (checkpoint.tour, checkpoint.frontier, checkpoint.iterations)
+        },
+        None => (start_tour(map, initial_tour(map, initial)?), Vec::new(), 0)
+    };
+    let shared = SharedSearch::new(start_length(map, &solution));
+    let mut state = BranchState::new(map, options, start, &shared, Counter::Local(iterations), solution, iterations);
+    state.tree = tree;
+    state.log = log;
+    if let Some(tree) = &mut state.tree {
+        tree.nodes.push(Node {
+            parent: None,
+            point: None,
+            length: 0.0,
+            bound: data.lower_bound(0.0),
+            outcome: Outcome::Expanded
+        });
+    }
+    let constructed = start.elapsed();
+    state.reporter.time(instrument::CONSTRUCTION, constructed);
+    state.resume = resume;
+    state.reporter.improved(state.min_dist);
+
+    let weight = 1.0 / map.size() as f64;
+    for point in data.iter() {
+        let skipped = state.skip(point.index());
+        if skipped || !data.ready(point.index()) {
+            if !skipped {
+                state.trace(point.index(), 0.0, data.lower_bound(0.0), Outcome::Precedence);
+            }
+            if state.progress_depth == 1 {
+                state.cover(weight);
+            }
+            continue;
+        }
+
+        let node = state.trace(point.index(), 0.0, data.lower_bound(0.0), Outcome::Expanded);
+        state.path.push(point.index());
+        state.node = node;
+        branch_and_bound_internal(&data, &point, 0f32, weight, &mut state);
+        state.node = 0;
+        state.path.pop();
+
+        if state.progress_depth == 1 {
+            state.cover(weight);
         }
 
         // Explicit for clarity
         drop(point);
+    } 
+
+    state.reporter.count(instrument::NODES, state.iterations - iterations);
+    state.reporter.count(instrument::PRUNES, state.prunes);
+    state.reporter.time(instrument::SEARCH, start.elapsed() - constructed);
+    if state.budget.termination() == Termination::Completed {
+        check_found(map, &state.solution)?;
+    }
+    let mut result = SolveResult::new(map, state.solution.clone(), state.iterations, start);
+    result.termination = state.budget.termination();
+    if result.termination == Termination::Completed {
+        result.lower_bound = Some(result.length);
+    } else if let Some(frontier) = state.frontier {
+        *checkpoint = Some(BranchCheckpoint {
+            tour: state.solution,
+            frontier,
+            iterations: state.iterations
+        });
     }
 
-    if count == 0 && accumulated < *min_dist {
-        *min_dist = accumulated;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        length = result.length,
+        iterations = result.iterations,
+        termination = ?result.termination,
+        "finished"
+    );
+
+    Ok(result)
+}
+
+// The search trees of larger instances are too big to be of use
+const TRACE_MAX_POINTS: usize = 10;
+
+/// Runs [`branch_and_bound`] with the [`NearestNeighborBound`] on a single thread, recording every node of the search
+/// tree it explores: each path it extended, pruned or ruled out, with its length and lower bound, which
+/// [`SearchTree::write_dot`] draws with Graphviz. Fails on instances of more than 10 points, whose trees are too large
+/// to draw.
+pub fn trace_branch_and_bound(map: &Map, options: &SolveOptions) -> Result<(SolveResult, SearchTree)> {
+    if map.size() > TRACE_MAX_POINTS {
+        return Err(Error::TooLarge(format!(
+            "search trees are traced for at most {} points, not {}",
+            TRACE_MAX_POINTS,
+            map.size()
+        )));
     }
+
+    let mut tree = SearchTree::default();
+    let result = search(map, options, &NearestNeighborBound, &mut None, Some(&mut tree), None, None)?;
+    Ok((result, tree))
 }
 
-struct PathData {
-    points: Box<[(Point, f32)]>,
-    visited: Box<[Cell<bool>]>
+// Each thread takes the next unclaimed first point and searches every path starting from it
+fn parallel_branch_and_bound<B: Bound>(
+    map: &Map,
+    options: &SolveOptions,
+    bound: &B,
+    threads: usize,
+    initial: Option<&[usize]>
+) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    check_no_turns(map, "branch-and-bound")?;
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("branch_and_bound", size = map.size(), threads).entered();
+
+    let initial = start_tour(map, initial_tour(map, initial)?);
+    let shared = SharedSearch::new(start_length(map, &initial));
+    let nodes = AtomicUsize::new(0);
+    let mut reporter = Reporter::new(options, start);
+    reporter.improved(start_length(map, &initial));
+    let constructed = start.elapsed();
+    reporter.time(instrument::CONSTRUCTION, constructed);
+    let next = AtomicUsize::new(0);
+
+    let outcomes: Vec<(Vec<usize>, f32, u64, Termination)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| {
+                let data = PathData::new(map, bound);
+                let counter = Counter::Shared(&nodes);
+                let mut state = BranchState::new(map, options, start, &shared, counter, initial.clone(), 0);
+                let weight = 1.0 / map.size() as f64;
+                while state.budget.termination() == Termination::Completed {
+                    let mut index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= map.size() {
+                        break;
+                    }
+                    // Infallible: nothing else is visited between subtrees, so the point is free
+                    let point = data.visit_next(&mut index).unwrap();
+                    if !data.ready(point.index()) {
+                        if state.progress_depth == 1 {
+                            state.cover(weight);
+                        }
+                        continue;
+                    }
+
+                    state.path.push(point.index());
+                    branch_and_bound_internal(&data, &point, 0f32, weight, &mut state);
+                    state.path.pop();
+
+                    if state.progress_depth == 1 {
+                        state.cover(weight);
+                    }
+                }
+
+                state.reporter.count(instrument::NODES, state.iterations);
+                state.reporter.count(instrument::PRUNES, state.prunes);
+                (state.solution, state.min_dist, state.iterations, state.budget.termination())
+            }))
+            .collect();
+
+        // Infallible: the search does not panic
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+
+    let iterations = outcomes.iter().map(|&(_, _, iterations, _)| iterations).sum();
+    reporter.time(instrument::SEARCH, start.elapsed() - constructed);
+    let termination = outcomes.iter()
+        .map(|&(_, _, _, termination)| termination)
+        .find(|&termination| termination != Termination::Completed)
+        .unwrap_or(Termination::Completed);
+    // Infallible: there is at least one thread
+    let (solution, ..) = outcomes.into_iter()
+        .min_by(|(_, a, ..), (_, b, ..)| a.total_cmp(b))
+        .unwrap();
+    if termination == Termination::Completed {
+        check_found(map, &solution)?;
+    }
+
+    let mut result = SolveResult::new(map, solution, iterations, start);
+    result.termination = termination;
+    if result.termination == Termination::Completed {
+        result.lower_bound = Some(result.length);
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        length = result.length,
+        iterations = result.iterations,
+        termination = ?result.termination,
+        "finished"
+    );
+
+    Ok(result)
 }
 
-impl PathData {
-    fn new(map: &Map) -> Self {
-        // Get a list of the points, each with its distance to its nearest neighbor
-        let mut points = vec![(Point::new(), 0f32); map.size()].into_boxed_slice();
-        for i in 0..map.size() {
-            let point = map.get(i).unwrap().clone();
-            let mut min = f32::MAX;
-            for j in 0..map.size() {
-                let dist = map.get(j).map(|p| p.dist(&point)).unwrap_or(f32::MAX);
-                if i != j && dist < min {
-                    min = dist;
+// Extends the paths of the beam a point at a time, keeping the `width` of lowest bound at every depth, and returns
+// the shortest complete path, unless the initial tour is shorter
+fn beam_search<B: Bound>(
+    map: &Map,
+    options: &SolveOptions,
+    bound: &B,
+    width: usize,
+    initial: Option<&[usize]>
+) -> Result<SolveResult> {
+    let start = Instant::now();
+    check_map(map)?;
+    if width == 0 {
+        return Err(Error::InvalidSettings("a beam search must keep at least one path".to_string()));
+    }
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("beam_search", size = map.size(), width).entered();
+
+    let data = PathData::new(map, bound);
+    let mut solution = start_tour(map, initial_tour(map, initial)?);
+    let mut best = start_length(map, &solution);
+    let mut budget = Budget::new(options, start, 1);
+    let mut reporter = Reporter::new(options, start);
+    reporter.improved(best);
+    let constructed = start.elapsed();
+    reporter.time(instrument::CONSTRUCTION, constructed);
+
+    // Every path of the beam with its length and a hash of the points on it, and the children of the beam as the
+    // bound, length, parent and point of each, so that only the paths kept are ever copied
+    let mut beam: Vec<(Vec<usize>, f32, u64)> = vec![(Vec::new(), 0.0, 0)];
+    let mut children: Vec<(f32, f32, usize, usize)> = Vec::new();
+    let mut kept = HashSet::new();
+    let (mut iterations, mut prunes) = (0, 0);
+    for depth in 0..map.size() {
+        if budget.exhausted() {
+            break;
+        }
+
+        children.clear();
+        for (parent, (path, length, _)) in beam.iter().enumerate() {
+            // Infallible: a path never visits a point twice. The guards are dropped in reverse, as they must be
+            let mut visits: Vec<_> = path.iter().map(|&point| data.search.visit(point).unwrap()).collect();
+            for point in data.iter() {
+                let last = path.last().copied();
+                if !data.ready(point.index()) || last.is_some_and(|last| !data.joins(last, point.index())) {
+                    continue;
+                }
+
+                iterations += 1;
+                let length = last.map_or(0.0, |last| length + data.dist_between(last, point.index()));
+                let bound = data.lower_bound(length);
+                if bound < best {
+                    children.push((bound, length, parent, point.index()));
+                } else {
+                    prunes += 1;
                 }
             }
-            points[i] = (point, min);
+            while visits.pop().is_some() {}
         }
 
-        PathData {
-            points,
-            visited: vec![Cell::new(false); map.size()].into_boxed_slice()
+        // Of the paths through the same points to the same last point, only the one of lowest bound, which is the
+        // shortest, is worth keeping
+        children.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        kept.clear();
+        let next: Vec<_> = children.iter()
+            .filter_map(|&(_, length, parent, point)| {
+                let points = beam[parent].2 ^ point_hash(point);
+                kept.insert((points, point)).then_some((parent, point, length, points))
+            })
+            .take(width)
+            .collect();
+        prunes += (children.len() - next.len()) as u64;
+        beam = next.into_iter()
+            .map(|(parent, point, length, points)| {
+                let mut path = Vec::with_capacity(depth + 1);
+                path.extend_from_slice(&beam[parent].0);
+                path.push(point);
+                (path, length, points)
+            })
+            .collect();
+        reporter.completion((depth + 1) as f32 / map.size() as f32);
+        reporter.nodes(iterations);
+        if beam.is_empty() {
+            break;
         }
     }
 
-    const fn iter(&self) -> PathDataIter<'_> {
-        PathDataIter::new(self)
+    let complete = beam.into_iter()
+        .filter(|(path, ..)| path.len() == map.size())
+        .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+    if let Some((path, length, _)) = complete.filter(|&(_, length, _)| length < best) {
+        best = length;
+        solution = path;
+        reporter.improved(best);
     }
 
-    #[inline]
-    fn lower_bound(&self, accumulated: f32) -> f32 {
-        // The lower bound is calculated by summing the remaining nearest-neighbor distances (excluding one)
-        // and adding that to the current accumulated distance.
+    reporter.count(instrument::NODES, iterations);
+    reporter.count(instrument::PRUNES, prunes);
+    reporter.time(instrument::SEARCH, start.elapsed() - constructed);
+    if !feasible(map, &solution) {
+        return Err(Error::InvalidProblem(
+            "the beam search found no tour that satisfies the constraints of the map".to_string()
+        ));
+    }
+    let mut result = SolveResult::new(map, solution, iterations, start);
+    result.termination = budget.termination();
 
-        accumulated + self.visited.iter()
-            .enumerate()
-            .filter(|(_, flag)| !flag.get())
-            .skip(1)
-            // Infallible: visited.len() == points.len()
-            .map(|(index, _)| unsafe { self.points.get_unchecked(index).1 })
-            .sum::<f32>()
+    #[cfg(feature = "tracing")]
+    tracing::debug!(length = result.length, iterations = result.iterations, width, "finished");
+
+    Ok(result)
+}
+
+// A hash of the point, whose exclusive or over the points of a path tells the sets of points of paths apart with
+// all but certainty, by the finalizer of SplitMix64
+fn point_hash(point: usize) -> u64 {
+    let mut hash = (point as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+// Completion is measured by the fraction of subtrees at this depth which have been fully explored or pruned
+const PROGRESS_DEPTH: usize = 3;
+
+// What the threads of a search share, so that each prunes with the best length found by any of them and
+// completion is reported for the search as a whole
+struct SharedSearch {
+    best: SharedBest,
+    covered: Mutex<f64>
+}
+
+impl SharedSearch {
+    fn new(best: f32) -> Self {
+        SharedSearch {
+            best: SharedBest::with_tour(best, Vec::new()),
+            covered: Mutex::new(0.0)
+        }
     }
 
-    #[inline]
-    fn visit_next(&self, index: &mut usize) -> Option<VisitedPoint<'_>> {
-        while self.visited.get(*index).map(Cell::get).unwrap_or(false) {
-            *index += 1;
+    fn best(&self) -> f32 {
+        self.best.loss()
+    }
+}
+
+// Mutable state of one thread of the search
+struct BranchState<'o> {
+    // The length of this thread's solution
+    min_dist: f32,
+    path: Vec<usize>,
+    solution: Vec<usize>,
+    iterations: u64,
+    // The nodes expanded by the whole search, and the iterations of this thread already added to them
+    nodes: Counter<'o>,
+    reported: u64,
+    // Children cut off by the lower bound
+    prunes: u64,
+    budget: Budget,
+    reporter: Reporter<'o>,
+    shared: &'o SharedSearch,
+    size: usize,
+    progress_depth: usize,
+    // The frontier of the checkpoint being resumed from, cleared once the search passes it
+    resume: Vec<usize>,
+    // Where the search stopped, if the budget ran out
+    frontier: Option<Vec<usize>>,
+    // The tree being recorded, if the search is traced, and the index in it of the node of the current path
+    tree: Option<&'o mut SearchTree>,
+    node: usize,
+    // The pruning decisions made so far, if the search is certified
+    log: Option<&'o mut Vec<Prune>>
+}
+
+impl<'o> BranchState<'o> {
+    fn new(
+        map: &Map,
+        options: &'o SolveOptions,
+        start: Instant,
+        shared: &'o SharedSearch,
+        nodes: Counter<'o>,
+        solution: Vec<usize>,
+        iterations: u64
+    ) -> Self {
+        BranchState {
+            min_dist: start_length(map, &solution),
+            path: Vec::with_capacity(map.size()),
+            solution,
+            iterations,
+            nodes,
+            reported: iterations,
+            prunes: 0,
+            budget: Budget::new(options, start, 1024),
+            reporter: Reporter::new(options, start),
+            shared,
+            size: map.size(),
+            progress_depth: PROGRESS_DEPTH.min(map.size()),
+            resume: Vec::new(),
+            frontier: None,
+            tree: None,
+            node: 0,
+            log: None
         }
+    }
 
-        if *index == self.points.len() {
-            return None;
+    // Records a child of the current path's node in the tree, if the search is traced, returning its index
+    fn trace(&mut self, point: usize, length: f32, bound: f32, outcome: Outcome) -> usize {
+        match &mut self.tree {
+            Some(tree) => {
+                tree.nodes.push(Node {
+                    parent: Some(self.node),
+                    point: Some(point),
+                    length,
+                    bound,
+                    outcome
+                });
+                tree.nodes.len() - 1
+            },
+            None => 0
         }
+    }
 
-        // Index checked beforehand
-        unsafe  {
-            self.visited.get_unchecked(*index).set(true);
+    fn cover(&mut self, weight: f64) {
+        // Subtrees cut short by the budget were not covered
+        if self.budget.termination() != Termination::Completed {
+            return;
+        }
 
-            let ret = Some(VisitedPoint{
-                value: self.points.get_unchecked(*index).0.clone(),
-                source: self,
-                index: *index
-            });
+        let nodes = self.nodes.add(self.iterations - self.reported);
+        self.reported = self.iterations;
+        let mut covered = self.shared.covered.lock().unwrap();
+        *covered += weight;
+        self.reporter.nodes(nodes);
+        self.reporter.completion(*covered as f32);
+    }
 
-            *index += 1;
-            ret
+    // Whether the child of the current path was already explored before the checkpoint being resumed from
+    fn skip(&mut self, index: usize) -> bool {
+        match self.resume.get(self.path.len()) {
+            Some(&next) if index < next => true,
+            Some(&next) if index > next => {
+                self.resume.clear();
+                false
+            },
+            _ => false
         }
     }
 }
 
-struct PathDataIter<'a> {
-    path_data: &'a PathData,
-    index: usize
-}
+// `weight` is the fraction of the whole search tree lying under the current partial path
+fn branch_and_bound_internal<B: Bound + ?Sized>(
+    data: &PathData<'_, B>,
+    last: &Visit<'_, f32>,
+    accumulated: f32,
+    weight: f64,
+    state: &mut BranchState<'_>
+) {
+    let depth = state.path.len() + 1;
+    let child_weight = weight / (state.size + 1 - depth).max(1) as f64;
+    let mut count = 0;
+    for point in data.iter() {
+        // Returning before the leaf check keeps a partial path from being recorded as a solution
+        if state.budget.exhausted() {
+            // The innermost call stops first, so keep its path rather than those of the calls unwinding
+            if state.frontier.is_none() {
+                let mut frontier = state.path.clone();
+                frontier.push(point.index());
+                state.frontier = Some(frontier);
+                if state.tree.is_some() {
+                    let length = accumulated + data.dist(last, &point);
+                    state.trace(point.index(), length, data.lower_bound(length), Outcome::Stopped);
+                }
+            }
+            return;
+        }
+
+        count += 1;
+        let skipped = state.skip(point.index());
+        let ready = data.ready(point.index());
+        let joins = state.path.last().is_none_or(|&last| data.joins(last, point.index()));
+        if skipped || !ready || !joins {
+            if state.tree.is_some() && !skipped {
+                let length = accumulated + data.dist(last, &point);
+                let outcome = if ready { Outcome::Edge } else { Outcome::Precedence };
+                state.trace(point.index(), length, data.lower_bound(length), outcome);
+            }
+            if depth <= state.progress_depth {
+                state.cover(child_weight);
+            }
+            continue;
+        }
+
+        state.iterations += 1;
+        
+        let new_accumulated = accumulated + data.dist(last, &point);
+        let bound = data.lower_bound(new_accumulated);
+        if bound < state.shared.best() {
+            let parent = state.node;
+            state.node = state.trace(point.index(), new_accumulated, bound, Outcome::Expanded);
+            state.path.push(point.index());
+            branch_and_bound_internal(data, &point, new_accumulated, child_weight, state);
+            state.path.pop();
+            state.node = parent;
 
-impl<'a> PathDataIter<'a> {
-    const fn new(path_data: &'a PathData) -> Self {
-        PathDataIter {
-            path_data,
-            index: 0
+            if depth == state.progress_depth {
+                state.cover(child_weight);
+            }
+        } else {
+            let best = state.shared.best();
+            state.trace(point.index(), new_accumulated, bound, Outcome::Pruned { best });
+            state.prunes += 1;
+            if let Some(log) = &mut state.log {
+                let mut path = state.path.clone();
+                path.push(point.index());
+                log.push(Prune {
+                    path,
+                    bound,
+                    incumbent: best
+                });
+            }
+            if depth <= state.progress_depth {
+                state.cover(child_weight);
+            }
         }
+
+        // Explicit for clarity
+        drop(point);
     }
 
-    #[inline]
-    fn lower_bound(&self, accumulated: f32) -> f32 {
-        self.path_data.lower_bound(accumulated)
+    if count == 0 {
+        let improved = accumulated < state.min_dist;
+        let node = state.node;
+        if let Some(tree) = &mut state.tree {
+            tree.nodes[node].outcome = Outcome::Leaf { improved };
+        }
     }
+    if count == 0 && accumulated < state.min_dist {
+        state.min_dist = accumulated;
+        state.solution.clone_from(&state.path);
+        if state.shared.best.offer(accumulated, &state.path) {
+            state.reporter.improved(accumulated);
+        }
 
-    const fn clone_reset(&self) -> Self {
-        Self::new(self.path_data)
+        #[cfg(feature = "tracing")]
+        tracing::debug!(length = accumulated, iterations = state.iterations, "improved");
     }
 }
 
-impl<'a> Iterator for PathDataIter<'a> {
-    type Item = VisitedPoint<'a>;
+// A tour to start from which respects the map's precedences, if it has any, and uses its fixed edges, unless
+// the precedences break them up again
+fn start_tour(map: &Map, tour: Vec<usize>) -> Vec<usize> {
+    let tour = match map.edge_constraints() {
+        Some(edges) => edges.repair(&tour),
+        None => tour
+    };
+    match map.precedences() {
+        Some(precedences) => precedences.repair(&tour),
+        None => tour
+    }
+}
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        self.path_data.visit_next(&mut self.index)
+// The tour a branch-and-bound starts from: the given one, or else the nearest neighbor tour
+fn initial_tour(map: &Map, tour: Option<&[usize]>) -> Result<Vec<usize>> {
+    match tour {
+        Some(tour) => Ok(tour.to_vec()),
+        None => Ok(nearest_neighbor(map)?.tour)
     }
 }
 
-struct VisitedPoint<'a> {
-    value: Point,
-    source: &'a PathData,
-    index: usize
+// Whether the tour respects the map's precedences and edge constraints
+fn feasible(map: &Map, tour: &[usize]) -> bool {
+    map.precedences().is_none_or(|precedences| precedences.check(tour).is_ok())
+        && map.edge_constraints().is_none_or(|edges| edges.check(tour).is_ok())
 }
 
-impl<'a> Deref for VisitedPoint<'a> {
-    type Target = Point;
+// The length to prune with before a search finds a tour of its own, which only a feasible start tour gives
+fn start_length(map: &Map, tour: &[usize]) -> f32 {
+    if feasible(map, tour) { tour.evaluate(map) } else { f32::INFINITY }
+}
 
-    #[inline]
-    fn deref(&self) -> &Point {
-        &self.value
+// A search that ran to completion without beating an infeasible start tour has shown that no tour is feasible
+fn check_found(map: &Map, tour: &[usize]) -> Result<()> {
+    if feasible(map, tour) {
+        Ok(())
+    } else {
+        Err(Error::InvalidProblem("no tour satisfies the constraints of the map".to_string()))
     }
 }
 
-impl<'a> Drop for VisitedPoint<'a> {
+struct PathData<'b, B: ?Sized> {
+    points: Box<[Point]>,
+    // The distance from every point to its nearest neighbor, for the bound
+    search: SearchState<f32>,
+    bound: &'b B,
+    // The map's own distances, if it has them, used instead of the distances between the points
+    distances: Option<DistanceMatrix>,
+    // The points that must be on the path before each point can be
+    predecessors: Box<[Vec<usize>]>,
+    edges: Option<EdgeConstraints>
+}
+
+impl<'b, B: Bound + ?Sized> PathData<'b, B> {
+    fn new(map: &Map, bound: &'b B) -> Self {
+        // Get the distance from every point to its nearest neighbor
+        let nearest = (0..map.size())
+            .map(|i| {
+                let mut min = f32::MAX;
+                for j in 0..map.size() {
+                    let dist = map.dist(i, j);
+                    if i != j && dist < min {
+                        min = dist;
+                    }
+                }
+                min
+            })
+            .collect();
+
+        let predecessors = (0..map.size())
+            .map(|point| match map.precedences() {
+                Some(precedences) => precedences.predecessors(point).to_vec(),
+                None => Vec::new()
+            })
+            .collect();
+        PathData {
+            points: map.as_slice().into(),
+            search: SearchState::new(nearest),
+            bound,
+            distances: map.distances().cloned().or_else(|| map.has_costs().then(|| DistanceMatrix::new(map))),
+            predecessors,
+            edges: map.edge_constraints().cloned()
+        }
+    }
+
+    const fn iter(&self) -> Unvisited<'_, f32> {
+        self.search.unvisited()
+    }
+
     #[inline]
-    fn drop(&mut self) {
-        // Infallible: in order for this type to be constructed the index must be valid
-        unsafe {
-            self.source.visited.get_unchecked(self.index).set(false);
+    fn lower_bound(&self, accumulated: f32) -> f32 {
+        self.bound.bound(&self.search, accumulated)
+    }
+
+    #[inline]
+    fn dist(&self, a: &Visit<'_, f32>, b: &Visit<'_, f32>) -> f32 {
+        self.dist_between(a.index(), b.index())
+    }
+
+    #[inline]
+    fn dist_between(&self, a: usize, b: usize) -> f32 {
+        match &self.distances {
+            Some(distances) => distances.get(a, b),
+            None => self.points[a].dist(&self.points[b])
         }
     }
-}
 
-impl<'a> Debug for VisitedPoint<'a> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Debug::fmt(&self.value, f)
+    // Whether every point that must come before the given one is on the path
+    #[inline]
+    fn ready(&self, index: usize) -> bool {
+        self.predecessors[index].iter().all(|&before| self.search.is_visited(before))
+    }
+
+    // Whether the given point, just put on the path, may follow the last point before it: the edge between them
+    // must not be forbidden, the last point must have no fixed neighbor left to go to, and the new point no fixed
+    // neighbor already on the path other than the last point
+    #[inline]
+    fn joins(&self, last: usize, index: usize) -> bool {
+        let edges = match &self.edges {
+            Some(edges) => edges,
+            None => return true
+        };
+        !edges.is_forbidden(last, index)
+            && edges.fixed_neighbors(last).iter().all(|&other| self.search.is_visited(other))
+            && edges.fixed_neighbors(index).iter().all(|&other| other == last || !self.search.is_visited(other))
+    }
+
+    #[inline]
+    fn visit_next(&self, index: &mut usize) -> Option<Visit<'_, f32>> {
+        self.search.visit_next(index)
     }
 }
\ No newline at end of file
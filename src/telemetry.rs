@@ -0,0 +1,157 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path;
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+use crate::solve::{Progress, ProgressEvent};
+use crate::time::Instant;
+
+// One line of the output. Every line is tagged with its event and the seconds since the telemetry was created
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Record {
+    Completion {
+        seconds: f64,
+        fraction: f32
+    },
+    Nodes {
+        seconds: f64,
+        nodes: u64
+    },
+    Generation {
+        seconds: f64,
+        generation: u32,
+        generations: u32
+    },
+    Sample {
+        seconds: f64,
+        step: u64,
+        best: f32,
+        mean: f32
+    },
+    Diversity {
+        seconds: f64,
+        diversity: f32
+    },
+    Improvement {
+        seconds: f64,
+        length: f32
+    }
+}
+
+// The writer and the first error writing to it, after which nothing more is written
+struct Output<W> {
+    out: W,
+    error: Option<Error>
+}
+
+/// Streams the progress of a solve as JSON Lines when attached as its progress observer: one object per event,
+/// such as `{"event":"improvement","seconds":0.25,"length":7.5}`, flushed as soon as it is written so that a long
+/// run can be followed with `tail -f` and the file read by `jq` or any JSON Lines reader. Every object has an
+/// `event` naming the [`ProgressEvent`] in snake case, the `seconds` since the telemetry was created, and the
+/// fields of the event. Samples and diversity are only written by [`with_samples`](Telemetry::with_samples).
+///
+/// Errors writing the output cannot stop the solve, so the first one is kept and returned by
+/// [`finish`](Telemetry::finish), and nothing more is written after it.
+pub struct Telemetry<W> {
+    output: Mutex<Output<W>>,
+    start: Instant,
+    samples: bool
+}
+
+impl Telemetry<BufWriter<File>> {
+    /// Telemetry written to a new file, replacing any file already at `path`.
+    pub fn create<P: AsRef<path::Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Telemetry<W> {
+    /// Telemetry written to `out`, with the clock started now.
+    pub fn new(out: W) -> Self {
+        Telemetry {
+            output: Mutex::new(Output {
+                out,
+                error: None
+            }),
+            start: Instant::now(),
+            samples: false
+        }
+    }
+
+    /// Also writes the [`Sample`](ProgressEvent::Sample) and [`Diversity`](ProgressEvent::Diversity) events,
+    /// which makes the solvers compute them.
+    pub fn with_samples(self) -> Self {
+        Telemetry {
+            samples: true,
+            ..self
+        }
+    }
+
+    /// Returns the first error writing the output, if there was one, once the solve has finished. Every line has
+    /// already been flushed.
+    pub fn finish(&self) -> Result<()> {
+        match &self.output.lock().unwrap().error {
+            Some(error) => Err(error.clone()),
+            None => Ok(())
+        }
+    }
+
+    fn write(&self, record: &Record) {
+        let mut output = self.output.lock().unwrap();
+        if output.error.is_some() {
+            return;
+        }
+
+        let written = serde_json::to_writer(&mut output.out, record)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writeln!(output.out))
+            .and_then(|_| output.out.flush());
+        if let Err(error) = written {
+            output.error = Some(error.into());
+        }
+    }
+}
+
+impl<W: Write + Send> Progress for Telemetry<W> {
+    fn report(&self, event: ProgressEvent) {
+        let seconds = self.start.elapsed().as_secs_f64();
+        let record = match event {
+            ProgressEvent::Completion(fraction) => Record::Completion {
+                seconds,
+                fraction
+            },
+            ProgressEvent::Nodes(nodes) => Record::Nodes {
+                seconds,
+                nodes
+            },
+            ProgressEvent::Generation { generation, generations } => Record::Generation {
+                seconds,
+                generation,
+                generations
+            },
+            ProgressEvent::Sample { step, best, mean, .. } if self.samples => Record::Sample {
+                seconds,
+                step,
+                best,
+                mean
+            },
+            ProgressEvent::Diversity(diversity) if self.samples => Record::Diversity {
+                seconds,
+                diversity
+            },
+            ProgressEvent::Improvement { length, .. } => Record::Improvement {
+                seconds,
+                length
+            },
+            ProgressEvent::Sample { .. } | ProgressEvent::Diversity(_) => return
+        };
+        self.write(&record);
+    }
+
+    fn detailed(&self) -> bool {
+        self.samples
+    }
+}
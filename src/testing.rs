@@ -0,0 +1,156 @@
+use std::result;
+
+use crate::error::Result;
+use crate::ga::Chromosome;
+use crate::generate::PointDistribution;
+use crate::map::{Map, Path, check_permutation};
+use crate::solve::{SolveOptions, SolveResult, Solver, Termination};
+
+// Lengths are sums of many f32 distances, so they are compared relative to their size
+const TOLERANCE: f32 = 1e-4;
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Asserts that the tour visits every point of the map exactly once.
+pub fn assert_valid_tour(map: &Map, tour: &[usize]) {
+    if let Err(error) = check_permutation(map, tour) {
+        panic!("{}", error);
+    }
+}
+
+/// Asserts that the result's tour is valid and that its length is the length of the tour.
+pub fn assert_valid_result(map: &Map, result: &SolveResult) {
+    if let Err(message) = check_result(map, result) {
+        panic!("{}", message);
+    }
+}
+
+// The first violation of `assert_valid_result`, if any
+fn check_result(map: &Map, result: &SolveResult) -> result::Result<(), String> {
+    check_permutation(map, &result.tour).map_err(|error| error.to_string())?;
+    let length = result.tour.evaluate(map);
+    if !close(result.length, length) {
+        return Err(format!("the result's length is {} but its tour has length {}", result.length, length));
+    }
+    match result.lower_bound {
+        Some(bound) if !(bound <= result.length || close(bound, result.length)) => {
+            Err(format!("the lower bound {} is above the length {}", bound, result.length))
+        },
+        _ => Ok(())
+    }
+}
+
+/// Asserts that the chromosome validates against the map, decodes to a valid tour of the length it evaluates to,
+/// and decodes to the same tour after encoding that tour into a copy of itself.
+pub fn assert_consistent_chromosome<C: Chromosome>(map: &Map, indv: &C) {
+    if let Err(error) = indv.validate(map) {
+        panic!("the chromosome does not validate: {}", error);
+    }
+
+    let tour = indv.tour(map);
+    assert_valid_tour(map, &tour);
+    let (evaluated, decoded) = (indv.evaluate(map), tour.evaluate(map));
+    assert!(close(evaluated, decoded), "the chromosome evaluates to {} but its tour has length {}", evaluated, decoded);
+
+    let mut copy = indv.clone();
+    if let Err(error) = copy.encode(map, &tour) {
+        panic!("the chromosome cannot encode its own tour: {}", error);
+    }
+    assert_eq!(copy.tour(map), tour, "encoding the tour and decoding it again gives a different tour");
+}
+
+/// Asserts that `distance` behaves like the distances of a symmetric instance over `size` points: every distance is
+/// finite and non-negative, the distance from a point to itself is zero, and the distance between two points is
+/// the same in both directions.
+pub fn assert_symmetric_distances<F: Fn(usize, usize) -> f32>(size: usize, distance: F) {
+    for i in 0..size {
+        let own = distance(i, i);
+        assert!(own == 0.0, "the distance from {} to itself is {}", i, own);
+
+        for j in i + 1..size {
+            let (there, back) = (distance(i, j), distance(j, i));
+            assert!(there.is_finite() && there >= 0.0, "the distance from {} to {} is {}", i, j, there);
+            assert!(close(there, back), "the distance from {} to {} is {} but back is {}", i, j, there, back);
+        }
+    }
+}
+
+/// Asserts that `distance` gives the same distances as [`Map::dist`].
+pub fn assert_matches_map<F: Fn(usize, usize) -> f32>(map: &Map, distance: F) {
+    for i in 0..map.size() {
+        for j in 0..map.size() {
+            let (found, expected) = (distance(i, j), map.dist(i, j));
+            assert!(close(found, expected), "the distance from {} to {} is {} but should be {}", i, j, found, expected);
+        }
+    }
+}
+
+/// Asserts that a heuristic's tour is no shorter than an exact solver's, and that the exact length is no shorter
+/// than any lower bound either of them proved. Both results are checked with [`assert_valid_result`] first.
+pub fn assert_bounds_ordered(map: &Map, heuristic: &SolveResult, exact: &SolveResult) {
+    if let Err(message) = check_bounds(map, heuristic, exact) {
+        panic!("{}", message);
+    }
+}
+
+// The first violation of `assert_bounds_ordered`, if any
+fn check_bounds(map: &Map, heuristic: &SolveResult, exact: &SolveResult) -> result::Result<(), String> {
+    check_result(map, heuristic)?;
+    check_result(map, exact)?;
+
+    if !(heuristic.length >= exact.length || close(heuristic.length, exact.length)) {
+        return Err(format!("the heuristic length {} is below the exact length {}", heuristic.length, exact.length));
+    }
+    let mut bounds = heuristic.lower_bound.iter().chain(exact.lower_bound.iter());
+    match bounds.find(|&&bound| !(bound <= exact.length || close(bound, exact.length))) {
+        Some(bound) => Err(format!("the lower bound {} is above the exact length {}", bound, exact.length)),
+        None => Ok(())
+    }
+}
+
+/// A correctness sweep of heuristics against an exact solver: every instance, drawn from the distribution for
+/// each size, is solved by the exact solver and by every heuristic, and each heuristic's result is checked
+/// against the exact one as by [`assert_bounds_ordered`]. The sizes should be small enough for the exact solver to
+/// finish, and it must not be stopped by the options, since a search cut short proves nothing.
+///
+/// With a seed in the options, the instances are generated from it, so that a failing sweep can be repeated.
+pub struct CrossValidation<'a> {
+    pub distribution: &'a dyn PointDistribution,
+    pub sizes: &'a [usize],
+    /// The instances generated of every size
+    pub instances: usize,
+    pub exact: &'a dyn Solver,
+    pub heuristics: &'a [(String, Box<dyn Solver + Sync>)],
+    pub options: &'a SolveOptions
+}
+
+impl CrossValidation<'_> {
+    /// Runs the sweep, failing with the first error of a solver, and panicking at the first violation with the
+    /// solver, size and instance it was found on.
+    pub fn run(&self) -> Result<()> {
+        let mut rng = self.options.rng();
+        for &size in self.sizes {
+            for instance in 0..self.instances {
+                let map = Map::random(size, self.distribution, &mut rng);
+                let exact = self.exact.solve(&map, self.options)?;
+                assert!(
+                    exact.termination == Termination::Completed,
+                    "the exact solver stopped early ({:?}) on instance {} of {} points",
+                    exact.termination,
+                    instance,
+                    size
+                );
+
+                for (name, heuristic) in self.heuristics {
+                    let result = heuristic.solve(&map, self.options)?;
+                    if let Err(message) = check_bounds(&map, &result, &exact) {
+                        panic!("{} on instance {} of {} points: {}", name, instance, size, message);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use self::js::Instant;
+
+use std::result;
+use std::time::Duration;
+
+/// Parses durations like `30s`, `500ms`, `2m` or `1h`. A bare number is taken as seconds.
+pub fn parse_duration(text: &str) -> result::Result<Duration, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.trim().parse().map_err(|_| format!("invalid duration `{}`", text))?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "ms" => number / 1000.0,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(format!("unknown duration unit `{}`, expected ms, s, m or h", unit))
+    };
+
+    // Negative, infinite and overly long durations cannot be represented
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("invalid duration `{}`", text))
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_seconds<S>(duration: &Duration, serializer: S) -> result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer
+{
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+// Durations in configuration files are written either as a number of seconds or in the form accepted by
+// `parse_duration`
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_duration<'de, D>(deserializer: D) -> result::Result<Option<Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>
+{
+    use serde::Deserialize;
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Seconds(f64),
+        Text(String)
+    }
+
+    match Option::<Repr>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Repr::Seconds(seconds)) => parse_duration(&seconds.to_string()).map(Some).map_err(D::Error::custom),
+        Some(Repr::Text(text)) => parse_duration(&text).map(Some).map_err(D::Error::custom)
+    }
+}
+
+// std::time::Instant panics on wasm32-unknown-unknown, so read the JavaScript clock instead
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod js {
+    use std::ops::Add;
+    use std::time::Duration;
+
+    /// A point in time measured in milliseconds by the JavaScript `Date` clock.
+    #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+    pub struct Instant(f64);
+
+    impl Instant {
+        pub fn now() -> Self {
+            Instant(js_sys::Date::now())
+        }
+
+        pub fn duration_since(&self, earlier: Instant) -> Duration {
+            Duration::from_secs_f64((self.0 - earlier.0).max(0.0) / 1000.0)
+        }
+
+        pub fn elapsed(&self) -> Duration {
+            Instant::now().duration_since(*self)
+        }
+
+        // Milliseconds in an f64 cannot overflow, so there is always a later instant, if an infinite one
+        pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+            Some(*self + duration)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+
+        fn add(self, duration: Duration) -> Instant {
+            Instant(self.0 + duration.as_secs_f64() * 1000.0)
+        }
+    }
+}
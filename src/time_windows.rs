@@ -0,0 +1,149 @@
+use crate::error::{Error, Result};
+use crate::map::Map;
+
+// Local search weighs lateness this many times more than length when late tours are rejected, so that it still
+// has a direction to move in from an infeasible tour
+const REJECT_PENALTY: f32 = 1e4;
+
+/// When a point may be served, and for how long. Travel between points takes as long as the distance between
+/// them, arriving before `earliest` means waiting until then, and arriving after `latest` is late.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeWindow {
+    pub earliest: f32,
+    pub latest: f32,
+    /// The time spent at the point before leaving for the next one
+    pub service: f32
+}
+
+impl Default for TimeWindow {
+    /// A window that is always open, with no service time.
+    fn default() -> Self {
+        TimeWindow {
+            earliest: 0.0,
+            latest: f32::INFINITY,
+            service: 0.0
+        }
+    }
+}
+
+/// How arriving after a window closes affects the cost of a tour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Lateness {
+    /// Adds this much to the cost for every unit of time a point is served late
+    Penalty(f32),
+    /// Makes any tour that serves a point late cost infinity
+    Reject
+}
+
+/// The length of a tour, and the total time by which it misses the windows of the points it serves late.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Schedule {
+    pub length: f32,
+    pub lateness: f32,
+    /// When service ends at the last point
+    pub finish: f32
+}
+
+/// A time window for every point of a map, attached to it with [`Map::with_time_windows`].
+#[derive(Clone, Debug)]
+pub struct TimeWindows {
+    windows: Box<[TimeWindow]>,
+    lateness: Lateness
+}
+
+impl TimeWindows {
+    /// Checks that every window opens before it closes and that no time is negative.
+    pub fn new(windows: Vec<TimeWindow>, lateness: Lateness) -> Result<Self> {
+        let invalid = windows.iter().position(|window| {
+            !(window.earliest >= 0.0 && window.earliest <= window.latest && window.service >= 0.0)
+        });
+        if let Some(point) = invalid {
+            let window = windows[point];
+            return Err(Error::InvalidProblem(format!(
+                "the window [{}, {}] with service time {} of point {} is empty or negative",
+                window.earliest,
+                window.latest,
+                window.service,
+                point
+            )));
+        }
+        if let Lateness::Penalty(penalty) = lateness {
+            if penalty.is_nan() || penalty < 0.0 {
+                return Err(Error::InvalidProblem(format!("the lateness penalty {} is negative", penalty)));
+            }
+        }
+
+        Ok(TimeWindows {
+            windows: windows.into_boxed_slice(),
+            lateness
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    pub fn window(&self, point: usize) -> TimeWindow {
+        self.windows[point]
+    }
+
+    pub fn lateness(&self) -> Lateness {
+        self.lateness
+    }
+
+    /// Serves the points in the order of the tour, starting at the first point as soon as its window opens.
+    pub fn schedule(&self, map: &Map, tour: &[usize]) -> Schedule {
+        let mut schedule = Schedule::default();
+        let mut last: Option<usize> = None;
+        for &point in tour {
+            let window = self.windows[point];
+            let arrival = match last {
+                Some(last) => {
+                    let travel = map.dist(last, point);
+                    schedule.length += travel;
+                    schedule.finish + travel
+                },
+                None => 0.0
+            };
+
+            schedule.lateness += (arrival - window.latest).max(0.0);
+            schedule.finish = arrival.max(window.earliest) + window.service;
+            last = Some(point);
+        }
+        schedule
+    }
+
+    /// The cost of a schedule: its length, with lateness penalized or rejected.
+    pub fn cost(&self, schedule: &Schedule) -> f32 {
+        match self.lateness {
+            Lateness::Penalty(penalty) => schedule.length + penalty * schedule.lateness,
+            Lateness::Reject if schedule.lateness > 0.0 => f32::INFINITY,
+            Lateness::Reject => schedule.length
+        }
+    }
+
+    /// The cost of serving the points in the order of the tour. This is what
+    /// [`Path::evaluate`](crate::map::Path::evaluate) returns on a map with time windows, before the cost of any
+    /// turns.
+    pub fn evaluate(&self, map: &Map, tour: &[usize]) -> f32 {
+        self.cost(&self.schedule(map, tour))
+    }
+
+    // The cost local search minimizes, which stays finite for late tours when they are rejected
+    pub(crate) fn search_cost(&self, schedule: &Schedule) -> f32 {
+        match self.lateness {
+            Lateness::Penalty(_) => self.cost(schedule),
+            Lateness::Reject => schedule.length + REJECT_PENALTY * schedule.lateness
+        }
+    }
+
+    pub(crate) fn swap(&mut self, first: usize, second: usize) {
+        self.windows.swap(first, second);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::{Error, Result};
+use crate::map::{Map, Path};
+
+/// Every ordering of the points `0..n`, generated lazily one at a time, which [`brute_force`] searches through
+/// and which callers can enumerate, filter, or split between threads with [`starting_with`](Self::starting_with)
+/// to search exhaustively their own way. Each ordering is made from the one before by a few swaps, so
+/// [`next_tour`](Self::next_tour) lends it out rather than allocating a new one; the `Iterator` implementation
+/// copies every tour instead.
+///
+/// The first ordering is `0, 1, ..., n - 1`, and the order of the rest is not lexicographic.
+///
+/// [`brute_force`]: crate::solve::brute_force
+#[derive(Clone, Debug)]
+pub struct Tours {
+    current: Vec<usize>,
+    // The leading points which stay where they are
+    fixed: usize,
+    // The end of the ascending run at the front of the free points, which the next step extends or breaks
+    increase: usize,
+    started: bool,
+    done: bool
+}
+
+impl Tours {
+    pub fn new(n: usize) -> Self {
+        Tours {
+            current: (0..n).collect(),
+            fixed: 0,
+            increase: 0,
+            started: false,
+            done: false
+        }
+    }
+
+    /// The orderings of `0..n` that start with `first`, which are the `(n - 1)!` orderings of the other points after
+    /// it. The tours starting with each point together make up every tour, so a search can be split between
+    /// threads by the first point. Fails if `first` is not below `n`.
+    pub fn starting_with(n: usize, first: usize) -> Result<Self> {
+        if first >= n {
+            return Err(Error::InvalidProblem(format!("there is no point {} among {} points to start from", first, n)));
+        }
+
+        let mut current = Vec::with_capacity(n);
+        current.push(first);
+        current.extend((0..n).filter(|&point| point != first));
+        Ok(Tours {
+            current,
+            fixed: 1,
+            increase: 0,
+            started: false,
+            done: false
+        })
+    }
+
+    /// The next ordering, or `None` once every one has been generated.
+    pub fn next_tour(&mut self) -> Option<&[usize]> {
+        if !self.started {
+            self.started = true;
+            return Some(&self.current);
+        }
+        if self.done || !self.advance() {
+            self.done = true;
+            return None;
+        }
+        Some(&self.current)
+    }
+
+    // Steps to the next ordering of the free points, returning false if the last one has been reached
+    fn advance(&mut self) -> bool {
+        let current = &mut self.current[self.fixed..];
+        let max = current.len().saturating_sub(1);
+        let increase = self.increase;
+        if increase == max {
+            return false;
+        }
+
+        if increase == 0 {
+            current.swap(0, 1);
+            self.increase = 1;
+            while self.increase < max && current[self.increase] > current[self.increase + 1] {
+                self.increase += 1;
+            }
+        } else {
+            if current[increase + 1] > current[0] {
+                current.swap(increase + 1, 0);
+            } else {
+                let mut start = 0;
+                let mut end = increase;
+                let mut mid = (start + end) / 2;
+                let top = current[increase + 1];
+                while !(current[mid] < top && current[mid - 1] > top) {
+                    if current[mid] < top {
+                        end = mid - 1;
+                    } else {
+                        start = mid + 1;
+                    }
+                    mid = (start + end) / 2;
+                }
+
+                current.swap(increase + 1, mid);
+            }
+
+            for i in 0..=increase / 2 {
+                current.swap(i, increase - i);
+            }
+            self.increase = 0;
+        }
+        true
+    }
+}
+
+impl Iterator for Tours {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_tour().map(<[usize]>::to_vec)
+    }
+}
+
+/// Turns the path around if it ends at a lower point than it starts at, so that a path and its reverse, which visit
+/// the same points along the same edges, become the same sequence and can be hashed, deduplicated and compared
+/// across runs. The start is not moved: every path in this crate is open, and a path started elsewhere has other
+/// edges. Paths of fewer than two points are left as they are.
+pub fn canonicalize(tour: &mut [usize]) {
+    if tour.last() < tour.first() {
+        tour.reverse();
+    }
+}
+
+/// The [canonical](canonicalize) direction of the path, as a copy.
+pub fn canonical(tour: &[usize]) -> Vec<usize> {
+    let mut tour = tour.to_vec();
+    canonicalize(&mut tour);
+    tour
+}
+
+/// Like [`canonicalize`], but for a closed tour that returns to its start, which is the same tour from any point:
+/// it is rotated to start at its lowest point, and then turned around if that makes the sequence lexicographically
+/// smaller, so that every rotation and reversal of a closed tour becomes the same sequence.
+pub fn canonicalize_cycle(tour: &mut [usize]) {
+    if let Some(lowest) = tour.iter().enumerate().min_by_key(|&(_, &point)| point).map(|(index, _)| index) {
+        tour.rotate_left(lowest);
+    }
+    if tour.len() > 2 && tour[tour.len() - 1] < tour[1] {
+        tour[1..].reverse();
+    }
+}
+
+/// Whether the two paths are the same up to their direction.
+pub fn same_path(a: &[usize], b: &[usize]) -> bool {
+    a.len() == b.len() && (a == b || a.iter().eq(b.iter().rev()))
+}
+
+/// Evaluates the path in both directions and turns it around if it costs less the other way, returning its cost.
+/// On most maps both directions cost the same and the path is left as it is, but on a map with
+/// [time windows](Map::with_time_windows) the order in which the points are served decides when each is reached.
+pub fn orient(map: &Map, tour: &mut [usize]) -> f32 {
+    let forward = tour.evaluate(map);
+    tour.reverse();
+    let backward = tour.evaluate(map);
+    if backward < forward {
+        backward
+    } else {
+        tour.reverse();
+        forward
+    }
+}
@@ -0,0 +1,114 @@
+use crate::map::{Map, Point};
+
+/// The optimal tour lengths of the symmetric TSPLIB instances with `EUC_2D` distances, by instance name. Instances
+/// with other distance functions are left out, since maps measure straight line distances.
+///
+/// TSPLIB rounds every distance to the nearest integer, so the length of a tour over a map can differ slightly
+/// from its TSPLIB length, and an optimal tour can even come out a little shorter than its optimum. Measure tours
+/// with [`tour_length`] to compare them with these exactly.
+pub const OPTIMA: &[(&str, u64)] = &[
+    ("a280", 2579),
+    ("berlin52", 7542),
+    ("bier127", 118282),
+    ("ch130", 6110),
+    ("ch150", 6528),
+    ("d198", 15780),
+    ("d493", 35002),
+    ("d657", 48912),
+    ("d1291", 50801),
+    ("d1655", 62128),
+    ("d2103", 80450),
+    ("eil51", 426),
+    ("eil76", 538),
+    ("eil101", 629),
+    ("fl417", 11861),
+    ("fl1400", 20127),
+    ("fl1577", 22249),
+    ("fl3795", 28772),
+    ("fnl4461", 182566),
+    ("gil262", 2378),
+    ("kroA100", 21282),
+    ("kroB100", 22141),
+    ("kroC100", 20749),
+    ("kroD100", 21294),
+    ("kroE100", 22068),
+    ("kroA150", 26524),
+    ("kroB150", 26130),
+    ("kroA200", 29368),
+    ("kroB200", 29437),
+    ("lin105", 14379),
+    ("lin318", 42029),
+    ("nrw1379", 56638),
+    ("p654", 34643),
+    ("pcb442", 50778),
+    ("pcb1173", 56892),
+    ("pcb3038", 137694),
+    ("pr76", 108159),
+    ("pr107", 44303),
+    ("pr124", 59030),
+    ("pr136", 96772),
+    ("pr144", 58537),
+    ("pr152", 73682),
+    ("pr226", 80369),
+    ("pr264", 49135),
+    ("pr299", 48191),
+    ("pr439", 107217),
+    ("pr1002", 259045),
+    ("pr2392", 378032),
+    ("rat99", 1211),
+    ("rat195", 2323),
+    ("rat575", 6773),
+    ("rat783", 8806),
+    ("rd100", 7910),
+    ("rd400", 15281),
+    ("rl1304", 252948),
+    ("rl1323", 270199),
+    ("rl1889", 316536),
+    ("rl5915", 565530),
+    ("rl5934", 556045),
+    ("st70", 675),
+    ("ts225", 126643),
+    ("tsp225", 3916),
+    ("u159", 42080),
+    ("u574", 36905),
+    ("u724", 41910),
+    ("u1060", 224094),
+    ("u1432", 152970),
+    ("u1817", 57201),
+    ("u2152", 64253),
+    ("u2319", 234256),
+    ("vm1084", 239297),
+    ("vm1748", 336556)
+];
+
+/// The optimal length of the TSPLIB instance with the given name, ignoring case.
+pub fn optimum(name: &str) -> Option<f32> {
+    OPTIMA.iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|&(_, optimum)| optimum as f32)
+}
+
+/// How far the length is above the optimum of the named TSPLIB instance, relative to the optimum, if the instance
+/// is known.
+pub fn above_optimal(name: &str, length: f32) -> Option<f32> {
+    optimum(name).map(|optimum| (length - optimum) / optimum)
+}
+
+/// The `EUC_2D` distance between two points: the straight line distance rounded to the nearest integer.
+#[inline]
+pub fn euc_2d(a: &Point, b: &Point) -> u64 {
+    // Computed in f64, since f32 rounding can push a distance just below a half across it
+    let (x, y) = (a.x as f64 - b.x as f64, a.y as f64 - b.y as f64);
+    (x.hypot(y) + 0.5) as u64
+}
+
+/// The length of the tour as TSPLIB measures it: the sum of the [`euc_2d`] distances of its edges, including the
+/// edge back from the last point to the first, which map paths leave out. The sum is exact, so it matches the
+/// published optimum of an optimal tour.
+pub fn tour_length(map: &Map, tour: &[usize]) -> u64 {
+    let closing = match (tour.first(), tour.last()) {
+        (Some(&first), Some(&last)) => euc_2d(&map[last], &map[first]),
+        _ => 0
+    };
+    tour.windows(2).map(|edge| euc_2d(&map[edge[0]], &map[edge[1]])).sum::<u64>() + closing
+}
@@ -0,0 +1,204 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+
+use crate::ga::{AnyRecombinator, Chromosome, GaParams, Settings, random_population, repair_neighbors, run_generations};
+use crate::error::Result;
+use crate::map::Map;
+use crate::solve::{Budget, Objective, Reporter, nearest_neighbor};
+
+/// The values each tunable parameter may take. Every field must contain at least one value.
+#[derive(Clone, Debug)]
+pub struct ParameterSpace {
+    pub population: Vec<usize>,
+    pub replace_percent: Vec<f32>,
+    pub elitist_percent: Vec<f32>,
+    pub crossover_prob: Vec<f32>,
+    pub mutate_prob: Vec<f32>,
+    pub recombinators: Vec<AnyRecombinator>
+}
+
+/// How candidate configurations are drawn from the parameter space.
+#[derive(Clone, Copy, Debug)]
+pub enum Search {
+    /// Try every combination in the parameter space
+    Grid,
+    /// Try the given number of combinations drawn uniformly from the parameter space
+    Random(usize)
+}
+
+/// A configuration that was evaluated.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    pub settings: Settings,
+    pub population: usize,
+    pub recombinator: AnyRecombinator
+}
+
+/// Scores are the final loss divided by the nearest neighbor loss on the same instance, so lower is better
+/// and results are comparable across instances of different sizes.
+#[derive(Clone, Copy, Debug)]
+pub struct TrialStats {
+    pub mean: f32,
+    pub std_dev: f32,
+    pub best: f32,
+    pub worst: f32
+}
+
+/// Every evaluated configuration, sorted best first.
+#[derive(Clone, Debug)]
+pub struct TuneReport {
+    pub best: Candidate,
+    pub best_stats: TrialStats,
+    pub trials: Vec<(Candidate, TrialStats)>
+}
+
+/// Evaluates GA configurations by running each for a fixed number of generations on every instance.
+pub struct Tuner<'a> {
+    pub instances: &'a [Map],
+    pub base: Settings,
+    pub generations: u32,
+    pub repetitions: usize,
+    pub fix: bool,
+    /// Seeds the candidate draws and every GA run, so that tuning can be repeated exactly
+    pub seed: Option<u64>
+}
+
+impl<'a> Tuner<'a> {
+    pub fn tune<C, F>(&self, space: &ParameterSpace, search: Search, new_chromosome: F) -> Result<TuneReport>
+    where
+        C: Chromosome,
+        F: Fn(&Map) -> C
+    {
+        assert!(!self.instances.is_empty(), "At least one instance is required");
+        assert!(self.repetitions > 0, "At least one repetition is required");
+
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed.unwrap_or_else(random));
+        let references = self.instances.iter()
+            .map(|map| nearest_neighbor(map).map(|result| result.length))
+            .collect::<Result<Vec<f32>>>()?;
+        let mut trials: Vec<(Candidate, TrialStats)> = self.candidates(space, search, &mut rng)
+            .into_iter()
+            .map(|candidate| {
+                let stats = self.evaluate(&candidate, &references, &new_chromosome, &mut rng);
+                (candidate, stats)
+            })
+            .collect();
+
+        trials.sort_by(|(_, a), (_, b)| a.mean.total_cmp(&b.mean));
+        let (best, best_stats) = trials[0].clone();
+
+        Ok(TuneReport {
+            best,
+            best_stats,
+            trials
+        })
+    }
+
+    fn candidates(&self, space: &ParameterSpace, search: Search, rng: &mut impl Rng) -> Vec<Candidate> {
+        assert!(
+            !space.population.is_empty()
+                && !space.replace_percent.is_empty()
+                && !space.elitist_percent.is_empty()
+                && !space.crossover_prob.is_empty()
+                && !space.mutate_prob.is_empty()
+                && !space.recombinators.is_empty(),
+            "Every parameter must have at least one value"
+        );
+
+        let make = |population, replace_percent, elitist_percent, crossover_prob, mutate_prob, recombinator| {
+            Candidate {
+                settings: Settings {
+                    replace_percent,
+                    elitist_percent,
+                    crossover_prob,
+                    mutate_prob,
+                    ..self.base.clone()
+                },
+                population,
+                recombinator
+            }
+        };
+
+        match search {
+            Search::Grid => {
+                let mut candidates = Vec::new();
+                for &population in &space.population {
+                    for &replace in &space.replace_percent {
+                        for &elitist in &space.elitist_percent {
+                            for &crossover in &space.crossover_prob {
+                                for &mutate in &space.mutate_prob {
+                                    for &recombinator in &space.recombinators {
+                                        candidates.push(make(population, replace, elitist, crossover, mutate, recombinator));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                candidates
+            },
+            Search::Random(count) => {
+                (0..count)
+                    .map(|_| make(
+                        *space.population.choose(rng).unwrap(),
+                        *space.replace_percent.choose(rng).unwrap(),
+                        *space.elitist_percent.choose(rng).unwrap(),
+                        *space.crossover_prob.choose(rng).unwrap(),
+                        *space.mutate_prob.choose(rng).unwrap(),
+                        *space.recombinators.choose(rng).unwrap()
+                    ))
+                    .collect()
+            }
+        }
+    }
+
+    fn evaluate<C, F>(
+        &self,
+        candidate: &Candidate,
+        references: &[f32],
+        new_chromosome: &F,
+        rng: &mut impl Rng
+    ) -> TrialStats
+    where
+        C: Chromosome,
+        F: Fn(&Map) -> C
+    {
+        let mut scores = Vec::with_capacity(self.instances.len() * self.repetitions);
+
+        for (map, &reference) in self.instances.iter().zip(references.iter()) {
+            let neighbors = repair_neighbors(map, self.fix, None);
+            for _ in 0..self.repetitions {
+                let mut population = random_population(map, candidate.population, new_chromosome, rng);
+                let params = GaParams {
+                    settings: &candidate.settings,
+                    recombinator: &candidate.recombinator,
+                    repair: neighbors.as_ref().into(),
+                    objective: Objective::Minimize,
+                    first_generation: 0,
+                    generations: self.generations
+                };
+                let outcome = run_generations(
+                    &params,
+                    map,
+                    &mut population,
+                    &mut Budget::unlimited(),
+                    &mut Reporter::silent(),
+                    rng
+                );
+
+                scores.push(if reference > 0.0 { outcome.loss / reference } else { 1.0 });
+            }
+        }
+
+        let count = scores.len() as f32;
+        let mean = scores.iter().sum::<f32>() / count;
+        let variance = scores.iter().map(|score| (score - mean) * (score - mean)).sum::<f32>() / count;
+
+        TrialStats {
+            mean,
+            std_dev: variance.sqrt(),
+            best: scores.iter().cloned().fold(f32::MAX, f32::min),
+            worst: scores.iter().cloned().fold(f32::MIN, f32::max)
+        }
+    }
+}
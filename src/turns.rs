@@ -0,0 +1,75 @@
+use crate::error::{Error, Result};
+use crate::map::{Map, Point};
+
+/// A cost for every change of direction along a tour, as for a plotter or CNC tool which has to slow down to turn.
+/// Turning by an angle at a point costs `weight` times the angle in radians, so that going straight on is free and
+/// turning back the way the tour came costs `weight` times pi. Angles are measured between the positions of the
+/// points, even on a map with other [distances](Map::with_distances). Attached to a map with
+/// [`Map::with_turn_penalty`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TurnPenalty {
+    weight: f32
+}
+
+impl TurnPenalty {
+    /// Checks that the weight is finite and not negative.
+    pub fn new(weight: f32) -> Result<Self> {
+        if !(weight.is_finite() && weight >= 0.0) {
+            return Err(Error::InvalidProblem(format!("the turn weight {} is negative or not finite", weight)));
+        }
+
+        Ok(TurnPenalty {
+            weight
+        })
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// The angle in radians, from zero to pi, by which a path from `a` to `b` turns at `b` to go on to `c`. Turning
+    /// at a point the path reaches or leaves along an edge of no length is free.
+    pub fn angle(map: &Map, a: usize, b: usize, c: usize) -> f32 {
+        let (a, b, c) = (map[a], map[b], map[c]);
+        let (u, v) = (b - a, c - b);
+        if u == Point::new() || v == Point::new() {
+            return 0.0;
+        }
+        (u.x * v.y - u.y * v.x).abs().atan2(u.x * v.x + u.y * v.y)
+    }
+
+    /// The cost of turning at `b` between `a` and `c`.
+    #[inline]
+    pub fn cost(&self, map: &Map, a: usize, b: usize, c: usize) -> f32 {
+        self.weight * Self::angle(map, a, b, c)
+    }
+
+    /// The cost of every turn the tour makes, which [`Path::evaluate`](crate::map::Path::evaluate) adds to the
+    /// length of the tour on a map with a turn penalty.
+    pub fn evaluate(&self, map: &Map, tour: &[usize]) -> f32 {
+        tour.windows(3).map(|turn| self.cost(map, turn[0], turn[1], turn[2])).sum()
+    }
+
+    // The change in turn cost from reversing `tour[i..=j]`. Turns inside the reversed part keep their angles, so
+    // only the turns at its ends and just outside them change.
+    pub(crate) fn reversal_delta(&self, map: &Map, tour: &[usize], i: usize, j: usize) -> f32 {
+        let reversed = |k: usize| if (i..=j).contains(&k) { tour[i + j - k] } else { tour[k] };
+        let mut positions = [i.wrapping_sub(1), i, j, j + 1];
+        positions.sort_unstable();
+
+        let mut delta = 0.0;
+        let mut previous = None;
+        for &k in &positions {
+            // Only points with a neighbor on both sides turn
+            if previous == Some(k) || k == 0 || k == usize::MAX || k + 1 >= tour.len() {
+                continue;
+            }
+            previous = Some(k);
+
+            let before = self.cost(map, tour[k - 1], tour[k], tour[k + 1]);
+            let after = self.cost(map, reversed(k - 1), reversed(k), reversed(k + 1));
+            delta += after - before;
+        }
+        delta
+    }
+}
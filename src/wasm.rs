@@ -0,0 +1,193 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+use crate::checkpoint::GaCheckpoint;
+use crate::ga::{GaSolver, best_of};
+use crate::map::{Map, Point, RandomKeyPath};
+use crate::solve::{self, SolveOptions, SolveResult, Termination};
+
+/// The points to visit, shared with JavaScript.
+#[wasm_bindgen]
+pub struct Instance {
+    map: Map
+}
+
+#[wasm_bindgen]
+impl Instance {
+    /// Builds an instance from interleaved x and y coordinates.
+    #[wasm_bindgen(constructor)]
+    pub fn new(coords: &[f32]) -> Result<Instance, JsError> {
+        if !coords.len().is_multiple_of(2) {
+            return Err(JsError::new("coordinates must come in x, y pairs"));
+        }
+
+        let points = coords.chunks(2)
+            .map(|pair| Point {
+                x: pair[0],
+                y: pair[1]
+            })
+            .collect();
+
+        Ok(Instance {
+            map: Map::from_points(points)
+        })
+    }
+
+    /// `count` random points on the unit disc.
+    pub fn random(count: usize) -> Instance {
+        Instance {
+            map: Map::new(count)
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.map.size()
+    }
+
+    /// The points as interleaved x and y coordinates.
+    pub fn coords(&self) -> Vec<f32> {
+        self.map.clone_to_vec()
+            .into_iter()
+            .flat_map(|point| vec![point.x, point.y])
+            .collect()
+    }
+}
+
+/// The outcome of a solve.
+#[wasm_bindgen]
+pub struct Solution {
+    result: SolveResult
+}
+
+#[wasm_bindgen]
+impl Solution {
+    #[wasm_bindgen(getter)]
+    pub fn tour(&self) -> Vec<u32> {
+        to_js_tour(&self.result.tour)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> f32 {
+        self.result.length
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn iterations(&self) -> f64 {
+        self.result.iterations as f64
+    }
+
+    #[wasm_bindgen(getter, js_name = elapsedMs)]
+    pub fn elapsed_ms(&self) -> f64 {
+        self.result.elapsed.as_secs_f64() * 1000.0
+    }
+
+    /// One of `"completed"`, `"timeLimit"`, `"cancelled"`, `"targetGap"` or `"evaluationLimit"`.
+    #[wasm_bindgen(getter)]
+    pub fn termination(&self) -> String {
+        match self.result.termination {
+            Termination::Completed => "completed",
+            Termination::TimeLimit => "timeLimit",
+            Termination::Cancelled => "cancelled",
+            Termination::TargetGap => "targetGap",
+            Termination::EvaluationLimit => "evaluationLimit"
+        }.to_owned()
+    }
+}
+
+#[wasm_bindgen(js_name = nearestNeighbor)]
+pub fn nearest_neighbor(instance: &Instance) -> Result<Solution, JsError> {
+    Ok(Solution {
+        result: solve::nearest_neighbor(&instance.map)?
+    })
+}
+
+#[wasm_bindgen(js_name = branchAndBound)]
+pub fn branch_and_bound(instance: &Instance, time_limit_ms: Option<f64>) -> Result<Solution, JsError> {
+    Ok(Solution {
+        result: solve::branch_and_bound(&instance.map, &options(time_limit_ms)?, &solve::NearestNeighborBound)?
+    })
+}
+
+#[wasm_bindgen(js_name = bruteForce)]
+pub fn brute_force(instance: &Instance, time_limit_ms: Option<f64>) -> Result<Solution, JsError> {
+    Ok(Solution {
+        result: solve::brute_force(&instance.map, &options(time_limit_ms)?)?
+    })
+}
+
+/// A random-key GA advanced a few generations at a time, so that a page can redraw between steps.
+/// Runs with the same instance and seed evolve identically.
+#[wasm_bindgen]
+pub struct GaRun {
+    map: Map,
+    solver: GaSolver<RandomKeyPath>,
+    checkpoint: GaCheckpoint<RandomKeyPath>
+}
+
+#[wasm_bindgen]
+impl GaRun {
+    #[wasm_bindgen(constructor)]
+    pub fn new(instance: &Instance, population: usize, seed: u32) -> Result<GaRun, JsError> {
+        if population < 2 {
+            return Err(JsError::new("the population must contain at least two individuals"));
+        }
+
+        // Draw the initial keys from the same stream the run continues with
+        let map = instance.map.clone();
+        let mut rng = ChaCha20Rng::seed_from_u64(seed as u64);
+        let individuals = (0..population)
+            .map(|_| {
+                let mut indv = RandomKeyPath::new(&map);
+                indv.inner_mut().iter_mut().for_each(|key| *key = rng.gen());
+                indv
+            })
+            .collect();
+
+        let mut checkpoint = GaCheckpoint::new(individuals, seed as u64);
+        checkpoint.word_pos = rng.get_word_pos();
+
+        Ok(GaRun {
+            map,
+            solver: GaSolver::builder().population(population).generations(0).build(),
+            checkpoint
+        })
+    }
+
+    /// Runs the given number of generations and returns the best length in the population.
+    pub fn step(&mut self, generations: u32) -> Result<f32, JsError> {
+        self.solver.generations = self.checkpoint.generation + generations;
+        self.solver.resume(&self.map, &SolveOptions::default(), &mut self.checkpoint)?;
+        Ok(best_of(&self.map, &self.checkpoint.population).0)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u32 {
+        self.checkpoint.generation
+    }
+
+    /// The tour of the best individual in the current population.
+    #[wasm_bindgen(getter)]
+    pub fn best(&self) -> Vec<u32> {
+        to_js_tour(&best_of(&self.map, &self.checkpoint.population).1)
+    }
+}
+
+// Negative time limits stop at once, and ones too long to represent, such as `Infinity`, are rejected
+fn options(time_limit_ms: Option<f64>) -> Result<SolveOptions, JsError> {
+    let time_limit = time_limit_ms
+        .map(|ms| {
+            Duration::try_from_secs_f64(ms.max(0.0) / 1000.0)
+                .map_err(|_| JsError::new(&format!("invalid time limit of {} ms", ms)))
+        })
+        .transpose()?;
+    Ok(SolveOptions {
+        time_limit,
+        ..SolveOptions::default()
+    })
+}
+
+fn to_js_tour(tour: &[usize]) -> Vec<u32> {
+    tour.iter().map(|&city| city as u32).collect()
+}